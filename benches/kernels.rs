@@ -0,0 +1,128 @@
+// Compares the vectorization-friendly kernels in `linear_algebra::kernels`
+// against the scalar iterator chains they replaced. Run with
+// `cargo bench --bench kernels`.
+use ml_toolkit::linear_algebra::{Matrix, RowVector, kernels};
+use std::hint::black_box;
+use std::iter::zip;
+use std::time::{Duration, Instant};
+
+fn time<T>(iterations: u32, mut f: impl FnMut() -> T) -> Duration {
+    // warm up caches and the branch predictor before timing
+    for _ in 0..iterations.div_ceil(10) {
+        black_box(f());
+    }
+    let start = Instant::now();
+    for _ in 0..iterations {
+        black_box(f());
+    }
+    start.elapsed() / iterations
+}
+
+fn report(name: &str, iterations: u32, scalar: impl FnMut() -> f32, kernel: impl FnMut() -> f32) {
+    let scalar_time = time(iterations, scalar);
+    let kernel_time = time(iterations, kernel);
+    println!(
+        "{:<28} scalar {:>12?}  kernel {:>12?}  speedup {:>5.2}x",
+        name,
+        scalar_time,
+        kernel_time,
+        scalar_time.as_secs_f64() / kernel_time.as_secs_f64()
+    );
+}
+
+fn sample(len: usize, offset: usize) -> Vec<f32> {
+    (0..len)
+        .map(|i| ((i * 7 + offset) % 101) as f32 / 101.0 - 0.5)
+        .collect()
+}
+
+// the iterator chains `RowVector` used before the kernels
+fn scalar_multiply_by_scalar(v: &[f32], scalar: f32) -> Vec<f32> {
+    v.iter().map(|c| c * scalar).collect()
+}
+
+fn scalar_add_vector(v1: &[f32], v2: &[f32]) -> Vec<f32> {
+    zip(v1, v2).map(|(c1, c2)| *c1 + *c2).collect()
+}
+
+fn scalar_multiply(m1: &Matrix, m2: &Matrix) -> Matrix {
+    let mut output_matrix = Vec::new();
+    for i in 0..m1.len() {
+        let mut new_row = Vec::new();
+        for j in 0..m2.get(0).len() {
+            let mut sum = 0.0;
+            for k in 0..m2.len() {
+                sum += m1.get(i).get(k) * m2.get(k).get(j);
+            }
+            new_row.push(sum);
+        }
+        output_matrix.push(RowVector::new(&new_row));
+    }
+    Matrix::new(&output_matrix)
+}
+
+fn main() {
+    let len = 1 << 16;
+    let (a, b) = (sample(len, 0), sample(len, 13));
+    report(
+        "dot (65536)",
+        2000,
+        || zip(&a, &b).fold(0.0, |acc, (x, y)| acc + x * y),
+        || kernels::dot(&a, &b),
+    );
+    report(
+        "sum (65536)",
+        2000,
+        || a.iter().fold(0.0, |acc, x| acc + x),
+        || kernels::sum(&a),
+    );
+    report(
+        "scale (65536)",
+        2000,
+        || scalar_multiply_by_scalar(&a, 1.5)[0],
+        || kernels::scaled(1.5, &a)[0],
+    );
+    report(
+        "add (65536)",
+        2000,
+        || scalar_add_vector(&a, &b)[0],
+        || kernels::add(&a, &b)[0],
+    );
+    // the row update in the old Gauss-Jordan inverse, `y + alpha * x`, allocated
+    // two vectors; axpy updates in place
+    let mut y = b.clone();
+    report(
+        "axpy (65536)",
+        2000,
+        || scalar_add_vector(&b, &scalar_multiply_by_scalar(&a, 1e-6))[0],
+        || {
+            kernels::axpy(1e-6, &a, &mut y);
+            y[0]
+        },
+    );
+
+    // a housing-sized design matrix and its Gram matrix
+    let rows = 16384;
+    let columns = 14;
+    let design = Matrix::to_matrix(&(0..rows).map(|i| sample(columns, i)).collect());
+    let design_transpose = design.transpose();
+    report(
+        "matrix X^T X (16384 x 14)",
+        5,
+        || scalar_multiply(&design_transpose, &design).get(0).get(0),
+        || design_transpose.multiply(&design).get(0).get(0),
+    );
+    let weights = RowVector::new(&sample(columns, 3));
+    report(
+        "predict X w (16384 x 14)",
+        50,
+        || {
+            design
+                .matrix()
+                .iter()
+                .map(|row| zip(row.vector(), weights.vector()).fold(0.0, |acc, (x, w)| acc + x * w))
+                .sum()
+        },
+        || design.matrix().iter().map(|row| row.dot(&weights)).sum(),
+    );
+}
@@ -0,0 +1,551 @@
+use super::kmeans::insert_cluster_labels;
+use crate::algorithms::estimators::{Clusterer, Estimator, ParamError, ParamValue};
+use crate::algorithms::neighbors::spatial::Distance;
+use crate::dataframe::DataFrame;
+use crate::linear_algebra::{LinalgError, Matrix, RowVector};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Linkage {
+    /// Closest pair of rows, follows elongated clusters.
+    Single,
+    /// Farthest pair of rows, favours compact clusters.
+    Complete,
+    /// Mean distance over all pairs of rows.
+    Average,
+    /// Increase in within cluster variance, euclidean distance only.
+    Ward,
+}
+
+/// One merge of two clusters. Clusters `0..num_points` are the rows and the
+/// cluster created by merge `i` is `num_points + i`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Merge {
+    pub left: usize,
+    pub right: usize,
+    /// Linkage distance between the merged clusters.
+    pub height: f32,
+    /// Rows in the merged cluster.
+    pub size: usize,
+}
+
+/// The full merge history of an agglomerative clustering, ordered by height,
+/// which can be cut into any number of clusters or at any height.
+#[derive(Clone, Debug)]
+pub struct MergeTree {
+    num_points: usize,
+    merges: Vec<Merge>,
+}
+
+impl MergeTree {
+    pub fn num_points(&self) -> usize {
+        return self.num_points;
+    }
+
+    pub fn merges(&self) -> &Vec<Merge> {
+        return &self.merges;
+    }
+
+    /// Labels after stopping with `num_clusters` clusters left, numbered in
+    /// order of their first row.
+    pub fn cut(&self, num_clusters: usize) -> RowVector {
+        if num_clusters == 0 || num_clusters > self.num_points {
+            panic!(
+                "cannot cut {} points into {} clusters",
+                self.num_points, num_clusters
+            );
+        }
+        return self.labels_after(self.num_points - num_clusters);
+    }
+
+    /// Labels after applying every merge no higher than `height`.
+    pub fn cut_at_height(&self, height: f32) -> RowVector {
+        let applied = self
+            .merges
+            .iter()
+            .take_while(|merge| merge.height <= height)
+            .count();
+        return self.labels_after(applied);
+    }
+
+    fn labels_after(&self, applied: usize) -> RowVector {
+        let mut sets = DisjointSets::new(self.num_points + applied);
+        for (i, merge) in self.merges[..applied].iter().enumerate() {
+            sets.union_into(merge.left, self.num_points + i);
+            sets.union_into(merge.right, self.num_points + i);
+        }
+        let mut numbering: Vec<Option<usize>> = vec![None; self.num_points + applied];
+        let mut next = 0;
+        let labels = (0..self.num_points)
+            .map(|point| {
+                let root = sets.find(point);
+                let label = *numbering[root].get_or_insert_with(|| {
+                    next += 1;
+                    next - 1
+                });
+                label as f32
+            })
+            .collect();
+        return RowVector::new(&labels);
+    }
+}
+
+enum Cut {
+    Clusters(usize),
+    Height(f32),
+}
+
+/// Bottom up hierarchical clustering: starts from one cluster per row and
+/// repeatedly merges the two closest clusters under the linkage. Single
+/// linkage runs on a minimum spanning tree in linear memory, the others keep
+/// all pairwise distances (quadratic memory) and use the nearest neighbour
+/// chain algorithm.
+pub struct AgglomerativeClustering {
+    linkage: Linkage,
+    distance: Distance,
+    cut: Cut,
+    tree: Option<MergeTree>,
+    labels: RowVector,
+}
+
+impl AgglomerativeClustering {
+    pub fn new(num_clusters: usize, linkage: Linkage) -> Self {
+        return Self {
+            linkage,
+            distance: Distance::Euclidean,
+            cut: Cut::Clusters(num_clusters.max(1)),
+            tree: None,
+            labels: RowVector::new(&Vec::new()),
+        };
+    }
+
+    /// Distance between rows for the single, complete and average linkages.
+    pub fn set_distance(&mut self, distance: Distance) {
+        self.distance = distance;
+    }
+
+    pub fn set_num_clusters(&mut self, num_clusters: usize) {
+        self.cut = Cut::Clusters(num_clusters.max(1));
+    }
+
+    /// Cuts the tree at a linkage distance instead of a cluster count.
+    pub fn set_distance_threshold(&mut self, threshold: f32) {
+        self.cut = Cut::Height(threshold);
+    }
+
+    pub fn fit(&mut self, data: &Matrix) {
+        let (rows, columns) = data.shape();
+        if rows == 0 {
+            panic!("cannot cluster an empty matrix");
+        }
+        if self.linkage == Linkage::Ward && self.distance != Distance::Euclidean {
+            panic!("ward linkage requires euclidean distance");
+        }
+        self.distance.validate(columns);
+        let points = data.to_f64();
+        if points.iter().flatten().any(|value| !value.is_finite()) {
+            panic!("agglomerative clustering data must be finite");
+        }
+        let raw = match self.linkage {
+            Linkage::Single => minimum_spanning_tree(&points, &self.distance),
+            _ => nearest_neighbor_chain(&points, &self.distance, &self.linkage),
+        };
+        let tree = build_tree(rows, raw);
+        self.labels = match self.cut {
+            Cut::Clusters(num_clusters) => tree.cut(num_clusters.min(rows)),
+            Cut::Height(height) => tree.cut_at_height(height),
+        };
+        self.tree = Some(tree);
+    }
+
+    pub fn fit_predict(&mut self, data: &Matrix) -> RowVector {
+        self.fit(data);
+        return self.labels();
+    }
+
+    pub fn labels(&self) -> RowVector {
+        return self.labels.clone();
+    }
+
+    pub fn insert_labels(&self, df: &mut DataFrame, column_name: &str) {
+        insert_cluster_labels(df, column_name, &self.labels);
+    }
+
+    pub fn tree(&self) -> &MergeTree {
+        return self
+            .tree
+            .as_ref()
+            .expect("agglomerative clustering must be fitted first");
+    }
+
+    pub fn num_clusters(&self) -> usize {
+        return self
+            .labels
+            .vector()
+            .iter()
+            .fold(0.0, |acc: f32, label| acc.max(*label + 1.0)) as usize;
+    }
+}
+
+struct DisjointSets {
+    parents: Vec<usize>,
+}
+
+impl DisjointSets {
+    fn new(len: usize) -> Self {
+        return Self {
+            parents: (0..len).collect(),
+        };
+    }
+
+    fn find(&mut self, mut node: usize) -> usize {
+        while self.parents[node] != node {
+            self.parents[node] = self.parents[self.parents[node]];
+            node = self.parents[node];
+        }
+        return node;
+    }
+
+    // makes `root` (a root) the parent of the set containing `node`
+    fn union_into(&mut self, node: usize, root: usize) {
+        let node_root = self.find(node);
+        self.parents[node_root] = root;
+    }
+}
+
+// merges `(row, row, height)` in any order, where each row stands for the
+// cluster it currently belongs to, into cluster ids ordered by height
+fn build_tree(num_points: usize, mut raw: Vec<(usize, usize, f64)>) -> MergeTree {
+    raw.sort_by(|a, b| a.2.total_cmp(&b.2));
+    let mut sets = DisjointSets::new(2 * num_points);
+    let mut sizes: Vec<usize> = vec![1; 2 * num_points];
+    let mut merges = Vec::with_capacity(raw.len());
+    for (i, (a, b, height)) in raw.iter().enumerate() {
+        let (left, right) = (sets.find(*a), sets.find(*b));
+        let cluster = num_points + i;
+        sizes[cluster] = sizes[left] + sizes[right];
+        sets.union_into(left, cluster);
+        sets.union_into(right, cluster);
+        merges.push(Merge {
+            left: left.min(right),
+            right: left.max(right),
+            height: *height as f32,
+            size: sizes[cluster],
+        });
+    }
+    return MergeTree { num_points, merges };
+}
+
+// single linkage merges are exactly the edges of a minimum spanning tree,
+// found with Prim's algorithm without storing the distance matrix
+fn minimum_spanning_tree(points: &Vec<Vec<f64>>, distance: &Distance) -> Vec<(usize, usize, f64)> {
+    let rows = points.len();
+    let mut in_tree = vec![false; rows];
+    let mut closest = vec![(f64::INFINITY, 0); rows];
+    let mut edges = Vec::with_capacity(rows.saturating_sub(1));
+    let mut current = 0;
+    in_tree[0] = true;
+    for _ in 1..rows {
+        let mut next = None;
+        for row in 0..rows {
+            if in_tree[row] {
+                continue;
+            }
+            let d = distance.between(&points[current], &points[row]);
+            if d < closest[row].0 {
+                closest[row] = (d, current);
+            }
+            if next.is_none_or(|next: usize| closest[row].0 < closest[next].0) {
+                next = Some(row);
+            }
+        }
+        let next = next.unwrap();
+        in_tree[next] = true;
+        edges.push((closest[next].1, next, closest[next].0));
+        current = next;
+    }
+    return edges;
+}
+
+struct Condensed {
+    len: usize,
+    values: Vec<f64>,
+}
+
+impl Condensed {
+    fn index(&self, a: usize, b: usize) -> usize {
+        let (i, j) = if a < b { (a, b) } else { (b, a) };
+        return self.len * i - i * (i + 1) / 2 + (j - i - 1);
+    }
+
+    fn get(&self, a: usize, b: usize) -> f64 {
+        return self.values[self.index(a, b)];
+    }
+
+    fn set(&mut self, a: usize, b: usize, value: f64) {
+        let index = self.index(a, b);
+        self.values[index] = value;
+    }
+}
+
+// nearest neighbour chain with Lance-Williams updates, valid for every
+// reducible linkage; ward works on squared distances internally
+fn nearest_neighbor_chain(
+    points: &Vec<Vec<f64>>,
+    distance: &Distance,
+    linkage: &Linkage,
+) -> Vec<(usize, usize, f64)> {
+    let rows = points.len();
+    let mut values = Vec::with_capacity(rows * rows.saturating_sub(1) / 2);
+    for i in 0..rows {
+        for j in i + 1..rows {
+            let d = distance.between(&points[i], &points[j]);
+            values.push(if *linkage == Linkage::Ward { d * d } else { d });
+        }
+    }
+    let mut distances = Condensed { len: rows, values };
+    let mut active = vec![true; rows];
+    let mut sizes = vec![1usize; rows];
+    let mut chain: Vec<usize> = Vec::new();
+    let mut merges = Vec::with_capacity(rows.saturating_sub(1));
+    while merges.len() + 1 < rows {
+        if chain.is_empty() {
+            chain.push(active.iter().position(|is_active| *is_active).unwrap());
+        }
+        let (a, b) = loop {
+            let top = chain[chain.len() - 1];
+            let previous = if chain.len() > 1 {
+                Some(chain[chain.len() - 2])
+            } else {
+                None
+            };
+            // preferring the previous link on ties keeps the chain acyclic
+            let mut best = previous.map(|previous| (previous, distances.get(top, previous)));
+            for (other, is_active) in active.iter().enumerate() {
+                if other == top || !is_active {
+                    continue;
+                }
+                let d = distances.get(top, other);
+                if best.is_none_or(|(_, best)| d < best) {
+                    best = Some((other, d));
+                }
+            }
+            let (nearest, _) = best.unwrap();
+            if Some(nearest) == previous {
+                chain.pop();
+                chain.pop();
+                break (top, nearest);
+            }
+            chain.push(nearest);
+        };
+        let height = distances.get(a, b);
+        let (size_a, size_b) = (sizes[a] as f64, sizes[b] as f64);
+        // the merged cluster lives on in `b`
+        for other in 0..rows {
+            if !active[other] || other == a || other == b {
+                continue;
+            }
+            let (d_a, d_b) = (distances.get(a, other), distances.get(b, other));
+            let size_other = sizes[other] as f64;
+            let updated = match linkage {
+                Linkage::Single => d_a.min(d_b),
+                Linkage::Complete => d_a.max(d_b),
+                Linkage::Average => (size_a * d_a + size_b * d_b) / (size_a + size_b),
+                Linkage::Ward => {
+                    ((size_a + size_other) * d_a + (size_b + size_other) * d_b
+                        - size_other * height)
+                        / (size_a + size_b + size_other)
+                }
+            };
+            distances.set(b, other, updated);
+        }
+        active[a] = false;
+        sizes[b] += sizes[a];
+        let height = if *linkage == Linkage::Ward {
+            height.max(0.0).sqrt()
+        } else {
+            height
+        };
+        merges.push((a, b, height));
+    }
+    return merges;
+}
+
+// the tree is cut either by `num_clusters` or by `distance_threshold`, the
+// other one reads `None` and setting either switches the cut
+impl Estimator for AgglomerativeClustering {
+    fn params(&self) -> Vec<(&'static str, ParamValue)> {
+        let (num_clusters, threshold) = match self.cut {
+            Cut::Clusters(num_clusters) => (Some(num_clusters), None),
+            Cut::Height(threshold) => (None, Some(threshold)),
+        };
+        return vec![
+            ("num_clusters", ParamValue::OptionalInt(num_clusters)),
+            ("distance_threshold", ParamValue::OptionalFloat(threshold)),
+        ];
+    }
+
+    fn set_param(&mut self, name: &str, value: ParamValue) -> Result<(), ParamError> {
+        match name {
+            "num_clusters" => match value.as_optional_int(name)? {
+                Some(num_clusters) => self.set_num_clusters(num_clusters),
+                None => {
+                    return Err(ParamError::WrongType {
+                        name: name.to_string(),
+                        expected: "a cluster count",
+                    });
+                }
+            },
+            "distance_threshold" => match value.as_optional_float(name)? {
+                Some(threshold) => self.set_distance_threshold(threshold),
+                None => {
+                    return Err(ParamError::WrongType {
+                        name: name.to_string(),
+                        expected: "a linkage distance",
+                    });
+                }
+            },
+            _ => return Err(ParamError::Unknown(name.to_string())),
+        }
+        Ok(())
+    }
+}
+
+impl Clusterer for AgglomerativeClustering {
+    fn fit(&mut self, data: &Matrix) -> Result<(), LinalgError> {
+        AgglomerativeClustering::fit(self, data);
+        Ok(())
+    }
+
+    fn labels(&self) -> RowVector {
+        return AgglomerativeClustering::labels(self);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sampling::random::Rng;
+
+    fn random_points(rows: usize, seed: u64) -> Matrix {
+        let mut rng = Rng::new(seed);
+        let data: Vec<Vec<f32>> = (0..rows)
+            .map(|_| vec![rng.normal(), rng.normal()])
+            .collect();
+        Matrix::to_matrix(&data)
+    }
+
+    // textbook cubic algorithm over explicit clusters
+    fn naive_heights(data: &Matrix, linkage: &Linkage) -> Vec<f32> {
+        let points: Vec<Vec<f32>> = data
+            .matrix()
+            .iter()
+            .map(|row| row.vector().clone())
+            .collect();
+        let mut clusters: Vec<Vec<usize>> = (0..points.len()).map(|row| vec![row]).collect();
+        let d = |a: usize, b: usize| Distance::Euclidean.distance(&points[a], &points[b]) as f64;
+        let centroid = |cluster: &Vec<usize>| -> Vec<f64> {
+            (0..2)
+                .map(|j| {
+                    cluster
+                        .iter()
+                        .map(|row| points[*row][j] as f64)
+                        .sum::<f64>()
+                        / cluster.len() as f64
+                })
+                .collect()
+        };
+        let mut heights = Vec::new();
+        while clusters.len() > 1 {
+            let mut best = (0, 0, f64::INFINITY);
+            for i in 0..clusters.len() {
+                for j in i + 1..clusters.len() {
+                    let pairs = clusters[i]
+                        .iter()
+                        .flat_map(|a| clusters[j].iter().map(move |b| (*a, *b)));
+                    let value = match linkage {
+                        Linkage::Single => {
+                            pairs.map(|(a, b)| d(a, b)).fold(f64::INFINITY, f64::min)
+                        }
+                        Linkage::Complete => pairs.map(|(a, b)| d(a, b)).fold(0.0, f64::max),
+                        Linkage::Average => {
+                            pairs.map(|(a, b)| d(a, b)).sum::<f64>()
+                                / (clusters[i].len() * clusters[j].len()) as f64
+                        }
+                        Linkage::Ward => {
+                            let (ci, cj) = (centroid(&clusters[i]), centroid(&clusters[j]));
+                            let (ni, nj) = (clusters[i].len() as f64, clusters[j].len() as f64);
+                            let squared: f64 = ci
+                                .iter()
+                                .zip(cj.iter())
+                                .map(|(x, y)| (x - y) * (x - y))
+                                .sum();
+                            (2.0 * ni * nj / (ni + nj) * squared).sqrt()
+                        }
+                    };
+                    if value < best.2 {
+                        best = (i, j, value);
+                    }
+                }
+            }
+            let merged = clusters.remove(best.1);
+            clusters[best.0].extend(merged);
+            heights.push(best.2 as f32);
+        }
+        heights
+    }
+
+    #[test]
+    fn test_linkages_match_naive_merges() {
+        let data = random_points(40, 1);
+        for linkage in [
+            Linkage::Single,
+            Linkage::Complete,
+            Linkage::Average,
+            Linkage::Ward,
+        ] {
+            let mut model = AgglomerativeClustering::new(3, linkage.clone());
+            model.fit(&data);
+            let tree = model.tree();
+            assert!(tree.merges().len() == 39);
+            assert!(tree.merges().last().unwrap().size == 40);
+            let expected = naive_heights(&data, &linkage);
+            for (merge, height) in tree.merges().iter().zip(expected.iter()) {
+                assert!((merge.height - height).abs() < 1e-4 * height.max(1.0));
+            }
+            assert!(model.num_clusters() == 3);
+            assert!(tree.cut(3).vector() == model.labels().vector());
+        }
+    }
+
+    #[test]
+    fn test_single_linkage_follows_rings_and_cuts() {
+        let mut rng = Rng::new(2);
+        let mut data = Vec::new();
+        for radius in [1.0, 4.0] {
+            for _ in 0..150 {
+                let angle = rng.next_f32() * std::f32::consts::TAU;
+                data.push(vec![radius * angle.cos(), radius * angle.sin()]);
+            }
+        }
+        let data = Matrix::to_matrix(&data);
+        let mut single = AgglomerativeClustering::new(2, Linkage::Single);
+        let labels = single.fit_predict(&data);
+        assert!((0..150).all(|row| labels.get(row) == 0.0));
+        assert!((150..300).all(|row| labels.get(row) == 1.0));
+
+        let mut ward = AgglomerativeClustering::new(2, Linkage::Ward);
+        let ward_labels = ward.fit_predict(&data);
+        assert!(!(150..300).all(|row| ward_labels.get(row) == ward_labels.get(150)));
+
+        // cutting between the last two merge heights leaves two clusters
+        let tree = single.tree();
+        let merges = tree.merges();
+        let between = (merges[297].height + merges[298].height) / 2.0;
+        assert!(tree.cut_at_height(between).vector() == labels.vector());
+        assert!(tree.cut(300).vector() == &(0..300).map(|row| row as f32).collect::<Vec<f32>>());
+        assert!(tree.cut(1).vector().iter().all(|label| *label == 0.0));
+        single.set_distance_threshold(between);
+        single.fit(&data);
+        assert!(single.num_clusters() == 2);
+    }
+}
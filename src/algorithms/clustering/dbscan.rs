@@ -0,0 +1,237 @@
+use super::kmeans::insert_cluster_labels;
+use crate::algorithms::estimators::{Clusterer, Estimator, ParamError, ParamValue};
+use crate::algorithms::neighbors::spatial::{Distance, NeighborIndex, NeighborsAlgorithm};
+use crate::dataframe::DataFrame;
+use crate::linear_algebra::{LinalgError, Matrix, RowVector};
+use crate::parallel::parallel::parallel_map;
+
+/// Label given to rows that belong to no cluster.
+pub const NOISE: f32 = -1.0;
+
+/// Density based clustering. Rows with at least `min_samples` rows (counting
+/// themselves) within `eps` are core rows, clusters are the rows reachable
+/// through chains of core rows and everything else is noise. Finds clusters
+/// of any shape without fixing their number up front.
+pub struct Dbscan {
+    eps: f32,
+    min_samples: usize,
+    distance: Distance,
+    algorithm: NeighborsAlgorithm,
+    leaf_size: usize,
+    labels: Vec<Option<usize>>,
+    core_samples: Vec<usize>,
+    num_clusters: usize,
+}
+
+impl Dbscan {
+    pub fn new(eps: f32, min_samples: usize) -> Self {
+        if eps.is_nan() || eps <= 0.0 {
+            panic!("eps must be positive");
+        }
+        return Self {
+            eps,
+            min_samples: min_samples.max(1),
+            distance: Distance::Euclidean,
+            algorithm: NeighborsAlgorithm::Auto,
+            leaf_size: 30,
+            labels: Vec::new(),
+            core_samples: Vec::new(),
+            num_clusters: 0,
+        };
+    }
+
+    /// With haversine distance `eps` is in kilometres.
+    pub fn set_distance(&mut self, distance: Distance) {
+        self.distance = distance;
+    }
+
+    pub fn set_algorithm(&mut self, algorithm: NeighborsAlgorithm) {
+        self.algorithm = algorithm;
+    }
+
+    pub fn set_leaf_size(&mut self, leaf_size: usize) {
+        self.leaf_size = leaf_size.max(1);
+    }
+
+    pub fn fit(&mut self, data: &Matrix) {
+        let index = NeighborIndex::new(
+            data,
+            self.distance.clone(),
+            self.algorithm.clone(),
+            self.leaf_size,
+        );
+        let neighborhoods: Vec<Vec<usize>> = parallel_map(data.len(), 64, |row| {
+            index
+                .query_radius(data.get(row).vector(), self.eps)
+                .iter()
+                .map(|(neighbor, _)| *neighbor)
+                .collect()
+        });
+        let is_core: Vec<bool> = neighborhoods
+            .iter()
+            .map(|neighborhood| neighborhood.len() >= self.min_samples)
+            .collect();
+        let mut labels: Vec<Option<usize>> = vec![None; data.len()];
+        let mut num_clusters = 0;
+        for start in 0..data.len() {
+            if !is_core[start] || labels[start].is_some() {
+                continue;
+            }
+            // expand through core rows, border rows join the first
+            // cluster that reaches them
+            labels[start] = Some(num_clusters);
+            let mut frontier = vec![start];
+            while let Some(row) = frontier.pop() {
+                for neighbor in neighborhoods[row].iter() {
+                    if labels[*neighbor].is_none() {
+                        labels[*neighbor] = Some(num_clusters);
+                        if is_core[*neighbor] {
+                            frontier.push(*neighbor);
+                        }
+                    }
+                }
+            }
+            num_clusters += 1;
+        }
+        self.core_samples = (0..data.len()).filter(|row| is_core[*row]).collect();
+        self.labels = labels;
+        self.num_clusters = num_clusters;
+    }
+
+    /// Fits on numeric `feature_columns`, with haversine distance these must
+    /// be latitude then longitude.
+    pub fn fit_df(&mut self, df: &DataFrame, feature_columns: &Vec<String>) {
+        self.fit(&df.get_columns_as_df(feature_columns).as_matrix(false));
+    }
+
+    pub fn fit_predict(&mut self, data: &Matrix) -> RowVector {
+        self.fit(data);
+        return self.labels();
+    }
+
+    /// Cluster of every row, `NOISE` for rows in no cluster.
+    pub fn labels(&self) -> RowVector {
+        return RowVector::new(
+            &self
+                .labels
+                .iter()
+                .map(|label| label.map_or(NOISE, |label| label as f32))
+                .collect(),
+        );
+    }
+
+    pub fn insert_labels(&self, df: &mut DataFrame, column_name: &str) {
+        insert_cluster_labels(df, column_name, &self.labels());
+    }
+
+    /// Rows with at least `min_samples` rows within `eps`.
+    pub fn core_samples(&self) -> &Vec<usize> {
+        return &self.core_samples;
+    }
+
+    pub fn num_clusters(&self) -> usize {
+        return self.num_clusters;
+    }
+
+    pub fn num_noise(&self) -> usize {
+        return self.labels.iter().filter(|label| label.is_none()).count();
+    }
+}
+
+impl Estimator for Dbscan {
+    fn params(&self) -> Vec<(&'static str, ParamValue)> {
+        return vec![
+            ("eps", ParamValue::Float(self.eps)),
+            ("min_samples", ParamValue::Int(self.min_samples)),
+            ("leaf_size", ParamValue::Int(self.leaf_size)),
+        ];
+    }
+
+    fn set_param(&mut self, name: &str, value: ParamValue) -> Result<(), ParamError> {
+        match name {
+            "eps" => {
+                let eps = value.as_float(name)?;
+                if eps.is_nan() || eps <= 0.0 {
+                    panic!("eps must be positive");
+                }
+                self.eps = eps;
+            }
+            "min_samples" => self.min_samples = value.as_int(name)?.max(1),
+            "leaf_size" => self.set_leaf_size(value.as_int(name)?),
+            _ => return Err(ParamError::Unknown(name.to_string())),
+        }
+        Ok(())
+    }
+}
+
+impl Clusterer for Dbscan {
+    fn fit(&mut self, data: &Matrix) -> Result<(), LinalgError> {
+        Dbscan::fit(self, data);
+        Ok(())
+    }
+
+    fn labels(&self) -> RowVector {
+        return Dbscan::labels(self);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::clustering::KMeans;
+    use crate::dataframe::csv::df_from_csv;
+    use crate::sampling::random::Rng;
+
+    // two noisy concentric rings
+    fn rings(per_ring: usize, seed: u64) -> Matrix {
+        let mut rng = Rng::new(seed);
+        let mut data = Vec::new();
+        for radius in [1.0, 4.0] {
+            for _ in 0..per_ring {
+                let angle = rng.next_f32() * std::f32::consts::TAU;
+                let r = radius + 0.1 * rng.normal();
+                data.push(vec![r * angle.cos(), r * angle.sin()]);
+            }
+        }
+        Matrix::to_matrix(&data)
+    }
+
+    #[test]
+    fn test_dbscan_separates_rings() {
+        let mut data = rings(300, 1).matrix().clone();
+        data.push(RowVector::new(&vec![10.0, 10.0]));
+        data.push(RowVector::new(&vec![-10.0, 10.0]));
+        let data = Matrix::new(&data);
+        let mut model = Dbscan::new(0.5, 4);
+        let labels = model.fit_predict(&data);
+        assert!(model.num_clusters() == 2);
+        assert!(model.num_noise() == 2);
+        assert!(labels.get(600) == NOISE && labels.get(601) == NOISE);
+        let inner = labels.get(0);
+        assert!((0..300).all(|row| labels.get(row) == inner));
+        assert!((300..600).all(|row| labels.get(row) != inner && labels.get(row) != NOISE));
+        assert!(!model.core_samples().contains(&600));
+
+        // k-means cuts straight through both rings instead
+        let mut kmeans = KMeans::new(2);
+        let kmeans_labels = kmeans.fit_predict(&data);
+        let first = kmeans_labels.get(0);
+        assert!(!(0..300).all(|row| kmeans_labels.get(row) == first));
+
+        let mut brute = Dbscan::new(0.5, 4);
+        brute.set_algorithm(NeighborsAlgorithm::BruteForce);
+        assert!(brute.fit_predict(&data).vector() == labels.vector());
+    }
+
+    #[test]
+    fn test_dbscan_on_housing_locations() {
+        let mut df = df_from_csv("housing.csv", Some(3000));
+        let mut model = Dbscan::new(5.0, 20);
+        model.set_distance(Distance::Haversine);
+        model.fit_df(&df, &vec!["latitude".to_string(), "longitude".to_string()]);
+        assert!(model.num_clusters() > 1);
+        assert!(model.num_noise() < 3000);
+        model.insert_labels(&mut df, "location_cluster");
+        assert!(df.get_column("location_cluster").1.len() == 3000);
+    }
+}
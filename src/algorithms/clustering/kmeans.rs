@@ -0,0 +1,790 @@
+use crate::algorithms::estimators::{Clusterer, Estimator, ParamError, ParamValue};
+use crate::dataframe::{DataFrame, DataType, DataTypeValue};
+use crate::linear_algebra::{LinalgError, Matrix, RowVector};
+use crate::parallel::parallel::parallel_map;
+use crate::sampling::random::Rng;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum KMeansInit {
+    /// Spreads the initial centroids out by sampling each new one with
+    /// probability proportional to its squared distance from the chosen ones.
+    KMeansPlusPlus,
+    /// Distinct rows drawn uniformly at random.
+    Random,
+}
+
+/// Lloyd's k-means: alternately assigns every row to its closest centroid and
+/// moves every centroid to the mean of its rows, keeping the best of several
+/// restarts by inertia (the sum of squared distances to the closest centroid).
+pub struct KMeans {
+    num_clusters: usize,
+    init: KMeansInit,
+    num_restarts: usize,
+    max_iterations: usize,
+    tolerance: f32,
+    seed: u64,
+    centroids: Vec<Vec<f64>>,
+    labels: Vec<usize>,
+    inertia: f64,
+    iterations: usize,
+    converged: bool,
+}
+
+/// k-means fitted on random mini-batches, each centroid moving towards its
+/// batch rows with a step of one over the number of rows it has seen so far.
+/// Much cheaper than `KMeans` on large frames at a slightly higher inertia.
+pub struct MiniBatchKMeans {
+    num_clusters: usize,
+    batch_size: usize,
+    num_restarts: usize,
+    max_epochs: usize,
+    tolerance: f32,
+    patience: usize,
+    seed: u64,
+    rng: Rng,
+    centroids: Vec<Vec<f64>>,
+    counts: Vec<usize>,
+    labels: Vec<usize>,
+    inertia: f64,
+    steps: usize,
+}
+
+impl KMeans {
+    pub fn new(num_clusters: usize) -> Self {
+        if num_clusters == 0 {
+            panic!("number of clusters must be positive");
+        }
+        return Self {
+            num_clusters,
+            init: KMeansInit::KMeansPlusPlus,
+            num_restarts: 10,
+            max_iterations: 300,
+            tolerance: 1e-4,
+            seed: 0,
+            centroids: Vec::new(),
+            labels: Vec::new(),
+            inertia: 0.0,
+            iterations: 0,
+            converged: false,
+        };
+    }
+
+    pub fn set_init(&mut self, init: KMeansInit) {
+        self.init = init;
+    }
+
+    /// Independent initializations to run, the lowest inertia one is kept.
+    pub fn set_num_restarts(&mut self, num_restarts: usize) {
+        self.num_restarts = num_restarts.max(1);
+    }
+
+    pub fn set_max_iterations(&mut self, max_iterations: usize) {
+        self.max_iterations = max_iterations.max(1);
+    }
+
+    /// Stops once the squared centroid movement of an iteration falls below
+    /// `tolerance` times the mean feature variance.
+    pub fn set_tolerance(&mut self, tolerance: f32) {
+        self.tolerance = tolerance;
+    }
+
+    pub fn set_seed(&mut self, seed: u64) {
+        self.seed = seed;
+    }
+
+    pub fn fit(&mut self, data: &Matrix) {
+        let data = data.to_f64();
+        check_data(&data, self.num_clusters);
+        let threshold = self.tolerance as f64 * mean_variance(&data);
+        let mut rng = Rng::new(self.seed);
+        let all_rows: Vec<usize> = (0..data.len()).collect();
+        let mut best: Option<LloydRun> = None;
+        for _ in 0..self.num_restarts {
+            let centroids = match self.init {
+                KMeansInit::KMeansPlusPlus => {
+                    kmeans_plus_plus(&data, &all_rows, self.num_clusters, &mut rng)
+                }
+                KMeansInit::Random => random_rows(&data, self.num_clusters, &mut rng),
+            };
+            let run = lloyd(&data, centroids, self.max_iterations, threshold);
+            if best.as_ref().is_none_or(|best| run.inertia < best.inertia) {
+                best = Some(run);
+            }
+        }
+        let best = best.unwrap();
+        self.centroids = best.centroids;
+        self.labels = best.labels;
+        self.inertia = best.inertia;
+        self.iterations = best.iterations;
+        self.converged = best.converged;
+    }
+
+    /// Index of the closest centroid of every row.
+    pub fn predict(&self, data: &Matrix) -> RowVector {
+        return predict_labels(&self.centroids, data);
+    }
+
+    pub fn fit_predict(&mut self, data: &Matrix) -> RowVector {
+        self.fit(data);
+        return self.labels();
+    }
+
+    /// Negative inertia of `data` under the fitted centroids, higher is better.
+    pub fn score(&self, data: &Matrix) -> f32 {
+        return -score_inertia(&self.centroids, data);
+    }
+
+    /// Cluster of every training row.
+    pub fn labels(&self) -> RowVector {
+        return RowVector::new(&self.labels.iter().map(|label| *label as f32).collect());
+    }
+
+    /// Inserts the training labels as a float column, `df` must have the rows
+    /// the model was fitted on in the same order (such as the frame passed to
+    /// `ColumnTransformer::transform`).
+    pub fn insert_labels(&self, df: &mut DataFrame, column_name: &str) {
+        insert_cluster_labels(df, column_name, &self.labels());
+    }
+
+    pub fn centroids(&self) -> Matrix {
+        return Matrix::from_f64(&self.centroids);
+    }
+
+    pub fn inertia(&self) -> f32 {
+        return self.inertia as f32;
+    }
+
+    pub fn num_clusters(&self) -> usize {
+        return self.num_clusters;
+    }
+
+    pub fn iterations(&self) -> usize {
+        return self.iterations;
+    }
+
+    pub fn converged(&self) -> bool {
+        return self.converged;
+    }
+}
+
+impl MiniBatchKMeans {
+    pub fn new(num_clusters: usize, batch_size: usize) -> Self {
+        if num_clusters == 0 || batch_size == 0 {
+            panic!("number of clusters and batch size must be positive");
+        }
+        return Self {
+            num_clusters,
+            batch_size,
+            num_restarts: 3,
+            max_epochs: 100,
+            tolerance: 0.0,
+            patience: 10,
+            seed: 0,
+            rng: Rng::new(0),
+            centroids: Vec::new(),
+            counts: Vec::new(),
+            labels: Vec::new(),
+            inertia: 0.0,
+            steps: 0,
+        };
+    }
+
+    /// k-means++ initializations tried on a sample of rows, the lowest
+    /// inertia one is kept.
+    pub fn set_num_restarts(&mut self, num_restarts: usize) {
+        self.num_restarts = num_restarts.max(1);
+    }
+
+    /// Upper bound on the passes over the data.
+    pub fn set_max_epochs(&mut self, max_epochs: usize) {
+        self.max_epochs = max_epochs.max(1);
+    }
+
+    /// Stops once the squared centroid movement of a batch falls below
+    /// `tolerance` times the mean feature variance, 0 disables the check.
+    pub fn set_tolerance(&mut self, tolerance: f32) {
+        self.tolerance = tolerance;
+    }
+
+    /// Stops after this many batches without a lower smoothed batch inertia.
+    pub fn set_patience(&mut self, patience: usize) {
+        self.patience = patience.max(1);
+    }
+
+    pub fn set_seed(&mut self, seed: u64) {
+        self.seed = seed;
+        self.rng = Rng::new(seed);
+    }
+
+    pub fn fit(&mut self, data: &Matrix) {
+        let data = data.to_f64();
+        check_data(&data, self.num_clusters);
+        let rows = data.len();
+        let threshold = self.tolerance as f64 * mean_variance(&data);
+        self.rng = Rng::new(self.seed);
+        self.initialize(&data);
+        let batch_size = self.batch_size.min(rows);
+        let max_steps = self.max_epochs * rows.div_ceil(batch_size);
+        let mut smoothed: Option<f64> = None;
+        let mut best = f64::INFINITY;
+        let mut without_improvement = 0;
+        for _ in 0..max_steps {
+            let batch: Vec<usize> = (0..batch_size).map(|_| self.rng.gen_range(rows)).collect();
+            let (batch_inertia, shift) = self.step(&data, &batch);
+            // exponentially weighted batch inertia, a single batch is too noisy
+            let alpha = (batch_size as f64 * 2.0 / (rows as f64 + 1.0)).min(1.0);
+            let average = match smoothed {
+                Some(previous) => previous * (1.0 - alpha) + batch_inertia * alpha,
+                None => batch_inertia,
+            };
+            smoothed = Some(average);
+            if threshold > 0.0 && shift <= threshold {
+                break;
+            }
+            if average < best {
+                best = average;
+                without_improvement = 0;
+            } else {
+                without_improvement += 1;
+                if without_improvement >= self.patience {
+                    break;
+                }
+            }
+        }
+        let assignments = assign(&data, &self.centroids);
+        self.labels = assignments.iter().map(|(label, _)| *label).collect();
+        self.inertia = assignments.iter().map(|(_, distance)| distance).sum();
+    }
+
+    /// Updates the centroids with one batch, initializing them from it on the
+    /// first call. Labels and inertia then describe this batch only.
+    pub fn partial_fit(&mut self, data: &Matrix) {
+        let data = data.to_f64();
+        if self.centroids.is_empty() {
+            check_data(&data, self.num_clusters);
+            self.initialize(&data);
+        }
+        let batch: Vec<usize> = (0..data.len()).collect();
+        self.step(&data, &batch);
+        let assignments = assign(&data, &self.centroids);
+        self.labels = assignments.iter().map(|(label, _)| *label).collect();
+        self.inertia = assignments.iter().map(|(_, distance)| distance).sum();
+    }
+
+    fn initialize(&mut self, data: &Vec<Vec<f64>>) {
+        let rows = data.len();
+        let sample_len = (3 * self.batch_size).max(3 * self.num_clusters).min(rows);
+        let sample: Vec<usize> = self.rng.permutation(rows)[..sample_len].to_vec();
+        let sample_data: Vec<Vec<f64>> = sample.iter().map(|row| data[*row].clone()).collect();
+        let all_sample: Vec<usize> = (0..sample_len).collect();
+        let mut best: Option<(f64, Vec<Vec<f64>>)> = None;
+        for _ in 0..self.num_restarts {
+            let centroids =
+                kmeans_plus_plus(&sample_data, &all_sample, self.num_clusters, &mut self.rng);
+            let inertia: f64 = assign(&sample_data, &centroids)
+                .iter()
+                .map(|(_, distance)| distance)
+                .sum();
+            if best.as_ref().is_none_or(|(best, _)| inertia < *best) {
+                best = Some((inertia, centroids));
+            }
+        }
+        self.centroids = best.unwrap().1;
+        self.counts = vec![0; self.num_clusters];
+        self.steps = 0;
+    }
+
+    // moves the centroids towards the batch rows, returning the batch inertia
+    // per row and the total squared centroid movement
+    fn step(&mut self, data: &Vec<Vec<f64>>, batch: &Vec<usize>) -> (f64, f64) {
+        let batch_data: Vec<Vec<f64>> = batch.iter().map(|row| data[*row].clone()).collect();
+        let assignments = assign(&batch_data, &self.centroids);
+        let previous = self.centroids.clone();
+        for (point, (label, _)) in batch_data.iter().zip(assignments.iter()) {
+            self.counts[*label] += 1;
+            let rate = 1.0 / self.counts[*label] as f64;
+            for (centroid, value) in self.centroids[*label].iter_mut().zip(point.iter()) {
+                *centroid += rate * (value - *centroid);
+            }
+        }
+        self.steps += 1;
+        let inertia = assignments
+            .iter()
+            .map(|(_, distance)| distance)
+            .sum::<f64>()
+            / batch.len().max(1) as f64;
+        let shift = previous
+            .iter()
+            .zip(self.centroids.iter())
+            .map(|(old, new)| squared_distance(old, new))
+            .sum();
+        return (inertia, shift);
+    }
+
+    pub fn predict(&self, data: &Matrix) -> RowVector {
+        return predict_labels(&self.centroids, data);
+    }
+
+    pub fn fit_predict(&mut self, data: &Matrix) -> RowVector {
+        self.fit(data);
+        return self.labels();
+    }
+
+    pub fn score(&self, data: &Matrix) -> f32 {
+        return -score_inertia(&self.centroids, data);
+    }
+
+    /// Cluster of every row of the last `fit` (or batch of `partial_fit`).
+    pub fn labels(&self) -> RowVector {
+        return RowVector::new(&self.labels.iter().map(|label| *label as f32).collect());
+    }
+
+    pub fn insert_labels(&self, df: &mut DataFrame, column_name: &str) {
+        insert_cluster_labels(df, column_name, &self.labels());
+    }
+
+    pub fn centroids(&self) -> Matrix {
+        return Matrix::from_f64(&self.centroids);
+    }
+
+    pub fn inertia(&self) -> f32 {
+        return self.inertia as f32;
+    }
+
+    pub fn num_clusters(&self) -> usize {
+        return self.num_clusters;
+    }
+
+    /// Batches processed since the centroids were initialized.
+    pub fn steps(&self) -> usize {
+        return self.steps;
+    }
+}
+
+/// Inserts cluster labels (such as `predict` output) as a float column.
+pub fn insert_cluster_labels(df: &mut DataFrame, column_name: &str, labels: &RowVector) {
+    if labels.len() != df.len() {
+        panic!(
+            "{} labels cannot be inserted into a dataframe with {} rows",
+            labels.len(),
+            df.len()
+        );
+    }
+    let values = labels
+        .vector()
+        .iter()
+        .map(|label| DataTypeValue::Float(*label))
+        .collect();
+    df.insert_column(column_name, &values, &DataType::Float);
+}
+
+struct LloydRun {
+    centroids: Vec<Vec<f64>>,
+    labels: Vec<usize>,
+    inertia: f64,
+    iterations: usize,
+    converged: bool,
+}
+
+fn check_data(data: &Vec<Vec<f64>>, num_clusters: usize) {
+    if data.len() < num_clusters {
+        panic!(
+            "cannot form {} clusters from {} rows",
+            num_clusters,
+            data.len()
+        );
+    }
+    if data.iter().flatten().any(|value| !value.is_finite()) {
+        panic!("k-means data must be finite");
+    }
+}
+
+fn squared_distance(a: &[f64], b: &[f64]) -> f64 {
+    return a.iter().zip(b.iter()).map(|(x, y)| (x - y) * (x - y)).sum();
+}
+
+// closest centroid and squared distance to it
+fn nearest(point: &[f64], centroids: &Vec<Vec<f64>>) -> (usize, f64) {
+    let mut best = (0, f64::INFINITY);
+    for (index, centroid) in centroids.iter().enumerate() {
+        let distance = squared_distance(point, centroid);
+        if distance < best.1 {
+            best = (index, distance);
+        }
+    }
+    return best;
+}
+
+fn assign(data: &Vec<Vec<f64>>, centroids: &Vec<Vec<f64>>) -> Vec<(usize, f64)> {
+    return parallel_map(data.len(), 256, |row| nearest(&data[row], centroids));
+}
+
+fn predict_labels(centroids: &Vec<Vec<f64>>, data: &Matrix) -> RowVector {
+    if centroids.is_empty() {
+        panic!("k-means must be fitted first");
+    }
+    let labels = assign(&data.to_f64(), centroids)
+        .iter()
+        .map(|(label, _)| *label as f32)
+        .collect();
+    return RowVector::new(&labels);
+}
+
+fn score_inertia(centroids: &Vec<Vec<f64>>, data: &Matrix) -> f32 {
+    if centroids.is_empty() {
+        panic!("k-means must be fitted first");
+    }
+    return assign(&data.to_f64(), centroids)
+        .iter()
+        .map(|(_, distance)| distance)
+        .sum::<f64>() as f32;
+}
+
+fn mean_variance(data: &Vec<Vec<f64>>) -> f64 {
+    let rows = data.len() as f64;
+    let columns = data[0].len();
+    if columns == 0 {
+        return 0.0;
+    }
+    return (0..columns)
+        .map(|j| {
+            let mean = data.iter().map(|row| row[j]).sum::<f64>() / rows;
+            data.iter().map(|row| (row[j] - mean).powi(2)).sum::<f64>() / rows
+        })
+        .sum::<f64>()
+        / columns as f64;
+}
+
+fn random_rows(data: &Vec<Vec<f64>>, num_clusters: usize, rng: &mut Rng) -> Vec<Vec<f64>> {
+    return rng.permutation(data.len())[..num_clusters]
+        .iter()
+        .map(|row| data[*row].clone())
+        .collect();
+}
+
+// greedy k-means++ over `rows`: each new centroid is the best of a few
+// candidates sampled proportionally to the squared distance
+fn kmeans_plus_plus(
+    data: &Vec<Vec<f64>>,
+    rows: &Vec<usize>,
+    num_clusters: usize,
+    rng: &mut Rng,
+) -> Vec<Vec<f64>> {
+    let trials = 2 + (num_clusters as f64).ln() as usize;
+    let mut centroids = vec![data[rows[rng.gen_range(rows.len())]].clone()];
+    let mut closest: Vec<f64> = rows
+        .iter()
+        .map(|row| squared_distance(&data[*row], &centroids[0]))
+        .collect();
+    while centroids.len() < num_clusters {
+        let potential: f64 = closest.iter().sum();
+        let mut best: Option<(f64, usize, Vec<f64>)> = None;
+        for _ in 0..trials {
+            let candidate = if potential > 0.0 {
+                let target = rng.next_f64() * potential;
+                let mut cumulative = 0.0;
+                closest
+                    .iter()
+                    .position(|distance| {
+                        cumulative += distance;
+                        cumulative > target
+                    })
+                    .unwrap_or(rows.len() - 1)
+            } else {
+                rng.gen_range(rows.len())
+            };
+            let updated: Vec<f64> = rows
+                .iter()
+                .zip(closest.iter())
+                .map(|(row, distance)| {
+                    distance.min(squared_distance(&data[*row], &data[rows[candidate]]))
+                })
+                .collect();
+            let updated_potential: f64 = updated.iter().sum();
+            if best
+                .as_ref()
+                .is_none_or(|(best_potential, _, _)| updated_potential < *best_potential)
+            {
+                best = Some((updated_potential, candidate, updated));
+            }
+        }
+        let (_, candidate, updated) = best.unwrap();
+        centroids.push(data[rows[candidate]].clone());
+        closest = updated;
+    }
+    return centroids;
+}
+
+fn lloyd(
+    data: &Vec<Vec<f64>>,
+    mut centroids: Vec<Vec<f64>>,
+    max_iterations: usize,
+    threshold: f64,
+) -> LloydRun {
+    let num_clusters = centroids.len();
+    let columns = data[0].len();
+    let mut assignments = assign(data, &centroids);
+    let mut iterations = 0;
+    let mut converged = false;
+    while iterations < max_iterations {
+        iterations += 1;
+        let mut sums = vec![vec![0.0; columns]; num_clusters];
+        let mut counts = vec![0usize; num_clusters];
+        for (point, (label, _)) in data.iter().zip(assignments.iter()) {
+            counts[*label] += 1;
+            for (sum, value) in sums[*label].iter_mut().zip(point.iter()) {
+                *sum += value;
+            }
+        }
+        let mut updated: Vec<Vec<f64>> = sums
+            .iter()
+            .zip(counts.iter())
+            .map(|(sum, count)| sum.iter().map(|value| value / *count as f64).collect())
+            .collect();
+        // an empty cluster takes over the row farthest from its centroid
+        let mut taken = vec![false; data.len()];
+        for cluster in 0..num_clusters {
+            if counts[cluster] == 0 {
+                let farthest = (0..data.len())
+                    .filter(|row| !taken[*row])
+                    .max_by(|a, b| assignments[*a].1.total_cmp(&assignments[*b].1))
+                    .unwrap();
+                taken[farthest] = true;
+                updated[cluster] = data[farthest].clone();
+            }
+        }
+        let shift: f64 = centroids
+            .iter()
+            .zip(updated.iter())
+            .map(|(old, new)| squared_distance(old, new))
+            .sum();
+        centroids = updated;
+        assignments = assign(data, &centroids);
+        if shift <= threshold {
+            converged = true;
+            break;
+        }
+    }
+    return LloydRun {
+        labels: assignments.iter().map(|(label, _)| *label).collect(),
+        inertia: assignments.iter().map(|(_, distance)| distance).sum(),
+        centroids,
+        iterations,
+        converged,
+    };
+}
+
+impl Estimator for KMeans {
+    fn params(&self) -> Vec<(&'static str, ParamValue)> {
+        return vec![
+            ("num_clusters", ParamValue::Int(self.num_clusters)),
+            ("num_restarts", ParamValue::Int(self.num_restarts)),
+            ("max_iterations", ParamValue::Int(self.max_iterations)),
+            ("tolerance", ParamValue::Float(self.tolerance)),
+            ("seed", ParamValue::Int(self.seed as usize)),
+        ];
+    }
+
+    fn set_param(&mut self, name: &str, value: ParamValue) -> Result<(), ParamError> {
+        match name {
+            "num_clusters" => {
+                let num_clusters = value.as_int(name)?;
+                if num_clusters == 0 {
+                    panic!("number of clusters must be positive");
+                }
+                self.num_clusters = num_clusters;
+            }
+            "num_restarts" => self.set_num_restarts(value.as_int(name)?),
+            "max_iterations" => self.set_max_iterations(value.as_int(name)?),
+            "tolerance" => self.set_tolerance(value.as_float(name)?),
+            "seed" => self.set_seed(value.as_int(name)? as u64),
+            _ => return Err(ParamError::Unknown(name.to_string())),
+        }
+        Ok(())
+    }
+}
+
+impl Clusterer for KMeans {
+    fn fit(&mut self, data: &Matrix) -> Result<(), LinalgError> {
+        KMeans::fit(self, data);
+        Ok(())
+    }
+
+    fn labels(&self) -> RowVector {
+        return KMeans::labels(self);
+    }
+}
+
+impl Estimator for MiniBatchKMeans {
+    fn params(&self) -> Vec<(&'static str, ParamValue)> {
+        return vec![
+            ("num_clusters", ParamValue::Int(self.num_clusters)),
+            ("batch_size", ParamValue::Int(self.batch_size)),
+            ("num_restarts", ParamValue::Int(self.num_restarts)),
+            ("max_epochs", ParamValue::Int(self.max_epochs)),
+            ("tolerance", ParamValue::Float(self.tolerance)),
+            ("patience", ParamValue::Int(self.patience)),
+            ("seed", ParamValue::Int(self.seed as usize)),
+        ];
+    }
+
+    fn set_param(&mut self, name: &str, value: ParamValue) -> Result<(), ParamError> {
+        match name {
+            "num_clusters" | "batch_size" => {
+                let count = value.as_int(name)?;
+                if count == 0 {
+                    panic!("number of clusters and batch size must be positive");
+                }
+                if name == "num_clusters" {
+                    self.num_clusters = count;
+                } else {
+                    self.batch_size = count;
+                }
+            }
+            "num_restarts" => self.set_num_restarts(value.as_int(name)?),
+            "max_epochs" => self.set_max_epochs(value.as_int(name)?),
+            "tolerance" => self.set_tolerance(value.as_float(name)?),
+            "patience" => self.set_patience(value.as_int(name)?),
+            "seed" => self.set_seed(value.as_int(name)? as u64),
+            _ => return Err(ParamError::Unknown(name.to_string())),
+        }
+        Ok(())
+    }
+}
+
+impl Clusterer for MiniBatchKMeans {
+    fn fit(&mut self, data: &Matrix) -> Result<(), LinalgError> {
+        MiniBatchKMeans::fit(self, data);
+        Ok(())
+    }
+
+    fn labels(&self) -> RowVector {
+        return MiniBatchKMeans::labels(self);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dataframe::csv::df_from_csv;
+    use crate::pipeline::{
+        encoders::one_hot_encoder::OneHotEncoder,
+        imputers::imputer::{Imputer, ImputerStrategy},
+        pipeline::{CategoricalPipeline, ColumnTransformer, NumericalPipeline},
+        scalars::standard_scalar::StandardScalar,
+        transformers::Transformer,
+    };
+
+    const CENTERS: [[f32; 2]; 3] = [[0.0, 0.0], [8.0, 0.0], [0.0, 8.0]];
+
+    fn blobs(per_cluster: usize, seed: u64) -> Matrix {
+        let mut rng = Rng::new(seed);
+        let data: Vec<Vec<f32>> = CENTERS
+            .iter()
+            .flat_map(|center| {
+                (0..per_cluster)
+                    .map(|_| vec![center[0] + rng.normal(), center[1] + rng.normal()])
+                    .collect::<Vec<Vec<f32>>>()
+            })
+            .collect();
+        Matrix::to_matrix(&data)
+    }
+
+    fn to_f64(values: &[f32]) -> Vec<f64> {
+        values.iter().map(|value| *value as f64).collect()
+    }
+
+    // every true center has a fitted centroid close by
+    fn recovers_centers(centroids: &Matrix) -> bool {
+        CENTERS.iter().all(|center| {
+            centroids
+                .matrix()
+                .iter()
+                .any(|centroid| squared_distance(&to_f64(centroid.vector()), &to_f64(center)) < 0.1)
+        })
+    }
+
+    #[test]
+    fn test_kmeans_recovers_blobs() {
+        let data = blobs(200, 1);
+        let mut model = KMeans::new(3);
+        model.set_seed(7);
+        let labels = model.fit_predict(&data);
+        assert!(model.converged());
+        assert!(recovers_centers(&model.centroids()));
+        // rows of one blob share a label and the blobs get different labels
+        for blob in 0..3 {
+            let first = labels.get(blob * 200);
+            assert!((blob * 200..(blob + 1) * 200).all(|row| labels.get(row) == first));
+        }
+        assert!(model.predict(&model.centroids()).vector() == &vec![0.0, 1.0, 2.0]);
+        assert!((model.score(&data) + model.inertia()).abs() < 1e-3 * model.inertia());
+        assert!(model.inertia() / 600.0 < 2.2);
+
+        // random initialization and a single restart still give a valid fit
+        let mut random = KMeans::new(3);
+        random.set_init(KMeansInit::Random);
+        random.set_num_restarts(1);
+        random.fit(&data);
+        assert!(random.inertia() >= model.inertia() - 1e-3);
+        let mut again = KMeans::new(3);
+        again.set_seed(7);
+        again.fit(&data);
+        assert!(again.labels().vector() == labels.vector());
+    }
+
+    #[test]
+    fn test_mini_batch_kmeans() {
+        let data = blobs(2000, 2);
+        let mut full = KMeans::new(3);
+        full.fit(&data);
+        let mut model = MiniBatchKMeans::new(3, 100);
+        model.set_seed(3);
+        model.fit(&data);
+        assert!(recovers_centers(&model.centroids()));
+        assert!(model.inertia() < 1.02 * full.inertia());
+        assert!(model.steps() < 100 * 60);
+        assert!(model.labels().len() == 6000);
+
+        let mut streamed = MiniBatchKMeans::new(3, 100);
+        let mut rng = Rng::new(4);
+        let order = rng.permutation(6000);
+        for chunk in order.chunks(500) {
+            let batch: Vec<Vec<f32>> = chunk
+                .iter()
+                .map(|row| data.get(*row).vector().clone())
+                .collect();
+            streamed.partial_fit(&Matrix::to_matrix(&batch));
+        }
+        assert!(streamed.steps() == 12);
+        assert!(recovers_centers(&streamed.centroids()));
+    }
+
+    #[test]
+    fn test_kmeans_on_transformed_housing() {
+        let mut df = df_from_csv("housing.csv", Some(3000));
+        let encoder: Box<dyn Transformer> = Box::new(OneHotEncoder::new(false));
+        let imputer: Box<dyn Transformer> = Box::new(Imputer::new(&ImputerStrategy::Median));
+        let scalar: Box<dyn Transformer> = Box::new(StandardScalar::new());
+        let transformer = ColumnTransformer::new(
+            NumericalPipeline::new(vec![imputer, scalar]),
+            CategoricalPipeline::new(vec![encoder]),
+        );
+        let data = transformer.transform(&df);
+        let mut model = KMeans::new(5);
+        model.set_num_restarts(3);
+        model.fit(&data);
+        model.insert_labels(&mut df, "cluster");
+        let (dtype, values) = df.get_column("cluster");
+        assert!(matches!(dtype, DataType::Float));
+        let mut sizes = [0; 5];
+        for value in values.iter() {
+            match value {
+                DataTypeValue::Float(label) => sizes[*label as usize] += 1,
+                _ => panic!("labels must be floats"),
+            }
+        }
+        assert!(sizes.iter().all(|size| *size > 0));
+        assert!(sizes.iter().sum::<usize>() == 3000);
+    }
+}
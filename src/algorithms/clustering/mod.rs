@@ -0,0 +1,6 @@
+pub mod agglomerative;
+pub mod dbscan;
+pub mod kmeans;
+pub use agglomerative::{AgglomerativeClustering, Linkage, MergeTree};
+pub use dbscan::Dbscan;
+pub use kmeans::{KMeans, MiniBatchKMeans};
@@ -0,0 +1,502 @@
+use super::models::CurveModel;
+use crate::linear_algebra::{LinalgError, Matrix, decompositions::CholeskyDecomposition};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Termination {
+    /// The accepted step was small relative to the parameters.
+    SmallStep,
+    /// The accepted step barely reduced the residual sum of squares.
+    SmallCostChange,
+    /// The gradient of the residual sum of squares vanished.
+    SmallGradient,
+    /// No step reduced the residuals, even with maximal damping.
+    NoImprovement,
+    MaxIterations,
+}
+
+/// Diagnostics of a finished fit.
+#[derive(Clone, Debug)]
+pub struct CurveFitResult {
+    parameters: Vec<f32>,
+    covariance: Option<Matrix>,
+    residual_sum_of_squares: f32,
+    cost_history: Vec<f32>,
+    iterations: usize,
+    evaluations: usize,
+    termination: Termination,
+}
+
+impl CurveFitResult {
+    pub fn parameters(&self) -> &Vec<f32> {
+        return &self.parameters;
+    }
+
+    /// Asymptotic covariance `s²·(JᵀJ)⁻¹` of the parameters with
+    /// `s² = RSS / (n - p)`, `None` when the Jacobian is rank deficient or
+    /// there are no more rows than parameters.
+    pub fn covariance(&self) -> Option<&Matrix> {
+        return self.covariance.as_ref();
+    }
+
+    /// Square roots of the covariance diagonal.
+    pub fn standard_errors(&self) -> Option<Vec<f32>> {
+        return self.covariance.as_ref().map(|covariance| {
+            (0..self.parameters.len())
+                .map(|j| covariance.get(j).get(j).max(0.0).sqrt())
+                .collect()
+        });
+    }
+
+    pub fn residual_sum_of_squares(&self) -> f32 {
+        return self.residual_sum_of_squares;
+    }
+
+    /// Residual sum of squares after the initial guess and every accepted step.
+    pub fn cost_history(&self) -> &Vec<f32> {
+        return &self.cost_history;
+    }
+
+    pub fn iterations(&self) -> usize {
+        return self.iterations;
+    }
+
+    /// Model evaluations over all rows, Jacobians excluded.
+    pub fn evaluations(&self) -> usize {
+        return self.evaluations;
+    }
+
+    pub fn termination(&self) -> &Termination {
+        return &self.termination;
+    }
+
+    pub fn converged(&self) -> bool {
+        return matches!(
+            self.termination,
+            Termination::SmallStep | Termination::SmallCostChange | Termination::SmallGradient
+        );
+    }
+}
+
+/// Nonlinear least squares with Levenberg–Marquardt: every iteration solves
+/// the damped normal equations `(JᵀJ + λ·diag(JᵀJ))·δ = Jᵀr`, accepting the
+/// step and relaxing `λ` when it lowers the residual sum of squares and
+/// otherwise raising `λ` towards a short gradient descent step.
+pub struct CurveFit {
+    model: CurveModel,
+    max_iterations: usize,
+    tolerance: f32,
+    gradient_tolerance: f32,
+    initial_damping: f32,
+    result: Option<CurveFitResult>,
+}
+
+// cost and normal equations of the linearized model at one parameter vector
+struct Linearization {
+    cost: f64,
+    normal: Vec<Vec<f64>>,
+    gradient: Vec<f64>,
+}
+
+impl CurveFit {
+    pub fn new(model: CurveModel) -> Self {
+        return Self {
+            model,
+            max_iterations: 200,
+            tolerance: 1e-6,
+            gradient_tolerance: 1e-10,
+            initial_damping: 1e-3,
+            result: None,
+        };
+    }
+
+    pub fn set_max_iterations(&mut self, max_iterations: usize) {
+        self.max_iterations = max_iterations.max(1);
+    }
+
+    /// Relative size of the step and of the cost reduction below which the
+    /// fit has converged.
+    pub fn set_tolerance(&mut self, tolerance: f32) {
+        self.tolerance = tolerance;
+    }
+
+    /// Largest gradient component of the residual sum of squares at which the
+    /// fit has converged.
+    pub fn set_gradient_tolerance(&mut self, gradient_tolerance: f32) {
+        self.gradient_tolerance = gradient_tolerance;
+    }
+
+    /// Starting damping, larger values start closer to gradient descent.
+    pub fn set_initial_damping(&mut self, initial_damping: f32) {
+        self.initial_damping = initial_damping;
+    }
+
+    pub fn model(&self) -> &CurveModel {
+        return &self.model;
+    }
+
+    pub fn max_iterations(&self) -> usize {
+        return self.max_iterations;
+    }
+
+    pub fn tolerance(&self) -> f32 {
+        return self.tolerance;
+    }
+
+    fn residuals(&self, data: &Matrix, labels: &Vec<f32>, parameters: &[f64]) -> Vec<f64> {
+        let parameters: Vec<f32> = parameters.iter().map(|value| *value as f32).collect();
+        return data
+            .matrix()
+            .iter()
+            .zip(labels.iter())
+            .map(|(x, y)| (*y - self.model.evaluate(x.vector(), &parameters)) as f64)
+            .collect();
+    }
+
+    fn linearize(&self, data: &Matrix, residuals: &Vec<f64>, parameters: &[f64]) -> Linearization {
+        let num_parameters = parameters.len();
+        let parameters: Vec<f32> = parameters.iter().map(|value| *value as f32).collect();
+        let mut normal = vec![vec![0.0; num_parameters]; num_parameters];
+        let mut gradient = vec![0.0; num_parameters];
+        let mut row_derivatives = vec![0.0; num_parameters];
+        for (x, residual) in data.matrix().iter().zip(residuals.iter()) {
+            self.model
+                .derivatives(x.vector(), &parameters, &mut row_derivatives);
+            for i in 0..num_parameters {
+                let di = row_derivatives[i] as f64;
+                gradient[i] += di * residual;
+                for j in i..num_parameters {
+                    normal[i][j] += di * row_derivatives[j] as f64;
+                }
+            }
+        }
+        for i in 1..num_parameters {
+            let (upper, lower) = normal.split_at_mut(i);
+            for (j, row) in upper.iter().enumerate() {
+                lower[0][j] = row[i];
+            }
+        }
+        let cost = residuals.iter().map(|r| r * r).sum();
+        return Linearization {
+            cost,
+            normal,
+            gradient,
+        };
+    }
+
+    /// Fits the parameters starting from `initial`, one row of `data` per
+    /// label. Running out of iterations is not an error, check `result()`.
+    pub fn fit(
+        &mut self,
+        data: &Matrix,
+        labels: &Vec<f32>,
+        initial: &Vec<f32>,
+    ) -> Result<(), LinalgError> {
+        let rows = data.len();
+        if labels.len() != rows {
+            return Err(LinalgError::DimensionMismatch {
+                expected: (rows, 1),
+                found: (labels.len(), 1),
+            });
+        }
+        let num_parameters = self.model.num_parameters();
+        if initial.len() != num_parameters {
+            return Err(LinalgError::DimensionMismatch {
+                expected: (num_parameters, 1),
+                found: (initial.len(), 1),
+            });
+        }
+        let mut parameters: Vec<f64> = initial.iter().map(|value| *value as f64).collect();
+        let residuals = self.residuals(data, labels, &parameters);
+        let mut evaluations = 1;
+        let mut state = self.linearize(data, &residuals, &parameters);
+        if !state.cost.is_finite() {
+            return Err(LinalgError::NoConvergence { iterations: 0 });
+        }
+        let mut cost_history = vec![state.cost as f32];
+        let tolerance = self.tolerance as f64;
+        let mut damping = self.initial_damping as f64;
+        let mut growth = 2.0;
+        let mut termination = Termination::MaxIterations;
+        let mut iterations = 0;
+        while iterations < self.max_iterations {
+            iterations += 1;
+            if state
+                .gradient
+                .iter()
+                .all(|g| g.abs() <= self.gradient_tolerance as f64)
+            {
+                termination = Termination::SmallGradient;
+                break;
+            }
+            // marquardt scaling, floored so unidentified parameters stay damped
+            let largest = (0..num_parameters).fold(0.0_f64, |acc, j| acc.max(state.normal[j][j]));
+            let scaling: Vec<f64> = (0..num_parameters)
+                .map(|j| {
+                    state.normal[j][j]
+                        .max(1e-12 * largest)
+                        .max(f64::MIN_POSITIVE)
+                })
+                .collect();
+            let Some(step) = damped_step(&state, &scaling, damping) else {
+                damping *= growth;
+                growth *= 2.0;
+                continue;
+            };
+            // reduction of the residual sum of squares predicted by the
+            // linearized model, too small to measure in f32 at a minimum
+            let predicted: f64 = step
+                .iter()
+                .enumerate()
+                .map(|(j, d)| d * (damping * scaling[j] * d + state.gradient[j]))
+                .sum();
+            if predicted <= tolerance * state.cost {
+                termination = Termination::SmallCostChange;
+                break;
+            }
+            let candidate: Vec<f64> = parameters
+                .iter()
+                .zip(step.iter())
+                .map(|(p, d)| p + d)
+                .collect();
+            let candidate_residuals = self.residuals(data, labels, &candidate);
+            evaluations += 1;
+            let candidate_cost: f64 = candidate_residuals.iter().map(|r| r * r).sum();
+            if candidate_cost.is_finite() && candidate_cost < state.cost {
+                let ratio = (state.cost - candidate_cost) / predicted.max(f64::MIN_POSITIVE);
+                let step_norm = step.iter().map(|d| d * d).sum::<f64>().sqrt();
+                let parameter_norm = parameters.iter().map(|p| p * p).sum::<f64>().sqrt();
+                let small_step = step_norm <= tolerance * (parameter_norm + tolerance);
+                let small_change = state.cost - candidate_cost <= tolerance * state.cost;
+                parameters = candidate;
+                state = self.linearize(data, &candidate_residuals, &parameters);
+                cost_history.push(state.cost as f32);
+                damping *= (1.0 - (2.0 * ratio - 1.0).powi(3)).max(1.0 / 3.0);
+                growth = 2.0;
+                if small_step {
+                    termination = Termination::SmallStep;
+                    break;
+                }
+                if small_change {
+                    termination = Termination::SmallCostChange;
+                    break;
+                }
+            } else {
+                damping *= growth;
+                growth *= 2.0;
+                if damping > 1e16 {
+                    termination = Termination::NoImprovement;
+                    break;
+                }
+            }
+        }
+        let covariance = if rows > num_parameters {
+            let variance = state.cost / (rows - num_parameters) as f64;
+            inverse(&state.normal).map(|inverse| {
+                Matrix::from_f64(
+                    &inverse
+                        .iter()
+                        .map(|row| row.iter().map(|value| value * variance).collect())
+                        .collect::<Vec<Vec<f64>>>(),
+                )
+            })
+        } else {
+            None
+        };
+        self.result = Some(CurveFitResult {
+            parameters: parameters.iter().map(|value| *value as f32).collect(),
+            covariance,
+            residual_sum_of_squares: state.cost as f32,
+            cost_history,
+            iterations,
+            evaluations,
+            termination,
+        });
+        return Ok(());
+    }
+
+    pub fn result(&self) -> Option<&CurveFitResult> {
+        return self.result.as_ref();
+    }
+
+    pub fn parameters(&self) -> &Vec<f32> {
+        return &self.fitted().parameters;
+    }
+
+    fn fitted(&self) -> &CurveFitResult {
+        return self.result.as_ref().expect("curve must be fitted first");
+    }
+
+    pub fn predict(&self, data: &Matrix) -> Vec<f32> {
+        let parameters = &self.fitted().parameters;
+        return data
+            .matrix()
+            .iter()
+            .map(|x| self.model.evaluate(x.vector(), parameters))
+            .collect();
+    }
+}
+
+fn damped_step(state: &Linearization, scaling: &Vec<f64>, damping: f64) -> Option<Vec<f64>> {
+    let mut damped = state.normal.clone();
+    for (j, row) in damped.iter_mut().enumerate() {
+        row[j] += damping * scaling[j];
+    }
+    let decomposition = CholeskyDecomposition::new(&Matrix::from_f64(&damped)).ok()?;
+    let right = Matrix::from_f64(
+        &state
+            .gradient
+            .iter()
+            .map(|g| vec![*g])
+            .collect::<Vec<Vec<f64>>>(),
+    );
+    let step = decomposition.solve(&right).ok()?.to_f64();
+    let step: Vec<f64> = step.iter().map(|row| row[0]).collect();
+    if step.iter().any(|value| !value.is_finite()) {
+        return None;
+    }
+    return Some(step);
+}
+
+fn inverse(normal: &Vec<Vec<f64>>) -> Option<Vec<Vec<f64>>> {
+    let n = normal.len();
+    let decomposition = CholeskyDecomposition::new(&Matrix::from_f64(normal)).ok()?;
+    let identity: Vec<Vec<f64>> = (0..n)
+        .map(|i| (0..n).map(|j| if i == j { 1.0 } else { 0.0 }).collect())
+        .collect();
+    return decomposition
+        .solve(&Matrix::from_f64(&identity))
+        .ok()
+        .map(|inverse| inverse.to_f64());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sampling::random::Rng;
+
+    fn column(values: &Vec<f32>) -> Matrix {
+        Matrix::to_matrix(&values.iter().map(|value| vec![*value]).collect())
+    }
+
+    // noisy samples of a preset at known parameters
+    fn sample(model: &CurveModel, truth: &[f32], xs: &Vec<f32>, noise: f32) -> Vec<f32> {
+        let mut rng = Rng::new(11);
+        xs.iter()
+            .map(|x| model.evaluate(&[*x], truth) + noise * rng.normal())
+            .collect()
+    }
+
+    // model, true parameters, x values and initial guess
+    type Case = (CurveModel, Vec<f32>, Vec<f32>, Vec<f32>);
+
+    #[test]
+    fn test_presets_recover_parameters() {
+        let cases: Vec<Case> = vec![
+            (
+                CurveModel::exponential(),
+                vec![0.3, 50.0],
+                (0..60).map(|i| i as f32 * 0.25).collect(),
+                vec![0.1, 10.0],
+            ),
+            (
+                CurveModel::power_law(),
+                vec![2.5, 1.7],
+                (1..60).map(|i| i as f32 * 0.2).collect(),
+                vec![1.0, 1.0],
+            ),
+            (
+                CurveModel::logistic_growth(),
+                vec![100.0, 0.8, 10.0],
+                (0..80).map(|i| i as f32 * 0.25).collect(),
+                vec![80.0, 0.5, 8.0],
+            ),
+            (
+                CurveModel::polynomial(3),
+                vec![1.0, -2.0, 0.5, 0.1],
+                (0..50).map(|i| i as f32 * 0.2 - 5.0).collect(),
+                vec![0.0, 0.0, 0.0, 0.0],
+            ),
+        ];
+        for (model, truth, xs, initial) in cases {
+            let labels = sample(&model, &truth, &xs, 0.05);
+            let mut fit = CurveFit::new(model);
+            fit.fit(&column(&xs), &labels, &initial).unwrap();
+            let result = fit.result().unwrap();
+            assert!(result.converged(), "{:?}", result.termination());
+            let errors = result.standard_errors().unwrap();
+            for ((estimate, actual), error) in
+                fit.parameters().iter().zip(truth.iter()).zip(errors.iter())
+            {
+                assert!(*error > 0.0 && *error < 0.1 * actual.abs().max(1.0));
+                assert!((estimate - actual).abs() < 5.0 * error + 1e-4);
+            }
+            let history = result.cost_history();
+            assert!(history.windows(2).all(|pair| pair[1] <= pair[0]));
+            let predictions = fit.predict(&column(&xs));
+            let rss: f32 = predictions
+                .iter()
+                .zip(labels.iter())
+                .map(|(p, y)| (p - y) * (p - y))
+                .sum();
+            assert!((rss - result.residual_sum_of_squares()).abs() < 1e-3 * rss.max(1.0));
+        }
+    }
+
+    #[test]
+    fn test_closure_model_with_finite_differences() {
+        // y = a·x0 + sin(b·x1) over two features, no analytic jacobian
+        let model = CurveModel::new(&["a", "b"], |x, p| p[0] * x[0] + (p[1] * x[1]).sin());
+        assert!(!model.has_jacobian());
+        let mut rng = Rng::new(3);
+        let data: Vec<Vec<f32>> = (0..200)
+            .map(|_| vec![rng.next_f32() * 4.0, rng.next_f32() * 3.0])
+            .collect();
+        let labels: Vec<f32> = data
+            .iter()
+            .map(|x| 1.5 * x[0] + (0.7 * x[1]).sin() + 0.01 * rng.normal())
+            .collect();
+        let mut fit = CurveFit::new(model);
+        fit.fit(&Matrix::to_matrix(&data), &labels, &vec![1.0, 1.0])
+            .unwrap();
+        let parameters = fit.parameters();
+        assert!((parameters[0] - 1.5).abs() < 0.01);
+        assert!((parameters[1] - 0.7).abs() < 0.01);
+        assert!(fit.result().unwrap().converged());
+    }
+
+    #[test]
+    fn test_fit_diagnostics_and_errors() {
+        let xs: Vec<f32> = (0..40).map(|i| i as f32 * 0.5).collect();
+        let labels = sample(&CurveModel::logistic_growth(), &[10.0, 1.0, 8.0], &xs, 0.0);
+        let mut fit = CurveFit::new(CurveModel::logistic_growth());
+        fit.set_max_iterations(2);
+        fit.fit(&column(&xs), &labels, &vec![1.0, 0.1, 1.0])
+            .unwrap();
+        let result = fit.result().unwrap();
+        assert!(*result.termination() == Termination::MaxIterations);
+        assert!(!result.converged() && result.iterations() == 2);
+
+        assert!(matches!(
+            fit.fit(&column(&xs), &labels[..10].to_vec(), &vec![1.0, 1.0, 1.0]),
+            Err(LinalgError::DimensionMismatch { .. })
+        ));
+        assert!(matches!(
+            fit.fit(&column(&xs), &labels, &vec![1.0, 1.0]),
+            Err(LinalgError::DimensionMismatch { .. })
+        ));
+        // x^b at x = 0 with negative b overflows
+        let mut power = CurveFit::new(CurveModel::power_law());
+        assert!(matches!(
+            power.fit(&column(&xs), &labels, &vec![1.0, -1.0]),
+            Err(LinalgError::NoConvergence { iterations: 0 })
+        ));
+        // two parameters cannot be told apart: no standard errors
+        let mut redundant =
+            CurveFit::new(CurveModel::new(&["a", "b"], |x, p| (p[0] + p[1]) * x[0]));
+        redundant.fit(&column(&xs), &xs, &vec![0.2, 0.3]).unwrap();
+        let result = redundant.result().unwrap();
+        assert!(result.converged() && result.standard_errors().is_none());
+        let parameters = redundant.parameters();
+        assert!((parameters[0] + parameters[1] - 1.0).abs() < 1e-4);
+    }
+}
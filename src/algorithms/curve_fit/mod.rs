@@ -0,0 +1,4 @@
+pub mod curve_fit;
+pub mod models;
+pub use curve_fit::{CurveFit, CurveFitResult, Termination};
+pub use models::CurveModel;
@@ -0,0 +1,160 @@
+use crate::linear_algebra::kernels;
+
+type ModelFunction = dyn Fn(&[f32], &[f32]) -> f32 + Send + Sync;
+type JacobianFunction = dyn Fn(&[f32], &[f32], &mut [f32]) + Send + Sync;
+
+/// A parametric model `y = f(x, parameters)` for `CurveFit`, where `x` is one
+/// row of features. Without an analytic Jacobian the fit falls back to
+/// central finite differences.
+pub struct CurveModel {
+    parameter_names: Vec<String>,
+    function: Box<ModelFunction>,
+    jacobian: Option<Box<JacobianFunction>>,
+}
+
+impl CurveModel {
+    pub fn new<F>(parameter_names: &[&str], function: F) -> Self
+    where
+        F: Fn(&[f32], &[f32]) -> f32 + Send + Sync + 'static,
+    {
+        if parameter_names.is_empty() {
+            panic!("a curve model needs at least one parameter");
+        }
+        return Self {
+            parameter_names: parameter_names
+                .iter()
+                .map(|name| name.to_string())
+                .collect(),
+            function: Box::new(function),
+            jacobian: None,
+        };
+    }
+
+    /// Analytic derivatives of the model with respect to every parameter,
+    /// written into the last argument in parameter order.
+    pub fn set_jacobian<J>(&mut self, jacobian: J)
+    where
+        J: Fn(&[f32], &[f32], &mut [f32]) + Send + Sync + 'static,
+    {
+        self.jacobian = Some(Box::new(jacobian));
+    }
+
+    pub fn num_parameters(&self) -> usize {
+        return self.parameter_names.len();
+    }
+
+    pub fn parameter_names(&self) -> &Vec<String> {
+        return &self.parameter_names;
+    }
+
+    pub fn has_jacobian(&self) -> bool {
+        return self.jacobian.is_some();
+    }
+
+    pub fn evaluate(&self, x: &[f32], parameters: &[f32]) -> f32 {
+        return (self.function)(x, parameters);
+    }
+
+    /// Derivatives of the model at `x`, analytic when available.
+    pub fn derivatives(&self, x: &[f32], parameters: &[f32], output: &mut [f32]) {
+        match &self.jacobian {
+            Some(jacobian) => jacobian(x, parameters, output),
+            None => {
+                let mut shifted = parameters.to_vec();
+                for (j, derivative) in output.iter_mut().enumerate() {
+                    let original = parameters[j];
+                    let step = f32::EPSILON.cbrt() * original.abs().max(1.0);
+                    shifted[j] = original + step;
+                    let forward = self.evaluate(x, &shifted);
+                    shifted[j] = original - step;
+                    let backward = self.evaluate(x, &shifted);
+                    shifted[j] = original;
+                    // the representable step, not the requested one
+                    let width = (original + step) - (original - step);
+                    *derivative = (forward - backward) / width;
+                }
+            }
+        }
+    }
+
+    /// `y = c·e^(-k·x)` with parameters `[k, c]`.
+    pub fn exponential() -> Self {
+        let mut model = Self::new(&["k", "c"], |x, p| p[1] * (-p[0] * x[0]).exp());
+        model.set_jacobian(|x, p, output| {
+            let decay = (-p[0] * x[0]).exp();
+            output[0] = -p[1] * x[0] * decay;
+            output[1] = decay;
+        });
+        return model;
+    }
+
+    /// `y = a·x^b` with parameters `[a, b]`, for positive `x`.
+    pub fn power_law() -> Self {
+        let mut model = Self::new(&["a", "b"], |x, p| p[0] * x[0].powf(p[1]));
+        model.set_jacobian(|x, p, output| {
+            let power = x[0].powf(p[1]);
+            output[0] = power;
+            output[1] = p[0] * power * x[0].ln();
+        });
+        return model;
+    }
+
+    /// `y = capacity / (1 + e^(-rate·(x - midpoint)))` with parameters
+    /// `[capacity, rate, midpoint]`.
+    pub fn logistic_growth() -> Self {
+        let mut model = Self::new(&["capacity", "rate", "midpoint"], |x, p| {
+            p[0] / (1.0 + (-p[1] * (x[0] - p[2])).exp())
+        });
+        model.set_jacobian(|x, p, output| {
+            let share = 1.0 / (1.0 + (-p[1] * (x[0] - p[2])).exp());
+            let slope = p[0] * share * (1.0 - share);
+            output[0] = share;
+            output[1] = slope * (x[0] - p[2]);
+            output[2] = -slope * p[1];
+        });
+        return model;
+    }
+
+    /// `y = e^(w·x + b)` over `num_features` features with parameters
+    /// `[w1, ..., wd, b]`.
+    pub fn log_linear(num_features: usize) -> Self {
+        let names: Vec<String> = (1..=num_features)
+            .map(|j| format!("w{}", j))
+            .chain(["b".to_string()])
+            .collect();
+        let names: Vec<&str> = names.iter().map(|name| name.as_str()).collect();
+        let mut model = Self::new(&names, log_linear);
+        model.set_jacobian(|x, p, output| {
+            let value = log_linear(x, p);
+            for (derivative, feature) in output.iter_mut().zip(x.iter()) {
+                *derivative = value * feature;
+            }
+            output[x.len()] = value;
+        });
+        return model;
+    }
+
+    /// `y = c0 + c1·x + ... + cd·x^d` with parameters `[c0, ..., cd]`.
+    pub fn polynomial(degree: usize) -> Self {
+        let names: Vec<String> = (0..=degree).map(|i| format!("c{}", i)).collect();
+        let names: Vec<&str> = names.iter().map(|name| name.as_str()).collect();
+        let mut model = Self::new(&names, |x, p| {
+            p.iter()
+                .rev()
+                .fold(0.0, |acc, coefficient| acc * x[0] + coefficient)
+        });
+        model.set_jacobian(|x, _, output| {
+            let mut power = 1.0;
+            for derivative in output.iter_mut() {
+                *derivative = power;
+                power *= x[0];
+            }
+        });
+        return model;
+    }
+}
+
+fn log_linear(x: &[f32], parameters: &[f32]) -> f32 {
+    let bias = parameters[x.len()];
+    return (kernels::dot(x, &parameters[..x.len()]) + bias).exp();
+}
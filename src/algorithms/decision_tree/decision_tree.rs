@@ -0,0 +1,560 @@
+use super::tree::{
+    ClassificationCriterion, FeatureKind, FeatureTable, MaxFeatures, RegressionCriterion, Target,
+    Tree, TreeParameters, normalize_importances,
+};
+use crate::algorithms::estimators::{
+    Classifier, Estimator, ParamError, ParamValue, Regressor, encode_classes,
+    labels_from_dataframe, labels_from_matrix, most_likely_classes,
+};
+use crate::dataframe::DataFrame;
+use crate::linear_algebra::{LinalgError, Matrix, RowVector};
+use crate::sampling::random::Rng;
+
+/// CART regression tree. Fits on a numeric `Matrix` (NaN is missing) or
+/// directly on `DataFrame` columns, where string columns are split on category
+/// subsets and nulls are routed to whichever side fits them best.
+pub struct DecisionTreeRegressor {
+    criterion: RegressionCriterion,
+    parameters: TreeParameters,
+    seed: u64,
+    features: Vec<(String, FeatureKind)>,
+    tree: Option<Tree>,
+}
+
+/// CART classification tree over numeric class labels, see
+/// `DecisionTreeRegressor` for the supported inputs.
+pub struct DecisionTreeClassifier {
+    criterion: ClassificationCriterion,
+    parameters: TreeParameters,
+    seed: u64,
+    classes: Vec<f32>,
+    features: Vec<(String, FeatureKind)>,
+    tree: Option<Tree>,
+}
+
+impl DecisionTreeRegressor {
+    pub fn new(criterion: RegressionCriterion) -> Self {
+        return Self {
+            criterion,
+            parameters: TreeParameters::new(),
+            seed: 0,
+            features: Vec::new(),
+            tree: None,
+        };
+    }
+
+    pub fn set_max_depth(&mut self, max_depth: Option<usize>) {
+        self.parameters.max_depth = max_depth;
+    }
+
+    pub fn set_min_samples_split(&mut self, min_samples_split: usize) {
+        self.parameters.min_samples_split = min_samples_split;
+    }
+
+    pub fn set_min_samples_leaf(&mut self, min_samples_leaf: usize) {
+        if min_samples_leaf == 0 {
+            panic!("leaves need at least one sample");
+        }
+        self.parameters.min_samples_leaf = min_samples_leaf;
+    }
+
+    pub fn set_max_features(&mut self, max_features: MaxFeatures) {
+        self.parameters.max_features = max_features;
+    }
+
+    /// Seeds the feature sampling used when `max_features` is below all features.
+    pub fn set_seed(&mut self, seed: u64) {
+        self.seed = seed;
+    }
+
+    pub fn fit(&mut self, data: &Matrix, labels: &Matrix) {
+        self.fit_table(
+            &FeatureTable::from_matrix(data),
+            &labels_from_matrix(labels),
+        );
+    }
+
+    pub fn fit_df(&mut self, df: &DataFrame, feature_columns: &Vec<String>, target_column: &str) {
+        self.fit_table(
+            &FeatureTable::learn(df, feature_columns),
+            &labels_from_dataframe(df, target_column),
+        );
+    }
+
+    fn fit_table(&mut self, table: &FeatureTable, labels: &Vec<f32>) {
+        assert!(
+            table.len() == labels.len(),
+            "data and labels must have the same length"
+        );
+        let target = Target::Regression {
+            values: labels,
+            criterion: self.criterion.clone(),
+        };
+        let samples = (0..labels.len()).collect();
+        let mut rng = Rng::new(self.seed);
+        self.tree = Some(Tree::grow(
+            table,
+            &target,
+            samples,
+            &self.parameters,
+            &mut rng,
+        ));
+        self.features = table.features.clone();
+    }
+
+    pub fn predict(&self, data: &Matrix) -> RowVector {
+        return self.predict_table(&FeatureTable::from_matrix(data));
+    }
+
+    pub fn predict_df(&self, df: &DataFrame) -> RowVector {
+        return self.predict_table(&FeatureTable::encode(df, &self.features));
+    }
+
+    fn predict_table(&self, table: &FeatureTable) -> RowVector {
+        let tree = self
+            .tree
+            .as_ref()
+            .expect("decision tree must be fitted first");
+        return RowVector::new(
+            &tree
+                .predict_table(table)
+                .iter()
+                .map(|value| value[0])
+                .collect(),
+        );
+    }
+
+    /// Share of the total impurity decrease contributed by each feature, in
+    /// the order of `feature_names`.
+    pub fn feature_importances(&self) -> Vec<f32> {
+        return self
+            .tree
+            .as_ref()
+            .map_or(Vec::new(), |tree| normalize_importances(tree.importances()));
+    }
+
+    pub fn feature_names(&self) -> Vec<&String> {
+        return self.features.iter().map(|(name, _)| name).collect();
+    }
+
+    pub fn depth(&self) -> usize {
+        return self.tree.as_ref().map_or(0, |tree| tree.depth());
+    }
+
+    pub fn leaf_count(&self) -> usize {
+        return self.tree.as_ref().map_or(0, |tree| tree.leaf_count());
+    }
+}
+
+impl DecisionTreeClassifier {
+    pub fn new(criterion: ClassificationCriterion) -> Self {
+        return Self {
+            criterion,
+            parameters: TreeParameters::new(),
+            seed: 0,
+            classes: Vec::new(),
+            features: Vec::new(),
+            tree: None,
+        };
+    }
+
+    pub fn set_max_depth(&mut self, max_depth: Option<usize>) {
+        self.parameters.max_depth = max_depth;
+    }
+
+    pub fn set_min_samples_split(&mut self, min_samples_split: usize) {
+        self.parameters.min_samples_split = min_samples_split;
+    }
+
+    pub fn set_min_samples_leaf(&mut self, min_samples_leaf: usize) {
+        if min_samples_leaf == 0 {
+            panic!("leaves need at least one sample");
+        }
+        self.parameters.min_samples_leaf = min_samples_leaf;
+    }
+
+    pub fn set_max_features(&mut self, max_features: MaxFeatures) {
+        self.parameters.max_features = max_features;
+    }
+
+    pub fn set_seed(&mut self, seed: u64) {
+        self.seed = seed;
+    }
+
+    pub fn fit(&mut self, data: &Matrix, labels: &Matrix) {
+        self.fit_table(
+            &FeatureTable::from_matrix(data),
+            &labels_from_matrix(labels),
+        );
+    }
+
+    pub fn fit_df(&mut self, df: &DataFrame, feature_columns: &Vec<String>, target_column: &str) {
+        self.fit_table(
+            &FeatureTable::learn(df, feature_columns),
+            &labels_from_dataframe(df, target_column),
+        );
+    }
+
+    fn fit_table(&mut self, table: &FeatureTable, labels: &Vec<f32>) {
+        assert!(
+            table.len() == labels.len(),
+            "data and labels must have the same length"
+        );
+        let (classes, targets) = encode_classes(labels);
+        let target = Target::Classification {
+            classes: &targets,
+            num_classes: classes.len(),
+            criterion: self.criterion.clone(),
+        };
+        let samples = (0..labels.len()).collect();
+        let mut rng = Rng::new(self.seed);
+        self.tree = Some(Tree::grow(
+            table,
+            &target,
+            samples,
+            &self.parameters,
+            &mut rng,
+        ));
+        self.classes = classes;
+        self.features = table.features.clone();
+    }
+
+    /// Class proportions of the reached leaf, columns in the order of `classes()`.
+    pub fn predict_proba(&self, data: &Matrix) -> Matrix {
+        return self.predict_proba_table(&FeatureTable::from_matrix(data));
+    }
+
+    pub fn predict_proba_df(&self, df: &DataFrame) -> Matrix {
+        return self.predict_proba_table(&FeatureTable::encode(df, &self.features));
+    }
+
+    fn predict_proba_table(&self, table: &FeatureTable) -> Matrix {
+        let tree = self
+            .tree
+            .as_ref()
+            .expect("decision tree must be fitted first");
+        return Matrix::new(
+            &tree
+                .predict_table(table)
+                .iter()
+                .map(|proportions| RowVector::new(proportions))
+                .collect(),
+        );
+    }
+
+    pub fn predict(&self, data: &Matrix) -> RowVector {
+        return self.most_likely(&self.predict_proba(data));
+    }
+
+    pub fn predict_df(&self, df: &DataFrame) -> RowVector {
+        return self.most_likely(&self.predict_proba_df(df));
+    }
+
+    pub(crate) fn most_likely(&self, probabilities: &Matrix) -> RowVector {
+        return most_likely_classes(&self.classes, probabilities);
+    }
+
+    pub fn classes(&self) -> &Vec<f32> {
+        return &self.classes;
+    }
+
+    pub fn feature_importances(&self) -> Vec<f32> {
+        return self
+            .tree
+            .as_ref()
+            .map_or(Vec::new(), |tree| normalize_importances(tree.importances()));
+    }
+
+    pub fn feature_names(&self) -> Vec<&String> {
+        return self.features.iter().map(|(name, _)| name).collect();
+    }
+
+    pub fn depth(&self) -> usize {
+        return self.tree.as_ref().map_or(0, |tree| tree.depth());
+    }
+
+    pub fn leaf_count(&self) -> usize {
+        return self.tree.as_ref().map_or(0, |tree| tree.leaf_count());
+    }
+}
+
+impl Estimator for DecisionTreeRegressor {
+    fn params(&self) -> Vec<(&'static str, ParamValue)> {
+        return vec![
+            (
+                "max_depth",
+                ParamValue::OptionalInt(self.parameters.max_depth),
+            ),
+            (
+                "min_samples_split",
+                ParamValue::Int(self.parameters.min_samples_split),
+            ),
+            (
+                "min_samples_leaf",
+                ParamValue::Int(self.parameters.min_samples_leaf),
+            ),
+            ("seed", ParamValue::Int(self.seed as usize)),
+        ];
+    }
+
+    fn set_param(&mut self, name: &str, value: ParamValue) -> Result<(), ParamError> {
+        match name {
+            "max_depth" => self.set_max_depth(value.as_optional_int(name)?),
+            "min_samples_split" => self.set_min_samples_split(value.as_int(name)?),
+            "min_samples_leaf" => self.set_min_samples_leaf(value.as_int(name)?),
+            "seed" => self.set_seed(value.as_int(name)? as u64),
+            _ => return Err(ParamError::Unknown(name.to_string())),
+        }
+        Ok(())
+    }
+}
+
+impl Regressor for DecisionTreeRegressor {
+    fn fit(&mut self, data: &Matrix, labels: &Matrix) -> Result<(), LinalgError> {
+        DecisionTreeRegressor::fit(self, data, labels);
+        Ok(())
+    }
+
+    fn predict(&self, data: &Matrix) -> RowVector {
+        return DecisionTreeRegressor::predict(self, data);
+    }
+}
+
+impl Estimator for DecisionTreeClassifier {
+    fn params(&self) -> Vec<(&'static str, ParamValue)> {
+        return vec![
+            (
+                "max_depth",
+                ParamValue::OptionalInt(self.parameters.max_depth),
+            ),
+            (
+                "min_samples_split",
+                ParamValue::Int(self.parameters.min_samples_split),
+            ),
+            (
+                "min_samples_leaf",
+                ParamValue::Int(self.parameters.min_samples_leaf),
+            ),
+            ("seed", ParamValue::Int(self.seed as usize)),
+        ];
+    }
+
+    fn set_param(&mut self, name: &str, value: ParamValue) -> Result<(), ParamError> {
+        match name {
+            "max_depth" => self.set_max_depth(value.as_optional_int(name)?),
+            "min_samples_split" => self.set_min_samples_split(value.as_int(name)?),
+            "min_samples_leaf" => self.set_min_samples_leaf(value.as_int(name)?),
+            "seed" => self.set_seed(value.as_int(name)? as u64),
+            _ => return Err(ParamError::Unknown(name.to_string())),
+        }
+        Ok(())
+    }
+}
+
+impl Classifier for DecisionTreeClassifier {
+    fn fit(&mut self, data: &Matrix, labels: &Matrix) -> Result<(), LinalgError> {
+        DecisionTreeClassifier::fit(self, data, labels);
+        Ok(())
+    }
+
+    fn predict(&self, data: &Matrix) -> RowVector {
+        return DecisionTreeClassifier::predict(self, data);
+    }
+
+    fn classes(&self) -> &Vec<f32> {
+        return DecisionTreeClassifier::classes(self);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dataframe::csv::df_from_csv;
+    use crate::dataframe::{DataType, DataTypeValue};
+    use crate::inference::inference::{accuracy, rmse};
+    use std::collections::HashMap;
+
+    fn df_from_rows(columns: &Vec<(&str, DataType)>, rows: &Vec<Vec<DataTypeValue>>) -> DataFrame {
+        let mut df = DataFrame::new();
+        for (name, dtype) in columns {
+            df.insert_column(name, &Vec::new(), dtype);
+        }
+        for row in rows {
+            let values: HashMap<String, DataTypeValue> = columns
+                .iter()
+                .zip(row.iter())
+                .map(|((name, _), value)| (name.to_string(), value.clone()))
+                .collect();
+            df.insert_row(&values);
+        }
+        df
+    }
+
+    #[test]
+    fn test_regression_tree_fits_thresholds() {
+        // a step function of x0, x1 is irrelevant
+        let data: Vec<Vec<f32>> = (0..100)
+            .map(|i| vec![i as f32, (i * 37 % 11) as f32])
+            .collect();
+        let labels: Vec<Vec<f32>> = (0..100)
+            .map(|i| {
+                vec![if i < 30 {
+                    1.0
+                } else if i < 70 {
+                    5.0
+                } else {
+                    -2.0
+                }]
+            })
+            .collect();
+        let (data, labels) = (Matrix::to_matrix(&data), Matrix::to_matrix(&labels));
+        let mut tree = DecisionTreeRegressor::new(RegressionCriterion::SquaredError);
+        tree.fit(&data, &labels);
+        assert!(tree.depth() == 2 && tree.leaf_count() == 3);
+        assert!(tree.predict(&data).vector() == &labels_from_matrix(&labels));
+        assert!(tree.feature_importances() == vec![1.0, 0.0]);
+        let test = Matrix::to_matrix(&vec![vec![29.4, 0.0], vec![29.6, 0.0], vec![f32::NAN, 3.0]]);
+        let predictions = tree.predict(&test);
+        assert!(predictions.get(0) == 1.0 && predictions.get(1) == 5.0);
+    }
+
+    #[test]
+    fn test_absolute_error_uses_medians() {
+        let data = Matrix::to_matrix(&(0..5).map(|i| vec![i as f32]).collect());
+        let labels = Matrix::to_matrix(&vec![
+            vec![1.0],
+            vec![2.0],
+            vec![3.0],
+            vec![4.0],
+            vec![100.0],
+        ]);
+        let mut tree = DecisionTreeRegressor::new(RegressionCriterion::AbsoluteError);
+        tree.set_max_depth(Some(0));
+        tree.fit(&data, &labels);
+        assert!(tree.predict(&data).get(0) == 3.0);
+        tree.set_max_depth(None);
+        tree.set_min_samples_leaf(2);
+        tree.fit(&data, &labels);
+        assert!(
+            tree.predict(&data)
+                .vector()
+                .iter()
+                .all(|prediction| *prediction != 21.0)
+        );
+    }
+
+    #[test]
+    fn test_categorical_and_null_values() {
+        // the price depends on the category subset {a, c} and on whether
+        // size is missing
+        let categories = ["a", "b", "c", "d"];
+        let rows: Vec<Vec<DataTypeValue>> = (0..80)
+            .map(|i| {
+                let category = categories[i % 4];
+                let size = if i % 5 == 0 {
+                    DataTypeValue::Null
+                } else {
+                    DataTypeValue::Float((i % 7) as f32)
+                };
+                let price = if category == "a" || category == "c" {
+                    10.0
+                } else {
+                    0.0
+                } + if i % 5 == 0 { 3.0 } else { 0.0 };
+                vec![
+                    DataTypeValue::String(category.to_string()),
+                    size,
+                    DataTypeValue::Float(price),
+                ]
+            })
+            .collect();
+        let columns = vec![
+            ("category", DataType::String),
+            ("size", DataType::Float),
+            ("price", DataType::Float),
+        ];
+        let df = df_from_rows(&columns, &rows);
+        let features = vec!["category".to_string(), "size".to_string()];
+        let mut tree = DecisionTreeRegressor::new(RegressionCriterion::SquaredError);
+        tree.fit_df(&df, &features, "price");
+        let predictions = tree.predict_df(&df);
+        assert!(predictions.vector() == &labels_from_dataframe(&df, "price"));
+        // one category split and one missing split on each side
+        assert!(tree.leaf_count() == 4);
+        assert!(tree.feature_names() == vec!["category", "size"]);
+        // unseen categories are handled like missing values
+        let unseen = df_from_rows(
+            &columns,
+            &vec![vec![
+                DataTypeValue::String("z".to_string()),
+                DataTypeValue::Float(1.0),
+                DataTypeValue::Float(0.0),
+            ]],
+        );
+        assert!(tree.predict_df(&unseen).len() == 1);
+    }
+
+    #[test]
+    fn test_classification_criteria() {
+        let data: Vec<Vec<f32>> = (0..90)
+            .map(|i| vec![(i % 30) as f32, (i / 30) as f32])
+            .collect();
+        let labels: Vec<Vec<f32>> = (0..90).map(|i| vec![((i % 30) / 10) as f32]).collect();
+        let (data, labels) = (Matrix::to_matrix(&data), Matrix::to_matrix(&labels));
+        for criterion in [
+            ClassificationCriterion::Gini,
+            ClassificationCriterion::Entropy,
+        ] {
+            let mut tree = DecisionTreeClassifier::new(criterion);
+            tree.fit(&data, &labels);
+            assert!(tree.classes() == &vec![0.0, 1.0, 2.0]);
+            assert!(accuracy(tree.predict(&data).vector(), &labels_from_matrix(&labels)) == 1.0);
+            assert!(tree.feature_importances()[0] == 1.0);
+            let probabilities = tree.predict_proba(&data);
+            assert!(probabilities.shape() == (90, 3));
+        }
+        let mut stump = DecisionTreeClassifier::new(ClassificationCriterion::Gini);
+        stump.set_max_depth(Some(1));
+        stump.fit(&data, &labels);
+        let probabilities = stump.predict_proba(&data);
+        assert!(
+            probabilities
+                .matrix()
+                .iter()
+                .any(|row| row.get(1) == 0.5 && row.get(2) == 0.5)
+        );
+    }
+
+    #[test]
+    fn test_housing_tree_with_categories_and_nulls() {
+        let df = df_from_csv("housing.csv", Some(2000));
+        let features: Vec<String> = [
+            "longitude",
+            "latitude",
+            "housing_median_age",
+            "total_rooms",
+            "total_bedrooms",
+            "population",
+            "households",
+            "median_income",
+            "ocean_proximity",
+        ]
+        .iter()
+        .map(|column| column.to_string())
+        .collect();
+        let mut tree = DecisionTreeRegressor::new(RegressionCriterion::SquaredError);
+        tree.set_max_depth(Some(8));
+        tree.set_min_samples_leaf(5);
+        tree.fit_df(&df, &features, "median_house_value");
+        let labels = labels_from_dataframe(&df, "median_house_value");
+        let tree_rmse = rmse(tree.predict_df(&df).vector(), &labels);
+        assert!(tree.depth() <= 8);
+        assert!(tree_rmse < 50000.0);
+        let importances = tree.feature_importances();
+        assert!((importances.iter().sum::<f32>() - 1.0).abs() < 1e-5);
+        let most_important = (0..importances.len())
+            .max_by(|a, b| importances[*a].total_cmp(&importances[*b]))
+            .unwrap();
+        assert!(features[most_important] == "median_income");
+    }
+}
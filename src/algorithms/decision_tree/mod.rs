@@ -0,0 +1,2 @@
+pub mod decision_tree;
+pub mod tree;
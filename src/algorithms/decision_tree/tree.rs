@@ -0,0 +1,760 @@
+use crate::dataframe::{DataFrame, DataType, DataTypeValue};
+use crate::linear_algebra::Matrix;
+use crate::sampling::random::Rng;
+use std::cmp::{Ordering, Reverse};
+use std::collections::BinaryHeap;
+
+/// How a feature column is split. Categorical columns keep their sorted
+/// category names so the same codes can be rebuilt at prediction time.
+#[derive(Clone, Debug, PartialEq)]
+pub enum FeatureKind {
+    Numeric,
+    Categorical(Vec<String>),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum RegressionCriterion {
+    /// Variance reduction, leaves predict the mean.
+    SquaredError,
+    /// Absolute deviation reduction, leaves predict the median.
+    AbsoluteError,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ClassificationCriterion {
+    Gini,
+    Entropy,
+}
+
+/// Number of features considered at each split.
+#[derive(Clone, Debug, PartialEq)]
+pub enum MaxFeatures {
+    All,
+    Sqrt,
+    Log2,
+    Count(usize),
+    Fraction(f32),
+}
+
+impl MaxFeatures {
+    pub fn resolve(&self, num_features: usize) -> usize {
+        let count = match self {
+            MaxFeatures::All => num_features,
+            MaxFeatures::Sqrt => (num_features as f32).sqrt().round() as usize,
+            MaxFeatures::Log2 => (num_features as f32).log2().round() as usize,
+            MaxFeatures::Count(count) => *count,
+            MaxFeatures::Fraction(fraction) => (num_features as f32 * fraction).round() as usize,
+        };
+        return count.clamp(1, num_features.max(1));
+    }
+}
+
+/// Column major feature values. Missing values are NaN and categorical values
+/// are stored as the index of their category.
+pub(crate) struct FeatureTable {
+    pub columns: Vec<Vec<f32>>,
+    pub features: Vec<(String, FeatureKind)>,
+}
+
+impl FeatureTable {
+    pub fn from_matrix(data: &Matrix) -> Self {
+        let (rows, num_columns) = data.shape();
+        let mut columns = vec![vec![0.0; rows]; num_columns];
+        for (i, row) in data.matrix().iter().enumerate() {
+            for (j, value) in row.vector().iter().enumerate() {
+                columns[j][i] = *value;
+            }
+        }
+        let features = (0..num_columns)
+            .map(|j| (format!("x{}", j), FeatureKind::Numeric))
+            .collect();
+        return Self { columns, features };
+    }
+
+    /// Float columns become numeric features and string columns categorical
+    /// ones, with nulls kept as missing values.
+    pub fn learn(df: &DataFrame, feature_columns: &Vec<String>) -> Self {
+        let features = feature_columns
+            .iter()
+            .map(|column_name| {
+                let (dtype, values) = df.get_column(column_name);
+                let kind = match dtype {
+                    DataType::Float => FeatureKind::Numeric,
+                    DataType::String => {
+                        let mut categories: Vec<String> = values
+                            .iter()
+                            .filter_map(|value| match value {
+                                DataTypeValue::String(category) => Some(category.clone()),
+                                _ => None,
+                            })
+                            .collect();
+                        categories.sort();
+                        categories.dedup();
+                        FeatureKind::Categorical(categories)
+                    }
+                    DataType::Id => panic!("{} is an id column, not a feature", column_name),
+                };
+                (column_name.clone(), kind)
+            })
+            .collect();
+        return Self::encode(df, &features);
+    }
+
+    /// Encodes `df` with previously learned features. Categories that were not
+    /// seen during fitting are treated as missing.
+    pub fn encode(df: &DataFrame, features: &Vec<(String, FeatureKind)>) -> Self {
+        let columns = features
+            .iter()
+            .map(|(column_name, kind)| {
+                let (_, values) = df.get_column(column_name);
+                values
+                    .iter()
+                    .map(|value| match (value, kind) {
+                        (DataTypeValue::Float(value), FeatureKind::Numeric) => *value,
+                        (DataTypeValue::String(category), FeatureKind::Categorical(categories)) => {
+                            categories
+                                .binary_search(category)
+                                .map_or(f32::NAN, |code| code as f32)
+                        }
+                        (DataTypeValue::Null, _) => f32::NAN,
+                        (value, kind) => {
+                            panic!(
+                                "{:?} in column {} does not match {:?}",
+                                value, column_name, kind
+                            )
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+        return Self {
+            columns,
+            features: features.clone(),
+        };
+    }
+
+    pub fn len(&self) -> usize {
+        return self.columns.first().map_or(0, |column| column.len());
+    }
+}
+
+pub(crate) enum Target<'a> {
+    Regression {
+        values: &'a [f32],
+        criterion: RegressionCriterion,
+    },
+    Classification {
+        classes: &'a [usize],
+        num_classes: usize,
+        criterion: ClassificationCriterion,
+    },
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct TreeParameters {
+    pub max_depth: Option<usize>,
+    pub min_samples_split: usize,
+    pub min_samples_leaf: usize,
+    pub max_features: MaxFeatures,
+}
+
+impl TreeParameters {
+    pub fn new() -> Self {
+        return Self {
+            max_depth: None,
+            min_samples_split: 2,
+            min_samples_leaf: 1,
+            max_features: MaxFeatures::All,
+        };
+    }
+}
+
+#[derive(Clone, Debug)]
+enum SplitRule {
+    Threshold(f32),
+    // codes of the categories sent left
+    Categories(Vec<usize>),
+}
+
+#[derive(Clone, Debug)]
+struct Split {
+    feature: usize,
+    rule: SplitRule,
+    missing_left: bool,
+    left: usize,
+    right: usize,
+}
+
+impl Split {
+    fn goes_left(&self, value: f32) -> bool {
+        if value.is_nan() {
+            return self.missing_left;
+        }
+        match &self.rule {
+            SplitRule::Threshold(threshold) => value <= *threshold,
+            SplitRule::Categories(categories) => categories.contains(&(value as usize)),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Node {
+    // mean or median for regression, class proportions for classification
+    value: Vec<f32>,
+    split: Option<Split>,
+}
+
+/// A fitted CART tree stored as a flat list of nodes, root first.
+#[derive(Clone, Debug)]
+pub(crate) struct Tree {
+    nodes: Vec<Node>,
+    // total impurity decrease per feature, unnormalized
+    importances: Vec<f64>,
+}
+
+struct Candidate {
+    loss: f64,
+    rule: SplitRule,
+    missing_left: bool,
+}
+
+impl Tree {
+    pub fn grow(
+        table: &FeatureTable,
+        target: &Target,
+        samples: Vec<usize>,
+        parameters: &TreeParameters,
+        rng: &mut Rng,
+    ) -> Self {
+        let mut tree = Self {
+            nodes: Vec::new(),
+            importances: vec![0.0; table.columns.len()],
+        };
+        tree.build(table, target, samples, parameters, rng, 0);
+        return tree;
+    }
+
+    fn build(
+        &mut self,
+        table: &FeatureTable,
+        target: &Target,
+        samples: Vec<usize>,
+        parameters: &TreeParameters,
+        rng: &mut Rng,
+        depth: usize,
+    ) -> usize {
+        let index = self.nodes.len();
+        self.nodes.push(Node {
+            value: leaf_value(target, &samples),
+            split: None,
+        });
+        let mut accumulator = Accumulator::new(target);
+        samples
+            .iter()
+            .for_each(|sample| accumulator.add(target, *sample));
+        let node_loss = accumulator.loss();
+        if parameters
+            .max_depth
+            .is_some_and(|max_depth| depth >= max_depth)
+            || samples.len()
+                < parameters
+                    .min_samples_split
+                    .max(2 * parameters.min_samples_leaf)
+            || node_loss <= 1e-12
+        {
+            return index;
+        }
+
+        let num_features = table.columns.len();
+        let mut features = rng.permutation(num_features);
+        features.truncate(parameters.max_features.resolve(num_features));
+        let mut best: Option<(usize, Candidate)> = None;
+        for feature in features {
+            let candidate = best_split(
+                table,
+                target,
+                feature,
+                &samples,
+                parameters.min_samples_leaf,
+            );
+            if let Some(candidate) = candidate {
+                if best
+                    .as_ref()
+                    .is_none_or(|(_, best)| candidate.loss < best.loss)
+                {
+                    best = Some((feature, candidate));
+                }
+            }
+        }
+        let Some((feature, candidate)) = best else {
+            return index;
+        };
+        if node_loss - candidate.loss <= 1e-12 * node_loss {
+            return index;
+        }
+        self.importances[feature] += node_loss - candidate.loss;
+        let mut split = Split {
+            feature,
+            rule: candidate.rule,
+            missing_left: candidate.missing_left,
+            left: 0,
+            right: 0,
+        };
+        let column = &table.columns[feature];
+        let (left_samples, right_samples): (Vec<usize>, Vec<usize>) = samples
+            .iter()
+            .partition(|sample| split.goes_left(column[**sample]));
+        split.left = self.build(table, target, left_samples, parameters, rng, depth + 1);
+        split.right = self.build(table, target, right_samples, parameters, rng, depth + 1);
+        self.nodes[index].split = Some(split);
+        return index;
+    }
+
+    /// Leaf reached by a row, `value(feature)` returns the encoded feature value.
+    pub fn leaf(&self, value: impl Fn(usize) -> f32) -> &Vec<f32> {
+        let mut node = &self.nodes[0];
+        while let Some(split) = &node.split {
+            node = if split.goes_left(value(split.feature)) {
+                &self.nodes[split.left]
+            } else {
+                &self.nodes[split.right]
+            };
+        }
+        return &node.value;
+    }
+
+    pub fn predict_table(&self, table: &FeatureTable) -> Vec<&Vec<f32>> {
+        return (0..table.len())
+            .map(|i| self.leaf(|feature| table.columns[feature][i]))
+            .collect();
+    }
+
+    pub fn importances(&self) -> &Vec<f64> {
+        return &self.importances;
+    }
+
+    pub fn depth(&self) -> usize {
+        fn depth(nodes: &Vec<Node>, index: usize) -> usize {
+            match &nodes[index].split {
+                Some(split) => 1 + depth(nodes, split.left).max(depth(nodes, split.right)),
+                None => 0,
+            }
+        }
+        return depth(&self.nodes, 0);
+    }
+
+    pub fn leaf_count(&self) -> usize {
+        return self
+            .nodes
+            .iter()
+            .filter(|node| node.split.is_none())
+            .count();
+    }
+}
+
+/// Importances scaled to sum to one.
+pub(crate) fn normalize_importances(importances: &Vec<f64>) -> Vec<f32> {
+    let total: f64 = importances.iter().sum();
+    return importances
+        .iter()
+        .map(|importance| {
+            if total > 0.0 {
+                (importance / total) as f32
+            } else {
+                0.0
+            }
+        })
+        .collect();
+}
+
+fn leaf_value(target: &Target, samples: &Vec<usize>) -> Vec<f32> {
+    match target {
+        Target::Regression { values, criterion } => {
+            let mut node_values: Vec<f32> = samples.iter().map(|sample| values[*sample]).collect();
+            match criterion {
+                RegressionCriterion::SquaredError => {
+                    let sum: f64 = node_values.iter().map(|value| *value as f64).sum();
+                    vec![(sum / node_values.len().max(1) as f64) as f32]
+                }
+                RegressionCriterion::AbsoluteError => {
+                    node_values.sort_by(|a, b| a.total_cmp(b));
+                    let middle = node_values.len() / 2;
+                    let median = if node_values.len() % 2 == 0 && middle > 0 {
+                        (node_values[middle - 1] + node_values[middle]) / 2.0
+                    } else {
+                        node_values.get(middle).copied().unwrap_or(0.0)
+                    };
+                    vec![median]
+                }
+            }
+        }
+        Target::Classification {
+            classes,
+            num_classes,
+            ..
+        } => {
+            let mut counts = vec![0.0; *num_classes];
+            samples
+                .iter()
+                .for_each(|sample| counts[classes[*sample]] += 1.0);
+            counts
+                .iter()
+                .map(|count| count / samples.len().max(1) as f32)
+                .collect()
+        }
+    }
+}
+
+// Finds the lowest loss split of `feature`. Present values are ordered (by
+// value, or for categories by their mean response so the best contiguous
+// partition is the best subset for two outcomes), then each boundary is tried
+// with the missing values on either side.
+fn best_split(
+    table: &FeatureTable,
+    target: &Target,
+    feature: usize,
+    samples: &Vec<usize>,
+    min_samples_leaf: usize,
+) -> Option<Candidate> {
+    let column = &table.columns[feature];
+    let (mut present, missing): (Vec<usize>, Vec<usize>) = samples
+        .iter()
+        .partition(|sample| !column[**sample].is_nan());
+    let categorical = matches!(table.features[feature].1, FeatureKind::Categorical(_));
+    if categorical {
+        let ranks = category_ranks(target, column, &present);
+        present.sort_by(|a, b| {
+            let (a, b) = (column[*a] as usize, column[*b] as usize);
+            ranks[a].total_cmp(&ranks[b]).then(a.cmp(&b))
+        });
+    } else {
+        present.sort_by(|a, b| column[*a].total_cmp(&column[*b]));
+    }
+    let len = present.len();
+    if len == 0 || missing.is_empty() && (len < 2 || column[present[0]] == column[present[len - 1]])
+    {
+        return None;
+    }
+
+    let prefix_losses = |with_missing: bool| {
+        let mut accumulator = Accumulator::new(target);
+        if with_missing {
+            missing
+                .iter()
+                .for_each(|sample| accumulator.add(target, *sample));
+        }
+        let mut losses = Vec::with_capacity(len + 1);
+        losses.push(accumulator.loss());
+        for sample in present.iter() {
+            accumulator.add(target, *sample);
+            losses.push(accumulator.loss());
+        }
+        losses
+    };
+    let suffix_losses = |with_missing: bool| {
+        let mut accumulator = Accumulator::new(target);
+        if with_missing {
+            missing
+                .iter()
+                .for_each(|sample| accumulator.add(target, *sample));
+        }
+        let mut losses = vec![0.0; len + 1];
+        losses[len] = accumulator.loss();
+        for p in (0..len).rev() {
+            accumulator.add(target, present[p]);
+            losses[p] = accumulator.loss();
+        }
+        losses
+    };
+    let (left, right) = (prefix_losses(false), suffix_losses(false));
+    let (left_missing, right_missing) = if missing.is_empty() {
+        (left.clone(), right.clone())
+    } else {
+        (prefix_losses(true), suffix_losses(true))
+    };
+
+    let mut best: Option<(f64, usize, bool)> = None;
+    // p = 0 and p = len separate the missing values from all present ones
+    for p in 0..=len {
+        if p > 0 && p < len && column[present[p - 1]] == column[present[p]] {
+            continue;
+        }
+        let options = if missing.is_empty() {
+            if p == 0 || p == len {
+                continue;
+            }
+            // nothing to learn, unseen missing values follow the larger side
+            vec![(left[p] + right[p], p >= len - p, p, len - p)]
+        } else if p == 0 {
+            vec![(left_missing[p] + right[p], true, missing.len(), len)]
+        } else if p == len {
+            vec![(left[p] + right_missing[p], false, len, missing.len())]
+        } else {
+            vec![
+                (left_missing[p] + right[p], true, p + missing.len(), len - p),
+                (
+                    left[p] + right_missing[p],
+                    false,
+                    p,
+                    len - p + missing.len(),
+                ),
+            ]
+        };
+        for (loss, missing_left, left_count, right_count) in options {
+            if left_count < min_samples_leaf || right_count < min_samples_leaf {
+                continue;
+            }
+            if best.is_none_or(|(best_loss, _, _)| loss < best_loss) {
+                best = Some((loss, p, missing_left));
+            }
+        }
+    }
+    let (loss, p, missing_left) = best?;
+    let rule = if categorical {
+        let mut categories: Vec<usize> = present[..p]
+            .iter()
+            .map(|sample| column[*sample] as usize)
+            .collect();
+        categories.dedup();
+        SplitRule::Categories(categories)
+    } else if p == 0 {
+        SplitRule::Threshold(f32::NEG_INFINITY)
+    } else if p == len {
+        SplitRule::Threshold(f32::INFINITY)
+    } else {
+        let (low, high) = (column[present[p - 1]], column[present[p]]);
+        let middle = low + (high - low) / 2.0;
+        SplitRule::Threshold(if middle < high { middle } else { low })
+    };
+    return Some(Candidate {
+        loss,
+        rule,
+        missing_left,
+    });
+}
+
+// mean response per category code (share of the node's majority class for
+// classification), unseen codes rank last
+fn category_ranks(target: &Target, column: &Vec<f32>, present: &Vec<usize>) -> Vec<f64> {
+    let num_categories = present
+        .iter()
+        .map(|sample| column[*sample] as usize + 1)
+        .max()
+        .unwrap_or(0);
+    let mut sums = vec![0.0; num_categories];
+    let mut counts = vec![0.0; num_categories];
+    let response: Box<dyn Fn(usize) -> f64> = match target {
+        Target::Regression { values, .. } => Box::new(move |sample| values[sample] as f64),
+        Target::Classification {
+            classes,
+            num_classes,
+            ..
+        } => {
+            let mut class_counts = vec![0; *num_classes];
+            present
+                .iter()
+                .for_each(|sample| class_counts[classes[*sample]] += 1);
+            let majority = (0..*num_classes)
+                .max_by_key(|class| class_counts[*class])
+                .unwrap_or(0);
+            Box::new(move |sample| (classes[sample] == majority) as usize as f64)
+        }
+    };
+    for sample in present {
+        let code = column[*sample] as usize;
+        sums[code] += response(*sample);
+        counts[code] += 1.0;
+    }
+    return sums
+        .iter()
+        .zip(counts.iter())
+        .map(|(sum, count)| {
+            if *count > 0.0 {
+                sum / count
+            } else {
+                f64::INFINITY
+            }
+        })
+        .collect();
+}
+
+#[derive(Clone, Copy, PartialEq)]
+struct OrderedFloat(f32);
+
+impl Eq for OrderedFloat {}
+
+impl PartialOrd for OrderedFloat {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for OrderedFloat {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+// Running total loss (impurity times sample count) of a growing set of samples.
+enum Accumulator {
+    Squared {
+        count: f64,
+        sum: f64,
+        sum_squares: f64,
+    },
+    // two heaps split at the median, with their sums, give the absolute
+    // deviation from the median in O(log n) per sample
+    Absolute {
+        lower: BinaryHeap<OrderedFloat>,
+        upper: BinaryHeap<Reverse<OrderedFloat>>,
+        lower_sum: f64,
+        upper_sum: f64,
+    },
+    Classes {
+        counts: Vec<f64>,
+        total: f64,
+        entropy: bool,
+    },
+}
+
+impl Accumulator {
+    fn new(target: &Target) -> Self {
+        match target {
+            Target::Regression {
+                criterion: RegressionCriterion::SquaredError,
+                ..
+            } => Accumulator::Squared {
+                count: 0.0,
+                sum: 0.0,
+                sum_squares: 0.0,
+            },
+            Target::Regression {
+                criterion: RegressionCriterion::AbsoluteError,
+                ..
+            } => Accumulator::Absolute {
+                lower: BinaryHeap::new(),
+                upper: BinaryHeap::new(),
+                lower_sum: 0.0,
+                upper_sum: 0.0,
+            },
+            Target::Classification {
+                num_classes,
+                criterion,
+                ..
+            } => Accumulator::Classes {
+                counts: vec![0.0; *num_classes],
+                total: 0.0,
+                entropy: *criterion == ClassificationCriterion::Entropy,
+            },
+        }
+    }
+
+    fn add(&mut self, target: &Target, sample: usize) {
+        match (self, target) {
+            (
+                Accumulator::Squared {
+                    count,
+                    sum,
+                    sum_squares,
+                },
+                Target::Regression { values, .. },
+            ) => {
+                let value = values[sample] as f64;
+                *count += 1.0;
+                *sum += value;
+                *sum_squares += value * value;
+            }
+            (
+                Accumulator::Absolute {
+                    lower,
+                    upper,
+                    lower_sum,
+                    upper_sum,
+                },
+                Target::Regression { values, .. },
+            ) => {
+                let value = values[sample];
+                if lower.peek().is_none_or(|top| value <= top.0) {
+                    lower.push(OrderedFloat(value));
+                    *lower_sum += value as f64;
+                } else {
+                    upper.push(Reverse(OrderedFloat(value)));
+                    *upper_sum += value as f64;
+                }
+                if lower.len() > upper.len() + 1 {
+                    let moved = lower.pop().unwrap().0;
+                    *lower_sum -= moved as f64;
+                    *upper_sum += moved as f64;
+                    upper.push(Reverse(OrderedFloat(moved)));
+                } else if upper.len() > lower.len() {
+                    let moved = upper.pop().unwrap().0.0;
+                    *upper_sum -= moved as f64;
+                    *lower_sum += moved as f64;
+                    lower.push(OrderedFloat(moved));
+                }
+            }
+            (
+                Accumulator::Classes { counts, total, .. },
+                Target::Classification { classes, .. },
+            ) => {
+                counts[classes[sample]] += 1.0;
+                *total += 1.0;
+            }
+            _ => unreachable!("accumulator does not match the target"),
+        }
+    }
+
+    fn loss(&self) -> f64 {
+        match self {
+            Accumulator::Squared {
+                count,
+                sum,
+                sum_squares,
+            } => {
+                if *count == 0.0 {
+                    0.0
+                } else {
+                    (sum_squares - sum * sum / count).max(0.0)
+                }
+            }
+            Accumulator::Absolute {
+                lower,
+                upper,
+                lower_sum,
+                upper_sum,
+            } => match lower.peek() {
+                Some(median) => {
+                    let median = median.0 as f64;
+                    (median * lower.len() as f64 - lower_sum)
+                        + (upper_sum - median * upper.len() as f64)
+                }
+                None => 0.0,
+            },
+            Accumulator::Classes {
+                counts,
+                total,
+                entropy,
+            } => {
+                if *total == 0.0 {
+                    return 0.0;
+                }
+                let impurity = if *entropy {
+                    counts
+                        .iter()
+                        .filter(|count| **count > 0.0)
+                        .map(|count| {
+                            let p = count / total;
+                            -p * p.log2()
+                        })
+                        .sum::<f64>()
+                } else {
+                    1.0 - counts
+                        .iter()
+                        .map(|count| (count / total).powi(2))
+                        .sum::<f64>()
+                };
+                impurity * total
+            }
+        }
+    }
+}
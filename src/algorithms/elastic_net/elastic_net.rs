@@ -0,0 +1,534 @@
+use crate::algorithms::estimators::{Estimator, ParamError, ParamValue, Regressor};
+use crate::linear_algebra::{LinalgError, Matrix, RowVector, kernels};
+
+/// Linear regression with a combined L1/L2 penalty, minimizing
+///
+/// `||y - X w - b||^2 / 2n + alpha * l1_ratio * ||w||_1 + alpha * (1 - l1_ratio) * ||w||^2 / 2`
+///
+/// by cyclic coordinate descent. The intercept `b` is not penalized, the data
+/// is centered and `b` recovered from the means.
+pub struct ElasticNet {
+    alpha: f32,
+    l1_ratio: f32,
+    max_iterations: usize,
+    tolerance: f32,
+    warm_start: bool,
+    weights: Vec<f32>,
+    bias: f32,
+    iterations: usize,
+    converged: bool,
+}
+
+/// `ElasticNet` with a pure L1 penalty.
+pub struct Lasso {
+    model: ElasticNet,
+}
+
+/// Coefficients from fitting the same data over a grid of alphas, in the
+/// order the alphas were given.
+pub struct RegularizationPath {
+    alphas: Vec<f32>,
+    weights: Vec<Vec<f32>>,
+    biases: Vec<f32>,
+}
+
+// centered copy of the data as columns, which is what coordinate descent walks over
+struct CenteredData {
+    columns: Vec<Vec<f64>>,
+    column_means: Vec<f64>,
+    targets: Vec<f64>,
+    target_mean: f64,
+}
+
+impl CenteredData {
+    fn new(data: &Matrix, labels: &Matrix) -> Result<Self, LinalgError> {
+        let (rows, num_columns) = data.shape();
+        if labels.len() != rows {
+            return Err(LinalgError::DimensionMismatch {
+                expected: (rows, 1),
+                found: labels.shape(),
+            });
+        }
+        let mut columns = vec![vec![0.0; rows]; num_columns];
+        for (i, row) in data.matrix().iter().enumerate() {
+            for (j, value) in row.vector().iter().enumerate() {
+                columns[j][i] = *value as f64;
+            }
+        }
+        let mean = |values: &Vec<f64>| values.iter().sum::<f64>() / rows.max(1) as f64;
+        let column_means: Vec<f64> = columns.iter().map(mean).collect();
+        for (column, column_mean) in columns.iter_mut().zip(column_means.iter()) {
+            column.iter_mut().for_each(|value| *value -= column_mean);
+        }
+        let mut targets: Vec<f64> = labels
+            .matrix()
+            .iter()
+            .map(|label| label.get(0) as f64)
+            .collect();
+        let target_mean = mean(&targets);
+        targets.iter_mut().for_each(|value| *value -= target_mean);
+        Ok(Self {
+            columns,
+            column_means,
+            targets,
+            target_mean,
+        })
+    }
+
+    /// Smallest alpha for which every coefficient is zero.
+    fn max_alpha(&self, l1_ratio: f32) -> f32 {
+        let rows = self.targets.len().max(1) as f64;
+        let correlation = self.columns.iter().fold(0.0_f64, |acc, column| {
+            let dot: f64 = column
+                .iter()
+                .zip(self.targets.iter())
+                .map(|(x, y)| x * y)
+                .sum();
+            acc.max(dot.abs())
+        });
+        // a pure ridge penalty never zeroes coefficients, use a small l1 share for scale
+        (correlation / (rows * l1_ratio.max(1e-3) as f64)) as f32
+    }
+}
+
+impl ElasticNet {
+    pub fn new(alpha: f32, l1_ratio: f32) -> Self {
+        if alpha < 0.0 {
+            panic!("alpha must be non negative");
+        }
+        if !(0.0..=1.0).contains(&l1_ratio) {
+            panic!("l1 ratio must be between 0 and 1");
+        }
+        return Self {
+            alpha,
+            l1_ratio,
+            max_iterations: 1000,
+            tolerance: 1e-4,
+            warm_start: false,
+            weights: Vec::new(),
+            bias: 0.0,
+            iterations: 0,
+            converged: false,
+        };
+    }
+
+    pub fn set_alpha(&mut self, alpha: f32) {
+        if alpha < 0.0 {
+            panic!("alpha must be non negative");
+        }
+        self.alpha = alpha;
+    }
+
+    pub fn set_max_iterations(&mut self, max_iterations: usize) {
+        self.max_iterations = max_iterations;
+    }
+
+    /// Stops once no coefficient moves by more than `tolerance` times the
+    /// largest coefficient in a full pass.
+    pub fn set_tolerance(&mut self, tolerance: f32) {
+        self.tolerance = tolerance;
+    }
+
+    /// Starts the next `fit` from the current coefficients instead of zero,
+    /// which makes refitting with a nearby alpha much cheaper.
+    pub fn set_warm_start(&mut self, warm_start: bool) {
+        self.warm_start = warm_start;
+    }
+
+    pub fn fit(&mut self, data: &Matrix, labels: &Matrix) -> Result<(), LinalgError> {
+        let centered = CenteredData::new(data, labels)?;
+        self.fit_centered(&centered);
+        Ok(())
+    }
+
+    fn fit_centered(&mut self, centered: &CenteredData) {
+        let rows = centered.targets.len();
+        let num_columns = centered.columns.len();
+        let mut weights: Vec<f64> = if self.warm_start && self.weights.len() == num_columns {
+            self.weights.iter().map(|weight| *weight as f64).collect()
+        } else {
+            vec![0.0; num_columns]
+        };
+        let mut residuals = centered.targets.clone();
+        for (column, weight) in centered.columns.iter().zip(weights.iter()) {
+            if *weight != 0.0 {
+                residuals
+                    .iter_mut()
+                    .zip(column.iter())
+                    .for_each(|(r, x)| *r -= weight * x);
+            }
+        }
+        let column_norms: Vec<f64> = centered
+            .columns
+            .iter()
+            .map(|column| column.iter().map(|x| x * x).sum())
+            .collect();
+        let l1_penalty = rows as f64 * self.alpha as f64 * self.l1_ratio as f64;
+        let l2_penalty = rows as f64 * self.alpha as f64 * (1.0 - self.l1_ratio as f64);
+
+        self.converged = false;
+        self.iterations = 0;
+        while self.iterations < self.max_iterations {
+            self.iterations += 1;
+            let mut max_change = 0.0_f64;
+            let mut max_weight = 0.0_f64;
+            for j in 0..num_columns {
+                if column_norms[j] == 0.0 {
+                    continue;
+                }
+                let column = &centered.columns[j];
+                let old_weight = weights[j];
+                let rho = column
+                    .iter()
+                    .zip(residuals.iter())
+                    .map(|(x, r)| x * r)
+                    .sum::<f64>()
+                    + column_norms[j] * old_weight;
+                let new_weight = soft_threshold(rho, l1_penalty) / (column_norms[j] + l2_penalty);
+                let change = new_weight - old_weight;
+                if change != 0.0 {
+                    residuals
+                        .iter_mut()
+                        .zip(column.iter())
+                        .for_each(|(r, x)| *r -= change * x);
+                    weights[j] = new_weight;
+                }
+                max_change = max_change.max(change.abs());
+                max_weight = max_weight.max(new_weight.abs());
+            }
+            if max_change <= self.tolerance as f64 * max_weight {
+                self.converged = true;
+                break;
+            }
+        }
+        let bias = centered.target_mean
+            - weights
+                .iter()
+                .zip(centered.column_means.iter())
+                .map(|(weight, mean)| weight * mean)
+                .sum::<f64>();
+        self.weights = weights.iter().map(|weight| *weight as f32).collect();
+        self.bias = bias as f32;
+    }
+
+    pub fn predict(&self, data: &Matrix) -> RowVector {
+        return RowVector::new(
+            &data
+                .matrix()
+                .iter()
+                .map(|row_vector| kernels::dot(row_vector.vector(), &self.weights) + self.bias)
+                .collect(),
+        );
+    }
+
+    pub fn weights(&self) -> &Vec<f32> {
+        return &self.weights;
+    }
+
+    pub fn bias(&self) -> f32 {
+        return self.bias;
+    }
+
+    pub fn alpha(&self) -> f32 {
+        return self.alpha;
+    }
+
+    pub fn l1_ratio(&self) -> f32 {
+        return self.l1_ratio;
+    }
+
+    /// Coordinate descent passes used by the last `fit`.
+    pub fn iterations(&self) -> usize {
+        return self.iterations;
+    }
+
+    /// False when the last `fit` stopped at `max_iterations`.
+    pub fn converged(&self) -> bool {
+        return self.converged;
+    }
+}
+
+impl Lasso {
+    pub fn new(alpha: f32) -> Self {
+        return Self {
+            model: ElasticNet::new(alpha, 1.0),
+        };
+    }
+
+    pub fn set_alpha(&mut self, alpha: f32) {
+        self.model.set_alpha(alpha);
+    }
+
+    pub fn set_max_iterations(&mut self, max_iterations: usize) {
+        self.model.set_max_iterations(max_iterations);
+    }
+
+    pub fn set_tolerance(&mut self, tolerance: f32) {
+        self.model.set_tolerance(tolerance);
+    }
+
+    pub fn set_warm_start(&mut self, warm_start: bool) {
+        self.model.set_warm_start(warm_start);
+    }
+
+    pub fn fit(&mut self, data: &Matrix, labels: &Matrix) -> Result<(), LinalgError> {
+        return self.model.fit(data, labels);
+    }
+
+    pub fn predict(&self, data: &Matrix) -> RowVector {
+        return self.model.predict(data);
+    }
+
+    pub fn weights(&self) -> &Vec<f32> {
+        return self.model.weights();
+    }
+
+    pub fn bias(&self) -> f32 {
+        return self.model.bias();
+    }
+
+    pub fn alpha(&self) -> f32 {
+        return self.model.alpha();
+    }
+
+    pub fn iterations(&self) -> usize {
+        return self.model.iterations();
+    }
+
+    pub fn converged(&self) -> bool {
+        return self.model.converged();
+    }
+}
+
+impl RegularizationPath {
+    pub fn alphas(&self) -> &Vec<f32> {
+        return &self.alphas;
+    }
+
+    /// One coefficient vector per alpha.
+    pub fn weights(&self) -> &Vec<Vec<f32>> {
+        return &self.weights;
+    }
+
+    pub fn biases(&self) -> &Vec<f32> {
+        return &self.biases;
+    }
+
+    /// Number of nonzero coefficients per alpha.
+    pub fn active_features(&self) -> Vec<usize> {
+        return self
+            .weights
+            .iter()
+            .map(|weights| weights.iter().filter(|weight| **weight != 0.0).count())
+            .collect();
+    }
+}
+
+/// `num_alphas` log-spaced alphas from the smallest alpha that zeroes every
+/// coefficient down to `min_ratio` times that, largest first.
+pub fn alpha_grid(
+    data: &Matrix,
+    labels: &Matrix,
+    l1_ratio: f32,
+    num_alphas: usize,
+    min_ratio: f32,
+) -> Result<Vec<f32>, LinalgError> {
+    let max_alpha = CenteredData::new(data, labels)?.max_alpha(l1_ratio);
+    if num_alphas == 1 {
+        return Ok(vec![max_alpha]);
+    }
+    let log_ratio = (min_ratio as f64).ln();
+    Ok((0..num_alphas)
+        .map(|i| max_alpha * (log_ratio * i as f64 / (num_alphas - 1) as f64).exp() as f32)
+        .collect())
+}
+
+/// Fits an `ElasticNet` for every alpha, visiting them from largest to
+/// smallest and warm starting each fit from the previous solution.
+pub fn regularization_path(
+    data: &Matrix,
+    labels: &Matrix,
+    alphas: &Vec<f32>,
+    l1_ratio: f32,
+) -> Result<RegularizationPath, LinalgError> {
+    let centered = CenteredData::new(data, labels)?;
+    let mut order: Vec<usize> = (0..alphas.len()).collect();
+    order.sort_by(|a, b| alphas[*b].total_cmp(&alphas[*a]));
+    let mut model = ElasticNet::new(0.0, l1_ratio);
+    model.set_warm_start(true);
+    let mut weights = vec![Vec::new(); alphas.len()];
+    let mut biases = vec![0.0; alphas.len()];
+    for i in order {
+        model.set_alpha(alphas[i]);
+        model.fit_centered(&centered);
+        weights[i] = model.weights().clone();
+        biases[i] = model.bias();
+    }
+    Ok(RegularizationPath {
+        alphas: alphas.clone(),
+        weights,
+        biases,
+    })
+}
+
+fn soft_threshold(value: f64, threshold: f64) -> f64 {
+    if value > threshold {
+        value - threshold
+    } else if value < -threshold {
+        value + threshold
+    } else {
+        0.0
+    }
+}
+
+impl Estimator for ElasticNet {
+    fn params(&self) -> Vec<(&'static str, ParamValue)> {
+        return vec![
+            ("alpha", ParamValue::Float(self.alpha)),
+            ("l1_ratio", ParamValue::Float(self.l1_ratio)),
+            ("max_iterations", ParamValue::Int(self.max_iterations)),
+            ("tolerance", ParamValue::Float(self.tolerance)),
+            ("warm_start", ParamValue::Bool(self.warm_start)),
+        ];
+    }
+
+    fn set_param(&mut self, name: &str, value: ParamValue) -> Result<(), ParamError> {
+        match name {
+            "alpha" => self.set_alpha(value.as_float(name)?),
+            "l1_ratio" => {
+                let l1_ratio = value.as_float(name)?;
+                if !(0.0..=1.0).contains(&l1_ratio) {
+                    panic!("l1 ratio must be between 0 and 1");
+                }
+                self.l1_ratio = l1_ratio;
+            }
+            "max_iterations" => self.set_max_iterations(value.as_int(name)?),
+            "tolerance" => self.set_tolerance(value.as_float(name)?),
+            "warm_start" => self.set_warm_start(value.as_bool(name)?),
+            _ => return Err(ParamError::Unknown(name.to_string())),
+        }
+        Ok(())
+    }
+}
+
+impl Regressor for ElasticNet {
+    fn fit(&mut self, data: &Matrix, labels: &Matrix) -> Result<(), LinalgError> {
+        return ElasticNet::fit(self, data, labels);
+    }
+
+    fn predict(&self, data: &Matrix) -> RowVector {
+        return ElasticNet::predict(self, data);
+    }
+}
+
+// the elastic net's parameters without the fixed l1 ratio
+impl Estimator for Lasso {
+    fn params(&self) -> Vec<(&'static str, ParamValue)> {
+        let mut params = self.model.params();
+        params.retain(|(name, _)| *name != "l1_ratio");
+        return params;
+    }
+
+    fn set_param(&mut self, name: &str, value: ParamValue) -> Result<(), ParamError> {
+        if name == "l1_ratio" {
+            return Err(ParamError::Unknown(name.to_string()));
+        }
+        return self.model.set_param(name, value);
+    }
+}
+
+impl Regressor for Lasso {
+    fn fit(&mut self, data: &Matrix, labels: &Matrix) -> Result<(), LinalgError> {
+        return Lasso::fit(self, data, labels);
+    }
+
+    fn predict(&self, data: &Matrix) -> RowVector {
+        return Lasso::predict(self, data);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::linear_regression::linear_regression::LinearRegression;
+    use crate::sampling::random::Rng;
+    use std::iter::zip;
+
+    // y = 4 x0 - 3 x2 + 10, the other three features are noise
+    fn sparse_data(rows: usize) -> (Matrix, Matrix) {
+        let mut rng = Rng::new(5);
+        let data: Vec<Vec<f32>> = (0..rows)
+            .map(|_| (0..5).map(|_| rng.normal()).collect())
+            .collect();
+        let labels: Vec<Vec<f32>> = data
+            .iter()
+            .map(|row| vec![4.0 * row[0] - 3.0 * row[2] + 10.0 + 0.1 * rng.normal()])
+            .collect();
+        (Matrix::to_matrix(&data), Matrix::to_matrix(&labels))
+    }
+
+    #[test]
+    fn test_lasso_selects_features() {
+        let (data, labels) = sparse_data(300);
+        let mut lasso = Lasso::new(0.1);
+        lasso.fit(&data, &labels).unwrap();
+        assert!(lasso.converged());
+        let weights = lasso.weights();
+        assert!(weights[1] == 0.0 && weights[3] == 0.0 && weights[4] == 0.0);
+        // the l1 penalty shrinks the kept coefficients by roughly alpha
+        assert!((weights[0] - 3.9).abs() < 0.1);
+        assert!((weights[2] + 2.9).abs() < 0.1);
+        // the intercept is not shrunk
+        assert!((lasso.bias() - 10.0).abs() < 0.1);
+    }
+
+    #[test]
+    fn test_elastic_net_without_penalty_matches_least_squares() {
+        let (data, labels) = sparse_data(200);
+        let mut elastic_net = ElasticNet::new(0.0, 0.5);
+        elastic_net.set_tolerance(1e-7);
+        elastic_net.fit(&data, &labels).unwrap();
+        let mut linear_regression = LinearRegression::new(0.0);
+        linear_regression.fit(&data, &labels).unwrap();
+        assert!(
+            zip(elastic_net.weights(), linear_regression.weights())
+                .all(|(a, b)| (a - b).abs() < 1e-3)
+        );
+        assert!((elastic_net.bias() - linear_regression.bias()).abs() < 1e-3);
+        let predictions = elastic_net.predict(&data);
+        assert!(predictions.len() == 200);
+    }
+
+    #[test]
+    fn test_warm_start_reuses_coefficients() {
+        let (data, labels) = sparse_data(300);
+        let mut cold = ElasticNet::new(0.05, 0.5);
+        cold.fit(&data, &labels).unwrap();
+        let mut warm = ElasticNet::new(0.05, 0.5);
+        warm.set_warm_start(true);
+        warm.fit(&data, &labels).unwrap();
+        warm.set_alpha(0.049);
+        warm.fit(&data, &labels).unwrap();
+        assert!(warm.iterations() <= cold.iterations());
+        assert!(zip(warm.weights(), cold.weights()).all(|(a, b)| (a - b).abs() < 0.01));
+    }
+
+    #[test]
+    fn test_regularization_path() {
+        let (data, labels) = sparse_data(300);
+        let alphas = alpha_grid(&data, &labels, 1.0, 10, 1e-3).unwrap();
+        assert!(alphas.len() == 10);
+        assert!(alphas.windows(2).all(|pair| pair[0] > pair[1]));
+        let path = regularization_path(&data, &labels, &alphas, 1.0).unwrap();
+        let active = path.active_features();
+        // nothing survives the largest alpha and the noise features only enter late
+        assert!(active[0] == 0);
+        assert!(active.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert!(active.contains(&2));
+        // matches independent fits
+        let mut lasso = Lasso::new(alphas[5]);
+        lasso.fit(&data, &labels).unwrap();
+        assert!(zip(lasso.weights(), &path.weights()[5]).all(|(a, b)| (a - b).abs() < 1e-3));
+        assert!((lasso.bias() - path.biases()[5]).abs() < 1e-3);
+    }
+}
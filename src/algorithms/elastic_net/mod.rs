@@ -0,0 +1 @@
+pub mod elastic_net;
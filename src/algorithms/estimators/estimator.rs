@@ -0,0 +1,419 @@
+use crate::dataframe::{DataFrame, DataTypeValue};
+use crate::inference::inference::{accuracy, r2_score, silhouette_score};
+use crate::linear_algebra::{LinalgError, Matrix, RowVector};
+use std::error::Error;
+use std::fmt;
+
+/// Value of a hyperparameter, loosely typed so that search utilities can set
+/// the parameters of any model by name. Settings with enum values (solvers,
+/// kernels, criteria, ...) keep to their typed setters.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ParamValue {
+    Float(f32),
+    Int(usize),
+    Bool(bool),
+    /// Optional limits such as a maximum depth.
+    OptionalInt(Option<usize>),
+    /// Optional thresholds, `None` usually selecting a data driven default.
+    OptionalFloat(Option<f32>),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ParamError {
+    Unknown(String),
+    WrongType {
+        name: String,
+        expected: &'static str,
+    },
+}
+
+impl fmt::Display for ParamError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParamError::Unknown(name) => write!(f, "unknown hyperparameter {}", name),
+            ParamError::WrongType { name, expected } => {
+                write!(f, "hyperparameter {} expects {}", name, expected)
+            }
+        }
+    }
+}
+
+impl Error for ParamError {}
+
+impl ParamValue {
+    pub fn as_float(&self, name: &str) -> Result<f32, ParamError> {
+        match self {
+            ParamValue::Float(value) => Ok(*value),
+            _ => Err(wrong_type(name, "a float")),
+        }
+    }
+
+    pub fn as_int(&self, name: &str) -> Result<usize, ParamError> {
+        match self {
+            ParamValue::Int(value) => Ok(*value),
+            _ => Err(wrong_type(name, "an integer")),
+        }
+    }
+
+    pub fn as_bool(&self, name: &str) -> Result<bool, ParamError> {
+        match self {
+            ParamValue::Bool(value) => Ok(*value),
+            _ => Err(wrong_type(name, "a bool")),
+        }
+    }
+
+    /// Also takes a plain `Int`.
+    pub fn as_optional_int(&self, name: &str) -> Result<Option<usize>, ParamError> {
+        match self {
+            ParamValue::OptionalInt(value) => Ok(*value),
+            ParamValue::Int(value) => Ok(Some(*value)),
+            _ => Err(wrong_type(name, "an optional integer")),
+        }
+    }
+
+    /// Also takes a plain `Float`.
+    pub fn as_optional_float(&self, name: &str) -> Result<Option<f32>, ParamError> {
+        match self {
+            ParamValue::OptionalFloat(value) => Ok(*value),
+            ParamValue::Float(value) => Ok(Some(*value)),
+            _ => Err(wrong_type(name, "an optional float")),
+        }
+    }
+}
+
+fn wrong_type(name: &str, expected: &'static str) -> ParamError {
+    return ParamError::WrongType {
+        name: name.to_string(),
+        expected,
+    };
+}
+
+/// Hyperparameters by name. `set_param` goes through the model's own setter,
+/// so out of range values panic just like they do there, and takes effect on
+/// the next `fit`.
+pub trait Estimator {
+    fn params(&self) -> Vec<(&'static str, ParamValue)>;
+
+    fn set_param(&mut self, name: &str, value: ParamValue) -> Result<(), ParamError>;
+
+    fn param(&self, name: &str) -> Option<ParamValue> {
+        return self
+            .params()
+            .into_iter()
+            .find(|(param, _)| *param == name)
+            .map(|(_, value)| value);
+    }
+}
+
+/// Models predicting a single numeric target. Models whose own `fit` cannot
+/// fail always return `Ok`.
+pub trait Regressor: Estimator {
+    /// `labels` is a single column.
+    fn fit(&mut self, data: &Matrix, labels: &Matrix) -> Result<(), LinalgError>;
+
+    fn predict(&self, data: &Matrix) -> RowVector;
+
+    /// r² of the predictions for `data`, higher is better.
+    fn score(&self, data: &Matrix, labels: &Matrix) -> f32 {
+        return r2_score(self.predict(data).vector(), &labels_from_matrix(labels));
+    }
+}
+
+/// Models predicting one of the class values seen during `fit`.
+pub trait Classifier: Estimator {
+    /// `labels` is a single column of class values.
+    fn fit(&mut self, data: &Matrix, labels: &Matrix) -> Result<(), LinalgError>;
+
+    fn predict(&self, data: &Matrix) -> RowVector;
+
+    /// Class values in ascending order.
+    fn classes(&self) -> &Vec<f32>;
+
+    /// Accuracy of the predictions for `data`, higher is better.
+    fn score(&self, data: &Matrix, labels: &Matrix) -> f32 {
+        return accuracy(self.predict(data).vector(), &labels_from_matrix(labels));
+    }
+}
+
+/// Models grouping the rows they are fitted on.
+pub trait Clusterer: Estimator {
+    fn fit(&mut self, data: &Matrix) -> Result<(), LinalgError>;
+
+    /// Cluster of every training row, negative for noise.
+    fn labels(&self) -> RowVector;
+
+    fn fit_predict(&mut self, data: &Matrix) -> Result<RowVector, LinalgError> {
+        self.fit(data)?;
+        return Ok(self.labels());
+    }
+
+    /// Mean silhouette of the training rows `data` under the fitted labels,
+    /// higher is better.
+    fn score(&self, data: &Matrix) -> f32 {
+        return silhouette_score(data, self.labels().vector());
+    }
+}
+
+/// Numeric labels from a single column matrix or a float `DataFrame` column.
+pub(crate) fn labels_from_matrix(labels: &Matrix) -> Vec<f32> {
+    return labels.matrix().iter().map(|label| label.get(0)).collect();
+}
+
+pub(crate) fn labels_from_dataframe(df: &DataFrame, target_column: &str) -> Vec<f32> {
+    let (_, values) = df.get_column(target_column);
+    return values
+        .iter()
+        .map(|value| match value {
+            DataTypeValue::Float(value) => *value,
+            DataTypeValue::Null => panic!("target column {} contains nulls", target_column),
+            _ => panic!("target column {} must be numeric", target_column),
+        })
+        .collect();
+}
+
+/// Sorted distinct labels and the class index of every label.
+pub(crate) fn encode_classes(labels: &Vec<f32>) -> (Vec<f32>, Vec<usize>) {
+    let mut classes = labels.clone();
+    classes.sort_by(|a, b| a.total_cmp(b));
+    classes.dedup();
+    if classes.len() < 2 {
+        panic!("classification needs at least two classes");
+    }
+    let targets = labels
+        .iter()
+        .map(|label| {
+            classes
+                .binary_search_by(|class| class.total_cmp(label))
+                .unwrap()
+        })
+        .collect();
+    return (classes, targets);
+}
+
+/// Class with the highest probability in each row, ties going to the first.
+pub(crate) fn most_likely_classes(classes: &Vec<f32>, probabilities: &Matrix) -> RowVector {
+    return RowVector::new(
+        &probabilities
+            .matrix()
+            .iter()
+            .map(|row| {
+                let best =
+                    (0..row.len()).fold(
+                        0,
+                        |best, i| if row.get(i) > row.get(best) { i } else { best },
+                    );
+                classes[best]
+            })
+            .collect(),
+    );
+}
+
+/// Logistic function, computed without overflowing for large `|z|`.
+pub(crate) fn sigmoid(z: f64) -> f64 {
+    if z >= 0.0 {
+        return 1.0 / (1.0 + (-z).exp());
+    }
+    let e = z.exp();
+    return e / (1.0 + e);
+}
+
+/// Probabilities from class scores, shifted by the largest score for stability.
+pub(crate) fn softmax(scores: &[f64]) -> Vec<f64> {
+    let max = scores
+        .iter()
+        .fold(f64::NEG_INFINITY, |acc, score| acc.max(*score));
+    let exponentials: Vec<f64> = scores.iter().map(|score| (score - max).exp()).collect();
+    let total: f64 = exponentials.iter().sum();
+    return exponentials.iter().map(|e| e / total).collect();
+}
+
+/// Three standard normal features with label 1 when 2 x0 - x1 + 0.5 plus
+/// `noise` scaled normal noise is positive, x2 carrying no signal.
+#[cfg(test)]
+pub(crate) fn binary_data(rows: usize, seed: u64, noise: f32) -> (Matrix, Matrix) {
+    let mut rng = crate::sampling::random::Rng::new(seed);
+    let data: Vec<Vec<f32>> = (0..rows)
+        .map(|_| (0..3).map(|_| rng.normal()).collect())
+        .collect();
+    let labels: Vec<Vec<f32>> = data
+        .iter()
+        .map(|row| {
+            let score = 2.0 * row[0] - row[1] + 0.5 + noise * rng.normal();
+            vec![(score > 0.0) as usize as f32]
+        })
+        .collect();
+    return (Matrix::to_matrix(&data), Matrix::to_matrix(&labels));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::clustering::{AgglomerativeClustering, Dbscan, KMeans, Linkage};
+    use crate::algorithms::decision_tree::decision_tree::{
+        DecisionTreeClassifier, DecisionTreeRegressor,
+    };
+    use crate::algorithms::decision_tree::tree::{ClassificationCriterion, RegressionCriterion};
+    use crate::algorithms::elastic_net::elastic_net::ElasticNet;
+    use crate::algorithms::gradient_boosting::gradient_boosting::{
+        GradientBoostingClassifier, GradientBoostingRegressor, RegressionLoss,
+    };
+    use crate::algorithms::linear_regression::linear_regression::LinearRegression;
+    use crate::algorithms::logistic_regression::logistic_regression::{
+        LogisticRegression, Penalty,
+    };
+    use crate::algorithms::naive_bayes::GaussianNaiveBayes;
+    use crate::algorithms::neighbors::neighbors::{KNeighborsClassifier, KNeighborsRegressor};
+    use crate::algorithms::random_forest::random_forest::{
+        RandomForestClassifier, RandomForestRegressor,
+    };
+    use crate::algorithms::svm::{Kernel, LinearSvc, LinearSvr, Svc};
+    use crate::sampling::random::Rng;
+
+    // y = 2 x0 - x1 + 1 with a little noise
+    fn regression_data(rows: usize) -> (Matrix, Matrix) {
+        let mut rng = Rng::new(5);
+        let data: Vec<Vec<f32>> = (0..rows)
+            .map(|_| vec![rng.normal(), rng.normal()])
+            .collect();
+        let labels: Vec<Vec<f32>> = data
+            .iter()
+            .map(|row| vec![2.0 * row[0] - row[1] + 1.0 + 0.05 * rng.normal()])
+            .collect();
+        return (Matrix::to_matrix(&data), Matrix::to_matrix(&labels));
+    }
+
+    // blobs around (-2, -2) and (2, 2), the class is the blob
+    fn classification_data(per_class: usize) -> (Matrix, Matrix) {
+        let mut rng = Rng::new(9);
+        let mut data = Vec::new();
+        let mut labels = Vec::new();
+        for (class, center) in [(0.0, -2.0), (1.0, 2.0)] {
+            for _ in 0..per_class {
+                data.push(vec![center + rng.normal(), center + rng.normal()]);
+                labels.push(vec![class]);
+            }
+        }
+        return (Matrix::to_matrix(&data), Matrix::to_matrix(&labels));
+    }
+
+    fn cluster_data(per_cluster: usize) -> Matrix {
+        let mut rng = Rng::new(2);
+        let data: Vec<Vec<f32>> = [[0.0, 0.0], [10.0, 0.0], [0.0, 10.0]]
+            .iter()
+            .flat_map(|center| {
+                (0..per_cluster)
+                    .map(|_| vec![center[0] + rng.normal(), center[1] + rng.normal()])
+                    .collect::<Vec<Vec<f32>>>()
+            })
+            .collect();
+        return Matrix::to_matrix(&data);
+    }
+
+    #[test]
+    fn test_regressors_through_trait() {
+        let (data, labels) = regression_data(300);
+        let mut boosting = GradientBoostingRegressor::new(RegressionLoss::SquaredError, 100);
+        boosting.set_min_samples_leaf(5);
+        let regressors: Vec<Box<dyn Regressor>> = vec![
+            Box::new(LinearRegression::new(0.0)),
+            Box::new(ElasticNet::new(0.01, 0.5)),
+            Box::new(DecisionTreeRegressor::new(
+                RegressionCriterion::SquaredError,
+            )),
+            Box::new(RandomForestRegressor::new(
+                20,
+                RegressionCriterion::SquaredError,
+            )),
+            Box::new(boosting),
+            Box::new(KNeighborsRegressor::new(5)),
+            Box::new(LinearSvr::new(1.0, 0.0)),
+        ];
+        for mut regressor in regressors {
+            regressor.fit(&data, &labels).unwrap();
+            assert_eq!(regressor.predict(&data).len(), data.len());
+            let score = regressor.score(&data, &labels);
+            assert!(score > 0.8, "{:?} r2 {}", regressor.params(), score);
+        }
+    }
+
+    #[test]
+    fn test_classifiers_through_trait() {
+        let (data, labels) = classification_data(100);
+        let classifiers: Vec<Box<dyn Classifier>> = vec![
+            Box::new(LogisticRegression::new(Penalty::None)),
+            Box::new(DecisionTreeClassifier::new(ClassificationCriterion::Gini)),
+            Box::new(RandomForestClassifier::new(
+                20,
+                ClassificationCriterion::Gini,
+            )),
+            Box::new(GradientBoostingClassifier::new(20)),
+            Box::new(KNeighborsClassifier::new(5)),
+            Box::new(LinearSvc::new(1.0)),
+            Box::new(Svc::new(1.0, Kernel::Rbf)),
+            Box::new(GaussianNaiveBayes::new()),
+        ];
+        for mut classifier in classifiers {
+            classifier.fit(&data, &labels).unwrap();
+            assert_eq!(classifier.classes(), &vec![0.0, 1.0]);
+            let score = classifier.score(&data, &labels);
+            assert!(score > 0.95, "{:?} accuracy {}", classifier.params(), score);
+        }
+    }
+
+    #[test]
+    fn test_clusterers_through_trait() {
+        let data = cluster_data(40);
+        let clusterers: Vec<Box<dyn Clusterer>> = vec![
+            Box::new(KMeans::new(3)),
+            Box::new(Dbscan::new(2.0, 5)),
+            Box::new(AgglomerativeClustering::new(3, Linkage::Ward)),
+        ];
+        for mut clusterer in clusterers {
+            let labels = clusterer.fit_predict(&data).unwrap();
+            assert_eq!(labels.len(), data.len());
+            assert!(clusterer.score(&data) > 0.7, "{:?}", clusterer.params());
+        }
+        // a single cluster has no silhouette
+        assert_eq!(silhouette_score(&data, &vec![0.0; data.len()]), 0.0);
+    }
+
+    #[test]
+    fn test_params_round_trip() {
+        let mut forest = RandomForestRegressor::new(10, RegressionCriterion::SquaredError);
+        forest.set_param("num_trees", ParamValue::Int(4)).unwrap();
+        forest.set_param("max_depth", ParamValue::Int(3)).unwrap();
+        assert_eq!(forest.param("num_trees"), Some(ParamValue::Int(4)));
+        assert_eq!(
+            forest.param("max_depth"),
+            Some(ParamValue::OptionalInt(Some(3)))
+        );
+        forest
+            .set_param("max_depth", ParamValue::OptionalInt(None))
+            .unwrap();
+        assert_eq!(
+            forest.param("max_depth"),
+            Some(ParamValue::OptionalInt(None))
+        );
+
+        let mut svc = Svc::new(1.0, Kernel::Rbf);
+        svc.set_param("gamma", ParamValue::Float(0.5)).unwrap();
+        assert_eq!(
+            svc.param("gamma"),
+            Some(ParamValue::OptionalFloat(Some(0.5)))
+        );
+
+        let mut regression = LinearRegression::new(0.0);
+        assert_eq!(
+            regression.set_param("alpha", ParamValue::Float(1.0)),
+            Err(ParamError::Unknown("alpha".to_string()))
+        );
+        assert_eq!(
+            regression.set_param("ridge_value", ParamValue::Bool(true)),
+            Err(ParamError::WrongType {
+                name: "ridge_value".to_string(),
+                expected: "a float"
+            })
+        );
+        assert_eq!(regression.param("missing"), None);
+    }
+}
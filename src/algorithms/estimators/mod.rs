@@ -0,0 +1,8 @@
+pub mod estimator;
+#[cfg(test)]
+pub(crate) use estimator::binary_data;
+pub use estimator::{Classifier, Clusterer, Estimator, ParamError, ParamValue, Regressor};
+pub(crate) use estimator::{
+    encode_classes, labels_from_dataframe, labels_from_matrix, most_likely_classes, sigmoid,
+    softmax,
+};
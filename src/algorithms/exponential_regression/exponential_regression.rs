@@ -1,80 +1,329 @@
-use super::utils::*;
-use crate::linear_algebra::{Matrix, RowVector};
-use std::iter::zip;
+use crate::algorithms::curve_fit::{CurveFit, CurveFitResult, CurveModel};
+use crate::algorithms::estimators::{
+    Estimator, ParamError, ParamValue, Regressor, labels_from_matrix,
+};
+use crate::linear_algebra::{LinalgError, Matrix, RowVector, kernels};
 
+/// `y = c·e^(-k·x)` fitted with Levenberg–Marquardt, the exponential preset
+/// of `CurveFit` over a single feature.
 pub struct ExponentialRegression {
-    c: f32,
-    k: f32,
+    fit: CurveFit,
 }
 
 impl ExponentialRegression {
     pub fn new() -> Self {
-        return Self { c: 0.0, k: 0.0 };
-    }
-
-    pub fn fit(&mut self, data: &Vec<f32>, labels: &Vec<f32>, k: f32, c: f32) {
-        // 1st parameter - k (exponent)
-        // 2nd parameter - c (scalar)
-        // y = Ce^-kx
-        let mut parameters = vec![k, c];
-        let converging_tolerance = (10.0 as f32).powf(-9.0);
-        loop {
-            let [k, c] = [parameters[0], parameters[1]];
-            let predictions: Vec<f32> = data.iter().map(|x_value| exp_x(*x_value, c, k)).collect();
-            let residuals: Matrix = Matrix::new(
-                &zip(labels, predictions.iter())
-                    .map(|(label, prediction)| RowVector::new(&vec![*label - *prediction]))
-                    .collect::<Vec<RowVector>>(),
-            );
-            let residual_p_k: RowVector = RowVector::new(
-                &data
-                    .iter()
-                    .map(|x_value| residual_p_k(*x_value, c, k))
-                    .collect(),
-            );
-            let residual_p_c: RowVector = RowVector::new(
-                &data
-                    .iter()
-                    .map(|x_value| residual_p_c(*x_value, k))
-                    .collect(),
-            );
-            let jacobian_transposed = Matrix::new(&vec![residual_p_k, residual_p_c]);
-            let jacobian = jacobian_transposed.transpose();
-            let jacobian_dotted = jacobian_transposed.multiply(&jacobian);
-            let jacobian_dotted_inverse = jacobian_dotted.inverse();
-            let residual_dotted = jacobian_transposed.multiply(&residuals);
-            let gradient = jacobian_dotted_inverse.multiply(&residual_dotted).transpose();
-            let gradient = gradient.get(0);
-            let gradient_norm = gradient.norm();
-            if gradient_norm.abs() < 0.0001 {
-                break;
-            }
-            parameters = zip(parameters, gradient.vector())
-                .map(|(parameter, gradient_value)| {
-                    parameter - (gradient_value * 0.01)
-                })
-                .collect();
-        }
-        self.k = parameters[0];
-        self.c = parameters[1];
+        return Self {
+            fit: CurveFit::new(CurveModel::exponential()),
+        };
+    }
+
+    pub fn set_max_iterations(&mut self, max_iterations: usize) {
+        self.fit.set_max_iterations(max_iterations);
+    }
+
+    pub fn set_tolerance(&mut self, tolerance: f32) {
+        self.fit.set_tolerance(tolerance);
+    }
+
+    /// Fits starting from the initial guesses `k` and `c`.
+    pub fn fit(
+        &mut self,
+        data: &Vec<f32>,
+        labels: &Vec<f32>,
+        k: f32,
+        c: f32,
+    ) -> Result<(), LinalgError> {
+        return self.fit.fit(&column(data), labels, &vec![k, c]);
     }
 
     pub fn predict(&self, data: &Vec<f32>) -> Vec<f32> {
-        data.iter()
-            .map(|x_value| exp_x(*x_value, self.c, self.k))
-            .collect()
+        return self.fit.predict(&column(data));
+    }
+
+    pub fn k(&self) -> f32 {
+        return self.fit.parameters()[0];
+    }
+
+    pub fn c(&self) -> f32 {
+        return self.fit.parameters()[1];
+    }
+
+    /// Convergence diagnostics and standard errors of `[k, c]`.
+    pub fn result(&self) -> Option<&CurveFitResult> {
+        return self.fit.result();
+    }
+}
+
+/// Starting point of `MultivariateExponentialRegression`. `LogTransform`
+/// solves least squares on `ln(y)` over the rows with positive targets, which
+/// is already the fit when the errors are multiplicative. `Constant` starts
+/// from zero weights and the log of the mean target.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ExponentialInit {
+    LogTransform,
+    Constant,
+}
+
+/// `y = e^(w·x + b)` over every column of a feature matrix, fitted by least
+/// squares on the original scale with Levenberg–Marquardt.
+pub struct MultivariateExponentialRegression {
+    init: ExponentialInit,
+    max_iterations: usize,
+    tolerance: f32,
+    initial_parameters: Vec<f32>,
+    fit: Option<CurveFit>,
+}
+
+impl MultivariateExponentialRegression {
+    pub fn new() -> Self {
+        return Self {
+            init: ExponentialInit::LogTransform,
+            max_iterations: 200,
+            tolerance: 1e-6,
+            initial_parameters: Vec::new(),
+            fit: None,
+        };
+    }
+
+    pub fn set_init(&mut self, init: ExponentialInit) {
+        self.init = init;
+    }
+
+    pub fn set_max_iterations(&mut self, max_iterations: usize) {
+        self.max_iterations = max_iterations;
+    }
+
+    pub fn set_tolerance(&mut self, tolerance: f32) {
+        self.tolerance = tolerance;
+    }
+
+    /// `labels` is a single column of targets.
+    pub fn fit(&mut self, data: &Matrix, labels: &Matrix) -> Result<(), LinalgError> {
+        let (rows, columns) = data.shape();
+        if labels.len() != rows {
+            return Err(LinalgError::DimensionMismatch {
+                expected: (rows, 1),
+                found: labels.shape(),
+            });
+        }
+        let labels: Vec<f32> = labels.matrix().iter().map(|label| label.get(0)).collect();
+        let initial = match self.init {
+            ExponentialInit::LogTransform => log_transform_fit(data, &labels)?,
+            ExponentialInit::Constant => None,
+        };
+        let initial = initial.unwrap_or_else(|| {
+            let mean = labels.iter().sum::<f32>() / rows.max(1) as f32;
+            let mut parameters = vec![0.0; columns + 1];
+            parameters[columns] = if mean > 0.0 { mean.ln() } else { 0.0 };
+            parameters
+        });
+        let mut fit = CurveFit::new(CurveModel::log_linear(columns));
+        fit.set_max_iterations(self.max_iterations);
+        fit.set_tolerance(self.tolerance);
+        fit.fit(data, &labels, &initial)?;
+        self.initial_parameters = initial;
+        self.fit = Some(fit);
+        Ok(())
+    }
+
+    fn fitted(&self) -> &CurveFit {
+        return self.fit.as_ref().expect("regression must be fitted first");
+    }
+
+    pub fn predict(&self, data: &Matrix) -> RowVector {
+        return RowVector::new(&self.fitted().predict(data));
+    }
+
+    pub fn weights(&self) -> Vec<f32> {
+        let parameters = self.fitted().parameters();
+        return parameters[..parameters.len() - 1].to_vec();
+    }
+
+    pub fn bias(&self) -> f32 {
+        return *self.fitted().parameters().last().unwrap();
+    }
+
+    /// Weights then bias the iterative fit started from.
+    pub fn initial_parameters(&self) -> &Vec<f32> {
+        return &self.initial_parameters;
+    }
+
+    /// Convergence diagnostics and standard errors of the weights then bias.
+    pub fn result(&self) -> Option<&CurveFitResult> {
+        return self.fitted().result();
+    }
+}
+
+// ordinary least squares of ln(y) on the rows with positive targets, `None`
+// when there are too few of them
+fn log_transform_fit(data: &Matrix, labels: &Vec<f32>) -> Result<Option<Vec<f32>>, LinalgError> {
+    let columns = data.shape().1;
+    let (rows, targets): (Vec<RowVector>, Vec<RowVector>) = data
+        .matrix()
+        .iter()
+        .zip(labels.iter())
+        .filter(|(_, label)| **label > 0.0)
+        .map(|(row, label)| {
+            let mut extended = row.vector().clone();
+            extended.push(1.0);
+            (RowVector::new(&extended), RowVector::new(&vec![label.ln()]))
+        })
+        .unzip();
+    if rows.len() <= columns {
+        return Ok(None);
+    }
+    let solution = Matrix::new(&rows).lstsq(&Matrix::new(&targets))?;
+    let parameters: Vec<f32> = solution.matrix().iter().map(|row| row.get(0)).collect();
+    // keep the iterative fit away from overflowing starts
+    let largest = data
+        .matrix()
+        .iter()
+        .map(|row| kernels::dot(row.vector(), &parameters[..columns]) + parameters[columns])
+        .fold(f32::MIN, f32::max);
+    if !largest.is_finite() || largest > 80.0 {
+        return Ok(None);
+    }
+    return Ok(Some(parameters));
+}
+
+fn column(data: &Vec<f32>) -> Matrix {
+    return Matrix::to_matrix(&data.iter().map(|x| vec![*x]).collect());
+}
+
+impl Estimator for ExponentialRegression {
+    fn params(&self) -> Vec<(&'static str, ParamValue)> {
+        return vec![
+            ("max_iterations", ParamValue::Int(self.fit.max_iterations())),
+            ("tolerance", ParamValue::Float(self.fit.tolerance())),
+        ];
+    }
+
+    fn set_param(&mut self, name: &str, value: ParamValue) -> Result<(), ParamError> {
+        match name {
+            "max_iterations" => self.set_max_iterations(value.as_int(name)?),
+            "tolerance" => self.set_tolerance(value.as_float(name)?),
+            _ => return Err(ParamError::Unknown(name.to_string())),
+        }
+        Ok(())
+    }
+}
+
+// `data` is the single feature column, the initial guesses come from least
+// squares on ln(y) when there are enough positive targets
+impl Regressor for ExponentialRegression {
+    fn fit(&mut self, data: &Matrix, labels: &Matrix) -> Result<(), LinalgError> {
+        if data.shape().1 != 1 {
+            return Err(LinalgError::DimensionMismatch {
+                expected: (data.len(), 1),
+                found: data.shape(),
+            });
+        }
+        let (data, labels) = (labels_from_matrix(data), labels_from_matrix(labels));
+        let (k, c) = match log_transform_fit(&column(&data), &labels)? {
+            Some(parameters) => (-parameters[0], parameters[1].exp()),
+            None => (0.0, labels.iter().sum::<f32>() / labels.len().max(1) as f32),
+        };
+        return ExponentialRegression::fit(self, &data, &labels, k, c);
+    }
+
+    fn predict(&self, data: &Matrix) -> RowVector {
+        return RowVector::new(&ExponentialRegression::predict(
+            self,
+            &labels_from_matrix(data),
+        ));
+    }
+}
+
+impl Estimator for MultivariateExponentialRegression {
+    fn params(&self) -> Vec<(&'static str, ParamValue)> {
+        return vec![
+            ("max_iterations", ParamValue::Int(self.max_iterations)),
+            ("tolerance", ParamValue::Float(self.tolerance)),
+        ];
+    }
+
+    fn set_param(&mut self, name: &str, value: ParamValue) -> Result<(), ParamError> {
+        match name {
+            "max_iterations" => self.set_max_iterations(value.as_int(name)?),
+            "tolerance" => self.set_tolerance(value.as_float(name)?),
+            _ => return Err(ParamError::Unknown(name.to_string())),
+        }
+        Ok(())
+    }
+}
+
+impl Regressor for MultivariateExponentialRegression {
+    fn fit(&mut self, data: &Matrix, labels: &Matrix) -> Result<(), LinalgError> {
+        return MultivariateExponentialRegression::fit(self, data, labels);
+    }
+
+    fn predict(&self, data: &Matrix) -> RowVector {
+        return MultivariateExponentialRegression::predict(self, data);
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::sampling::random::Rng;
+
     #[test]
     fn test_exponential_regression() {
         let labels = vec![2.0, 4.0, 8.0, 16.0, 24.0];
         let features = vec![1.0, 2.0, 3.0, 4.0, 5.0];
         let mut exponential_regression = ExponentialRegression::new();
-        exponential_regression.fit(&features, &labels, 1.0, 1.0);
-        println!("{:?}", exponential_regression.predict(&features));
+        exponential_regression
+            .fit(&features, &labels, 1.0, 1.0)
+            .unwrap();
+        let result = exponential_regression.result().unwrap();
+        assert!(result.converged());
+        // growth, so the fitted decay rate is negative
+        assert!(exponential_regression.k() < -0.4 && exponential_regression.k() > -0.8);
+        let predictions = exponential_regression.predict(&features);
+        for (prediction, label) in predictions.iter().zip(labels.iter()) {
+            assert!((prediction - label).abs() < 3.0);
+        }
+        assert!(result.standard_errors().unwrap().iter().all(|e| *e > 0.0));
+    }
+
+    #[test]
+    fn test_multivariate_exponential_regression() {
+        let mut rng = Rng::new(5);
+        let mut data = Vec::new();
+        let mut labels = Vec::new();
+        for _ in 0..300 {
+            let x = vec![
+                rng.next_f32() * 4.0,
+                rng.next_f32() * 2.0 - 1.0,
+                rng.normal(),
+            ];
+            let mean = (0.5 + 0.4 * x[0] - 0.8 * x[1] + 0.1 * x[2]).exp();
+            labels.push(vec![mean + 0.2 * rng.normal()]);
+            data.push(x);
+        }
+        let (data, labels) = (Matrix::to_matrix(&data), Matrix::to_matrix(&labels));
+        let mut from_logs = MultivariateExponentialRegression::new();
+        from_logs.fit(&data, &labels).unwrap();
+        let mut from_constant = MultivariateExponentialRegression::new();
+        from_constant.set_init(ExponentialInit::Constant);
+        from_constant.fit(&data, &labels).unwrap();
+        let (logs, constant) = (from_logs.result().unwrap(), from_constant.result().unwrap());
+        assert!(logs.converged() && constant.converged());
+        assert!(logs.iterations() < constant.iterations());
+        assert!(from_constant.initial_parameters()[..3] == [0.0, 0.0, 0.0]);
+
+        let truth = [0.4, -0.8, 0.1];
+        let errors = logs.standard_errors().unwrap();
+        for j in 0..3 {
+            assert!((from_logs.weights()[j] - truth[j]).abs() < 4.0 * errors[j] + 1e-3);
+            assert!((from_logs.weights()[j] - from_constant.weights()[j]).abs() < 1e-3);
+        }
+        assert!((from_logs.bias() - 0.5).abs() < 4.0 * errors[3] + 1e-3);
+        let predictions = from_logs.predict(&data);
+        assert!(predictions.len() == 300);
+        let rss: f32 = (0..300)
+            .map(|i| (predictions.get(i) - labels.get(i).get(0)).powi(2))
+            .sum();
+        assert!((rss - logs.residual_sum_of_squares()).abs() < 1e-2 * rss);
     }
 }
@@ -1,2 +1 @@
 pub mod exponential_regression;
-mod utils;
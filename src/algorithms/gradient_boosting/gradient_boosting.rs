@@ -0,0 +1,861 @@
+use super::histogram::{BinMapper, GrowthParameters, HistogramTree};
+use crate::algorithms::decision_tree::tree::{FeatureKind, FeatureTable};
+use crate::algorithms::estimators::{
+    Classifier, Estimator, ParamError, ParamValue, Regressor, encode_classes,
+    labels_from_dataframe, labels_from_matrix, most_likely_classes, sigmoid, softmax,
+};
+use crate::dataframe::DataFrame;
+use crate::linear_algebra::{LinalgError, Matrix, RowVector};
+use crate::sampling::random::Rng;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum RegressionLoss {
+    SquaredError,
+    /// Leaves are refit to the median residual, robust to outliers.
+    AbsoluteError,
+    /// Squared within `delta` of the prediction and absolute beyond it.
+    Huber(f32),
+}
+
+/// Histogram gradient boosted regression trees. Features are bucketed into at
+/// most `max_bins` quantile bins (categories get a bin each, nulls their own),
+/// then every round fits a shallow tree to the loss gradients and adds it
+/// scaled by the learning rate.
+pub struct GradientBoostingRegressor {
+    loss: RegressionLoss,
+    booster: Booster,
+}
+
+/// Histogram gradient boosted classifier with the logistic loss, one tree per
+/// round for two classes and one per class and round (softmax) otherwise.
+pub struct GradientBoostingClassifier {
+    booster: Booster,
+    classes: Vec<f32>,
+}
+
+// losses over raw scores, one score per row for regression and binary
+// classification and one per class for softmax
+enum Loss {
+    Squared,
+    Absolute,
+    Huber(f64),
+    Logistic,
+    Softmax(usize),
+}
+
+impl Loss {
+    fn num_scores(&self) -> usize {
+        match self {
+            Loss::Softmax(num_classes) => *num_classes,
+            _ => 1,
+        }
+    }
+
+    fn initial(&self, targets: &Vec<f32>, samples: &Vec<usize>) -> Vec<f64> {
+        let values: Vec<f64> = samples
+            .iter()
+            .map(|sample| targets[*sample] as f64)
+            .collect();
+        let count = values.len().max(1) as f64;
+        match self {
+            Loss::Squared => vec![values.iter().sum::<f64>() / count],
+            Loss::Absolute | Loss::Huber(_) => vec![median(values)],
+            Loss::Logistic => {
+                let p = (values.iter().sum::<f64>() / count).clamp(1e-12, 1.0 - 1e-12);
+                vec![(p / (1.0 - p)).ln()]
+            }
+            Loss::Softmax(num_classes) => (0..*num_classes)
+                .map(|k| {
+                    let share =
+                        values.iter().filter(|value| **value as usize == k).count() as f64 / count;
+                    share.max(1e-12).ln()
+                })
+                .collect(),
+        }
+    }
+
+    // gradient and hessian of score k
+    fn derivatives(&self, target: f32, raw: &Vec<f64>, k: usize) -> (f64, f64) {
+        let target = target as f64;
+        match self {
+            Loss::Squared => (raw[0] - target, 1.0),
+            Loss::Absolute => ((raw[0] - target).signum(), 1.0),
+            Loss::Huber(delta) => ((raw[0] - target).clamp(-delta, *delta), 1.0),
+            Loss::Logistic => {
+                let p = sigmoid(raw[0]);
+                (p - target, (p * (1.0 - p)).max(1e-16))
+            }
+            Loss::Softmax(_) => {
+                let p = softmax(raw)[k];
+                let indicator = (target as usize == k) as usize as f64;
+                (p - indicator, (p * (1.0 - p)).max(1e-16))
+            }
+        }
+    }
+
+    fn value(&self, target: f32, raw: &Vec<f64>) -> f64 {
+        let target = target as f64;
+        match self {
+            Loss::Squared => (target - raw[0]).powi(2),
+            Loss::Absolute => (target - raw[0]).abs(),
+            Loss::Huber(delta) => {
+                let residual = (target - raw[0]).abs();
+                if residual <= *delta {
+                    0.5 * residual * residual
+                } else {
+                    delta * (residual - 0.5 * delta)
+                }
+            }
+            Loss::Logistic => {
+                let p = sigmoid(raw[0]);
+                -(target * p.max(1e-15).ln() + (1.0 - target) * (1.0 - p).max(1e-15).ln())
+            }
+            Loss::Softmax(_) => -softmax(raw)[target as usize].max(1e-15).ln(),
+        }
+    }
+
+    // leaf value from the residuals of its rows for losses whose gradient step
+    // is a poor estimate of the optimal constant
+    fn refit(&self, residuals: Vec<f64>) -> Option<f64> {
+        match self {
+            Loss::Absolute => Some(median(residuals)),
+            Loss::Huber(delta) => {
+                let center = median(residuals.clone());
+                let shift = residuals
+                    .iter()
+                    .map(|residual| (residual - center).clamp(-delta, *delta))
+                    .sum::<f64>()
+                    / residuals.len().max(1) as f64;
+                Some(center + shift)
+            }
+            _ => None,
+        }
+    }
+}
+
+// settings and fitted rounds shared by both models
+struct Booster {
+    num_rounds: usize,
+    learning_rate: f32,
+    max_depth: usize,
+    min_samples_leaf: usize,
+    l2_regularization: f32,
+    max_bins: usize,
+    subsample: f32,
+    validation_fraction: Option<f32>,
+    patience: usize,
+    tolerance: f32,
+    seed: u64,
+    features: Vec<(String, FeatureKind)>,
+    mapper: Option<BinMapper>,
+    initial: Vec<f64>,
+    rounds: Vec<Vec<HistogramTree>>,
+    train_loss: Vec<f32>,
+    validation_loss: Vec<f32>,
+}
+
+impl Booster {
+    fn new(num_rounds: usize) -> Self {
+        return Self {
+            num_rounds,
+            learning_rate: 0.1,
+            max_depth: 3,
+            min_samples_leaf: 20,
+            l2_regularization: 0.0,
+            max_bins: 255,
+            subsample: 1.0,
+            validation_fraction: None,
+            patience: 10,
+            tolerance: 1e-7,
+            seed: 0,
+            features: Vec::new(),
+            mapper: None,
+            initial: Vec::new(),
+            rounds: Vec::new(),
+            train_loss: Vec::new(),
+            validation_loss: Vec::new(),
+        };
+    }
+
+    fn params(&self) -> Vec<(&'static str, ParamValue)> {
+        return vec![
+            ("num_rounds", ParamValue::Int(self.num_rounds)),
+            ("learning_rate", ParamValue::Float(self.learning_rate)),
+            ("max_depth", ParamValue::Int(self.max_depth)),
+            ("min_samples_leaf", ParamValue::Int(self.min_samples_leaf)),
+            (
+                "l2_regularization",
+                ParamValue::Float(self.l2_regularization),
+            ),
+            ("max_bins", ParamValue::Int(self.max_bins)),
+            ("subsample", ParamValue::Float(self.subsample)),
+            ("seed", ParamValue::Int(self.seed as usize)),
+        ];
+    }
+
+    fn fit(&mut self, table: &FeatureTable, targets: &Vec<f32>, loss: &Loss) {
+        assert!(
+            table.len() == targets.len(),
+            "data and labels must have the same length"
+        );
+        let rows = targets.len();
+        let mapper = BinMapper::fit(table, self.max_bins);
+        let bins = mapper.transform(table);
+        let mut rng = Rng::new(self.seed);
+        let mut training = rng.permutation(rows);
+        let validation_len = match self.validation_fraction {
+            Some(fraction) if rows > 1 => {
+                ((rows as f32 * fraction).round() as usize).clamp(1, rows - 1)
+            }
+            _ => 0,
+        };
+        let validation = training.split_off(rows - validation_len);
+        let sample_len = ((training.len() as f32 * self.subsample).round() as usize)
+            .clamp(1, training.len().max(1));
+        let parameters = GrowthParameters {
+            max_depth: self.max_depth,
+            min_samples_leaf: self.min_samples_leaf,
+            l2_regularization: self.l2_regularization as f64,
+        };
+        let num_scores = loss.num_scores();
+
+        self.initial = loss.initial(targets, &training);
+        self.rounds = Vec::new();
+        self.train_loss = Vec::new();
+        self.validation_loss = Vec::new();
+        let mut raw: Vec<Vec<f64>> = vec![self.initial.clone(); rows];
+        let mean_loss = |raw: &Vec<Vec<f64>>, rows: &Vec<usize>| {
+            (rows
+                .iter()
+                .map(|row| loss.value(targets[*row], &raw[*row]))
+                .sum::<f64>()
+                / rows.len().max(1) as f64) as f32
+        };
+        let (mut best_loss, mut best_rounds, mut rounds_without_improvement) =
+            (f32::INFINITY, 0, 0);
+        for round in 0..self.num_rounds {
+            let samples: Vec<usize> = if sample_len < training.len() {
+                let mut shuffled = training.clone();
+                rng.shuffle(&mut shuffled);
+                shuffled.truncate(sample_len);
+                shuffled
+            } else {
+                training.clone()
+            };
+            // all scores of a round are fitted to the gradients at the start of it
+            let derivatives: Vec<(Vec<f64>, Vec<f64>)> = (0..num_scores)
+                .map(|k| {
+                    (0..rows)
+                        .map(|row| loss.derivatives(targets[row], &raw[row], k))
+                        .unzip()
+                })
+                .collect();
+            let mut trees = Vec::with_capacity(num_scores);
+            for (k, (gradients, hessians)) in derivatives.iter().enumerate() {
+                let (mut tree, leaves) = HistogramTree::grow(
+                    &bins,
+                    &mapper,
+                    gradients,
+                    hessians,
+                    samples.clone(),
+                    &parameters,
+                );
+                for (leaf, leaf_rows) in leaves {
+                    let residuals = leaf_rows
+                        .iter()
+                        .map(|row| targets[*row] as f64 - raw[*row][k])
+                        .collect();
+                    let value = match loss.refit(residuals) {
+                        Some(value) => value as f32,
+                        None => tree.leaf_value(leaf),
+                    };
+                    tree.set_leaf_value(leaf, value * self.learning_rate);
+                }
+                trees.push(tree);
+            }
+            for (row, scores) in raw.iter_mut().enumerate() {
+                for (k, tree) in trees.iter().enumerate() {
+                    scores[k] +=
+                        tree.predict(&mapper, |feature| bins[feature][row] as usize) as f64;
+                }
+            }
+            self.rounds.push(trees);
+            self.train_loss.push(mean_loss(&raw, &training));
+            if !validation.is_empty() {
+                let validation_loss = mean_loss(&raw, &validation);
+                self.validation_loss.push(validation_loss);
+                if round == 0 || validation_loss < best_loss - self.tolerance * best_loss.abs() {
+                    (best_loss, best_rounds, rounds_without_improvement) =
+                        (validation_loss, round + 1, 0);
+                } else {
+                    rounds_without_improvement += 1;
+                    if rounds_without_improvement >= self.patience {
+                        break;
+                    }
+                }
+            }
+        }
+        if !validation.is_empty() {
+            self.rounds.truncate(best_rounds);
+        }
+        self.features = table.features.clone();
+        self.mapper = Some(mapper);
+    }
+
+    // raw scores after each round
+    fn staged_raw(&self, table: &FeatureTable) -> Vec<Vec<Vec<f64>>> {
+        let mapper = self
+            .mapper
+            .as_ref()
+            .expect("gradient boosting must be fitted first");
+        let bins = mapper.transform(table);
+        let mut raw: Vec<Vec<f64>> = vec![self.initial.clone(); table.len()];
+        let mut stages = Vec::with_capacity(self.rounds.len());
+        for trees in self.rounds.iter() {
+            for (row, scores) in raw.iter_mut().enumerate() {
+                for (k, tree) in trees.iter().enumerate() {
+                    scores[k] += tree.predict(mapper, |feature| bins[feature][row] as usize) as f64;
+                }
+            }
+            stages.push(raw.clone());
+        }
+        return stages;
+    }
+
+    fn raw(&self, table: &FeatureTable) -> Vec<Vec<f64>> {
+        let mapper = self
+            .mapper
+            .as_ref()
+            .expect("gradient boosting must be fitted first");
+        let bins = mapper.transform(table);
+        return (0..table.len())
+            .map(|row| {
+                let mut scores = self.initial.clone();
+                for trees in self.rounds.iter() {
+                    for (k, tree) in trees.iter().enumerate() {
+                        scores[k] +=
+                            tree.predict(mapper, |feature| bins[feature][row] as usize) as f64;
+                    }
+                }
+                scores
+            })
+            .collect();
+    }
+}
+
+impl GradientBoostingRegressor {
+    pub fn new(loss: RegressionLoss, num_rounds: usize) -> Self {
+        if let RegressionLoss::Huber(delta) = loss
+            && delta <= 0.0
+        {
+            panic!("huber delta must be positive");
+        }
+        return Self {
+            loss,
+            booster: Booster::new(num_rounds),
+        };
+    }
+
+    /// Shrinkage applied to every tree, smaller values need more rounds but
+    /// generalize better.
+    pub fn set_learning_rate(&mut self, learning_rate: f32) {
+        self.booster.learning_rate = learning_rate;
+    }
+
+    pub fn set_max_depth(&mut self, max_depth: usize) {
+        self.booster.max_depth = max_depth;
+    }
+
+    pub fn set_min_samples_leaf(&mut self, min_samples_leaf: usize) {
+        self.booster.min_samples_leaf = min_samples_leaf.max(1);
+    }
+
+    /// L2 penalty on leaf values, added to the hessian sums.
+    pub fn set_l2_regularization(&mut self, l2_regularization: f32) {
+        self.booster.l2_regularization = l2_regularization;
+    }
+
+    pub fn set_max_bins(&mut self, max_bins: usize) {
+        if max_bins < 2 || max_bins > u16::MAX as usize - 1 {
+            panic!("max bins must be between 2 and {}", u16::MAX - 1);
+        }
+        self.booster.max_bins = max_bins;
+    }
+
+    /// Share of the training rows drawn (without replacement) for each round.
+    pub fn set_subsample(&mut self, subsample: f32) {
+        if subsample <= 0.0 || subsample > 1.0 {
+            panic!("subsample must be in (0, 1]");
+        }
+        self.booster.subsample = subsample;
+    }
+
+    /// Holds out `validation_fraction` of the rows and stops once their loss
+    /// has not improved for `patience` rounds, keeping the best rounds.
+    pub fn set_early_stopping(&mut self, validation_fraction: f32, patience: usize) {
+        if validation_fraction <= 0.0 || validation_fraction >= 1.0 {
+            panic!("validation fraction must be between 0 and 1");
+        }
+        self.booster.validation_fraction = Some(validation_fraction);
+        self.booster.patience = patience.max(1);
+    }
+
+    pub fn set_seed(&mut self, seed: u64) {
+        self.booster.seed = seed;
+    }
+
+    fn internal_loss(&self) -> Loss {
+        match self.loss {
+            RegressionLoss::SquaredError => Loss::Squared,
+            RegressionLoss::AbsoluteError => Loss::Absolute,
+            RegressionLoss::Huber(delta) => Loss::Huber(delta as f64),
+        }
+    }
+
+    pub fn fit(&mut self, data: &Matrix, labels: &Matrix) {
+        let loss = self.internal_loss();
+        self.booster.fit(
+            &FeatureTable::from_matrix(data),
+            &labels_from_matrix(labels),
+            &loss,
+        );
+    }
+
+    pub fn fit_df(&mut self, df: &DataFrame, feature_columns: &Vec<String>, target_column: &str) {
+        let loss = self.internal_loss();
+        self.booster.fit(
+            &FeatureTable::learn(df, feature_columns),
+            &labels_from_dataframe(df, target_column),
+            &loss,
+        );
+    }
+
+    pub fn predict(&self, data: &Matrix) -> RowVector {
+        return scores_to_vector(&self.booster.raw(&FeatureTable::from_matrix(data)));
+    }
+
+    pub fn predict_df(&self, df: &DataFrame) -> RowVector {
+        return scores_to_vector(
+            &self
+                .booster
+                .raw(&FeatureTable::encode(df, &self.booster.features)),
+        );
+    }
+
+    /// Predictions after each boosting round, the last one equals `predict`.
+    pub fn staged_predict(&self, data: &Matrix) -> Vec<RowVector> {
+        return self
+            .booster
+            .staged_raw(&FeatureTable::from_matrix(data))
+            .iter()
+            .map(scores_to_vector)
+            .collect();
+    }
+
+    pub fn staged_predict_df(&self, df: &DataFrame) -> Vec<RowVector> {
+        return self
+            .booster
+            .staged_raw(&FeatureTable::encode(df, &self.booster.features))
+            .iter()
+            .map(scores_to_vector)
+            .collect();
+    }
+
+    /// Rounds kept after early stopping.
+    pub fn num_rounds(&self) -> usize {
+        return self.booster.rounds.len();
+    }
+
+    /// Mean training loss after every round that was run.
+    pub fn train_loss_history(&self) -> &Vec<f32> {
+        return &self.booster.train_loss;
+    }
+
+    /// Mean held out loss after every round when early stopping.
+    pub fn validation_loss_history(&self) -> &Vec<f32> {
+        return &self.booster.validation_loss;
+    }
+}
+
+impl GradientBoostingClassifier {
+    pub fn new(num_rounds: usize) -> Self {
+        return Self {
+            booster: Booster::new(num_rounds),
+            classes: Vec::new(),
+        };
+    }
+
+    pub fn set_learning_rate(&mut self, learning_rate: f32) {
+        self.booster.learning_rate = learning_rate;
+    }
+
+    pub fn set_max_depth(&mut self, max_depth: usize) {
+        self.booster.max_depth = max_depth;
+    }
+
+    pub fn set_min_samples_leaf(&mut self, min_samples_leaf: usize) {
+        self.booster.min_samples_leaf = min_samples_leaf.max(1);
+    }
+
+    pub fn set_l2_regularization(&mut self, l2_regularization: f32) {
+        self.booster.l2_regularization = l2_regularization;
+    }
+
+    pub fn set_max_bins(&mut self, max_bins: usize) {
+        if max_bins < 2 || max_bins > u16::MAX as usize - 1 {
+            panic!("max bins must be between 2 and {}", u16::MAX - 1);
+        }
+        self.booster.max_bins = max_bins;
+    }
+
+    pub fn set_subsample(&mut self, subsample: f32) {
+        if subsample <= 0.0 || subsample > 1.0 {
+            panic!("subsample must be in (0, 1]");
+        }
+        self.booster.subsample = subsample;
+    }
+
+    pub fn set_early_stopping(&mut self, validation_fraction: f32, patience: usize) {
+        if validation_fraction <= 0.0 || validation_fraction >= 1.0 {
+            panic!("validation fraction must be between 0 and 1");
+        }
+        self.booster.validation_fraction = Some(validation_fraction);
+        self.booster.patience = patience.max(1);
+    }
+
+    pub fn set_seed(&mut self, seed: u64) {
+        self.booster.seed = seed;
+    }
+
+    pub fn fit(&mut self, data: &Matrix, labels: &Matrix) {
+        self.fit_table(
+            &FeatureTable::from_matrix(data),
+            &labels_from_matrix(labels),
+        );
+    }
+
+    pub fn fit_df(&mut self, df: &DataFrame, feature_columns: &Vec<String>, target_column: &str) {
+        self.fit_table(
+            &FeatureTable::learn(df, feature_columns),
+            &labels_from_dataframe(df, target_column),
+        );
+    }
+
+    fn fit_table(&mut self, table: &FeatureTable, labels: &Vec<f32>) {
+        let (classes, targets) = encode_classes(labels);
+        let loss = if classes.len() == 2 {
+            Loss::Logistic
+        } else {
+            Loss::Softmax(classes.len())
+        };
+        let targets: Vec<f32> = targets.iter().map(|target| *target as f32).collect();
+        self.booster.fit(table, &targets, &loss);
+        self.classes = classes;
+    }
+
+    /// Class probabilities with columns in the order of `classes()`.
+    pub fn predict_proba(&self, data: &Matrix) -> Matrix {
+        return scores_to_probabilities(&self.booster.raw(&FeatureTable::from_matrix(data)));
+    }
+
+    pub fn predict_proba_df(&self, df: &DataFrame) -> Matrix {
+        return scores_to_probabilities(
+            &self
+                .booster
+                .raw(&FeatureTable::encode(df, &self.booster.features)),
+        );
+    }
+
+    pub fn predict(&self, data: &Matrix) -> RowVector {
+        return most_likely_classes(&self.classes, &self.predict_proba(data));
+    }
+
+    pub fn predict_df(&self, df: &DataFrame) -> RowVector {
+        return most_likely_classes(&self.classes, &self.predict_proba_df(df));
+    }
+
+    /// Class probabilities after each boosting round.
+    pub fn staged_predict_proba(&self, data: &Matrix) -> Vec<Matrix> {
+        return self
+            .booster
+            .staged_raw(&FeatureTable::from_matrix(data))
+            .iter()
+            .map(scores_to_probabilities)
+            .collect();
+    }
+
+    pub fn staged_predict_proba_df(&self, df: &DataFrame) -> Vec<Matrix> {
+        return self
+            .booster
+            .staged_raw(&FeatureTable::encode(df, &self.booster.features))
+            .iter()
+            .map(scores_to_probabilities)
+            .collect();
+    }
+
+    pub fn classes(&self) -> &Vec<f32> {
+        return &self.classes;
+    }
+
+    pub fn num_rounds(&self) -> usize {
+        return self.booster.rounds.len();
+    }
+
+    /// Mean log loss on the training rows after every round that was run.
+    pub fn train_loss_history(&self) -> &Vec<f32> {
+        return &self.booster.train_loss;
+    }
+
+    pub fn validation_loss_history(&self) -> &Vec<f32> {
+        return &self.booster.validation_loss;
+    }
+}
+
+fn scores_to_vector(raw: &Vec<Vec<f64>>) -> RowVector {
+    return RowVector::new(&raw.iter().map(|scores| scores[0] as f32).collect());
+}
+
+fn scores_to_probabilities(raw: &Vec<Vec<f64>>) -> Matrix {
+    return Matrix::new(
+        &raw.iter()
+            .map(|scores| {
+                let probabilities = if scores.len() == 1 {
+                    let p = sigmoid(scores[0]);
+                    vec![1.0 - p, p]
+                } else {
+                    softmax(scores)
+                };
+                RowVector::new(&probabilities.iter().map(|p| *p as f32).collect())
+            })
+            .collect(),
+    );
+}
+
+fn median(mut values: Vec<f64>) -> f64 {
+    if values.is_empty() {
+        return 0.0;
+    }
+    values.sort_by(|a, b| a.total_cmp(b));
+    let middle = values.len() / 2;
+    if values.len().is_multiple_of(2) {
+        return (values[middle - 1] + values[middle]) / 2.0;
+    }
+    return values[middle];
+}
+
+impl Estimator for GradientBoostingRegressor {
+    fn params(&self) -> Vec<(&'static str, ParamValue)> {
+        return self.booster.params();
+    }
+
+    fn set_param(&mut self, name: &str, value: ParamValue) -> Result<(), ParamError> {
+        match name {
+            "num_rounds" => self.booster.num_rounds = value.as_int(name)?,
+            "learning_rate" => self.set_learning_rate(value.as_float(name)?),
+            "max_depth" => self.set_max_depth(value.as_int(name)?),
+            "min_samples_leaf" => self.set_min_samples_leaf(value.as_int(name)?),
+            "l2_regularization" => self.set_l2_regularization(value.as_float(name)?),
+            "max_bins" => self.set_max_bins(value.as_int(name)?),
+            "subsample" => self.set_subsample(value.as_float(name)?),
+            "seed" => self.set_seed(value.as_int(name)? as u64),
+            _ => return Err(ParamError::Unknown(name.to_string())),
+        }
+        Ok(())
+    }
+}
+
+impl Regressor for GradientBoostingRegressor {
+    fn fit(&mut self, data: &Matrix, labels: &Matrix) -> Result<(), LinalgError> {
+        GradientBoostingRegressor::fit(self, data, labels);
+        Ok(())
+    }
+
+    fn predict(&self, data: &Matrix) -> RowVector {
+        return GradientBoostingRegressor::predict(self, data);
+    }
+}
+
+impl Estimator for GradientBoostingClassifier {
+    fn params(&self) -> Vec<(&'static str, ParamValue)> {
+        return self.booster.params();
+    }
+
+    fn set_param(&mut self, name: &str, value: ParamValue) -> Result<(), ParamError> {
+        match name {
+            "num_rounds" => self.booster.num_rounds = value.as_int(name)?,
+            "learning_rate" => self.set_learning_rate(value.as_float(name)?),
+            "max_depth" => self.set_max_depth(value.as_int(name)?),
+            "min_samples_leaf" => self.set_min_samples_leaf(value.as_int(name)?),
+            "l2_regularization" => self.set_l2_regularization(value.as_float(name)?),
+            "max_bins" => self.set_max_bins(value.as_int(name)?),
+            "subsample" => self.set_subsample(value.as_float(name)?),
+            "seed" => self.set_seed(value.as_int(name)? as u64),
+            _ => return Err(ParamError::Unknown(name.to_string())),
+        }
+        Ok(())
+    }
+}
+
+impl Classifier for GradientBoostingClassifier {
+    fn fit(&mut self, data: &Matrix, labels: &Matrix) -> Result<(), LinalgError> {
+        GradientBoostingClassifier::fit(self, data, labels);
+        Ok(())
+    }
+
+    fn predict(&self, data: &Matrix) -> RowVector {
+        return GradientBoostingClassifier::predict(self, data);
+    }
+
+    fn classes(&self) -> &Vec<f32> {
+        return GradientBoostingClassifier::classes(self);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dataframe::csv::df_from_csv;
+    use crate::inference::inference::{accuracy, rmse};
+
+    // y = 3 sin(x0) + 2 [x1 > 0.5] with x2 irrelevant
+    fn nonlinear_data(rows: usize, seed: u64) -> (Matrix, Matrix) {
+        let mut rng = Rng::new(seed);
+        let data: Vec<Vec<f32>> = (0..rows)
+            .map(|_| vec![6.0 * rng.next_f32() - 3.0, rng.next_f32(), rng.next_f32()])
+            .collect();
+        let labels: Vec<Vec<f32>> = data
+            .iter()
+            .map(|row| vec![3.0 * row[0].sin() + if row[1] > 0.5 { 2.0 } else { 0.0 }])
+            .collect();
+        (Matrix::to_matrix(&data), Matrix::to_matrix(&labels))
+    }
+
+    #[test]
+    fn test_boosting_regressor_fits_nonlinear_target() {
+        let (data, labels) = nonlinear_data(1000, 1);
+        let (test_data, test_labels) = nonlinear_data(300, 2);
+        let mut model = GradientBoostingRegressor::new(RegressionLoss::SquaredError, 200);
+        model.fit(&data, &labels);
+        let history = model.train_loss_history();
+        assert!(history.len() == 200);
+        assert!(history.windows(2).all(|pair| pair[1] <= pair[0] + 1e-6));
+        let predictions = model.predict(&test_data);
+        assert!(rmse(predictions.vector(), &labels_from_matrix(&test_labels)) < 0.2);
+        let stages = model.staged_predict(&test_data);
+        assert!(stages.len() == 200);
+        assert!(stages[199].vector() == predictions.vector());
+        let first_rmse = rmse(stages[0].vector(), &labels_from_matrix(&test_labels));
+        assert!(first_rmse > rmse(stages[50].vector(), &labels_from_matrix(&test_labels)));
+    }
+
+    #[test]
+    fn test_robust_losses_ignore_outliers() {
+        let (data, labels) = nonlinear_data(600, 3);
+        let mut corrupted = labels_from_matrix(&labels);
+        (0..600).step_by(20).for_each(|i| corrupted[i] += 1000.0);
+        let corrupted = Matrix::to_matrix(&corrupted.iter().map(|label| vec![*label]).collect());
+        let (test_data, test_labels) = nonlinear_data(300, 4);
+        let test_labels = labels_from_matrix(&test_labels);
+        let fit_rmse = |loss: RegressionLoss| {
+            let mut model = GradientBoostingRegressor::new(loss, 150);
+            model.fit(&data, &corrupted);
+            rmse(model.predict(&test_data).vector(), &test_labels)
+        };
+        let squared = fit_rmse(RegressionLoss::SquaredError);
+        let absolute = fit_rmse(RegressionLoss::AbsoluteError);
+        let huber = fit_rmse(RegressionLoss::Huber(1.0));
+        assert!(absolute < 0.5 && huber < 0.5);
+        assert!(squared > absolute && squared > huber);
+    }
+
+    #[test]
+    fn test_early_stopping_and_subsampling() {
+        let (data, labels) = nonlinear_data(800, 5);
+        let fit = |seed: u64| {
+            let mut model = GradientBoostingRegressor::new(RegressionLoss::SquaredError, 2000);
+            model.set_learning_rate(0.3);
+            model.set_subsample(0.5);
+            model.set_early_stopping(0.2, 5);
+            model.set_seed(seed);
+            model.fit(&data, &labels);
+            model
+        };
+        let model = fit(9);
+        let validation = model.validation_loss_history();
+        assert!(validation.len() < 2000);
+        assert!(model.num_rounds() + 5 == validation.len());
+        let best = validation
+            .iter()
+            .fold(f32::INFINITY, |acc, loss| acc.min(*loss));
+        assert!(validation[model.num_rounds() - 1] == best);
+        assert!(fit(9).predict(&data).vector() == model.predict(&data).vector());
+        assert!(fit(10).predict(&data).vector() != model.predict(&data).vector());
+    }
+
+    #[test]
+    fn test_boosting_classifier() {
+        let (data, labels) = nonlinear_data(900, 6);
+        // three classes from the regression target
+        let classes: Vec<Vec<f32>> = labels_from_matrix(&labels)
+            .iter()
+            .map(|label| {
+                vec![if *label < -1.0 {
+                    0.0
+                } else if *label < 2.0 {
+                    1.0
+                } else {
+                    2.0
+                }]
+            })
+            .collect();
+        let binary: Vec<Vec<f32>> = classes
+            .iter()
+            .map(|class| vec![(class[0] == 2.0) as usize as f32])
+            .collect();
+        for labels in [classes, binary] {
+            let labels = Matrix::to_matrix(&labels);
+            let mut model = GradientBoostingClassifier::new(100);
+            model.fit(&data, &labels);
+            let probabilities = model.predict_proba(&data);
+            assert!(probabilities.shape() == (900, model.classes().len()));
+            assert!(
+                probabilities
+                    .matrix()
+                    .iter()
+                    .all(|row| (row.vector().iter().sum::<f32>() - 1.0).abs() < 1e-5)
+            );
+            assert!(accuracy(model.predict(&data).vector(), &labels_from_matrix(&labels)) > 0.95);
+            let history = model.train_loss_history();
+            assert!(history[history.len() - 1] < history[0]);
+            assert!(model.staged_predict_proba(&data).len() == 100);
+        }
+    }
+
+    #[test]
+    fn test_boosting_on_housing_dataframe() {
+        let df = df_from_csv("housing.csv", Some(2000));
+        let features: Vec<String> = [
+            "longitude",
+            "latitude",
+            "housing_median_age",
+            "total_rooms",
+            "total_bedrooms",
+            "population",
+            "households",
+            "median_income",
+            "ocean_proximity",
+        ]
+        .iter()
+        .map(|column| column.to_string())
+        .collect();
+        let train = df.get_rows_as_df(&(0..1500).collect());
+        let test = df.get_rows_as_df(&(1500..2000).collect());
+        let mut model = GradientBoostingRegressor::new(RegressionLoss::SquaredError, 200);
+        model.fit_df(&train, &features, "median_house_value");
+        let test_labels = labels_from_dataframe(&test, "median_house_value");
+        let mean = test_labels.iter().sum::<f32>() / test_labels.len() as f32;
+        let baseline = rmse(&vec![mean; test_labels.len()], &test_labels);
+        assert!(rmse(model.predict_df(&test).vector(), &test_labels) < 0.7 * baseline);
+        assert!(model.staged_predict_df(&test).len() == 200);
+    }
+}
@@ -0,0 +1,401 @@
+use crate::algorithms::decision_tree::tree::{FeatureKind, FeatureTable};
+
+/// Maps feature values to small integer bins: quantile bins for numeric
+/// features, one bin per category for categorical ones, and a last bin for
+/// missing values.
+#[derive(Clone, Debug)]
+pub(crate) struct BinMapper {
+    // upper edge of every bin but the last, per numeric feature
+    edges: Vec<Vec<f32>>,
+    categorical: Vec<bool>,
+    num_bins: Vec<usize>,
+}
+
+impl BinMapper {
+    pub fn fit(table: &FeatureTable, max_bins: usize) -> Self {
+        let mut edges = Vec::new();
+        let mut categorical = Vec::new();
+        let mut num_bins = Vec::new();
+        for (column, (name, kind)) in table.columns.iter().zip(table.features.iter()) {
+            match kind {
+                FeatureKind::Categorical(categories) => {
+                    if categories.len() > max_bins {
+                        panic!("{} has more than {} categories", name, max_bins);
+                    }
+                    edges.push(Vec::new());
+                    categorical.push(true);
+                    num_bins.push(categories.len().max(1));
+                }
+                FeatureKind::Numeric => {
+                    let mut values: Vec<f32> = column
+                        .iter()
+                        .copied()
+                        .filter(|value| !value.is_nan())
+                        .collect();
+                    values.sort_by(|a, b| a.total_cmp(b));
+                    let mut distinct = values.clone();
+                    distinct.dedup();
+                    let feature_edges: Vec<f32> = if distinct.len() <= max_bins {
+                        distinct
+                            .windows(2)
+                            .map(|pair| pair[0] + (pair[1] - pair[0]) / 2.0)
+                            .collect()
+                    } else {
+                        let mut quantiles: Vec<f32> = (1..max_bins)
+                            .map(|b| values[b * values.len() / max_bins])
+                            .collect();
+                        quantiles.dedup();
+                        quantiles
+                    };
+                    num_bins.push(feature_edges.len() + 1);
+                    edges.push(feature_edges);
+                    categorical.push(false);
+                }
+            }
+        }
+        return Self {
+            edges,
+            categorical,
+            num_bins,
+        };
+    }
+
+    pub fn num_features(&self) -> usize {
+        return self.num_bins.len();
+    }
+
+    /// Bins of the present values, the missing bin is `num_bins(feature)`.
+    pub fn num_bins(&self, feature: usize) -> usize {
+        return self.num_bins[feature];
+    }
+
+    pub fn is_categorical(&self, feature: usize) -> bool {
+        return self.categorical[feature];
+    }
+
+    pub fn bin(&self, feature: usize, value: f32) -> usize {
+        if value.is_nan() {
+            return self.num_bins[feature];
+        }
+        if self.categorical[feature] {
+            return value as usize;
+        }
+        return self.edges[feature].partition_point(|edge| *edge < value);
+    }
+
+    /// Column major bins of every row.
+    pub fn transform(&self, table: &FeatureTable) -> Vec<Vec<u16>> {
+        return table
+            .columns
+            .iter()
+            .enumerate()
+            .map(|(feature, column)| {
+                column
+                    .iter()
+                    .map(|value| self.bin(feature, *value) as u16)
+                    .collect()
+            })
+            .collect();
+    }
+}
+
+#[derive(Clone, Debug)]
+enum BinRule {
+    // bins up to and including this one go left
+    Threshold(usize),
+    Categories(Vec<bool>),
+}
+
+#[derive(Clone, Debug)]
+struct BinSplit {
+    feature: usize,
+    rule: BinRule,
+    missing_left: bool,
+    left: usize,
+    right: usize,
+}
+
+impl BinSplit {
+    fn goes_left(&self, bin: usize, missing_bin: usize) -> bool {
+        if bin == missing_bin {
+            return self.missing_left;
+        }
+        match &self.rule {
+            BinRule::Threshold(threshold) => bin <= *threshold,
+            BinRule::Categories(left) => left.get(bin).copied().unwrap_or(self.missing_left),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct BinNode {
+    value: f32,
+    split: Option<BinSplit>,
+}
+
+pub(crate) struct GrowthParameters {
+    pub max_depth: usize,
+    pub min_samples_leaf: usize,
+    pub l2_regularization: f64,
+}
+
+/// Regression tree fitted to gradients and hessians over binned features,
+/// leaves hold the Newton step `-G / (H + l2)`.
+#[derive(Clone, Debug)]
+pub(crate) struct HistogramTree {
+    nodes: Vec<BinNode>,
+}
+
+#[derive(Clone, Copy, Default)]
+struct Bucket {
+    gradient: f64,
+    hessian: f64,
+    count: usize,
+}
+
+impl Bucket {
+    fn add(&mut self, other: &Bucket) {
+        self.gradient += other.gradient;
+        self.hessian += other.hessian;
+        self.count += other.count;
+    }
+
+    fn subtract(&self, other: &Bucket) -> Bucket {
+        Bucket {
+            gradient: self.gradient - other.gradient,
+            hessian: self.hessian - other.hessian,
+            count: self.count - other.count,
+        }
+    }
+
+    fn score(&self, l2: f64) -> f64 {
+        self.gradient * self.gradient / (self.hessian + l2)
+    }
+}
+
+struct BinCandidate {
+    gain: f64,
+    feature: usize,
+    rule: BinRule,
+    missing_left: bool,
+}
+
+impl HistogramTree {
+    /// Grows a tree on `samples` and returns it with every leaf and the samples
+    /// that reached it, so callers can refit leaf values for their loss.
+    pub fn grow(
+        bins: &Vec<Vec<u16>>,
+        mapper: &BinMapper,
+        gradients: &Vec<f64>,
+        hessians: &Vec<f64>,
+        samples: Vec<usize>,
+        parameters: &GrowthParameters,
+    ) -> (Self, Vec<(usize, Vec<usize>)>) {
+        let mut tree = Self { nodes: Vec::new() };
+        let mut leaves = Vec::new();
+        tree.build(
+            bins,
+            mapper,
+            gradients,
+            hessians,
+            samples,
+            parameters,
+            0,
+            &mut leaves,
+        );
+        return (tree, leaves);
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn build(
+        &mut self,
+        bins: &Vec<Vec<u16>>,
+        mapper: &BinMapper,
+        gradients: &Vec<f64>,
+        hessians: &Vec<f64>,
+        samples: Vec<usize>,
+        parameters: &GrowthParameters,
+        depth: usize,
+        leaves: &mut Vec<(usize, Vec<usize>)>,
+    ) -> usize {
+        let mut total = Bucket::default();
+        for sample in samples.iter() {
+            total.add(&Bucket {
+                gradient: gradients[*sample],
+                hessian: hessians[*sample],
+                count: 1,
+            });
+        }
+        let index = self.nodes.len();
+        self.nodes.push(BinNode {
+            value: (-total.gradient / (total.hessian + parameters.l2_regularization)) as f32,
+            split: None,
+        });
+        let candidate = if depth < parameters.max_depth
+            && samples.len() >= 2 * parameters.min_samples_leaf
+        {
+            (0..mapper.num_features())
+                .filter_map(|feature| {
+                    best_bin_split(
+                        bins, mapper, gradients, hessians, &samples, &total, feature, parameters,
+                    )
+                })
+                .fold(None, |best: Option<BinCandidate>, candidate| match best {
+                    Some(best) if best.gain >= candidate.gain => Some(best),
+                    _ => Some(candidate),
+                })
+        } else {
+            None
+        };
+        let Some(candidate) = candidate else {
+            leaves.push((index, samples));
+            return index;
+        };
+        let mut split = BinSplit {
+            feature: candidate.feature,
+            rule: candidate.rule,
+            missing_left: candidate.missing_left,
+            left: 0,
+            right: 0,
+        };
+        let missing_bin = mapper.num_bins(split.feature);
+        let column = &bins[split.feature];
+        let (left_samples, right_samples): (Vec<usize>, Vec<usize>) = samples
+            .iter()
+            .partition(|sample| split.goes_left(column[**sample] as usize, missing_bin));
+        split.left = self.build(
+            bins,
+            mapper,
+            gradients,
+            hessians,
+            left_samples,
+            parameters,
+            depth + 1,
+            leaves,
+        );
+        split.right = self.build(
+            bins,
+            mapper,
+            gradients,
+            hessians,
+            right_samples,
+            parameters,
+            depth + 1,
+            leaves,
+        );
+        self.nodes[index].split = Some(split);
+        return index;
+    }
+
+    pub fn leaf_value(&self, leaf: usize) -> f32 {
+        return self.nodes[leaf].value;
+    }
+
+    pub fn set_leaf_value(&mut self, leaf: usize, value: f32) {
+        self.nodes[leaf].value = value;
+    }
+
+    /// Leaf value for a row given its bin in each feature.
+    pub fn predict(&self, mapper: &BinMapper, bin: impl Fn(usize) -> usize) -> f32 {
+        let mut node = &self.nodes[0];
+        while let Some(split) = &node.split {
+            let missing_bin = mapper.num_bins(split.feature);
+            node = if split.goes_left(bin(split.feature), missing_bin) {
+                &self.nodes[split.left]
+            } else {
+                &self.nodes[split.right]
+            };
+        }
+        return node.value;
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn best_bin_split(
+    bins: &Vec<Vec<u16>>,
+    mapper: &BinMapper,
+    gradients: &Vec<f64>,
+    hessians: &Vec<f64>,
+    samples: &Vec<usize>,
+    total: &Bucket,
+    feature: usize,
+    parameters: &GrowthParameters,
+) -> Option<BinCandidate> {
+    let num_bins = mapper.num_bins(feature);
+    let mut histogram = vec![Bucket::default(); num_bins + 1];
+    let column = &bins[feature];
+    for sample in samples.iter() {
+        histogram[column[*sample] as usize].add(&Bucket {
+            gradient: gradients[*sample],
+            hessian: hessians[*sample],
+            count: 1,
+        });
+    }
+    let missing = histogram[num_bins];
+    // bins in scan order: by value, or for categories by gradient ratio so the
+    // best prefix is the best category subset
+    let mut order: Vec<usize> = (0..num_bins)
+        .filter(|bin| histogram[*bin].count > 0)
+        .collect();
+    if mapper.is_categorical(feature) {
+        let l2 = parameters.l2_regularization;
+        order.sort_by(|a, b| {
+            let ratio = |bin: &usize| histogram[*bin].gradient / (histogram[*bin].hessian + l2);
+            ratio(a).total_cmp(&ratio(b))
+        });
+    }
+    let l2 = parameters.l2_regularization;
+    let parent_score = total.score(l2);
+    let mut best: Option<(f64, usize, bool)> = None;
+    let mut left = Bucket::default();
+    // position p puts order[..p] left, p = 0 and p = order.len() split off the missing values
+    for p in 0..=order.len() {
+        if p > 0 {
+            left.add(&histogram[order[p - 1]]);
+        }
+        for missing_left in [true, false] {
+            if missing.count == 0 && (!missing_left || p == 0 || p == order.len()) {
+                continue;
+            }
+            let left_side = if missing_left {
+                let mut with_missing = left;
+                with_missing.add(&missing);
+                with_missing
+            } else {
+                left
+            };
+            let right_side = total.subtract(&left_side);
+            if left_side.count < parameters.min_samples_leaf
+                || right_side.count < parameters.min_samples_leaf
+            {
+                continue;
+            }
+            let gain = left_side.score(l2) + right_side.score(l2) - parent_score;
+            if gain > 1e-10 && best.is_none_or(|(best_gain, _, _)| gain > best_gain) {
+                best = Some((gain, p, missing_left));
+            }
+        }
+    }
+    let (gain, p, mut missing_left) = best?;
+    if missing.count == 0 {
+        // nothing to learn, unseen missing values follow the larger side
+        let left_count: usize = order[..p].iter().map(|bin| histogram[*bin].count).sum();
+        missing_left = 2 * left_count >= total.count;
+    }
+    let rule = if mapper.is_categorical(feature) {
+        let mut left_bins = vec![false; num_bins];
+        order[..p].iter().for_each(|bin| left_bins[*bin] = true);
+        BinRule::Categories(left_bins)
+    } else if p == 0 {
+        // every present value goes right
+        BinRule::Categories(vec![false; num_bins])
+    } else {
+        BinRule::Threshold(order[p - 1])
+    };
+    return Some(BinCandidate {
+        gain,
+        feature,
+        rule,
+        missing_left,
+    });
+}
@@ -0,0 +1,2 @@
+pub mod gradient_boosting;
+pub(crate) mod histogram;
@@ -1,22 +1,42 @@
-use crate::linear_algebra::{Matrix, RowVector};
-use std::iter::zip;
+use crate::algorithms::estimators::{Estimator, ParamError, ParamValue, Regressor};
+use crate::algorithms::linear_regression::solver::{GradientDescent, LinearRegressionSolver};
+use crate::linear_algebra::decompositions::CholeskyDecomposition;
+use crate::linear_algebra::{LinalgError, Matrix, RowVector, SparseMatrix, kernels};
+use crate::sampling::random::Rng;
 
 pub struct LinearRegression {
     weights: Vec<f32>,
     bias: f32,
     ridge_value: f32,
+    solver: LinearRegressionSolver,
+    loss_history: Vec<f32>,
+    validation_loss_history: Vec<f32>,
 }
 
 impl LinearRegression {
     pub fn new(ridge_value: f32) -> Self {
+        return Self::with_solver(ridge_value, LinearRegressionSolver::ClosedForm);
+    }
+
+    pub fn with_solver(ridge_value: f32, solver: LinearRegressionSolver) -> Self {
         return Self {
             weights: Vec::new(),
             bias: 0.0,
             ridge_value,
+            solver,
+            loss_history: Vec::new(),
+            validation_loss_history: Vec::new(),
         };
     }
-    pub fn fit(&mut self, data: &Matrix, labels: &Matrix) {
-        let X = Matrix::new(
+
+    pub fn fit(&mut self, data: &Matrix, labels: &Matrix) -> Result<(), LinalgError> {
+        self.loss_history.clear();
+        self.validation_loss_history.clear();
+        if let LinearRegressionSolver::GradientDescent(options) = &self.solver {
+            let options = options.clone();
+            return self.fit_gradient_descent(data, labels, &options);
+        }
+        let x = Matrix::new(
             &data
                 .matrix()
                 .iter()
@@ -27,31 +47,160 @@ impl LinearRegression {
                 })
                 .collect(),
         );
-        let y = labels.clone();
-        let X_transpose = X.transpose();
-        let mut X_output = X.multiply(&X_transpose);
-        if self.ridge_value != 0.0 {
-            X_output = Matrix::new(&X_output.matrix()
+        let parameter_matrix = if self.ridge_value != 0.0 {
+            let x_transpose = x.transpose();
+            let x_output = self.add_ridge(&x_transpose.multiply(&x));
+            let y_output = x_transpose.multiply(labels);
+            CholeskyDecomposition::new(&x_output)?.solve(&y_output)?
+        } else {
+            x.lstsq(labels)?
+        };
+        self.set_parameters(&parameter_matrix);
+        Ok(())
+    }
+
+    // Minimizes (||X w + b - y||^2 + ridge * ||(w, b)||^2) / 2n, the same
+    // objective as the closed form, so both solvers agree at convergence.
+    fn fit_gradient_descent(
+        &mut self,
+        data: &Matrix,
+        labels: &Matrix,
+        options: &GradientDescent,
+    ) -> Result<(), LinalgError> {
+        let (rows, columns) = data.shape();
+        if labels.len() != rows {
+            return Err(LinalgError::DimensionMismatch {
+                expected: (rows, 1),
+                found: labels.shape(),
+            });
+        }
+        let targets: Vec<f32> = labels.matrix().iter().map(|label| label.get(0)).collect();
+        let mut rng = Rng::new(options.seed);
+        let mut training = rng.permutation(rows);
+        let validation_len = match options.validation_fraction {
+            Some(fraction) if rows > 1 => {
+                ((rows as f32 * fraction).round() as usize).clamp(1, rows - 1)
+            }
+            _ => 0,
+        };
+        let validation = training.split_off(rows - validation_len);
+        let batch_size = options
+            .batch_size
+            .unwrap_or(training.len())
+            .clamp(1, training.len().max(1));
+        let penalty = self.ridge_value / training.len().max(1) as f32;
+        let residual = |weights: &Vec<f32>, bias: f32, i: usize| {
+            kernels::dot(data.get(i).vector(), weights) + bias - targets[i]
+        };
+        let mean_squared_error = |weights: &Vec<f32>, bias: f32, indices: &Vec<usize>| {
+            indices
+                .iter()
+                .map(|i| residual(weights, bias, *i).powi(2))
+                .sum::<f32>()
+                / indices.len().max(1) as f32
+        };
+
+        let mut weights = vec![0.0; columns];
+        let mut bias = 0.0;
+        let mut best = (f32::INFINITY, weights.clone(), bias);
+        let mut epochs_without_improvement = 0;
+        for epoch in 0..options.max_epochs {
+            let learning_rate = options.schedule.learning_rate(epoch);
+            if options.batch_size.is_some() {
+                rng.shuffle(&mut training);
+            }
+            for batch in training.chunks(batch_size) {
+                let mut gradient = vec![0.0; columns];
+                let mut bias_gradient = 0.0;
+                for i in batch {
+                    let error = residual(&weights, bias, *i);
+                    kernels::axpy(error, data.get(*i).vector(), &mut gradient);
+                    bias_gradient += error;
+                }
+                let scale = 1.0 / batch.len() as f32;
+                for (weight, gradient) in weights.iter_mut().zip(gradient.iter()) {
+                    *weight -= learning_rate * (scale * gradient + penalty * *weight);
+                }
+                bias -= learning_rate * (scale * bias_gradient + penalty * bias);
+            }
+
+            let loss = mean_squared_error(&weights, bias, &training);
+            if !loss.is_finite() {
+                return Err(LinalgError::NoConvergence {
+                    iterations: epoch + 1,
+                });
+            }
+            self.loss_history.push(loss);
+            let monitored = if validation.is_empty() {
+                loss
+            } else {
+                let validation_loss = mean_squared_error(&weights, bias, &validation);
+                self.validation_loss_history.push(validation_loss);
+                validation_loss
+            };
+            if epoch == 0 || monitored < best.0 - options.tolerance * best.0 {
+                best = (monitored, weights.clone(), bias);
+                epochs_without_improvement = 0;
+            } else {
+                epochs_without_improvement += 1;
+                if epochs_without_improvement >= options.patience {
+                    break;
+                }
+            }
+        }
+        if !validation.is_empty() {
+            (weights, bias) = (best.1, best.2);
+        }
+        self.weights = weights;
+        self.bias = bias;
+        Ok(())
+    }
+
+    /// Fits on a sparse design matrix (e.g. from `OneHotEncoder::transform_sparse`)
+    /// through the normal equations, which only need the dense Gram matrix of
+    /// the features rather than a dense copy of the data.
+    pub fn fit_sparse(&mut self, data: &SparseMatrix, labels: &Matrix) -> Result<(), LinalgError> {
+        let bias_column: Vec<(usize, usize, f32)> = (0..data.len()).map(|i| (i, 0, 1.0)).collect();
+        let x = data.hstack(&SparseMatrix::from_triplets(data.len(), 1, &bias_column));
+        let x_output = self.add_ridge(&x.gram());
+        let y_output = x.transpose().multiply_dense(labels)?;
+        let parameter_matrix = if self.ridge_value != 0.0 {
+            CholeskyDecomposition::new(&x_output)?.solve(&y_output)?
+        } else {
+            x_output.lstsq(&y_output)?
+        };
+        self.set_parameters(&parameter_matrix);
+        Ok(())
+    }
+
+    // (X^T X + ridge * I) is symmetric positive definite for ridge > 0
+    fn add_ridge(&self, x_output: &Matrix) -> Matrix {
+        Matrix::new(
+            &x_output
+                .matrix()
                 .iter()
                 .enumerate()
                 .map(|(i, row_vector)| {
-                    RowVector::new(&row_vector.vector()
-                        .iter()
-                        .enumerate()
-                        .map(|(j, value)| {
-                            if i == j {
-                                value + self.ridge_value
-                            } else {
-                                *value
-                            }
-                        })
-                        .collect::<Vec<f32>>())
+                    RowVector::new(
+                        &row_vector
+                            .vector()
+                            .iter()
+                            .enumerate()
+                            .map(|(j, value)| {
+                                if i == j {
+                                    value + self.ridge_value
+                                } else {
+                                    *value
+                                }
+                            })
+                            .collect::<Vec<f32>>(),
+                    )
                 })
-                .collect::<Vec<RowVector>>());
-        }
-        let y_output = X_transpose.multiply(&y);
-        let X_output_inverse = X_output.inverse();
-        let parameter_matrix = X_output_inverse.multiply(&y_output);
+                .collect::<Vec<RowVector>>(),
+        )
+    }
+
+    fn set_parameters(&mut self, parameter_matrix: &Matrix) {
         let parameters: Vec<f32> = parameter_matrix
             .matrix()
             .iter()
@@ -64,17 +213,28 @@ impl LinearRegression {
     }
 
     pub fn predict(&self, data: &Matrix) -> RowVector {
-        let outputs: RowVector = RowVector::new(&data.
-            matrix().iter()
-            .map(|row_vector| {
-                zip(row_vector.vector(), self.weights.iter())
-                    .fold(0.0, |acc, (row_v, weight)| acc + row_v * weight)
-                    + self.bias
-            })
-            .collect());
+        let outputs: RowVector = RowVector::new(
+            &data
+                .matrix()
+                .iter()
+                .map(|row_vector| kernels::dot(row_vector.vector(), &self.weights) + self.bias)
+                .collect(),
+        );
         return outputs;
     }
 
+    pub fn predict_sparse(&self, data: &SparseMatrix) -> RowVector {
+        RowVector::new(
+            &(0..data.len())
+                .map(|i| {
+                    data.row(i).fold(self.bias, |acc, (column, value)| {
+                        acc + value * self.weights[column]
+                    })
+                })
+                .collect(),
+        )
+    }
+
     pub fn weights(&self) -> &Vec<f32> {
         return &self.weights;
     }
@@ -82,4 +242,178 @@ impl LinearRegression {
     pub fn bias(&self) -> f32 {
         return self.bias;
     }
+
+    /// Training mean squared error after each gradient descent epoch, empty for
+    /// the closed form solver.
+    pub fn loss_history(&self) -> &Vec<f32> {
+        return &self.loss_history;
+    }
+
+    /// Held out mean squared error after each epoch when early stopping on a
+    /// validation fraction.
+    pub fn validation_loss_history(&self) -> &Vec<f32> {
+        return &self.validation_loss_history;
+    }
+
+    pub fn solver(&self) -> &LinearRegressionSolver {
+        return &self.solver;
+    }
+}
+
+impl Estimator for LinearRegression {
+    fn params(&self) -> Vec<(&'static str, ParamValue)> {
+        return vec![("ridge_value", ParamValue::Float(self.ridge_value))];
+    }
+
+    fn set_param(&mut self, name: &str, value: ParamValue) -> Result<(), ParamError> {
+        match name {
+            "ridge_value" => self.ridge_value = value.as_float(name)?,
+            _ => return Err(ParamError::Unknown(name.to_string())),
+        }
+        Ok(())
+    }
+}
+
+impl Regressor for LinearRegression {
+    fn fit(&mut self, data: &Matrix, labels: &Matrix) -> Result<(), LinalgError> {
+        return LinearRegression::fit(self, data, labels);
+    }
+
+    fn predict(&self, data: &Matrix) -> RowVector {
+        return LinearRegression::predict(self, data);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::linear_regression::solver::LearningRateSchedule;
+    use crate::dataframe::csv::df_from_csv;
+    use crate::pipeline::encoders::one_hot_encoder::OneHotEncoder;
+    use std::iter::zip;
+
+    // y = 3 x0 - 2 x1 + 0.5 with a little deterministic noise
+    fn linear_data(rows: usize) -> (Matrix, Matrix) {
+        let mut rng = Rng::new(3);
+        let data: Vec<Vec<f32>> = (0..rows)
+            .map(|_| vec![rng.normal(), rng.normal()])
+            .collect();
+        let labels: Vec<Vec<f32>> = data
+            .iter()
+            .map(|row| vec![3.0 * row[0] - 2.0 * row[1] + 0.5 + 0.01 * rng.normal()])
+            .collect();
+        (Matrix::to_matrix(&data), Matrix::to_matrix(&labels))
+    }
+
+    #[test]
+    fn test_gradient_descent_solvers_match_closed_form() {
+        let (data, labels) = linear_data(500);
+        let mut closed_form = LinearRegression::new(0.0);
+        closed_form.fit(&data, &labels).unwrap();
+        assert!(closed_form.loss_history().is_empty());
+        let solvers = vec![
+            GradientDescent::batch(LearningRateSchedule::Constant(0.1), 500),
+            GradientDescent::mini_batch(
+                32,
+                LearningRateSchedule::InverseScaling {
+                    initial: 0.1,
+                    power: 0.25,
+                },
+                200,
+            ),
+            GradientDescent::stochastic(
+                LearningRateSchedule::ExponentialDecay {
+                    initial: 0.01,
+                    decay: 0.95,
+                },
+                100,
+            ),
+        ];
+        for options in solvers {
+            let mut regression = LinearRegression::with_solver(
+                0.0,
+                LinearRegressionSolver::GradientDescent(options),
+            );
+            regression.fit(&data, &labels).unwrap();
+            let history = regression.loss_history();
+            assert!(!history.is_empty());
+            assert!(history[history.len() - 1] < history[0]);
+            assert!(
+                zip(regression.weights(), closed_form.weights()).all(|(a, b)| (a - b).abs() < 0.02)
+            );
+            assert!((regression.bias() - closed_form.bias()).abs() < 0.02);
+        }
+    }
+
+    #[test]
+    fn test_gradient_descent_early_stopping() {
+        let (data, labels) = linear_data(400);
+        let options = GradientDescent::mini_batch(16, LearningRateSchedule::Constant(0.05), 1000)
+            .with_early_stopping(0.2, 3)
+            .with_seed(11);
+        let mut regression = LinearRegression::with_solver(
+            0.0,
+            LinearRegressionSolver::GradientDescent(options.clone()),
+        );
+        regression.fit(&data, &labels).unwrap();
+        let validation_history = regression.validation_loss_history();
+        assert!(validation_history.len() == regression.loss_history().len());
+        assert!(validation_history.len() < 1000);
+        assert!(
+            validation_history
+                .iter()
+                .fold(f32::INFINITY, |acc, x| acc.min(*x))
+                < 1e-3
+        );
+        // seeded shuffling makes the fit reproducible
+        let mut repeated =
+            LinearRegression::with_solver(0.0, LinearRegressionSolver::GradientDescent(options));
+        repeated.fit(&data, &labels).unwrap();
+        assert!(repeated.weights() == regression.weights());
+    }
+
+    #[test]
+    fn test_gradient_descent_divergence_is_reported() {
+        let (data, labels) = linear_data(50);
+        let mut options = GradientDescent::batch(LearningRateSchedule::Constant(100.0), 100);
+        options.patience = usize::MAX;
+        let mut regression =
+            LinearRegression::with_solver(0.0, LinearRegressionSolver::GradientDescent(options));
+        assert!(matches!(
+            regression.fit(&data, &labels),
+            Err(LinalgError::NoConvergence { .. })
+        ));
+    }
+
+    #[test]
+    fn test_linear_regression_sparse_matches_dense() {
+        let df = df_from_csv("housing.csv", Some(1000));
+        let categorical_columns = vec!["ocean_proximity".to_string()];
+        let encoded = OneHotEncoder::new(true).transform_sparse(&df, &categorical_columns);
+        let numeric = df
+            .get_columns_as_df(&vec![
+                "median_income".to_string(),
+                "housing_median_age".to_string(),
+            ])
+            .as_matrix(false);
+        let data = SparseMatrix::from_dense(&numeric).hstack(&encoded);
+        let labels = df
+            .get_columns_as_df(&vec!["median_house_value".to_string()])
+            .as_matrix(false);
+        let mut sparse_regression = LinearRegression::new(0.0);
+        sparse_regression.fit_sparse(&data, &labels).unwrap();
+        let mut dense_regression = LinearRegression::new(0.0);
+        dense_regression.fit(&data.to_dense(), &labels).unwrap();
+        assert!(sparse_regression.weights().len() == data.shape().1);
+        assert!(
+            zip(sparse_regression.weights(), dense_regression.weights())
+                .all(|(a, b)| (a - b).abs() <= 1e-2 * b.abs().max(1.0))
+        );
+        let sparse_predictions = sparse_regression.predict_sparse(&data);
+        let dense_predictions = dense_regression.predict(&data.to_dense());
+        assert!(
+            zip(sparse_predictions.vector(), dense_predictions.vector())
+                .all(|(a, b)| (a - b).abs() <= 1e-2 * b.abs())
+        );
+    }
 }
@@ -1 +1,2 @@
-pub mod linear_regression;
+pub mod linear_regression;
+pub mod solver;
@@ -0,0 +1,143 @@
+/// How `LinearRegression::fit` finds its parameters.
+#[derive(Clone, Debug)]
+pub enum LinearRegressionSolver {
+    /// Direct least squares solve through QR/SVD (or Cholesky when a ridge
+    /// penalty is set). Exact, but needs the whole design matrix in memory.
+    ClosedForm,
+    /// First order optimization of the mean squared error, see `GradientDescent`.
+    GradientDescent(GradientDescent),
+}
+
+/// Step size as a function of the epoch (0 based).
+#[derive(Clone, Debug)]
+pub enum LearningRateSchedule {
+    Constant(f32),
+    /// `initial / (epoch + 1)^power`
+    InverseScaling {
+        initial: f32,
+        power: f32,
+    },
+    /// `initial * decay^epoch`
+    ExponentialDecay {
+        initial: f32,
+        decay: f32,
+    },
+    /// `initial * factor^(epoch / step_size)`
+    StepDecay {
+        initial: f32,
+        factor: f32,
+        step_size: usize,
+    },
+}
+
+impl LearningRateSchedule {
+    pub fn learning_rate(&self, epoch: usize) -> f32 {
+        match self {
+            LearningRateSchedule::Constant(learning_rate) => *learning_rate,
+            LearningRateSchedule::InverseScaling { initial, power } => {
+                initial / ((epoch + 1) as f32).powf(*power)
+            }
+            LearningRateSchedule::ExponentialDecay { initial, decay } => {
+                initial * decay.powi(epoch as i32)
+            }
+            LearningRateSchedule::StepDecay {
+                initial,
+                factor,
+                step_size,
+            } => initial * factor.powi((epoch / (*step_size).max(1)) as i32),
+        }
+    }
+}
+
+/// Gradient descent settings. `batch_size` of `None` uses the whole training
+/// set per step (batch gradient descent), `Some(1)` is plain stochastic
+/// gradient descent and anything in between is mini-batch.
+///
+/// Training stops after `max_epochs`, or earlier once the monitored loss has
+/// not improved by more than `tolerance` (relative) for `patience` epochs in a
+/// row. With `validation_fraction` set, that share of the rows is held out and
+/// its loss is monitored instead of the training loss, and the parameters
+/// from the best validation epoch are kept.
+#[derive(Clone, Debug)]
+pub struct GradientDescent {
+    pub batch_size: Option<usize>,
+    pub schedule: LearningRateSchedule,
+    pub max_epochs: usize,
+    pub tolerance: f32,
+    pub patience: usize,
+    pub validation_fraction: Option<f32>,
+    pub seed: u64,
+}
+
+impl GradientDescent {
+    pub fn batch(schedule: LearningRateSchedule, max_epochs: usize) -> Self {
+        return Self {
+            batch_size: None,
+            schedule,
+            max_epochs,
+            tolerance: 1e-6,
+            patience: 5,
+            validation_fraction: None,
+            seed: 0,
+        };
+    }
+
+    pub fn mini_batch(
+        batch_size: usize,
+        schedule: LearningRateSchedule,
+        max_epochs: usize,
+    ) -> Self {
+        if batch_size == 0 {
+            panic!("batch size must be at least 1");
+        }
+        return Self {
+            batch_size: Some(batch_size),
+            ..Self::batch(schedule, max_epochs)
+        };
+    }
+
+    pub fn stochastic(schedule: LearningRateSchedule, max_epochs: usize) -> Self {
+        return Self::mini_batch(1, schedule, max_epochs);
+    }
+
+    pub fn with_early_stopping(mut self, validation_fraction: f32, patience: usize) -> Self {
+        if validation_fraction <= 0.0 || validation_fraction >= 1.0 {
+            panic!("validation fraction must be between 0 and 1");
+        }
+        self.validation_fraction = Some(validation_fraction);
+        self.patience = patience;
+        return self;
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        return self;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_learning_rate_schedules() {
+        assert!(LearningRateSchedule::Constant(0.1).learning_rate(10) == 0.1);
+        let inverse = LearningRateSchedule::InverseScaling {
+            initial: 1.0,
+            power: 1.0,
+        };
+        assert!((inverse.learning_rate(3) - 0.25).abs() < 1e-7);
+        let exponential = LearningRateSchedule::ExponentialDecay {
+            initial: 1.0,
+            decay: 0.5,
+        };
+        assert!((exponential.learning_rate(2) - 0.25).abs() < 1e-7);
+        let step = LearningRateSchedule::StepDecay {
+            initial: 1.0,
+            factor: 0.1,
+            step_size: 10,
+        };
+        assert!(step.learning_rate(9) == 1.0);
+        assert!((step.learning_rate(10) - 0.1).abs() < 1e-7);
+    }
+}
@@ -0,0 +1,600 @@
+use crate::algorithms::estimators::{
+    Classifier, Estimator, ParamError, ParamValue, encode_classes, labels_from_matrix, sigmoid,
+    softmax,
+};
+use crate::linear_algebra::optimize::{LbfgsOptions, lbfgs};
+use crate::linear_algebra::{LinalgError, Matrix, RowVector, kernels};
+
+/// `Binary` fits one sigmoid score for exactly two classes, `Multinomial` one
+/// softmax score per class. `Auto` picks binary for two classes.
+#[derive(Clone, Debug, PartialEq)]
+pub enum LogisticMode {
+    Auto,
+    Binary,
+    Multinomial,
+}
+
+/// Penalty on the weights (never the intercepts), scaled like the mean loss:
+/// `L2(lambda)` adds `lambda / 2 * ||w||^2`, `L1(lambda)` adds `lambda * ||w||_1`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Penalty {
+    None,
+    L1(f32),
+    L2(f32),
+}
+
+/// `Newton` is iteratively reweighted least squares with the exact Hessian,
+/// fast for a handful of features but it needs a smooth penalty. `Lbfgs` scales
+/// to many features and handles L1 through orthant-wise steps.
+#[derive(Clone, Debug, PartialEq)]
+pub enum LogisticSolver {
+    Newton,
+    Lbfgs,
+}
+
+/// Per-sample loss weights by class. `Balanced` uses
+/// `samples / (classes * class_count)`, `Custom` lists `(class, weight)` pairs
+/// with unlisted classes weighted 1.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ClassWeight {
+    Uniform,
+    Balanced,
+    Custom(Vec<(f32, f32)>),
+}
+
+pub struct LogisticRegression {
+    penalty: Penalty,
+    mode: LogisticMode,
+    solver: LogisticSolver,
+    class_weight: ClassWeight,
+    max_iterations: usize,
+    tolerance: f32,
+    classes: Vec<f32>,
+    // one row of weights and one bias per score, a single score in binary mode
+    weights: Vec<Vec<f32>>,
+    biases: Vec<f32>,
+    iterations: usize,
+    converged: bool,
+}
+
+// mean weighted log loss over the training rows, with parameters laid out as
+// `num_scores` blocks of `features + 1` values (weights then bias)
+struct LogLoss<'a> {
+    data: &'a Vec<Vec<f64>>,
+    targets: Vec<usize>,
+    sample_weights: Vec<f64>,
+    num_scores: usize,
+    l2: f64,
+}
+
+impl<'a> LogLoss<'a> {
+    fn width(&self) -> usize {
+        self.data.first().map_or(0, |row| row.len()) + 1
+    }
+
+    // probability of class 1 in binary mode, of every class otherwise
+    fn probabilities(&self, parameters: &[f64], row: &[f64]) -> Vec<f64> {
+        let width = self.width();
+        let scores: Vec<f64> = (0..self.num_scores)
+            .map(|c| {
+                let block = &parameters[c * width..(c + 1) * width];
+                row.iter().zip(block).map(|(x, w)| x * w).sum::<f64>() + block[width - 1]
+            })
+            .collect();
+        if self.num_scores == 1 {
+            vec![sigmoid(scores[0])]
+        } else {
+            softmax(&scores)
+        }
+    }
+
+    fn value_and_gradient(&self, parameters: &[f64], gradient: &mut [f64]) -> f64 {
+        let width = self.width();
+        let rows = self.data.len() as f64;
+        gradient.iter_mut().for_each(|value| *value = 0.0);
+        let mut value = 0.0;
+        for (i, row) in self.data.iter().enumerate() {
+            let probabilities = self.probabilities(parameters, row);
+            let weight = self.sample_weights[i] / rows;
+            for c in 0..self.num_scores {
+                let target = if self.num_scores == 1 {
+                    self.targets[i] as f64
+                } else {
+                    (self.targets[i] == c) as usize as f64
+                };
+                let error = weight * (probabilities[c] - target);
+                let block = &mut gradient[c * width..(c + 1) * width];
+                block.iter_mut().zip(row).for_each(|(g, x)| *g += error * x);
+                block[width - 1] += error;
+            }
+            let target_probability = if self.num_scores == 1 {
+                if self.targets[i] == 1 {
+                    probabilities[0]
+                } else {
+                    1.0 - probabilities[0]
+                }
+            } else {
+                probabilities[self.targets[i]]
+            };
+            value -= weight * target_probability.max(f64::MIN_POSITIVE).ln();
+        }
+        for c in 0..self.num_scores {
+            for j in 0..width - 1 {
+                let parameter = parameters[c * width + j];
+                value += 0.5 * self.l2 * parameter * parameter;
+                gradient[c * width + j] += self.l2 * parameter;
+            }
+        }
+        value
+    }
+
+    fn hessian(&self, parameters: &[f64]) -> Vec<Vec<f64>> {
+        let width = self.width();
+        let size = self.num_scores * width;
+        let rows = self.data.len() as f64;
+        let mut hessian = vec![vec![0.0; size]; size];
+        for (i, row) in self.data.iter().enumerate() {
+            let probabilities = self.probabilities(parameters, row);
+            let weight = self.sample_weights[i] / rows;
+            let extended: Vec<f64> = row.iter().copied().chain([1.0]).collect();
+            for a in 0..self.num_scores {
+                for b in 0..self.num_scores {
+                    let curvature = if self.num_scores == 1 {
+                        probabilities[0] * (1.0 - probabilities[0])
+                    } else {
+                        probabilities[a] * ((a == b) as usize as f64 - probabilities[b])
+                    };
+                    let scale = weight * curvature;
+                    for (j, x_j) in extended.iter().enumerate() {
+                        let hessian_row = &mut hessian[a * width + j][b * width..(b + 1) * width];
+                        hessian_row
+                            .iter_mut()
+                            .zip(&extended)
+                            .for_each(|(h, x_k)| *h += scale * x_j * x_k);
+                    }
+                }
+            }
+        }
+        for c in 0..self.num_scores {
+            for j in 0..width - 1 {
+                hessian[c * width + j][c * width + j] += self.l2;
+            }
+        }
+        hessian
+    }
+}
+
+impl LogisticRegression {
+    pub fn new(penalty: Penalty) -> Self {
+        return Self {
+            penalty,
+            mode: LogisticMode::Auto,
+            solver: LogisticSolver::Lbfgs,
+            class_weight: ClassWeight::Uniform,
+            max_iterations: 100,
+            tolerance: 1e-5,
+            classes: Vec::new(),
+            weights: Vec::new(),
+            biases: Vec::new(),
+            iterations: 0,
+            converged: false,
+        };
+    }
+
+    pub fn set_mode(&mut self, mode: LogisticMode) {
+        self.mode = mode;
+    }
+
+    pub fn set_solver(&mut self, solver: LogisticSolver) {
+        if solver == LogisticSolver::Newton && matches!(self.penalty, Penalty::L1(_)) {
+            panic!("the newton solver needs a smooth penalty, use lbfgs for l1");
+        }
+        self.solver = solver;
+    }
+
+    pub fn set_class_weight(&mut self, class_weight: ClassWeight) {
+        self.class_weight = class_weight;
+    }
+
+    pub fn set_max_iterations(&mut self, max_iterations: usize) {
+        self.max_iterations = max_iterations;
+    }
+
+    /// Stops once the largest gradient entry of the objective is below `tolerance`.
+    pub fn set_tolerance(&mut self, tolerance: f32) {
+        self.tolerance = tolerance;
+    }
+
+    /// `labels` is a single column of class values.
+    pub fn fit(&mut self, data: &Matrix, labels: &Matrix) -> Result<(), LinalgError> {
+        let (rows, columns) = data.shape();
+        if labels.len() != rows {
+            return Err(LinalgError::DimensionMismatch {
+                expected: (rows, 1),
+                found: labels.shape(),
+            });
+        }
+        let (classes, targets) = encode_classes(&labels_from_matrix(labels));
+        let num_scores = match self.mode {
+            LogisticMode::Binary if classes.len() != 2 => {
+                panic!("binary mode needs exactly two classes")
+            }
+            LogisticMode::Binary => 1,
+            LogisticMode::Auto if classes.len() == 2 => 1,
+            _ => classes.len(),
+        };
+        let class_weights = class_weights(&self.class_weight, &classes, &targets);
+        let data_f64 = data.to_f64();
+        let (l1, l2) = match self.penalty {
+            Penalty::None => (0.0, 0.0),
+            Penalty::L1(lambda) => (lambda as f64, 0.0),
+            Penalty::L2(lambda) => (0.0, lambda as f64),
+        };
+        let loss = LogLoss {
+            data: &data_f64,
+            sample_weights: targets
+                .iter()
+                .map(|target| class_weights[*target])
+                .collect(),
+            targets,
+            num_scores,
+            l2,
+        };
+        let width = columns + 1;
+        let initial = vec![0.0; num_scores * width];
+        let (parameters, iterations, converged) = match self.solver {
+            LogisticSolver::Newton => self.newton(&loss, initial)?,
+            LogisticSolver::Lbfgs => {
+                let l1_weights: Vec<f64> = (0..initial.len())
+                    .map(|k| if k % width == width - 1 { 0.0 } else { l1 })
+                    .collect();
+                let options = LbfgsOptions {
+                    max_iterations: self.max_iterations,
+                    tolerance: self.tolerance as f64,
+                    memory: 10,
+                };
+                let minimum = lbfgs(
+                    |parameters, gradient| loss.value_and_gradient(parameters, gradient),
+                    initial,
+                    &l1_weights,
+                    &options,
+                );
+                (minimum.x, minimum.iterations, minimum.converged)
+            }
+        };
+        self.classes = classes;
+        self.weights = parameters
+            .chunks(width)
+            .map(|block| block[..columns].iter().map(|w| *w as f32).collect())
+            .collect();
+        self.biases = parameters
+            .chunks(width)
+            .map(|block| block[columns] as f32)
+            .collect();
+        self.iterations = iterations;
+        self.converged = converged;
+        Ok(())
+    }
+
+    // damped Newton steps; the softmax Hessian is singular along a shared shift
+    // of all scores, which the minimum norm least squares step ignores
+    fn newton(
+        &self,
+        loss: &LogLoss,
+        mut parameters: Vec<f64>,
+    ) -> Result<(Vec<f64>, usize, bool), LinalgError> {
+        let mut gradient = vec![0.0; parameters.len()];
+        let mut value = loss.value_and_gradient(&parameters, &mut gradient);
+        for iteration in 0..self.max_iterations {
+            if gradient.iter().fold(0.0_f64, |acc, g| acc.max(g.abs())) <= self.tolerance as f64 {
+                return Ok((parameters, iteration, true));
+            }
+            let hessian = Matrix::from_f64(&loss.hessian(&parameters));
+            let negative_gradient =
+                Matrix::from_f64(&gradient.iter().map(|g| vec![-g]).collect::<Vec<Vec<f64>>>());
+            let direction: Vec<f64> = hessian
+                .lstsq(&negative_gradient)?
+                .matrix()
+                .iter()
+                .map(|row| row.get(0) as f64)
+                .collect();
+            let slope: f64 = direction.iter().zip(&gradient).map(|(d, g)| d * g).sum();
+            let mut step = 1.0;
+            let mut next_gradient = vec![0.0; parameters.len()];
+            loop {
+                let candidate: Vec<f64> = parameters
+                    .iter()
+                    .zip(&direction)
+                    .map(|(p, d)| p + step * d)
+                    .collect();
+                let candidate_value = loss.value_and_gradient(&candidate, &mut next_gradient);
+                if candidate_value <= value + 1e-4 * step * slope || step < 1e-10 {
+                    parameters = candidate;
+                    value = candidate_value;
+                    break;
+                }
+                step *= 0.5;
+            }
+            gradient = next_gradient;
+        }
+        let converged =
+            gradient.iter().fold(0.0_f64, |acc, g| acc.max(g.abs())) <= self.tolerance as f64;
+        Ok((parameters, self.max_iterations, converged))
+    }
+
+    /// Raw scores, one column in binary mode (log odds of the second class)
+    /// and one per class otherwise.
+    pub fn decision_function(&self, data: &Matrix) -> Matrix {
+        return Matrix::new(
+            &data
+                .matrix()
+                .iter()
+                .map(|row| {
+                    RowVector::new(
+                        &self
+                            .weights
+                            .iter()
+                            .zip(self.biases.iter())
+                            .map(|(weights, bias)| kernels::dot(row.vector(), weights) + bias)
+                            .collect(),
+                    )
+                })
+                .collect(),
+        );
+    }
+
+    /// Class probabilities with columns in the order of `classes()`.
+    pub fn predict_proba(&self, data: &Matrix) -> Matrix {
+        let scores = self.decision_function(data);
+        return Matrix::new(
+            &scores
+                .matrix()
+                .iter()
+                .map(|row| {
+                    let scores: Vec<f64> = row.vector().iter().map(|score| *score as f64).collect();
+                    let probabilities = if scores.len() == 1 {
+                        let p = sigmoid(scores[0]);
+                        vec![1.0 - p, p]
+                    } else {
+                        softmax(&scores)
+                    };
+                    RowVector::new(&probabilities.iter().map(|p| *p as f32).collect())
+                })
+                .collect(),
+        );
+    }
+
+    pub fn predict(&self, data: &Matrix) -> RowVector {
+        let probabilities = self.predict_proba(data);
+        return RowVector::new(
+            &probabilities
+                .matrix()
+                .iter()
+                .map(|row| {
+                    let best = row
+                        .vector()
+                        .iter()
+                        .enumerate()
+                        .fold(0, |best, (i, p)| if *p > row.get(best) { i } else { best });
+                    self.classes[best]
+                })
+                .collect(),
+        );
+    }
+
+    pub fn classes(&self) -> &Vec<f32> {
+        return &self.classes;
+    }
+
+    pub fn weights(&self) -> &Vec<Vec<f32>> {
+        return &self.weights;
+    }
+
+    pub fn biases(&self) -> &Vec<f32> {
+        return &self.biases;
+    }
+
+    pub fn iterations(&self) -> usize {
+        return self.iterations;
+    }
+
+    /// False when the last `fit` stopped at `max_iterations` (e.g. perfectly
+    /// separable classes without a penalty).
+    pub fn converged(&self) -> bool {
+        return self.converged;
+    }
+}
+
+/// Loss weight of every class, indexed like `classes`, for `targets` given as
+/// class positions.
+pub(crate) fn class_weights(
+    class_weight: &ClassWeight,
+    classes: &Vec<f32>,
+    targets: &Vec<usize>,
+) -> Vec<f64> {
+    match class_weight {
+        ClassWeight::Uniform => vec![1.0; classes.len()],
+        ClassWeight::Balanced => {
+            let mut counts = vec![0usize; classes.len()];
+            targets.iter().for_each(|target| counts[*target] += 1);
+            counts
+                .iter()
+                .map(|count| targets.len() as f64 / (classes.len() * count) as f64)
+                .collect()
+        }
+        ClassWeight::Custom(weights) => classes
+            .iter()
+            .map(|class| {
+                weights
+                    .iter()
+                    .find(|(weighted_class, _)| weighted_class == class)
+                    .map_or(1.0, |(_, weight)| *weight as f64)
+            })
+            .collect(),
+    }
+}
+
+impl Estimator for LogisticRegression {
+    // `lambda` is the strength of an l1 or l2 penalty, absent without one
+    fn params(&self) -> Vec<(&'static str, ParamValue)> {
+        let mut params = vec![
+            ("max_iterations", ParamValue::Int(self.max_iterations)),
+            ("tolerance", ParamValue::Float(self.tolerance)),
+        ];
+        if let Penalty::L1(lambda) | Penalty::L2(lambda) = self.penalty {
+            params.insert(0, ("lambda", ParamValue::Float(lambda)));
+        }
+        return params;
+    }
+
+    fn set_param(&mut self, name: &str, value: ParamValue) -> Result<(), ParamError> {
+        match (name, &mut self.penalty) {
+            ("lambda", Penalty::L1(lambda) | Penalty::L2(lambda)) => {
+                *lambda = value.as_float(name)?
+            }
+            ("max_iterations", _) => self.set_max_iterations(value.as_int(name)?),
+            ("tolerance", _) => self.set_tolerance(value.as_float(name)?),
+            _ => return Err(ParamError::Unknown(name.to_string())),
+        }
+        Ok(())
+    }
+}
+
+impl Classifier for LogisticRegression {
+    fn fit(&mut self, data: &Matrix, labels: &Matrix) -> Result<(), LinalgError> {
+        return LogisticRegression::fit(self, data, labels);
+    }
+
+    fn predict(&self, data: &Matrix) -> RowVector {
+        return LogisticRegression::predict(self, data);
+    }
+
+    fn classes(&self) -> &Vec<f32> {
+        return LogisticRegression::classes(self);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::estimators::binary_data;
+    use crate::inference::inference::accuracy;
+    use crate::sampling::random::Rng;
+
+    #[test]
+    fn test_binary_newton_matches_lbfgs() {
+        let (data, labels) = binary_data(400, 9, 0.5);
+        let mut newton = LogisticRegression::new(Penalty::L2(0.01));
+        newton.set_solver(LogisticSolver::Newton);
+        newton.fit(&data, &labels).unwrap();
+        let mut lbfgs = LogisticRegression::new(Penalty::L2(0.01));
+        lbfgs.fit(&data, &labels).unwrap();
+        assert!(newton.converged() && lbfgs.converged());
+        assert!(newton.iterations() < lbfgs.iterations());
+        assert!(newton.weights().len() == 1 && newton.weights()[0].len() == 3);
+        assert!((0..3).all(|j| (newton.weights()[0][j] - lbfgs.weights()[0][j]).abs() < 1e-3));
+        assert!((newton.biases()[0] - lbfgs.biases()[0]).abs() < 1e-3);
+        let predictions = newton.predict(&data);
+        assert!(accuracy(predictions.vector(), &labels_from_matrix(&labels)) > 0.85);
+        let probabilities = newton.predict_proba(&data);
+        assert!(probabilities.shape() == (400, 2));
+        assert!(
+            probabilities
+                .matrix()
+                .iter()
+                .all(|row| (row.get(0) + row.get(1) - 1.0).abs() < 1e-6)
+        );
+        let scores = newton.decision_function(&data);
+        assert!(scores.shape() == (400, 1));
+        assert!((0..400).all(|i| (scores.get(i).get(0) > 0.0) == (predictions.get(i) == 1.0)));
+    }
+
+    #[test]
+    fn test_l1_penalty_drops_noise_feature() {
+        let (data, labels) = binary_data(400, 9, 0.5);
+        let mut regression = LogisticRegression::new(Penalty::L1(0.05));
+        regression.fit(&data, &labels).unwrap();
+        let weights = &regression.weights()[0];
+        assert!(weights[2] == 0.0);
+        assert!(weights[0] > 0.0 && weights[1] < 0.0);
+    }
+
+    #[test]
+    fn test_multinomial_three_classes() {
+        let mut rng = Rng::new(2);
+        let centers = [(0.0, 3.0), (3.0, -2.0), (-3.0, -2.0)];
+        let mut data = Vec::new();
+        let mut labels = Vec::new();
+        for i in 0..300 {
+            let (x, y) = centers[i % 3];
+            data.push(vec![x + rng.normal(), y + rng.normal()]);
+            labels.push(vec![(i % 3) as f32 * 10.0]);
+        }
+        let (data, labels) = (Matrix::to_matrix(&data), Matrix::to_matrix(&labels));
+        for solver in [LogisticSolver::Newton, LogisticSolver::Lbfgs] {
+            let mut regression = LogisticRegression::new(Penalty::L2(0.01));
+            regression.set_solver(solver);
+            regression.fit(&data, &labels).unwrap();
+            assert!(regression.classes() == &vec![0.0, 10.0, 20.0]);
+            assert!(regression.weights().len() == 3);
+            let probabilities = regression.predict_proba(&data);
+            assert!(probabilities.shape() == (300, 3));
+            assert!(
+                probabilities
+                    .matrix()
+                    .iter()
+                    .all(|row| (row.vector().iter().sum::<f32>() - 1.0).abs() < 1e-5)
+            );
+            assert!(
+                accuracy(
+                    regression.predict(&data).vector(),
+                    &labels_from_matrix(&labels)
+                ) > 0.95
+            );
+        }
+    }
+
+    #[test]
+    fn test_balanced_class_weight_favors_minority() {
+        let (data, labels) = binary_data(400, 9, 0.5);
+        // keep only a few positives
+        let mut positives = 0;
+        let rows: Vec<usize> = (0..400)
+            .filter(|i| {
+                if labels.get(*i).get(0) == 1.0 {
+                    positives += 1;
+                    positives <= 20
+                } else {
+                    true
+                }
+            })
+            .collect();
+        let data = Matrix::new(&rows.iter().map(|i| data.get(*i).clone()).collect());
+        let labels = Matrix::new(&rows.iter().map(|i| labels.get(*i).clone()).collect());
+        let positive_predictions = |class_weight: ClassWeight| {
+            let mut regression = LogisticRegression::new(Penalty::L2(0.01));
+            regression.set_class_weight(class_weight);
+            regression.fit(&data, &labels).unwrap();
+            regression
+                .predict(&data)
+                .vector()
+                .iter()
+                .filter(|p| **p == 1.0)
+                .count()
+        };
+        assert!(
+            positive_predictions(ClassWeight::Balanced)
+                > positive_predictions(ClassWeight::Uniform)
+        );
+        assert!(
+            positive_predictions(ClassWeight::Custom(vec![(1.0, 10.0)]))
+                > positive_predictions(ClassWeight::Uniform)
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_newton_rejects_l1() {
+        LogisticRegression::new(Penalty::L1(0.1)).set_solver(LogisticSolver::Newton);
+    }
+}
@@ -0,0 +1 @@
+pub mod logistic_regression;
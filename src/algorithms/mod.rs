@@ -1,2 +1,15 @@
+pub mod clustering;
+pub mod curve_fit;
+pub mod decision_tree;
+pub mod elastic_net;
+pub mod estimators;
 pub mod exponential_regression;
+pub mod gradient_boosting;
 pub mod linear_regression;
+pub mod logistic_regression;
+pub mod naive_bayes;
+pub mod neighbors;
+pub mod neural_network;
+pub mod poisson_regression;
+pub mod random_forest;
+pub mod svm;
@@ -0,0 +1,297 @@
+use super::naive_bayes::{
+    ClassPriors, ClassTally, exponentiate, group_by_class, log_probabilities, smoothing,
+};
+use crate::algorithms::estimators::{labels_from_dataframe, most_likely_classes};
+use crate::dataframe::{DataFrame, DataType, DataTypeValue};
+use crate::linear_algebra::{Matrix, RowVector};
+
+/// Categorical naive Bayes straight on `DataFrame` columns: string columns
+/// use their values as categories, float columns their distinct numbers (for
+/// codes or small counts). Every category gets additive smoothing `alpha`.
+/// Nulls and categories never seen during fitting are skipped.
+pub struct CategoricalNaiveBayes {
+    priors: ClassPriors,
+    alpha: f32,
+    features: Vec<String>,
+    // per feature, categories in ascending order
+    categories: Vec<Vec<String>>,
+    tally: ClassTally,
+    // per feature, per class, per category
+    counts: Vec<Vec<Vec<f64>>>,
+}
+
+fn category(value: &DataTypeValue, column_name: &str) -> Option<String> {
+    return match value {
+        DataTypeValue::String(category) => Some(category.clone()),
+        DataTypeValue::Float(value) => Some(value.to_string()),
+        DataTypeValue::Null => None,
+        DataTypeValue::Id(_) => panic!("{} is an id column, not a feature", column_name),
+    };
+}
+
+impl CategoricalNaiveBayes {
+    pub fn new(alpha: f32) -> Self {
+        return Self {
+            priors: ClassPriors::Fitted,
+            alpha: smoothing(alpha),
+            features: Vec::new(),
+            categories: Vec::new(),
+            tally: ClassTally::new(),
+            counts: Vec::new(),
+        };
+    }
+
+    pub fn set_priors(&mut self, priors: ClassPriors) {
+        self.priors = priors;
+    }
+
+    /// Forgets earlier batches and fits on `feature_columns` of `df`.
+    pub fn fit_df(&mut self, df: &DataFrame, feature_columns: &Vec<String>, target_column: &str) {
+        *self = Self {
+            priors: self.priors.clone(),
+            ..Self::new(self.alpha)
+        };
+        self.partial_fit_df(df, feature_columns, target_column);
+    }
+
+    /// Adds a batch of rows, the feature columns must match earlier batches.
+    pub fn partial_fit_df(
+        &mut self,
+        df: &DataFrame,
+        feature_columns: &Vec<String>,
+        target_column: &str,
+    ) {
+        if self.features.is_empty() {
+            self.features = feature_columns.clone();
+            self.categories = vec![Vec::new(); feature_columns.len()];
+            self.counts = vec![Vec::new(); feature_columns.len()];
+        } else if &self.features != feature_columns {
+            panic!("feature columns must match the earlier batches");
+        }
+        for column_name in feature_columns.iter() {
+            if matches!(df.get_column(column_name).0, DataType::Id) {
+                panic!("{} is an id column, not a feature", column_name);
+            }
+        }
+        let labels = labels_from_dataframe(df, target_column);
+        let counts = &mut self.counts;
+        let categories = &self.categories;
+        let groups = group_by_class(&mut self.tally, df.len(), &labels, |position| {
+            for (feature, per_class) in counts.iter_mut().enumerate() {
+                per_class.insert(position, vec![0.0; categories[feature].len()]);
+            }
+        });
+        let classes_of: Vec<usize> = {
+            let mut classes_of = vec![0; df.len()];
+            for (position, rows) in groups.iter().enumerate() {
+                rows.iter().for_each(|row| classes_of[*row] = position);
+            }
+            classes_of
+        };
+        for (feature, column_name) in feature_columns.iter().enumerate() {
+            let (_, values) = df.get_column(column_name);
+            for (row, value) in values.iter().enumerate() {
+                let Some(value) = category(value, column_name) else {
+                    continue;
+                };
+                let index = match self.categories[feature].binary_search(&value) {
+                    Ok(index) => index,
+                    Err(index) => {
+                        self.categories[feature].insert(index, value);
+                        self.counts[feature]
+                            .iter_mut()
+                            .for_each(|per_category| per_category.insert(index, 0.0));
+                        index
+                    }
+                };
+                self.counts[feature][classes_of[row]][index] += 1.0;
+            }
+        }
+    }
+
+    fn joint_log_likelihood(&self, df: &DataFrame) -> Vec<Vec<f64>> {
+        if self.tally.classes.is_empty() {
+            panic!("naive bayes must be fitted first");
+        }
+        let alpha = self.alpha as f64;
+        let mut joint = vec![self.tally.log_priors(&self.priors); df.len()];
+        for (feature, column_name) in self.features.iter().enumerate() {
+            let (_, values) = df.get_column(column_name);
+            let num_categories = self.categories[feature].len() as f64;
+            let totals: Vec<f64> = self.counts[feature]
+                .iter()
+                .map(|per_category| per_category.iter().sum::<f64>() + alpha * num_categories)
+                .collect();
+            for (row, value) in values.iter().enumerate() {
+                let Some(index) = category(value, column_name)
+                    .and_then(|value| self.categories[feature].binary_search(&value).ok())
+                else {
+                    continue;
+                };
+                for (class, total) in totals.iter().enumerate() {
+                    joint[row][class] +=
+                        ((self.counts[feature][class][index] + alpha) / total).ln();
+                }
+            }
+        }
+        return joint;
+    }
+
+    /// Log class probabilities with columns in the order of `classes()`.
+    pub fn predict_log_proba_df(&self, df: &DataFrame) -> Matrix {
+        return log_probabilities(&self.joint_log_likelihood(df));
+    }
+
+    pub fn predict_proba_df(&self, df: &DataFrame) -> Matrix {
+        return exponentiate(&self.predict_log_proba_df(df));
+    }
+
+    pub fn predict_df(&self, df: &DataFrame) -> RowVector {
+        return most_likely_classes(&self.tally.classes, &self.predict_log_proba_df(df));
+    }
+
+    pub fn classes(&self) -> &Vec<f32> {
+        return &self.tally.classes;
+    }
+
+    pub fn class_counts(&self) -> &Vec<f64> {
+        return &self.tally.counts;
+    }
+
+    pub fn features(&self) -> &Vec<String> {
+        return &self.features;
+    }
+
+    /// Categories seen for `feature_column`, in ascending order.
+    pub fn categories(&self, feature_column: &str) -> &Vec<String> {
+        let feature = self
+            .features
+            .iter()
+            .position(|name| name == feature_column)
+            .unwrap_or_else(|| panic!("{} is not a feature", feature_column));
+        return &self.categories[feature];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dataframe::csv::df_from_csv;
+    use crate::inference::inference::accuracy;
+    use std::collections::HashMap;
+
+    const WEATHER: [(&str, &str, &str, f32, f32); 14] = [
+        ("sunny", "hot", "high", 0.0, 0.0),
+        ("sunny", "hot", "high", 1.0, 0.0),
+        ("overcast", "hot", "high", 0.0, 1.0),
+        ("rainy", "mild", "high", 0.0, 1.0),
+        ("rainy", "cool", "normal", 0.0, 1.0),
+        ("rainy", "cool", "normal", 1.0, 0.0),
+        ("overcast", "cool", "normal", 1.0, 1.0),
+        ("sunny", "mild", "high", 0.0, 0.0),
+        ("sunny", "cool", "normal", 0.0, 1.0),
+        ("rainy", "mild", "normal", 0.0, 1.0),
+        ("sunny", "mild", "normal", 1.0, 1.0),
+        ("overcast", "mild", "high", 1.0, 1.0),
+        ("overcast", "hot", "normal", 0.0, 1.0),
+        ("rainy", "mild", "high", 1.0, 0.0),
+    ];
+
+    fn weather(rows: &[(&str, &str, &str, f32, f32)]) -> (DataFrame, Vec<String>) {
+        let strings = ["outlook", "temperature", "humidity"];
+        let mut df = DataFrame::new();
+        for name in strings.iter() {
+            df.insert_column(name, &Vec::new(), &DataType::String);
+        }
+        df.insert_column("windy", &Vec::new(), &DataType::Float);
+        df.insert_column("play", &Vec::new(), &DataType::Float);
+        for (outlook, temperature, humidity, windy, play) in rows.iter() {
+            let mut row: HashMap<String, DataTypeValue> = strings
+                .iter()
+                .zip([outlook, temperature, humidity])
+                .map(|(name, value)| (name.to_string(), DataTypeValue::String(value.to_string())))
+                .collect();
+            row.insert("windy".to_string(), DataTypeValue::Float(*windy));
+            row.insert("play".to_string(), DataTypeValue::Float(*play));
+            df.insert_row(&row);
+        }
+        let features = ["outlook", "temperature", "humidity", "windy"]
+            .iter()
+            .map(|name| name.to_string())
+            .collect();
+        (df, features)
+    }
+
+    #[test]
+    fn test_categorical_naive_bayes_on_weather() {
+        let (df, features) = weather(&WEATHER);
+        let mut model = CategoricalNaiveBayes::new(0.0);
+        model.fit_df(&df, &features, "play");
+        assert!(model.classes() == &vec![0.0, 1.0]);
+        assert!(model.class_counts() == &vec![5.0, 9.0]);
+        assert!(model.categories("outlook") == &vec!["overcast", "rainy", "sunny"]);
+        assert!(model.categories("windy") == &vec!["0", "1"]);
+
+        // sunny, cool, high humidity and windy, worked out by hand
+        let (query, _) = weather(&[("sunny", "cool", "high", 1.0, 0.0)]);
+        let stay = 5.0 / 14.0 * (3.0 / 5.0) * (1.0 / 5.0) * (4.0 / 5.0) * (3.0 / 5.0);
+        let play = 9.0 / 14.0 * (2.0 / 9.0) * (3.0 / 9.0) * (3.0 / 9.0) * (3.0 / 9.0);
+        let probabilities = model.predict_proba_df(&query);
+        assert!((probabilities.get(0).get(0) - stay / (stay + play)).abs() < 1e-5);
+        assert!(model.predict_df(&query).get(0) == 0.0);
+
+        // a batch with one class and one outlook, then the rest
+        let mut streamed = CategoricalNaiveBayes::new(1.0);
+        streamed.partial_fit_df(&weather(&WEATHER[..2]).0, &features, "play");
+        assert!(streamed.classes() == &vec![0.0]);
+        streamed.partial_fit_df(&weather(&WEATHER[2..]).0, &features, "play");
+        let mut smoothed = CategoricalNaiveBayes::new(1.0);
+        smoothed.fit_df(&df, &features, "play");
+        assert!(streamed.categories("outlook") == smoothed.categories("outlook"));
+        let (a, b) = (
+            streamed.predict_proba_df(&df),
+            smoothed.predict_proba_df(&df),
+        );
+        assert!((0..14).all(|i| (a.get(i).get(0) - b.get(i).get(0)).abs() < 1e-6));
+
+        // unseen categories and nulls leave only the other features
+        let (mut unseen, _) = weather(&[("foggy", "cool", "high", 1.0, 0.0)]);
+        unseen.replace_column_values("windy", vec![DataTypeValue::Null]);
+        let (known, _) = weather(&[("sunny", "cool", "high", 1.0, 0.0)]);
+        let without = smoothed.predict_proba_df(&unseen).get(0).get(0);
+        assert!(without != smoothed.predict_proba_df(&known).get(0).get(0));
+        assert!(without > 0.0 && without < 1.0);
+    }
+
+    #[test]
+    fn test_categorical_naive_bayes_on_housing() {
+        let df = df_from_csv("housing.csv", Some(3000));
+        let (_, values) = df.get_column("median_house_value");
+        let values: Vec<f32> = values
+            .iter()
+            .map(|value| df.extract_value_as_float(value))
+            .collect();
+        let mut sorted = values.clone();
+        sorted.sort_by(|a, b| a.total_cmp(b));
+        let median = sorted[sorted.len() / 2];
+        let mut df = df;
+        df.insert_column(
+            "expensive",
+            &values
+                .iter()
+                .map(|value| DataTypeValue::Float((*value > median) as usize as f32))
+                .collect(),
+            &DataType::Float,
+        );
+        let features = vec!["ocean_proximity".to_string()];
+        let mut model = CategoricalNaiveBayes::new(1.0);
+        model.fit_df(&df, &features, "expensive");
+        assert!(
+            model
+                .categories("ocean_proximity")
+                .contains(&"INLAND".to_string())
+        );
+        let labels = labels_from_dataframe(&df, "expensive");
+        assert!(accuracy(model.predict_df(&df).vector(), &labels) > 0.65);
+    }
+}
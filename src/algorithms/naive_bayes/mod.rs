@@ -0,0 +1,6 @@
+pub mod categorical;
+pub mod naive_bayes;
+pub use categorical::CategoricalNaiveBayes;
+pub use naive_bayes::{
+    BernoulliNaiveBayes, ClassPriors, GaussianNaiveBayes, MultinomialNaiveBayes,
+};
@@ -0,0 +1,915 @@
+use crate::algorithms::estimators::{
+    Classifier, Estimator, ParamError, ParamValue, labels_from_matrix, most_likely_classes,
+};
+use crate::linear_algebra::{LinalgError, Matrix, RowVector};
+
+/// Class probabilities before seeing the features. `Fitted` uses the class
+/// frequencies seen so far, `Uniform` weighs every class equally and `Custom`
+/// lists `(class, prior)` pairs, rescaled to sum to one.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ClassPriors {
+    Fitted,
+    Uniform,
+    Custom(Vec<(f32, f32)>),
+}
+
+/// Classes seen so far in ascending order with their row counts. Classes
+/// first seen in a later `partial_fit` batch are inserted in order, models
+/// insert their per class statistics at the same position.
+pub(crate) struct ClassTally {
+    pub classes: Vec<f32>,
+    pub counts: Vec<f64>,
+}
+
+impl ClassTally {
+    pub fn new() -> Self {
+        return Self {
+            classes: Vec::new(),
+            counts: Vec::new(),
+        };
+    }
+
+    /// Position of `class` and whether it was just inserted.
+    pub fn position(&mut self, class: f32) -> (usize, bool) {
+        if class.is_nan() {
+            panic!("class labels must not be NaN");
+        }
+        return match self
+            .classes
+            .binary_search_by(|existing| existing.total_cmp(&class))
+        {
+            Ok(position) => (position, false),
+            Err(position) => {
+                self.classes.insert(position, class);
+                self.counts.insert(position, 0.0);
+                (position, true)
+            }
+        };
+    }
+
+    pub fn log_priors(&self, priors: &ClassPriors) -> Vec<f64> {
+        let weights: Vec<f64> = match priors {
+            ClassPriors::Fitted => self.counts.clone(),
+            ClassPriors::Uniform => vec![1.0; self.classes.len()],
+            ClassPriors::Custom(pairs) => self
+                .classes
+                .iter()
+                .map(|class| {
+                    pairs
+                        .iter()
+                        .find(|(listed, _)| listed == class)
+                        .map(|(_, prior)| *prior as f64)
+                        .unwrap_or_else(|| panic!("no prior for class {}", class))
+                })
+                .collect(),
+        };
+        let total: f64 = weights.iter().sum();
+        return weights.iter().map(|weight| (weight / total).ln()).collect();
+    }
+}
+
+/// Normalizes joint log likelihoods (one row per sample, one column per
+/// class) into log probabilities.
+pub(crate) fn log_probabilities(joint: &Vec<Vec<f64>>) -> Matrix {
+    return Matrix::new(
+        &joint
+            .iter()
+            .map(|row| {
+                let largest = row.iter().fold(f64::NEG_INFINITY, |acc, v| acc.max(*v));
+                let total = largest + row.iter().map(|v| (v - largest).exp()).sum::<f64>().ln();
+                RowVector::new(&row.iter().map(|v| (v - total) as f32).collect())
+            })
+            .collect(),
+    );
+}
+
+pub(crate) fn exponentiate(log_probabilities: &Matrix) -> Matrix {
+    return Matrix::new(
+        &log_probabilities
+            .matrix()
+            .iter()
+            .map(|row| RowVector::new(&row.vector().iter().map(|v| v.exp()).collect()))
+            .collect(),
+    );
+}
+
+/// Rows of a batch grouped by their class position, calling `on_insert` with
+/// the position of every new class.
+pub(crate) fn group_by_class(
+    tally: &mut ClassTally,
+    rows: usize,
+    labels: &Vec<f32>,
+    mut on_insert: impl FnMut(usize),
+) -> Vec<Vec<usize>> {
+    assert!(
+        rows == labels.len(),
+        "data and labels must have the same length"
+    );
+    let mut positions = Vec::with_capacity(labels.len());
+    for label in labels.iter() {
+        let (position, inserted) = tally.position(*label);
+        if inserted {
+            on_insert(position);
+            // earlier rows of this batch shift up past the new class
+            positions
+                .iter_mut()
+                .filter(|existing| **existing >= position)
+                .for_each(|existing| *existing += 1);
+        }
+        positions.push(position);
+    }
+    let mut groups = vec![Vec::new(); tally.classes.len()];
+    for (row, position) in positions.iter().enumerate() {
+        groups[*position].push(row);
+        tally.counts[*position] += 1.0;
+    }
+    return groups;
+}
+
+pub(crate) fn smoothing(alpha: f32) -> f32 {
+    if alpha.is_nan() || alpha < 0.0 {
+        panic!("alpha must not be negative");
+    }
+    return alpha;
+}
+
+fn check_width(width: &mut Option<usize>, data: &Matrix) {
+    let columns = data.shape().1;
+    match width {
+        Some(width) if *width != columns => {
+            panic!("expected {} features, found {}", width, columns)
+        }
+        _ => *width = Some(columns),
+    }
+}
+
+/// Gaussian naive Bayes: every feature is normally distributed within a
+/// class. `var_smoothing` times the largest feature variance is added to
+/// every variance so constant features stay usable.
+pub struct GaussianNaiveBayes {
+    priors: ClassPriors,
+    var_smoothing: f32,
+    width: Option<usize>,
+    tally: ClassTally,
+    // per class running means and sums of squared deviations
+    means: Vec<Vec<f64>>,
+    squares: Vec<Vec<f64>>,
+    // the same over every row, for the smoothing
+    total_mean: Vec<f64>,
+    total_squares: Vec<f64>,
+}
+
+// merges the moments of `rows` into a running count, mean and sum of squared
+// deviations (chan et al.)
+fn merge_moments(
+    count: f64,
+    mean: &mut Vec<f64>,
+    squares: &mut Vec<f64>,
+    data: &Matrix,
+    rows: &Vec<usize>,
+) {
+    if rows.is_empty() {
+        return;
+    }
+    let added = rows.len() as f64;
+    for j in 0..mean.len() {
+        let batch_mean = rows
+            .iter()
+            .map(|row| data.get(*row).get(j) as f64)
+            .sum::<f64>()
+            / added;
+        let batch_squares: f64 = rows
+            .iter()
+            .map(|row| (data.get(*row).get(j) as f64 - batch_mean).powi(2))
+            .sum();
+        let delta = batch_mean - mean[j];
+        let merged = count + added;
+        mean[j] += delta * added / merged;
+        squares[j] += batch_squares + delta * delta * count * added / merged;
+    }
+}
+
+impl GaussianNaiveBayes {
+    pub fn new() -> Self {
+        return Self {
+            priors: ClassPriors::Fitted,
+            var_smoothing: 1e-9,
+            width: None,
+            tally: ClassTally::new(),
+            means: Vec::new(),
+            squares: Vec::new(),
+            total_mean: Vec::new(),
+            total_squares: Vec::new(),
+        };
+    }
+
+    pub fn set_priors(&mut self, priors: ClassPriors) {
+        self.priors = priors;
+    }
+
+    pub fn set_var_smoothing(&mut self, var_smoothing: f32) {
+        self.var_smoothing = var_smoothing;
+    }
+
+    /// Forgets earlier batches and fits on `data`.
+    pub fn fit(&mut self, data: &Matrix, labels: &Matrix) {
+        *self = Self {
+            priors: self.priors.clone(),
+            var_smoothing: self.var_smoothing,
+            ..Self::new()
+        };
+        self.partial_fit(data, labels);
+    }
+
+    /// Adds a batch of rows to the fitted statistics.
+    pub fn partial_fit(&mut self, data: &Matrix, labels: &Matrix) {
+        check_width(&mut self.width, data);
+        let columns = self.width.unwrap();
+        if self.total_mean.is_empty() {
+            self.total_mean = vec![0.0; columns];
+            self.total_squares = vec![0.0; columns];
+        }
+        let seen: f64 = self.tally.counts.iter().sum();
+        let (means, squares) = (&mut self.means, &mut self.squares);
+        let groups = group_by_class(
+            &mut self.tally,
+            data.len(),
+            &labels_from_matrix(labels),
+            |position| {
+                means.insert(position, vec![0.0; columns]);
+                squares.insert(position, vec![0.0; columns]);
+            },
+        );
+        for (position, rows) in groups.iter().enumerate() {
+            let count = self.tally.counts[position] - rows.len() as f64;
+            merge_moments(
+                count,
+                &mut self.means[position],
+                &mut self.squares[position],
+                data,
+                rows,
+            );
+        }
+        merge_moments(
+            seen,
+            &mut self.total_mean,
+            &mut self.total_squares,
+            data,
+            &(0..data.len()).collect(),
+        );
+    }
+
+    fn epsilon(&self) -> f64 {
+        let rows: f64 = self.tally.counts.iter().sum();
+        let largest = self
+            .total_squares
+            .iter()
+            .fold(0.0_f64, |acc, squares| acc.max(squares / rows));
+        return (self.var_smoothing as f64 * largest).max(1e-12);
+    }
+
+    /// Per class feature variances, smoothing included.
+    pub fn variances(&self) -> Vec<Vec<f32>> {
+        let epsilon = self.epsilon();
+        return self
+            .squares
+            .iter()
+            .zip(self.tally.counts.iter())
+            .map(|(squares, count)| {
+                squares
+                    .iter()
+                    .map(|square| (square / count + epsilon) as f32)
+                    .collect()
+            })
+            .collect();
+    }
+
+    pub fn means(&self) -> Vec<Vec<f32>> {
+        return self
+            .means
+            .iter()
+            .map(|mean| mean.iter().map(|value| *value as f32).collect())
+            .collect();
+    }
+
+    fn joint_log_likelihood(&self, data: &Matrix) -> Vec<Vec<f64>> {
+        if self.tally.classes.is_empty() {
+            panic!("naive bayes must be fitted first");
+        }
+        let log_priors = self.tally.log_priors(&self.priors);
+        let epsilon = self.epsilon();
+        let variances: Vec<Vec<f64>> = self
+            .squares
+            .iter()
+            .zip(self.tally.counts.iter())
+            .map(|(squares, count)| {
+                squares
+                    .iter()
+                    .map(|square| square / count + epsilon)
+                    .collect()
+            })
+            .collect();
+        return data
+            .matrix()
+            .iter()
+            .map(|row| {
+                (0..self.tally.classes.len())
+                    .map(|c| {
+                        log_priors[c]
+                            - 0.5
+                                * row
+                                    .vector()
+                                    .iter()
+                                    .zip(self.means[c].iter().zip(variances[c].iter()))
+                                    .map(|(x, (mean, variance))| {
+                                        (std::f64::consts::TAU * variance).ln()
+                                            + (*x as f64 - mean).powi(2) / variance
+                                    })
+                                    .sum::<f64>()
+                    })
+                    .collect()
+            })
+            .collect();
+    }
+
+    /// Log class probabilities with columns in the order of `classes()`.
+    pub fn predict_log_proba(&self, data: &Matrix) -> Matrix {
+        return log_probabilities(&self.joint_log_likelihood(data));
+    }
+
+    pub fn predict_proba(&self, data: &Matrix) -> Matrix {
+        return exponentiate(&self.predict_log_proba(data));
+    }
+
+    pub fn predict(&self, data: &Matrix) -> RowVector {
+        return most_likely_classes(&self.tally.classes, &self.predict_log_proba(data));
+    }
+
+    pub fn classes(&self) -> &Vec<f32> {
+        return &self.tally.classes;
+    }
+
+    /// Rows seen per class.
+    pub fn class_counts(&self) -> &Vec<f64> {
+        return &self.tally.counts;
+    }
+}
+
+/// Multinomial naive Bayes over non-negative counts (word counts, one-hot
+/// blocks), with additive smoothing `alpha` (1 is Laplace smoothing).
+pub struct MultinomialNaiveBayes {
+    priors: ClassPriors,
+    alpha: f32,
+    width: Option<usize>,
+    tally: ClassTally,
+    feature_counts: Vec<Vec<f64>>,
+}
+
+impl MultinomialNaiveBayes {
+    pub fn new(alpha: f32) -> Self {
+        return Self {
+            priors: ClassPriors::Fitted,
+            alpha: smoothing(alpha),
+            width: None,
+            tally: ClassTally::new(),
+            feature_counts: Vec::new(),
+        };
+    }
+
+    pub fn set_priors(&mut self, priors: ClassPriors) {
+        self.priors = priors;
+    }
+
+    /// Forgets earlier batches and fits on `data`.
+    pub fn fit(&mut self, data: &Matrix, labels: &Matrix) {
+        *self = Self {
+            priors: self.priors.clone(),
+            ..Self::new(self.alpha)
+        };
+        self.partial_fit(data, labels);
+    }
+
+    /// Adds a batch of rows to the fitted counts.
+    pub fn partial_fit(&mut self, data: &Matrix, labels: &Matrix) {
+        check_width(&mut self.width, data);
+        if data
+            .matrix()
+            .iter()
+            .any(|row| row.vector().iter().any(|x| x.is_nan() || *x < 0.0))
+        {
+            panic!("multinomial naive bayes needs non-negative features");
+        }
+        let columns = self.width.unwrap();
+        let feature_counts = &mut self.feature_counts;
+        let groups = group_by_class(
+            &mut self.tally,
+            data.len(),
+            &labels_from_matrix(labels),
+            |position| {
+                feature_counts.insert(position, vec![0.0; columns]);
+            },
+        );
+        for (position, rows) in groups.iter().enumerate() {
+            for row in rows.iter() {
+                self.feature_counts[position]
+                    .iter_mut()
+                    .zip(data.get(*row).vector())
+                    .for_each(|(count, x)| *count += *x as f64);
+            }
+        }
+    }
+
+    /// Smoothed log probability of every feature within every class.
+    pub fn feature_log_probabilities(&self) -> Vec<Vec<f64>> {
+        let alpha = self.alpha as f64;
+        return self
+            .feature_counts
+            .iter()
+            .map(|counts| {
+                let total = counts.iter().sum::<f64>() + alpha * counts.len() as f64;
+                counts
+                    .iter()
+                    .map(|count| ((count + alpha) / total).ln())
+                    .collect()
+            })
+            .collect();
+    }
+
+    fn joint_log_likelihood(&self, data: &Matrix) -> Vec<Vec<f64>> {
+        if self.tally.classes.is_empty() {
+            panic!("naive bayes must be fitted first");
+        }
+        let log_priors = self.tally.log_priors(&self.priors);
+        let log_probabilities = self.feature_log_probabilities();
+        return data
+            .matrix()
+            .iter()
+            .map(|row| {
+                log_probabilities
+                    .iter()
+                    .zip(log_priors.iter())
+                    .map(|(log_probabilities, log_prior)| {
+                        // zero counts contribute nothing even when alpha is 0
+                        log_prior
+                            + row
+                                .vector()
+                                .iter()
+                                .zip(log_probabilities)
+                                .filter(|(x, _)| **x != 0.0)
+                                .map(|(x, log_probability)| *x as f64 * log_probability)
+                                .sum::<f64>()
+                    })
+                    .collect()
+            })
+            .collect();
+    }
+
+    /// Log class probabilities with columns in the order of `classes()`.
+    pub fn predict_log_proba(&self, data: &Matrix) -> Matrix {
+        return log_probabilities(&self.joint_log_likelihood(data));
+    }
+
+    pub fn predict_proba(&self, data: &Matrix) -> Matrix {
+        return exponentiate(&self.predict_log_proba(data));
+    }
+
+    pub fn predict(&self, data: &Matrix) -> RowVector {
+        return most_likely_classes(&self.tally.classes, &self.predict_log_proba(data));
+    }
+
+    pub fn classes(&self) -> &Vec<f32> {
+        return &self.tally.classes;
+    }
+
+    pub fn class_counts(&self) -> &Vec<f64> {
+        return &self.tally.counts;
+    }
+}
+
+/// Bernoulli naive Bayes over binary features, with additive smoothing
+/// `alpha`. Unlike the multinomial model an absent feature counts as evidence
+/// too. Features above `binarize` are ones, `None` expects them binary.
+pub struct BernoulliNaiveBayes {
+    priors: ClassPriors,
+    alpha: f32,
+    binarize: Option<f32>,
+    width: Option<usize>,
+    tally: ClassTally,
+    feature_counts: Vec<Vec<f64>>,
+}
+
+impl BernoulliNaiveBayes {
+    pub fn new(alpha: f32) -> Self {
+        return Self {
+            priors: ClassPriors::Fitted,
+            alpha: smoothing(alpha),
+            binarize: Some(0.0),
+            width: None,
+            tally: ClassTally::new(),
+            feature_counts: Vec::new(),
+        };
+    }
+
+    pub fn set_priors(&mut self, priors: ClassPriors) {
+        self.priors = priors;
+    }
+
+    pub fn set_binarize(&mut self, binarize: Option<f32>) {
+        self.binarize = binarize;
+    }
+
+    fn indicator(&self, x: f32) -> f64 {
+        return match self.binarize {
+            Some(threshold) => (x > threshold) as usize as f64,
+            None if x == 0.0 || x == 1.0 => x as f64,
+            None => panic!("bernoulli naive bayes without binarize needs binary features"),
+        };
+    }
+
+    /// Forgets earlier batches and fits on `data`.
+    pub fn fit(&mut self, data: &Matrix, labels: &Matrix) {
+        *self = Self {
+            priors: self.priors.clone(),
+            binarize: self.binarize,
+            ..Self::new(self.alpha)
+        };
+        self.partial_fit(data, labels);
+    }
+
+    /// Adds a batch of rows to the fitted counts.
+    pub fn partial_fit(&mut self, data: &Matrix, labels: &Matrix) {
+        check_width(&mut self.width, data);
+        let columns = self.width.unwrap();
+        let feature_counts = &mut self.feature_counts;
+        let groups = group_by_class(
+            &mut self.tally,
+            data.len(),
+            &labels_from_matrix(labels),
+            |position| {
+                feature_counts.insert(position, vec![0.0; columns]);
+            },
+        );
+        for (position, rows) in groups.iter().enumerate() {
+            for row in rows.iter() {
+                for (j, x) in data.get(*row).vector().iter().enumerate() {
+                    self.feature_counts[position][j] += self.indicator(*x);
+                }
+            }
+        }
+    }
+
+    /// Smoothed probability of every feature being one within every class.
+    pub fn feature_probabilities(&self) -> Vec<Vec<f64>> {
+        let alpha = self.alpha as f64;
+        return self
+            .feature_counts
+            .iter()
+            .zip(self.tally.counts.iter())
+            .map(|(counts, total)| {
+                counts
+                    .iter()
+                    .map(|count| (count + alpha) / (total + 2.0 * alpha))
+                    .collect()
+            })
+            .collect();
+    }
+
+    fn joint_log_likelihood(&self, data: &Matrix) -> Vec<Vec<f64>> {
+        if self.tally.classes.is_empty() {
+            panic!("naive bayes must be fitted first");
+        }
+        let log_priors = self.tally.log_priors(&self.priors);
+        let probabilities = self.feature_probabilities();
+        return data
+            .matrix()
+            .iter()
+            .map(|row| {
+                let indicators: Vec<f64> =
+                    row.vector().iter().map(|x| self.indicator(*x)).collect();
+                probabilities
+                    .iter()
+                    .zip(log_priors.iter())
+                    .map(|(probabilities, log_prior)| {
+                        log_prior
+                            + indicators
+                                .iter()
+                                .zip(probabilities)
+                                .map(|(x, p)| if *x == 1.0 { p.ln() } else { (1.0 - p).ln() })
+                                .sum::<f64>()
+                    })
+                    .collect()
+            })
+            .collect();
+    }
+
+    /// Log class probabilities with columns in the order of `classes()`.
+    pub fn predict_log_proba(&self, data: &Matrix) -> Matrix {
+        return log_probabilities(&self.joint_log_likelihood(data));
+    }
+
+    pub fn predict_proba(&self, data: &Matrix) -> Matrix {
+        return exponentiate(&self.predict_log_proba(data));
+    }
+
+    pub fn predict(&self, data: &Matrix) -> RowVector {
+        return most_likely_classes(&self.tally.classes, &self.predict_log_proba(data));
+    }
+
+    pub fn classes(&self) -> &Vec<f32> {
+        return &self.tally.classes;
+    }
+
+    pub fn class_counts(&self) -> &Vec<f64> {
+        return &self.tally.counts;
+    }
+}
+
+impl Estimator for GaussianNaiveBayes {
+    fn params(&self) -> Vec<(&'static str, ParamValue)> {
+        return vec![("var_smoothing", ParamValue::Float(self.var_smoothing))];
+    }
+
+    fn set_param(&mut self, name: &str, value: ParamValue) -> Result<(), ParamError> {
+        match name {
+            "var_smoothing" => self.set_var_smoothing(value.as_float(name)?),
+            _ => return Err(ParamError::Unknown(name.to_string())),
+        }
+        Ok(())
+    }
+}
+
+impl Classifier for GaussianNaiveBayes {
+    fn fit(&mut self, data: &Matrix, labels: &Matrix) -> Result<(), LinalgError> {
+        GaussianNaiveBayes::fit(self, data, labels);
+        Ok(())
+    }
+
+    fn predict(&self, data: &Matrix) -> RowVector {
+        return GaussianNaiveBayes::predict(self, data);
+    }
+
+    fn classes(&self) -> &Vec<f32> {
+        return GaussianNaiveBayes::classes(self);
+    }
+}
+
+impl Estimator for MultinomialNaiveBayes {
+    fn params(&self) -> Vec<(&'static str, ParamValue)> {
+        return vec![("alpha", ParamValue::Float(self.alpha))];
+    }
+
+    fn set_param(&mut self, name: &str, value: ParamValue) -> Result<(), ParamError> {
+        match name {
+            "alpha" => self.alpha = smoothing(value.as_float(name)?),
+            _ => return Err(ParamError::Unknown(name.to_string())),
+        }
+        Ok(())
+    }
+}
+
+impl Classifier for MultinomialNaiveBayes {
+    fn fit(&mut self, data: &Matrix, labels: &Matrix) -> Result<(), LinalgError> {
+        MultinomialNaiveBayes::fit(self, data, labels);
+        Ok(())
+    }
+
+    fn predict(&self, data: &Matrix) -> RowVector {
+        return MultinomialNaiveBayes::predict(self, data);
+    }
+
+    fn classes(&self) -> &Vec<f32> {
+        return MultinomialNaiveBayes::classes(self);
+    }
+}
+
+impl Estimator for BernoulliNaiveBayes {
+    fn params(&self) -> Vec<(&'static str, ParamValue)> {
+        return vec![
+            ("alpha", ParamValue::Float(self.alpha)),
+            ("binarize", ParamValue::OptionalFloat(self.binarize)),
+        ];
+    }
+
+    fn set_param(&mut self, name: &str, value: ParamValue) -> Result<(), ParamError> {
+        match name {
+            "alpha" => self.alpha = smoothing(value.as_float(name)?),
+            "binarize" => self.set_binarize(value.as_optional_float(name)?),
+            _ => return Err(ParamError::Unknown(name.to_string())),
+        }
+        Ok(())
+    }
+}
+
+impl Classifier for BernoulliNaiveBayes {
+    fn fit(&mut self, data: &Matrix, labels: &Matrix) -> Result<(), LinalgError> {
+        BernoulliNaiveBayes::fit(self, data, labels);
+        Ok(())
+    }
+
+    fn predict(&self, data: &Matrix) -> RowVector {
+        return BernoulliNaiveBayes::predict(self, data);
+    }
+
+    fn classes(&self) -> &Vec<f32> {
+        return BernoulliNaiveBayes::classes(self);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::inference::inference::accuracy;
+    use crate::sampling::random::Rng;
+
+    fn rows(data: &Matrix, range: std::ops::Range<usize>) -> Matrix {
+        Matrix::new(&data.matrix()[range].to_vec())
+    }
+
+    #[test]
+    fn test_gaussian_naive_bayes() {
+        let mut rng = Rng::new(1);
+        let centers = [(-2.0, 0.0, 1.0), (2.0, 1.0, 0.5), (0.0, 4.0, 2.0)];
+        let mut data = Vec::new();
+        let mut labels = Vec::new();
+        for i in 0..900 {
+            let (x, y, spread) = centers[i % 3];
+            data.push(vec![
+                x + spread * rng.normal(),
+                y + spread * rng.normal(),
+                5.0,
+            ]);
+            labels.push(vec![(i % 3) as f32]);
+        }
+        let (data, labels) = (Matrix::to_matrix(&data), Matrix::to_matrix(&labels));
+        let mut model = GaussianNaiveBayes::new();
+        model.fit(&data, &labels);
+        assert!(model.classes() == &vec![0.0, 1.0, 2.0]);
+        assert!(accuracy(model.predict(&data).vector(), &labels_from_matrix(&labels)) > 0.93);
+        let variances = model.variances();
+        assert!((variances[1][0] - 0.25).abs() < 0.05);
+        // the constant feature only keeps the smoothing
+        assert!(variances[0][2] > 0.0 && variances[0][2] < 1e-6);
+        let probabilities = model.predict_proba(&data);
+        assert!(
+            probabilities
+                .matrix()
+                .iter()
+                .all(|row| (row.vector().iter().sum::<f32>() - 1.0).abs() < 1e-5)
+        );
+
+        // streaming batches, the last class only showing up later
+        let mut streamed = GaussianNaiveBayes::new();
+        streamed.partial_fit(&rows(&data, 0..2), &rows(&labels, 0..2));
+        for start in (2..900).step_by(149) {
+            let end = (start + 149).min(900);
+            streamed.partial_fit(&rows(&data, start..end), &rows(&labels, start..end));
+        }
+        assert!(streamed.classes() == model.classes());
+        assert!(streamed.class_counts() == &vec![300.0, 300.0, 300.0]);
+        for (a, b) in streamed.means().iter().zip(model.means().iter()) {
+            assert!(a.iter().zip(b).all(|(a, b)| (a - b).abs() < 1e-4));
+        }
+        for (a, b) in streamed.variances().iter().zip(variances.iter()) {
+            assert!(a.iter().zip(b).all(|(a, b)| (a - b).abs() < 1e-4));
+        }
+    }
+
+    #[test]
+    fn test_priors_shift_predictions() {
+        // a rare class overlapping a common one
+        let mut rng = Rng::new(2);
+        let mut data = Vec::new();
+        let mut labels = Vec::new();
+        for i in 0..1000 {
+            let rare = i % 10 == 0;
+            data.push(vec![if rare { 1.0 } else { -1.0 } + rng.normal()]);
+            labels.push(vec![rare as usize as f32]);
+        }
+        let (data, labels) = (Matrix::to_matrix(&data), Matrix::to_matrix(&labels));
+        let predicted_rare = |priors: ClassPriors| {
+            let mut model = GaussianNaiveBayes::new();
+            model.set_priors(priors);
+            model.fit(&data, &labels);
+            model
+                .predict(&data)
+                .vector()
+                .iter()
+                .filter(|label| **label == 1.0)
+                .count()
+        };
+        let fitted = predicted_rare(ClassPriors::Fitted);
+        let uniform = predicted_rare(ClassPriors::Uniform);
+        let custom = predicted_rare(ClassPriors::Custom(vec![(0.0, 1.0), (1.0, 9.0)]));
+        assert!(fitted < uniform && uniform < custom);
+    }
+
+    // word counts over six words, class 1 favouring the last three
+    fn documents(count: usize, seed: u64) -> (Matrix, Matrix) {
+        let mut rng = Rng::new(seed);
+        let topics = [
+            [0.3, 0.3, 0.2, 0.1, 0.05, 0.05],
+            [0.05, 0.05, 0.1, 0.2, 0.3, 0.3],
+        ];
+        let mut data = Vec::new();
+        let mut labels = Vec::new();
+        for i in 0..count {
+            let topic = i % 2;
+            let mut counts = vec![0.0; 6];
+            for _ in 0..8 {
+                let mut draw = rng.next_f32();
+                let word = topics[topic]
+                    .iter()
+                    .position(|p| {
+                        draw -= p;
+                        draw < 0.0
+                    })
+                    .unwrap_or(5);
+                counts[word] += 1.0;
+            }
+            data.push(counts);
+            labels.push(vec![topic as f32]);
+        }
+        (Matrix::to_matrix(&data), Matrix::to_matrix(&labels))
+    }
+
+    #[test]
+    fn test_multinomial_naive_bayes() {
+        let (data, labels) = documents(600, 3);
+        let (test_data, test_labels) = documents(300, 4);
+        let mut model = MultinomialNaiveBayes::new(1.0);
+        model.fit(&data, &labels);
+        assert!(
+            accuracy(
+                model.predict(&test_data).vector(),
+                &labels_from_matrix(&test_labels)
+            ) > 0.9
+        );
+        let log_probabilities = model.feature_log_probabilities();
+        assert!(
+            log_probabilities
+                .iter()
+                .all(|row| (row.iter().map(|v| v.exp()).sum::<f64>() - 1.0).abs() < 1e-9)
+        );
+
+        let mut streamed = MultinomialNaiveBayes::new(1.0);
+        for start in (0..600).step_by(100) {
+            streamed.partial_fit(
+                &rows(&data, start..start + 100),
+                &rows(&labels, start..start + 100),
+            );
+        }
+        assert!(streamed.predict(&test_data).vector() == model.predict(&test_data).vector());
+
+        // a word never seen with class 0 only rules it out without smoothing
+        let data = Matrix::to_matrix(&vec![vec![2.0, 0.0], vec![1.0, 1.0]]);
+        let labels = Matrix::to_matrix(&vec![vec![0.0], vec![1.0]]);
+        let query = Matrix::to_matrix(&vec![vec![3.0, 1.0]]);
+        let mut unsmoothed = MultinomialNaiveBayes::new(0.0);
+        unsmoothed.fit(&data, &labels);
+        assert!(unsmoothed.predict_proba(&query).get(0).get(0) == 0.0);
+        let mut smoothed = MultinomialNaiveBayes::new(1.0);
+        smoothed.fit(&data, &labels);
+        // (3/4)^3 (1/4) against (1/2)^4 with equal priors
+        let expected = (27.0 / 256.0) / (27.0 / 256.0 + 1.0 / 16.0);
+        assert!((smoothed.predict_proba(&query).get(0).get(0) - expected).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_bernoulli_naive_bayes() {
+        let (data, labels) = documents(600, 5);
+        let (test_data, test_labels) = documents(300, 6);
+        let mut model = BernoulliNaiveBayes::new(1.0);
+        model.fit(&data, &labels);
+        assert!(
+            accuracy(
+                model.predict(&test_data).vector(),
+                &labels_from_matrix(&test_labels)
+            ) > 0.85
+        );
+
+        // hand computed: class 0 always has the first feature, class 1 never
+        let data = Matrix::to_matrix(&vec![
+            vec![1.0, 0.0],
+            vec![1.0, 1.0],
+            vec![0.0, 1.0],
+            vec![0.0, 0.0],
+        ]);
+        let labels = Matrix::to_matrix(&vec![vec![0.0], vec![0.0], vec![1.0], vec![1.0]]);
+        let mut model = BernoulliNaiveBayes::new(1.0);
+        model.set_binarize(None);
+        model.fit(&data, &labels);
+        let probabilities = model.feature_probabilities();
+        assert!(probabilities[0] == vec![0.75, 0.5] && probabilities[1] == vec![0.25, 0.5]);
+        let query = Matrix::to_matrix(&vec![vec![1.0, 0.0]]);
+        let expected = (0.75 * 0.5) / (0.75 * 0.5 + 0.25 * 0.5);
+        assert!((model.predict_proba(&query).get(0).get(0) - expected).abs() < 1e-6);
+        // thresholding turns counts into presence
+        let mut thresholded = BernoulliNaiveBayes::new(1.0);
+        thresholded.set_binarize(Some(0.5));
+        thresholded.fit(
+            &Matrix::to_matrix(&vec![vec![3.0], vec![0.2]]),
+            &Matrix::to_matrix(&vec![vec![0.0], vec![1.0]]),
+        );
+        assert!(thresholded.feature_probabilities() == vec![vec![2.0 / 3.0], vec![1.0 / 3.0]]);
+    }
+}
@@ -0,0 +1,2 @@
+pub mod neighbors;
+pub mod spatial;
@@ -0,0 +1,429 @@
+use super::spatial::{Distance, NeighborIndex, NeighborsAlgorithm};
+use crate::algorithms::estimators::{
+    Classifier, Estimator, ParamError, ParamValue, Regressor, encode_classes,
+    labels_from_dataframe, labels_from_matrix, most_likely_classes,
+};
+use crate::dataframe::DataFrame;
+use crate::linear_algebra::{LinalgError, Matrix, RowVector};
+use crate::parallel::parallel::parallel_map;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Weights {
+    Uniform,
+    /// Inverse distance, points matching the query exactly take all the weight.
+    Distance,
+}
+
+/// Predicts the (weighted) mean target of the `k` closest training rows.
+pub struct KNeighborsRegressor {
+    neighbors: Neighbors,
+    targets: Vec<f32>,
+}
+
+/// Predicts the class with the largest (weighted) share among the `k`
+/// closest training rows.
+pub struct KNeighborsClassifier {
+    neighbors: Neighbors,
+    classes: Vec<f32>,
+    targets: Vec<usize>,
+}
+
+// settings and index shared by both models
+struct Neighbors {
+    num_neighbors: usize,
+    weights: Weights,
+    distance: Distance,
+    algorithm: NeighborsAlgorithm,
+    leaf_size: usize,
+    features: Vec<String>,
+    index: Option<NeighborIndex>,
+}
+
+impl Neighbors {
+    fn new(num_neighbors: usize) -> Self {
+        if num_neighbors == 0 {
+            panic!("number of neighbors must be positive");
+        }
+        return Self {
+            num_neighbors,
+            weights: Weights::Uniform,
+            distance: Distance::Euclidean,
+            algorithm: NeighborsAlgorithm::Auto,
+            leaf_size: 30,
+            features: Vec::new(),
+            index: None,
+        };
+    }
+
+    fn fit(&mut self, data: &Matrix, num_labels: usize) {
+        assert!(
+            data.len() == num_labels,
+            "data and labels must have the same length"
+        );
+        if data.len() == 0 {
+            panic!("cannot fit neighbors on an empty matrix");
+        }
+        self.index = Some(NeighborIndex::new(
+            data,
+            self.distance.clone(),
+            self.algorithm.clone(),
+            self.leaf_size,
+        ));
+    }
+
+    fn index(&self) -> &NeighborIndex {
+        return self
+            .index
+            .as_ref()
+            .expect("neighbors model must be fitted first");
+    }
+
+    fn kneighbors(&self, data: &Matrix, k: usize) -> Vec<Vec<(usize, f32)>> {
+        let index = self.index();
+        return parallel_map(data.len(), 64, |row| index.query(data.get(row).vector(), k));
+    }
+
+    // neighbours of every row with weights summing to one
+    fn weighted(&self, data: &Matrix) -> Vec<Vec<(usize, f64)>> {
+        return self
+            .kneighbors(data, self.num_neighbors)
+            .into_iter()
+            .map(|neighbors| match self.weights {
+                Weights::Uniform => {
+                    let weight = 1.0 / neighbors.len() as f64;
+                    neighbors
+                        .iter()
+                        .map(|(index, _)| (*index, weight))
+                        .collect()
+                }
+                Weights::Distance => {
+                    let exact = neighbors
+                        .iter()
+                        .filter(|(_, distance)| *distance == 0.0)
+                        .count();
+                    let raw: Vec<(usize, f64)> = neighbors
+                        .iter()
+                        .map(|(index, distance)| {
+                            let weight = if exact > 0 {
+                                (*distance == 0.0) as usize as f64
+                            } else {
+                                1.0 / *distance as f64
+                            };
+                            (*index, weight)
+                        })
+                        .collect();
+                    let total: f64 = raw.iter().map(|(_, weight)| weight).sum();
+                    raw.iter()
+                        .map(|(index, weight)| (*index, weight / total))
+                        .collect()
+                }
+            })
+            .collect();
+    }
+
+    fn matrix_from_df(&self, df: &DataFrame) -> Matrix {
+        return df.get_columns_as_df(&self.features).as_matrix(false);
+    }
+}
+
+impl KNeighborsRegressor {
+    pub fn new(num_neighbors: usize) -> Self {
+        return Self {
+            neighbors: Neighbors::new(num_neighbors),
+            targets: Vec::new(),
+        };
+    }
+
+    pub fn set_weights(&mut self, weights: Weights) {
+        self.neighbors.weights = weights;
+    }
+
+    pub fn set_distance(&mut self, distance: Distance) {
+        self.neighbors.distance = distance;
+    }
+
+    pub fn set_algorithm(&mut self, algorithm: NeighborsAlgorithm) {
+        self.neighbors.algorithm = algorithm;
+    }
+
+    /// Largest number of points a tree leaf holds before it is split.
+    pub fn set_leaf_size(&mut self, leaf_size: usize) {
+        self.neighbors.leaf_size = leaf_size.max(1);
+    }
+
+    pub fn fit(&mut self, data: &Matrix, labels: &Matrix) {
+        let targets = labels_from_matrix(labels);
+        self.neighbors.fit(data, targets.len());
+        self.neighbors.features = Vec::new();
+        self.targets = targets;
+    }
+
+    /// Fits on numeric `feature_columns`, with haversine distance these must
+    /// be latitude then longitude.
+    pub fn fit_df(&mut self, df: &DataFrame, feature_columns: &Vec<String>, target_column: &str) {
+        let targets = labels_from_dataframe(df, target_column);
+        self.neighbors.features = feature_columns.clone();
+        let data = self.neighbors.matrix_from_df(df);
+        self.neighbors.fit(&data, targets.len());
+        self.targets = targets;
+    }
+
+    pub fn predict(&self, data: &Matrix) -> RowVector {
+        let predictions = self
+            .neighbors
+            .weighted(data)
+            .iter()
+            .map(|neighbors| {
+                neighbors
+                    .iter()
+                    .map(|(index, weight)| weight * self.targets[*index] as f64)
+                    .sum::<f64>() as f32
+            })
+            .collect();
+        return RowVector::new(&predictions);
+    }
+
+    pub fn predict_df(&self, df: &DataFrame) -> RowVector {
+        return self.predict(&self.neighbors.matrix_from_df(df));
+    }
+
+    /// The `k` closest training rows of every row as `(row, distance)`,
+    /// closest first.
+    pub fn kneighbors(&self, data: &Matrix, k: usize) -> Vec<Vec<(usize, f32)>> {
+        return self.neighbors.kneighbors(data, k);
+    }
+
+    pub fn num_neighbors(&self) -> usize {
+        return self.neighbors.num_neighbors;
+    }
+}
+
+impl KNeighborsClassifier {
+    pub fn new(num_neighbors: usize) -> Self {
+        return Self {
+            neighbors: Neighbors::new(num_neighbors),
+            classes: Vec::new(),
+            targets: Vec::new(),
+        };
+    }
+
+    pub fn set_weights(&mut self, weights: Weights) {
+        self.neighbors.weights = weights;
+    }
+
+    pub fn set_distance(&mut self, distance: Distance) {
+        self.neighbors.distance = distance;
+    }
+
+    pub fn set_algorithm(&mut self, algorithm: NeighborsAlgorithm) {
+        self.neighbors.algorithm = algorithm;
+    }
+
+    pub fn set_leaf_size(&mut self, leaf_size: usize) {
+        self.neighbors.leaf_size = leaf_size.max(1);
+    }
+
+    pub fn fit(&mut self, data: &Matrix, labels: &Matrix) {
+        let labels = labels_from_matrix(labels);
+        self.neighbors.fit(data, labels.len());
+        self.neighbors.features = Vec::new();
+        (self.classes, self.targets) = encode_classes(&labels);
+    }
+
+    pub fn fit_df(&mut self, df: &DataFrame, feature_columns: &Vec<String>, target_column: &str) {
+        let labels = labels_from_dataframe(df, target_column);
+        self.neighbors.features = feature_columns.clone();
+        let data = self.neighbors.matrix_from_df(df);
+        self.neighbors.fit(&data, labels.len());
+        (self.classes, self.targets) = encode_classes(&labels);
+    }
+
+    /// Weighted class shares with columns in the order of `classes()`.
+    pub fn predict_proba(&self, data: &Matrix) -> Matrix {
+        let probabilities: Vec<Vec<f64>> = self
+            .neighbors
+            .weighted(data)
+            .iter()
+            .map(|neighbors| {
+                let mut shares = vec![0.0; self.classes.len()];
+                for (index, weight) in neighbors.iter() {
+                    shares[self.targets[*index]] += weight;
+                }
+                shares
+            })
+            .collect();
+        return Matrix::from_f64(&probabilities);
+    }
+
+    pub fn predict_proba_df(&self, df: &DataFrame) -> Matrix {
+        return self.predict_proba(&self.neighbors.matrix_from_df(df));
+    }
+
+    pub fn predict(&self, data: &Matrix) -> RowVector {
+        return most_likely_classes(&self.classes, &self.predict_proba(data));
+    }
+
+    pub fn predict_df(&self, df: &DataFrame) -> RowVector {
+        return self.predict(&self.neighbors.matrix_from_df(df));
+    }
+
+    pub fn kneighbors(&self, data: &Matrix, k: usize) -> Vec<Vec<(usize, f32)>> {
+        return self.neighbors.kneighbors(data, k);
+    }
+
+    pub fn classes(&self) -> &Vec<f32> {
+        return &self.classes;
+    }
+
+    pub fn num_neighbors(&self) -> usize {
+        return self.neighbors.num_neighbors;
+    }
+}
+
+impl Estimator for KNeighborsRegressor {
+    fn params(&self) -> Vec<(&'static str, ParamValue)> {
+        return self.neighbors.params();
+    }
+
+    fn set_param(&mut self, name: &str, value: ParamValue) -> Result<(), ParamError> {
+        return self.neighbors.set_param(name, value);
+    }
+}
+
+impl Regressor for KNeighborsRegressor {
+    fn fit(&mut self, data: &Matrix, labels: &Matrix) -> Result<(), LinalgError> {
+        KNeighborsRegressor::fit(self, data, labels);
+        Ok(())
+    }
+
+    fn predict(&self, data: &Matrix) -> RowVector {
+        return KNeighborsRegressor::predict(self, data);
+    }
+}
+
+impl Estimator for KNeighborsClassifier {
+    fn params(&self) -> Vec<(&'static str, ParamValue)> {
+        return self.neighbors.params();
+    }
+
+    fn set_param(&mut self, name: &str, value: ParamValue) -> Result<(), ParamError> {
+        return self.neighbors.set_param(name, value);
+    }
+}
+
+impl Classifier for KNeighborsClassifier {
+    fn fit(&mut self, data: &Matrix, labels: &Matrix) -> Result<(), LinalgError> {
+        KNeighborsClassifier::fit(self, data, labels);
+        Ok(())
+    }
+
+    fn predict(&self, data: &Matrix) -> RowVector {
+        return KNeighborsClassifier::predict(self, data);
+    }
+
+    fn classes(&self) -> &Vec<f32> {
+        return KNeighborsClassifier::classes(self);
+    }
+}
+
+impl Estimator for Neighbors {
+    fn params(&self) -> Vec<(&'static str, ParamValue)> {
+        return vec![
+            ("num_neighbors", ParamValue::Int(self.num_neighbors)),
+            ("leaf_size", ParamValue::Int(self.leaf_size)),
+        ];
+    }
+
+    fn set_param(&mut self, name: &str, value: ParamValue) -> Result<(), ParamError> {
+        match name {
+            "num_neighbors" => {
+                let num_neighbors = value.as_int(name)?;
+                if num_neighbors == 0 {
+                    panic!("number of neighbors must be positive");
+                }
+                self.num_neighbors = num_neighbors;
+            }
+            "leaf_size" => self.leaf_size = value.as_int(name)?.max(1),
+            _ => return Err(ParamError::Unknown(name.to_string())),
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dataframe::csv::df_from_csv;
+    use crate::inference::inference::{accuracy, rmse};
+    use crate::sampling::random::Rng;
+
+    #[test]
+    fn test_regressor_weighting() {
+        let data = Matrix::to_matrix(&vec![vec![0.0], vec![1.0], vec![3.0], vec![10.0]]);
+        let labels = Matrix::to_matrix(&vec![vec![0.0], vec![10.0], vec![30.0], vec![100.0]]);
+        let queries = Matrix::to_matrix(&vec![vec![1.0], vec![2.0]]);
+        let mut model = KNeighborsRegressor::new(2);
+        model.fit(&data, &labels);
+        assert!(model.predict(&queries).vector() == &vec![5.0, 20.0]);
+        model.set_weights(Weights::Distance);
+        model.fit(&data, &labels);
+        let predictions = model.predict(&queries);
+        // an exact match takes all the weight, otherwise 1/d weights
+        assert!(predictions.get(0) == 10.0);
+        assert!((predictions.get(1) - 20.0).abs() < 1e-5);
+        let neighbors = model.kneighbors(&queries, 3);
+        assert!(neighbors[1] == vec![(1, 1.0), (2, 1.0), (0, 2.0)]);
+    }
+
+    #[test]
+    fn test_classifier_on_blobs() {
+        let mut rng = Rng::new(4);
+        let mut data = Vec::new();
+        let mut labels = Vec::new();
+        for (class, center) in [(3.0, [0.0, 0.0]), (5.0, [4.0, 4.0]), (7.0, [0.0, 6.0])] {
+            for _ in 0..100 {
+                data.push(vec![center[0] + rng.normal(), center[1] + rng.normal()]);
+                labels.push(vec![class]);
+            }
+        }
+        let data = Matrix::to_matrix(&data);
+        let labels = Matrix::to_matrix(&labels);
+        let mut model = KNeighborsClassifier::new(15);
+        model.set_weights(Weights::Distance);
+        model.fit(&data, &labels);
+        assert!(model.classes() == &vec![3.0, 5.0, 7.0]);
+        let probabilities = model.predict_proba(&data);
+        assert!(probabilities.shape() == (300, 3));
+        assert!(
+            probabilities
+                .matrix()
+                .iter()
+                .all(|row| (row.vector().iter().sum::<f32>() - 1.0).abs() < 1e-5)
+        );
+        assert!(accuracy(model.predict(&data).vector(), &labels_from_matrix(&labels)) > 0.95);
+    }
+
+    #[test]
+    fn test_housing_location_neighbors() {
+        let df = df_from_csv("housing.csv", None);
+        let rows = df.len();
+        let mut order = Rng::new(5).permutation(rows);
+        let test = df.get_rows_as_df(&order.split_off(rows - 500));
+        let train = df.get_rows_as_df(&order);
+        let features = vec!["latitude".to_string(), "longitude".to_string()];
+        let test_labels = labels_from_dataframe(&test, "median_house_value");
+        let mean = test_labels.iter().sum::<f32>() / test_labels.len() as f32;
+        let baseline = rmse(&vec![mean; test_labels.len()], &test_labels);
+
+        let mut model = KNeighborsRegressor::new(10);
+        model.set_distance(Distance::Haversine);
+        model.set_weights(Weights::Distance);
+        model.fit_df(&train, &features, "median_house_value");
+        let predictions = model.predict_df(&test);
+        assert!(rmse(predictions.vector(), &test_labels) < 0.75 * baseline);
+
+        model.set_algorithm(NeighborsAlgorithm::BruteForce);
+        model.fit_df(&train, &features, "median_house_value");
+        assert!(model.predict_df(&test).vector() == predictions.vector());
+    }
+}
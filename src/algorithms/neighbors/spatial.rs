@@ -0,0 +1,442 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+use crate::linear_algebra::Matrix;
+
+const EARTH_RADIUS_KM: f64 = 6371.0088;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Distance {
+    Euclidean,
+    Manhattan,
+    /// Minkowski distance of order `p >= 1`.
+    Minkowski(f32),
+    /// Great circle distance in kilometres between `(latitude, longitude)`
+    /// pairs given in degrees.
+    Haversine,
+}
+
+impl Distance {
+    pub fn distance(&self, a: &[f32], b: &[f32]) -> f32 {
+        let a: Vec<f64> = a.iter().map(|value| *value as f64).collect();
+        let b: Vec<f64> = b.iter().map(|value| *value as f64).collect();
+        return self.between(&a, &b) as f32;
+    }
+
+    pub(crate) fn validate(&self, dimensions: usize) {
+        match self {
+            Distance::Minkowski(p) if p.is_nan() || *p < 1.0 => {
+                panic!("minkowski order must be at least 1")
+            }
+            Distance::Haversine if dimensions != 2 => {
+                panic!("haversine distance needs exactly latitude and longitude")
+            }
+            _ => {}
+        }
+    }
+
+    pub(crate) fn between(&self, a: &[f64], b: &[f64]) -> f64 {
+        match self {
+            Distance::Haversine => {
+                let (lat_a, lat_b) = (a[0].to_radians(), b[0].to_radians());
+                let half_lat = (lat_b - lat_a) / 2.0;
+                let half_lon = (b[1] - a[1]).to_radians() / 2.0;
+                let h = half_lat.sin().powi(2) + lat_a.cos() * lat_b.cos() * half_lon.sin().powi(2);
+                2.0 * EARTH_RADIUS_KM * h.sqrt().min(1.0).asin()
+            }
+            _ => self.combine(a.iter().zip(b.iter()).map(|(x, y)| (x - y).abs())),
+        }
+    }
+
+    // distance from per coordinate absolute differences, minkowski family only
+    fn combine(&self, differences: impl Iterator<Item = f64>) -> f64 {
+        match self {
+            Distance::Euclidean => differences.map(|d| d * d).sum::<f64>().sqrt(),
+            Distance::Manhattan => differences.sum(),
+            Distance::Minkowski(p) => {
+                let p = *p as f64;
+                differences.map(|d| d.powf(p)).sum::<f64>().powf(1.0 / p)
+            }
+            Distance::Haversine => unreachable!("haversine is not coordinate separable"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum NeighborsAlgorithm {
+    /// KD-tree for low dimensional coordinate distances, ball tree otherwise.
+    Auto,
+    BruteForce,
+    /// Axis aligned bounding boxes, only valid for the Minkowski family.
+    KdTree,
+    /// Bounding balls, valid for any metric distance including haversine.
+    BallTree,
+}
+
+#[derive(Clone, Debug)]
+enum Bound {
+    Box { lower: Vec<f64>, upper: Vec<f64> },
+    Ball { center: Vec<f64>, radius: f64 },
+}
+
+#[derive(Clone, Debug)]
+struct IndexNode {
+    // range into `order`
+    start: usize,
+    end: usize,
+    bound: Bound,
+    children: Option<(usize, usize)>,
+}
+
+/// Nearest neighbour index over a fixed set of points. Trees recursively
+/// split the points at the median of their widest coordinate until at most
+/// `leaf_size` remain, then queries skip every node whose bound is farther
+/// than the current candidates. Results match a brute force scan exactly,
+/// ties broken by the lower point index.
+#[derive(Clone, Debug)]
+pub struct NeighborIndex {
+    points: Vec<Vec<f64>>,
+    distance: Distance,
+    // point indices, each node owns a contiguous range
+    order: Vec<usize>,
+    nodes: Vec<IndexNode>,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Candidate {
+    distance: f64,
+    index: usize,
+}
+
+impl PartialEq for Candidate {
+    fn eq(&self, other: &Self) -> bool {
+        return self.cmp(other) == Ordering::Equal;
+    }
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        return Some(self.cmp(other));
+    }
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        return self
+            .distance
+            .total_cmp(&other.distance)
+            .then(self.index.cmp(&other.index));
+    }
+}
+
+impl NeighborIndex {
+    pub fn new(
+        points: &Matrix,
+        distance: Distance,
+        algorithm: NeighborsAlgorithm,
+        leaf_size: usize,
+    ) -> Self {
+        let (rows, dimensions) = points.shape();
+        distance.validate(dimensions);
+        let points = points.to_f64();
+        if points.iter().flatten().any(|value| !value.is_finite()) {
+            panic!("neighbor index points must be finite");
+        }
+        let algorithm = match algorithm {
+            NeighborsAlgorithm::Auto if distance == Distance::Haversine || dimensions > 16 => {
+                NeighborsAlgorithm::BallTree
+            }
+            NeighborsAlgorithm::Auto => NeighborsAlgorithm::KdTree,
+            NeighborsAlgorithm::KdTree if distance == Distance::Haversine => {
+                panic!("kd tree requires a minkowski distance")
+            }
+            algorithm => algorithm,
+        };
+        let mut index = Self {
+            points,
+            distance,
+            order: (0..rows).collect(),
+            nodes: Vec::new(),
+        };
+        if algorithm != NeighborsAlgorithm::BruteForce && rows > 0 {
+            index.build(
+                0,
+                rows,
+                leaf_size.max(1),
+                algorithm == NeighborsAlgorithm::KdTree,
+            );
+        }
+        return index;
+    }
+
+    fn build(&mut self, start: usize, end: usize, leaf_size: usize, boxes: bool) -> usize {
+        let dimensions = self.points[0].len();
+        let mut lower = vec![f64::INFINITY; dimensions];
+        let mut upper = vec![f64::NEG_INFINITY; dimensions];
+        for index in self.order[start..end].iter() {
+            for (j, value) in self.points[*index].iter().enumerate() {
+                lower[j] = lower[j].min(*value);
+                upper[j] = upper[j].max(*value);
+            }
+        }
+        let widest = (0..dimensions)
+            .max_by(|a, b| (upper[*a] - lower[*a]).total_cmp(&(upper[*b] - lower[*b])))
+            .unwrap_or(0);
+        let spread = if dimensions > 0 {
+            upper[widest] - lower[widest]
+        } else {
+            0.0
+        };
+        let bound = if boxes {
+            Bound::Box { lower, upper }
+        } else {
+            let count = (end - start) as f64;
+            let center: Vec<f64> = (0..dimensions)
+                .map(|j| {
+                    self.order[start..end]
+                        .iter()
+                        .map(|index| self.points[*index][j])
+                        .sum::<f64>()
+                        / count
+                })
+                .collect();
+            let radius = self.order[start..end]
+                .iter()
+                .map(|index| self.distance.between(&center, &self.points[*index]))
+                .fold(0.0, f64::max);
+            Bound::Ball { center, radius }
+        };
+        let node = self.nodes.len();
+        self.nodes.push(IndexNode {
+            start,
+            end,
+            bound,
+            children: None,
+        });
+        if end - start > leaf_size && spread > 0.0 {
+            let middle = start + (end - start) / 2;
+            let points = &self.points;
+            self.order[start..end].select_nth_unstable_by(middle - start, |a, b| {
+                points[*a][widest].total_cmp(&points[*b][widest])
+            });
+            let left = self.build(start, middle, leaf_size, boxes);
+            let right = self.build(middle, end, leaf_size, boxes);
+            self.nodes[node].children = Some((left, right));
+        }
+        return node;
+    }
+
+    pub fn len(&self) -> usize {
+        return self.points.len();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.points.is_empty();
+    }
+
+    pub fn distance(&self) -> &Distance {
+        return &self.distance;
+    }
+
+    // no point inside the node is closer than this
+    fn lower_bound(&self, node: &IndexNode, query: &[f64]) -> f64 {
+        match &node.bound {
+            Bound::Box { lower, upper } => self.distance.combine(
+                query
+                    .iter()
+                    .zip(lower.iter().zip(upper.iter()))
+                    .map(|(q, (lo, hi))| (lo - q).max(q - hi).max(0.0)),
+            ),
+            // slack keeps rounding in the triangle inequality from pruning a tie
+            Bound::Ball { center, radius } => {
+                ((self.distance.between(query, center) - radius) * (1.0 - 1e-9)).max(0.0)
+            }
+        }
+    }
+
+    fn check_query(&self, query: &[f32]) -> Vec<f64> {
+        if !self.points.is_empty() && query.len() != self.points[0].len() {
+            panic!(
+                "query has {} coordinates but the index has {}",
+                query.len(),
+                self.points[0].len()
+            );
+        }
+        return query.iter().map(|value| *value as f64).collect();
+    }
+
+    /// The `k` nearest points as `(index, distance)`, closest first.
+    pub fn query(&self, query: &[f32], k: usize) -> Vec<(usize, f32)> {
+        let query = self.check_query(query);
+        let mut heap: BinaryHeap<Candidate> = BinaryHeap::with_capacity(k + 1);
+        if k > 0 {
+            if self.nodes.is_empty() {
+                for index in 0..self.points.len() {
+                    self.offer(&mut heap, &query, index, k);
+                }
+            } else {
+                self.search(0, &query, k, &mut heap);
+            }
+        }
+        return heap
+            .into_sorted_vec()
+            .iter()
+            .map(|candidate| (candidate.index, candidate.distance as f32))
+            .collect();
+    }
+
+    fn offer(&self, heap: &mut BinaryHeap<Candidate>, query: &[f64], index: usize, k: usize) {
+        let candidate = Candidate {
+            distance: self.distance.between(query, &self.points[index]),
+            index,
+        };
+        if heap.len() < k {
+            heap.push(candidate);
+        } else if heap.peek().is_some_and(|worst| candidate < *worst) {
+            heap.pop();
+            heap.push(candidate);
+        }
+    }
+
+    fn search(&self, node: usize, query: &[f64], k: usize, heap: &mut BinaryHeap<Candidate>) {
+        let current = &self.nodes[node];
+        match current.children {
+            None => {
+                for index in self.order[current.start..current.end].iter() {
+                    self.offer(heap, query, *index, k);
+                }
+            }
+            Some((left, right)) => {
+                let left_bound = self.lower_bound(&self.nodes[left], query);
+                let right_bound = self.lower_bound(&self.nodes[right], query);
+                let visits = if left_bound <= right_bound {
+                    [(left, left_bound), (right, right_bound)]
+                } else {
+                    [(right, right_bound), (left, left_bound)]
+                };
+                for (child, bound) in visits {
+                    let full = heap.len() == k;
+                    if !full || heap.peek().is_some_and(|worst| bound <= worst.distance) {
+                        self.search(child, query, k, heap);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Every point within `radius` (inclusive) as `(index, distance)`, closest
+    /// first.
+    pub fn query_radius(&self, query: &[f32], radius: f32) -> Vec<(usize, f32)> {
+        let query = self.check_query(query);
+        let radius = radius as f64;
+        let mut found = Vec::new();
+        if self.nodes.is_empty() {
+            for index in 0..self.points.len() {
+                let distance = self.distance.between(&query, &self.points[index]);
+                if distance <= radius {
+                    found.push(Candidate { distance, index });
+                }
+            }
+        } else {
+            let mut stack = vec![0];
+            while let Some(node) = stack.pop() {
+                let current = &self.nodes[node];
+                if self.lower_bound(current, &query) > radius {
+                    continue;
+                }
+                match current.children {
+                    Some((left, right)) => stack.extend([right, left]),
+                    None => {
+                        for index in self.order[current.start..current.end].iter() {
+                            let distance = self.distance.between(&query, &self.points[*index]);
+                            if distance <= radius {
+                                found.push(Candidate {
+                                    distance,
+                                    index: *index,
+                                });
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        found.sort();
+        return found
+            .iter()
+            .map(|candidate| (candidate.index, candidate.distance as f32))
+            .collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sampling::random::Rng;
+
+    fn random_points(rows: usize, columns: usize, seed: u64) -> Matrix {
+        let mut rng = Rng::new(seed);
+        // a coarse grid adds plenty of exact ties
+        let data: Vec<Vec<f32>> = (0..rows)
+            .map(|_| {
+                (0..columns)
+                    .map(|_| (rng.next_f32() * 20.0).round() / 2.0)
+                    .collect()
+            })
+            .collect();
+        Matrix::to_matrix(&data)
+    }
+
+    #[test]
+    fn test_trees_match_brute_force() {
+        let points = random_points(500, 3, 1);
+        let queries = random_points(40, 3, 2);
+        for distance in [
+            Distance::Euclidean,
+            Distance::Manhattan,
+            Distance::Minkowski(3.0),
+        ] {
+            let brute =
+                NeighborIndex::new(&points, distance.clone(), NeighborsAlgorithm::BruteForce, 1);
+            for algorithm in [NeighborsAlgorithm::KdTree, NeighborsAlgorithm::BallTree] {
+                let tree = NeighborIndex::new(&points, distance.clone(), algorithm, 8);
+                for query in queries.matrix().iter() {
+                    assert!(tree.query(query.vector(), 7) == brute.query(query.vector(), 7));
+                    assert!(
+                        tree.query_radius(query.vector(), 2.0)
+                            == brute.query_radius(query.vector(), 2.0)
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_haversine_ball_tree() {
+        // london to paris is about 344 km
+        let distance = Distance::Haversine.distance(&[51.5074, -0.1278], &[48.8566, 2.3522]);
+        assert!((distance - 343.5).abs() < 1.0);
+        let mut rng = Rng::new(3);
+        let cities: Vec<Vec<f32>> = (0..400)
+            .map(|_| {
+                vec![
+                    rng.next_f32() * 160.0 - 80.0,
+                    rng.next_f32() * 360.0 - 180.0,
+                ]
+            })
+            .collect();
+        let cities = Matrix::to_matrix(&cities);
+        let brute = NeighborIndex::new(
+            &cities,
+            Distance::Haversine,
+            NeighborsAlgorithm::BruteForce,
+            1,
+        );
+        let tree = NeighborIndex::new(&cities, Distance::Haversine, NeighborsAlgorithm::Auto, 10);
+        for query in [[0.0, 179.9], [-45.0, -179.9], [89.0, 10.0]] {
+            assert!(tree.query(&query, 5) == brute.query(&query, 5));
+            assert!(tree.query_radius(&query, 1500.0) == brute.query_radius(&query, 1500.0));
+        }
+    }
+}
@@ -0,0 +1,447 @@
+use super::network::{Activation, Loss, Network, Optimizer};
+use crate::algorithms::estimators::{
+    Classifier, Estimator, ParamError, ParamValue, Regressor, encode_classes, labels_from_matrix,
+    most_likely_classes,
+};
+use crate::linear_algebra::{LinalgError, Matrix, RowVector};
+
+/// Multilayer perceptron for regression: fully connected hidden layers, a
+/// linear output unit and the squared error loss, trained on shuffled
+/// mini-batches. Features should be on comparable scales, e.g. standardized.
+pub struct MlpRegressor {
+    network: Network,
+}
+
+/// Multilayer perceptron classifier with a softmax output layer trained on
+/// the cross-entropy loss.
+pub struct MlpClassifier {
+    network: Network,
+    classes: Vec<f32>,
+}
+
+impl MlpRegressor {
+    /// `hidden_layers` lists the units of each hidden layer, none gives a
+    /// linear model.
+    pub fn new(hidden_layers: &Vec<usize>) -> Self {
+        return Self {
+            network: Network::new(hidden_layers),
+        };
+    }
+
+    /// Activation of the hidden layers, ReLU by default.
+    pub fn set_activation(&mut self, activation: Activation) {
+        self.network.activation = activation;
+    }
+
+    /// Adam with a learning rate of 1e-3 by default.
+    pub fn set_optimizer(&mut self, optimizer: Optimizer) {
+        self.network.optimizer = optimizer;
+    }
+
+    /// Adds `l2_regularization / 2 * ||W||^2` over the weights (not the
+    /// biases) to the loss.
+    pub fn set_l2_regularization(&mut self, l2_regularization: f32) {
+        self.network.l2_regularization = l2_regularization;
+    }
+
+    pub fn set_batch_size(&mut self, batch_size: usize) {
+        self.network.batch_size = batch_size.max(1);
+    }
+
+    pub fn set_max_epochs(&mut self, max_epochs: usize) {
+        self.network.max_epochs = max_epochs;
+    }
+
+    /// Training stops once the loss has not dropped by more than `tolerance`
+    /// for `patience` epochs (10 unless early stopping sets it).
+    pub fn set_tolerance(&mut self, tolerance: f32) {
+        self.network.tolerance = tolerance;
+    }
+
+    /// Holds out `validation_fraction` of the rows, watches their loss
+    /// instead of the training loss and keeps the weights of the best epoch.
+    pub fn set_early_stopping(&mut self, validation_fraction: f32, patience: usize) {
+        if validation_fraction <= 0.0 || validation_fraction >= 1.0 {
+            panic!("validation fraction must be between 0 and 1");
+        }
+        self.network.validation_fraction = Some(validation_fraction);
+        self.network.patience = patience.max(1);
+    }
+
+    /// Seeds the weight initialization, the shuffling and the validation split.
+    pub fn set_seed(&mut self, seed: u64) {
+        self.network.seed = seed;
+    }
+
+    /// `labels` is a single column.
+    pub fn fit(&mut self, data: &Matrix, labels: &Matrix) {
+        let targets = labels_from_matrix(labels)
+            .iter()
+            .map(|label| vec![*label])
+            .collect();
+        self.network
+            .fit(data, &Matrix::to_matrix(&targets), &Loss::SquaredError);
+    }
+
+    pub fn predict(&self, data: &Matrix) -> RowVector {
+        let outputs = self.network.predict(data, &Loss::SquaredError);
+        return RowVector::new(&labels_from_matrix(&outputs));
+    }
+
+    /// Weight matrices of the layers, inputs by outputs.
+    pub fn weights(&self) -> Vec<&Matrix> {
+        return self.network.weights();
+    }
+
+    pub fn biases(&self) -> Vec<&Vec<f32>> {
+        return self.network.biases();
+    }
+
+    /// Mean training loss of every epoch, including the penalty.
+    pub fn train_loss_history(&self) -> &Vec<f32> {
+        return self.network.train_loss_history();
+    }
+
+    /// Validation loss of every epoch, empty without early stopping.
+    pub fn validation_loss_history(&self) -> &Vec<f32> {
+        return self.network.validation_loss_history();
+    }
+
+    pub fn num_epochs(&self) -> usize {
+        return self.network.train_loss_history().len();
+    }
+
+    /// False when the last `fit` ran out of epochs before the loss settled.
+    pub fn converged(&self) -> bool {
+        return self.network.converged();
+    }
+}
+
+impl MlpClassifier {
+    /// `hidden_layers` lists the units of each hidden layer, none gives
+    /// multinomial logistic regression.
+    pub fn new(hidden_layers: &Vec<usize>) -> Self {
+        return Self {
+            network: Network::new(hidden_layers),
+            classes: Vec::new(),
+        };
+    }
+
+    /// Activation of the hidden layers, ReLU by default.
+    pub fn set_activation(&mut self, activation: Activation) {
+        self.network.activation = activation;
+    }
+
+    /// Adam with a learning rate of 1e-3 by default.
+    pub fn set_optimizer(&mut self, optimizer: Optimizer) {
+        self.network.optimizer = optimizer;
+    }
+
+    /// Adds `l2_regularization / 2 * ||W||^2` over the weights (not the
+    /// biases) to the loss.
+    pub fn set_l2_regularization(&mut self, l2_regularization: f32) {
+        self.network.l2_regularization = l2_regularization;
+    }
+
+    pub fn set_batch_size(&mut self, batch_size: usize) {
+        self.network.batch_size = batch_size.max(1);
+    }
+
+    pub fn set_max_epochs(&mut self, max_epochs: usize) {
+        self.network.max_epochs = max_epochs;
+    }
+
+    /// Training stops once the loss has not dropped by more than `tolerance`
+    /// for `patience` epochs (10 unless early stopping sets it).
+    pub fn set_tolerance(&mut self, tolerance: f32) {
+        self.network.tolerance = tolerance;
+    }
+
+    /// Holds out `validation_fraction` of the rows, watches their loss
+    /// instead of the training loss and keeps the weights of the best epoch.
+    pub fn set_early_stopping(&mut self, validation_fraction: f32, patience: usize) {
+        if validation_fraction <= 0.0 || validation_fraction >= 1.0 {
+            panic!("validation fraction must be between 0 and 1");
+        }
+        self.network.validation_fraction = Some(validation_fraction);
+        self.network.patience = patience.max(1);
+    }
+
+    /// Seeds the weight initialization, the shuffling and the validation split.
+    pub fn set_seed(&mut self, seed: u64) {
+        self.network.seed = seed;
+    }
+
+    /// `labels` is a single column of class values.
+    pub fn fit(&mut self, data: &Matrix, labels: &Matrix) {
+        let (classes, targets) = encode_classes(&labels_from_matrix(labels));
+        let one_hot: Vec<Vec<f32>> = targets
+            .iter()
+            .map(|target| {
+                (0..classes.len())
+                    .map(|k| (k == *target) as usize as f32)
+                    .collect()
+            })
+            .collect();
+        self.network
+            .fit(data, &Matrix::to_matrix(&one_hot), &Loss::CrossEntropy);
+        self.classes = classes;
+    }
+
+    /// Class probabilities with columns in the order of `classes()`.
+    pub fn predict_proba(&self, data: &Matrix) -> Matrix {
+        return self.network.predict(data, &Loss::CrossEntropy);
+    }
+
+    pub fn predict(&self, data: &Matrix) -> RowVector {
+        return most_likely_classes(&self.classes, &self.predict_proba(data));
+    }
+
+    pub fn classes(&self) -> &Vec<f32> {
+        return &self.classes;
+    }
+
+    /// Weight matrices of the layers, inputs by outputs.
+    pub fn weights(&self) -> Vec<&Matrix> {
+        return self.network.weights();
+    }
+
+    pub fn biases(&self) -> Vec<&Vec<f32>> {
+        return self.network.biases();
+    }
+
+    /// Mean training loss of every epoch, including the penalty.
+    pub fn train_loss_history(&self) -> &Vec<f32> {
+        return self.network.train_loss_history();
+    }
+
+    /// Validation loss of every epoch, empty without early stopping.
+    pub fn validation_loss_history(&self) -> &Vec<f32> {
+        return self.network.validation_loss_history();
+    }
+
+    pub fn num_epochs(&self) -> usize {
+        return self.network.train_loss_history().len();
+    }
+
+    /// False when the last `fit` ran out of epochs before the loss settled.
+    pub fn converged(&self) -> bool {
+        return self.network.converged();
+    }
+}
+
+impl Estimator for MlpRegressor {
+    fn params(&self) -> Vec<(&'static str, ParamValue)> {
+        return self.network.params();
+    }
+
+    fn set_param(&mut self, name: &str, value: ParamValue) -> Result<(), ParamError> {
+        return self.network.set_param(name, value);
+    }
+}
+
+impl Regressor for MlpRegressor {
+    fn fit(&mut self, data: &Matrix, labels: &Matrix) -> Result<(), LinalgError> {
+        MlpRegressor::fit(self, data, labels);
+        Ok(())
+    }
+
+    fn predict(&self, data: &Matrix) -> RowVector {
+        return MlpRegressor::predict(self, data);
+    }
+}
+
+impl Estimator for MlpClassifier {
+    fn params(&self) -> Vec<(&'static str, ParamValue)> {
+        return self.network.params();
+    }
+
+    fn set_param(&mut self, name: &str, value: ParamValue) -> Result<(), ParamError> {
+        return self.network.set_param(name, value);
+    }
+}
+
+impl Classifier for MlpClassifier {
+    fn fit(&mut self, data: &Matrix, labels: &Matrix) -> Result<(), LinalgError> {
+        MlpClassifier::fit(self, data, labels);
+        Ok(())
+    }
+
+    fn predict(&self, data: &Matrix) -> RowVector {
+        return MlpClassifier::predict(self, data);
+    }
+
+    fn classes(&self) -> &Vec<f32> {
+        return MlpClassifier::classes(self);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::estimators::labels_from_dataframe;
+    use crate::algorithms::linear_regression::linear_regression::LinearRegression;
+    use crate::dataframe::csv::df_from_csv;
+    use crate::inference::inference::{accuracy, rmse};
+    use crate::pipeline::{
+        encoders::one_hot_encoder::OneHotEncoder,
+        imputers::imputer::{Imputer, ImputerStrategy},
+        pipeline::{CategoricalPipeline, ColumnTransformer, NumericalPipeline},
+        scalars::standard_scalar::StandardScalar,
+        transformers::Transformer,
+    };
+    use crate::sampling::random::Rng;
+
+    // y = sin(2 x0) + x1^2 with x2 irrelevant
+    fn nonlinear_data(rows: usize, seed: u64) -> (Matrix, Matrix) {
+        let mut rng = Rng::new(seed);
+        let data: Vec<Vec<f32>> = (0..rows)
+            .map(|_| {
+                vec![
+                    2.0 * rng.next_f32() - 1.0,
+                    2.0 * rng.next_f32() - 1.0,
+                    rng.normal(),
+                ]
+            })
+            .collect();
+        let labels: Vec<Vec<f32>> = data
+            .iter()
+            .map(|row| vec![(2.0 * row[0]).sin() + row[1] * row[1]])
+            .collect();
+        (Matrix::to_matrix(&data), Matrix::to_matrix(&labels))
+    }
+
+    #[test]
+    fn test_mlp_regressor_fits_nonlinear_target() {
+        let (data, labels) = nonlinear_data(500, 1);
+        let (test_data, test_labels) = nonlinear_data(300, 2);
+        let test_labels = labels_from_matrix(&test_labels);
+        for (activation, optimizer) in [
+            (Activation::Relu, Optimizer::adam(1e-2)),
+            (Activation::Tanh, Optimizer::sgd(1e-2)),
+        ] {
+            let mut model = MlpRegressor::new(&vec![16, 16]);
+            model.set_activation(activation);
+            model.set_optimizer(optimizer);
+            model.set_max_epochs(200);
+            model.fit(&data, &labels);
+            let history = model.train_loss_history();
+            assert!(history[history.len() - 1] < 0.1 * history[0]);
+            assert!(rmse(model.predict(&test_data).vector(), &test_labels) < 0.15);
+        }
+
+        // without hidden layers the network is a linear model
+        let mut linear = MlpRegressor::new(&vec![]);
+        linear.fit(&data, &labels);
+        assert!(linear.weights().len() == 1 && linear.weights()[0].shape() == (3, 1));
+        assert!(rmse(linear.predict(&test_data).vector(), &test_labels) > 0.3);
+    }
+
+    #[test]
+    fn test_mlp_classifier_separates_rings() {
+        let mut rng = Rng::new(5);
+        let mut data = Vec::new();
+        let mut labels = Vec::new();
+        for i in 0..600 {
+            // class by ring, each ring a noisy circle around the origin
+            let class = i % 3;
+            let angle = 2.0 * std::f32::consts::PI * rng.next_f32();
+            let radius = 1.0 + class as f32 + 0.2 * rng.normal();
+            data.push(vec![radius * angle.cos(), radius * angle.sin()]);
+            labels.push(vec![10.0 * class as f32]);
+        }
+        let (data, labels) = (Matrix::to_matrix(&data), Matrix::to_matrix(&labels));
+        let mut model = MlpClassifier::new(&vec![16, 16]);
+        model.set_activation(Activation::Tanh);
+        model.set_optimizer(Optimizer::adam(1e-2));
+        model.fit(&data, &labels);
+        assert!(model.classes() == &vec![0.0, 10.0, 20.0]);
+        let probabilities = model.predict_proba(&data);
+        assert!(probabilities.shape() == (600, 3));
+        assert!(
+            probabilities
+                .matrix()
+                .iter()
+                .all(|row| (row.vector().iter().sum::<f32>() - 1.0).abs() < 1e-5)
+        );
+        assert!(accuracy(model.predict(&data).vector(), &labels_from_matrix(&labels)) > 0.9);
+
+        // a linear boundary cannot split rings
+        let mut linear = MlpClassifier::new(&vec![]);
+        linear.fit(&data, &labels);
+        assert!(accuracy(linear.predict(&data).vector(), &labels_from_matrix(&labels)) < 0.6);
+    }
+
+    #[test]
+    fn test_early_stopping_and_weight_decay() {
+        let (data, labels) = nonlinear_data(400, 3);
+        let fit = |seed: u64, l2_regularization: f32| {
+            let mut model = MlpRegressor::new(&vec![64]);
+            model.set_optimizer(Optimizer::adam(1e-2));
+            model.set_l2_regularization(l2_regularization);
+            model.set_early_stopping(0.2, 5);
+            model.set_max_epochs(1000);
+            model.set_seed(seed);
+            model.fit(&data, &labels);
+            model
+        };
+        let model = fit(7, 0.0);
+        let validation = model.validation_loss_history();
+        assert!(model.converged() && validation.len() < 1000);
+        assert!(validation.len() == model.num_epochs());
+        // the kept weights are those of the best epoch
+        let best = validation
+            .iter()
+            .fold(f32::INFINITY, |acc, loss| acc.min(*loss));
+        let validation_rows: Vec<usize> = Rng::new(7).permutation(400).split_off(320);
+        let held_out = Matrix::new(
+            &validation_rows
+                .iter()
+                .map(|row| data.get(*row).clone())
+                .collect(),
+        );
+        let held_out_labels: Vec<f32> = validation_rows
+            .iter()
+            .map(|row| labels.get(*row).get(0))
+            .collect();
+        let error = rmse(model.predict(&held_out).vector(), &held_out_labels);
+        assert!((0.5 * error * error - best).abs() < 1e-4);
+        assert!(fit(7, 0.0).predict(&data).vector() == model.predict(&data).vector());
+        assert!(fit(8, 0.0).predict(&data).vector() != model.predict(&data).vector());
+
+        let norm = |model: &MlpRegressor| {
+            model
+                .weights()
+                .iter()
+                .flat_map(|weights| weights.matrix().iter().map(|row| row.norm().powi(2)))
+                .sum::<f32>()
+        };
+        assert!(norm(&fit(7, 0.1)) < 0.5 * norm(&model));
+    }
+
+    #[test]
+    fn test_mlp_regressor_on_transformed_housing() {
+        let mut df = df_from_csv("housing.csv", Some(2000));
+        let values = labels_from_dataframe(&df, "median_house_value");
+        let labels: Vec<Vec<f32>> = values.iter().map(|value| vec![value / 1e5]).collect();
+        df.remove_column("median_house_value");
+        let encoder: Box<dyn Transformer> = Box::new(OneHotEncoder::new(false));
+        let imputer: Box<dyn Transformer> = Box::new(Imputer::new(&ImputerStrategy::Median));
+        let scalar: Box<dyn Transformer> = Box::new(StandardScalar::new());
+        let transformer = ColumnTransformer::new(
+            NumericalPipeline::new(vec![imputer, scalar]),
+            CategoricalPipeline::new(vec![encoder]),
+        );
+        let data = transformer.transform(&df);
+        let labels = Matrix::to_matrix(&labels);
+        let targets = labels_from_matrix(&labels);
+
+        let mut linear = LinearRegression::new(0.0);
+        linear.fit(&data, &labels).unwrap();
+        let linear_rmse = rmse(linear.predict(&data).vector(), &targets);
+        let mut model = MlpRegressor::new(&vec![32]);
+        model.set_early_stopping(0.1, 5);
+        model.fit(&data, &labels);
+        assert!(rmse(model.predict(&data).vector(), &targets) < linear_rmse);
+    }
+}
@@ -0,0 +1,4 @@
+pub mod mlp;
+pub mod network;
+pub use mlp::{MlpClassifier, MlpRegressor};
+pub use network::{Activation, Optimizer};
@@ -0,0 +1,582 @@
+use crate::algorithms::estimators::{Estimator, ParamError, ParamValue};
+use crate::linear_algebra::{Matrix, RowVector, kernels};
+use crate::sampling::random::Rng;
+
+/// Nonlinearity applied after every hidden layer.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Activation {
+    Identity,
+    Relu,
+    Tanh,
+    Sigmoid,
+}
+
+impl Activation {
+    fn apply(&self, z: f32) -> f32 {
+        match self {
+            Activation::Identity => z,
+            Activation::Relu => z.max(0.0),
+            Activation::Tanh => z.tanh(),
+            Activation::Sigmoid => 1.0 / (1.0 + (-z).exp()),
+        }
+    }
+
+    // written in terms of the activated value, which is what the forward pass keeps
+    fn derivative(&self, a: f32) -> f32 {
+        match self {
+            Activation::Identity => 1.0,
+            Activation::Relu => (a > 0.0) as usize as f32,
+            Activation::Tanh => 1.0 - a * a,
+            Activation::Sigmoid => a * (1.0 - a),
+        }
+    }
+}
+
+/// Update rule for the weights, applied after every mini-batch.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Optimizer {
+    /// Stochastic gradient descent with classical momentum.
+    Sgd { learning_rate: f32, momentum: f32 },
+    /// Adam, with bias corrected estimates of the first and second moments.
+    Adam {
+        learning_rate: f32,
+        beta1: f32,
+        beta2: f32,
+        epsilon: f32,
+    },
+}
+
+// per parameter buffers, one per layer with the weight rows followed by the biases
+type Buffers = Vec<Vec<Vec<f32>>>;
+
+struct OptimizerState {
+    first: Buffers,
+    second: Buffers,
+    steps: i32,
+}
+
+impl OptimizerState {
+    fn new(layers: &[Dense]) -> Self {
+        let zeros: Buffers = layers
+            .iter()
+            .map(|layer| {
+                let (inputs, outputs) = layer.weights.shape();
+                vec![vec![0.0; outputs]; inputs + 1]
+            })
+            .collect();
+        return Self {
+            first: zeros.clone(),
+            second: zeros,
+            steps: 0,
+        };
+    }
+}
+
+impl Optimizer {
+    /// SGD with momentum 0.9.
+    pub fn sgd(learning_rate: f32) -> Self {
+        return Optimizer::Sgd {
+            learning_rate,
+            momentum: 0.9,
+        };
+    }
+
+    /// Adam with the usual betas of 0.9 and 0.999 and epsilon 1e-8.
+    pub fn adam(learning_rate: f32) -> Self {
+        return Optimizer::Adam {
+            learning_rate,
+            beta1: 0.9,
+            beta2: 0.999,
+            epsilon: 1e-8,
+        };
+    }
+
+    fn step(&self, state: &mut OptimizerState, layers: &mut [Dense], gradients: &Buffers) {
+        state.steps += 1;
+        for (l, layer) in layers.iter_mut().enumerate() {
+            let mut parameters: Vec<Vec<f32>> = layer
+                .weights
+                .matrix()
+                .iter()
+                .map(|row| row.vector().clone())
+                .chain([layer.biases.clone()])
+                .collect();
+            for (i, row) in parameters.iter_mut().enumerate() {
+                self.update(
+                    row,
+                    &gradients[l][i],
+                    &mut state.first[l][i],
+                    &mut state.second[l][i],
+                    state.steps,
+                );
+            }
+            layer.biases = parameters.pop().unwrap();
+            layer.weights = Matrix::to_matrix(&parameters);
+        }
+    }
+
+    fn update(
+        &self,
+        parameters: &mut [f32],
+        gradient: &[f32],
+        first: &mut [f32],
+        second: &mut [f32],
+        step: i32,
+    ) {
+        match *self {
+            Optimizer::Sgd {
+                learning_rate,
+                momentum,
+            } => {
+                for ((p, g), velocity) in parameters.iter_mut().zip(gradient).zip(first) {
+                    *velocity = momentum * *velocity - learning_rate * g;
+                    *p += *velocity;
+                }
+            }
+            Optimizer::Adam {
+                learning_rate,
+                beta1,
+                beta2,
+                epsilon,
+            } => {
+                let rate =
+                    learning_rate * (1.0 - beta2.powi(step)).sqrt() / (1.0 - beta1.powi(step));
+                for (((p, g), m), v) in parameters.iter_mut().zip(gradient).zip(first).zip(second) {
+                    *m = beta1 * *m + (1.0 - beta1) * g;
+                    *v = beta2 * *v + (1.0 - beta2) * g * g;
+                    *p -= rate * *m / (v.sqrt() + epsilon);
+                }
+            }
+        }
+    }
+}
+
+// the output layer and loss travel together, with either pairing the error
+// at the output before the activation is simply the output minus the target
+pub(super) enum Loss {
+    /// Linear output, half the squared error.
+    SquaredError,
+    /// Softmax output, cross-entropy against one-hot targets.
+    CrossEntropy,
+}
+
+impl Loss {
+    fn activate(&self, row: &mut [f32]) {
+        if let Loss::CrossEntropy = self {
+            let max = kernels::max(row);
+            row.iter_mut()
+                .for_each(|value| *value = (*value - max).exp());
+            let total = kernels::sum(row);
+            row.iter_mut().for_each(|value| *value /= total);
+        }
+    }
+
+    // mean over the rows
+    fn value(&self, outputs: &Matrix, targets: &Matrix) -> f32 {
+        let total: f64 = outputs
+            .matrix()
+            .iter()
+            .zip(targets.matrix())
+            .map(|(output, target)| match self {
+                Loss::SquaredError => {
+                    0.5 * kernels::squared_norm(&kernels::subtract(
+                        output.vector(),
+                        target.vector(),
+                    )) as f64
+                }
+                Loss::CrossEntropy => output
+                    .vector()
+                    .iter()
+                    .zip(target.vector())
+                    .map(|(p, t)| -(*t as f64) * (p.max(1e-12) as f64).ln())
+                    .sum::<f64>(),
+            })
+            .sum();
+        return (total / outputs.len().max(1) as f64) as f32;
+    }
+}
+
+#[derive(Clone)]
+struct Dense {
+    // inputs x outputs
+    weights: Matrix,
+    biases: Vec<f32>,
+}
+
+impl Dense {
+    // glorot uniform weights and zero biases
+    fn new(inputs: usize, outputs: usize, rng: &mut Rng) -> Self {
+        let limit = (6.0 / (inputs + outputs) as f32).sqrt();
+        let weights: Vec<Vec<f32>> = (0..inputs)
+            .map(|_| {
+                (0..outputs)
+                    .map(|_| limit * (2.0 * rng.next_f32() - 1.0))
+                    .collect()
+            })
+            .collect();
+        return Self {
+            weights: Matrix::to_matrix(&weights),
+            biases: vec![0.0; outputs],
+        };
+    }
+
+    fn forward(&self, input: &Matrix, activate: impl Fn(&mut [f32])) -> Matrix {
+        let linear = input.multiply(&self.weights);
+        return Matrix::new(
+            &linear
+                .matrix()
+                .iter()
+                .map(|row| {
+                    let mut values = kernels::add(row.vector(), &self.biases);
+                    activate(&mut values);
+                    RowVector::new(&values)
+                })
+                .collect(),
+        );
+    }
+}
+
+/// Shared training loop of the perceptron estimators.
+pub(super) struct Network {
+    pub(super) hidden_layers: Vec<usize>,
+    pub(super) activation: Activation,
+    pub(super) optimizer: Optimizer,
+    pub(super) l2_regularization: f32,
+    pub(super) batch_size: usize,
+    pub(super) max_epochs: usize,
+    pub(super) tolerance: f32,
+    pub(super) validation_fraction: Option<f32>,
+    pub(super) patience: usize,
+    pub(super) seed: u64,
+    layers: Vec<Dense>,
+    train_loss: Vec<f32>,
+    validation_loss: Vec<f32>,
+    converged: bool,
+}
+
+fn select_rows(matrix: &Matrix, rows: &[usize]) -> Matrix {
+    return Matrix::new(&rows.iter().map(|row| matrix.get(*row).clone()).collect());
+}
+
+impl Network {
+    pub(super) fn new(hidden_layers: &Vec<usize>) -> Self {
+        if hidden_layers.contains(&0) {
+            panic!("hidden layers need at least one unit");
+        }
+        return Self {
+            hidden_layers: hidden_layers.clone(),
+            activation: Activation::Relu,
+            optimizer: Optimizer::adam(1e-3),
+            l2_regularization: 0.0,
+            batch_size: 32,
+            max_epochs: 200,
+            tolerance: 1e-4,
+            validation_fraction: None,
+            patience: 10,
+            seed: 0,
+            layers: Vec::new(),
+            train_loss: Vec::new(),
+            validation_loss: Vec::new(),
+            converged: false,
+        };
+    }
+
+    // outputs of every layer, the last one through the loss's output activation
+    fn forward(&self, data: &Matrix, loss: &Loss) -> Vec<Matrix> {
+        let mut outputs: Vec<Matrix> = Vec::with_capacity(self.layers.len());
+        for (l, layer) in self.layers.iter().enumerate() {
+            let input = if l == 0 { data } else { &outputs[l - 1] };
+            let output = if l + 1 == self.layers.len() {
+                layer.forward(input, |row| loss.activate(row))
+            } else {
+                layer.forward(input, |row| {
+                    row.iter_mut()
+                        .for_each(|value| *value = self.activation.apply(*value))
+                })
+            };
+            outputs.push(output);
+        }
+        return outputs;
+    }
+
+    pub(super) fn predict(&self, data: &Matrix, loss: &Loss) -> Matrix {
+        let first = self
+            .layers
+            .first()
+            .expect("the network must be fitted first");
+        if data.shape().1 != first.weights.len() {
+            panic!(
+                "expected {} features, found {}",
+                first.weights.len(),
+                data.shape().1
+            );
+        }
+        return self.forward(data, loss).pop().unwrap();
+    }
+
+    // penalized mean loss over the batch and its gradient, laid out like the
+    // optimizer buffers
+    fn loss_and_gradients(&self, batch: &Matrix, targets: &Matrix, loss: &Loss) -> (f32, Buffers) {
+        let outputs = self.forward(batch, loss);
+        let output = outputs.last().unwrap();
+        let mut value = loss.value(output, targets);
+        let scale = 1.0 / batch.len() as f32;
+        let mut delta = Matrix::new(
+            &output
+                .matrix()
+                .iter()
+                .zip(targets.matrix())
+                .map(|(a, y)| {
+                    RowVector::new(&kernels::scaled(
+                        scale,
+                        &kernels::subtract(a.vector(), y.vector()),
+                    ))
+                })
+                .collect(),
+        );
+        let mut gradients: Buffers = vec![Vec::new(); self.layers.len()];
+        for l in (0..self.layers.len()).rev() {
+            let layer = &self.layers[l];
+            let input = if l == 0 { batch } else { &outputs[l - 1] };
+            let mut rows: Vec<Vec<f32>> = input
+                .transpose()
+                .multiply(&delta)
+                .matrix()
+                .iter()
+                .zip(layer.weights.matrix())
+                .map(|(gradient, weights)| {
+                    value += 0.5 * self.l2_regularization * kernels::squared_norm(weights.vector());
+                    let mut gradient = gradient.vector().clone();
+                    kernels::axpy(self.l2_regularization, weights.vector(), &mut gradient);
+                    gradient
+                })
+                .collect();
+            let mut bias_gradient = vec![0.0; layer.biases.len()];
+            delta
+                .matrix()
+                .iter()
+                .for_each(|row| kernels::axpy(1.0, row.vector(), &mut bias_gradient));
+            rows.push(bias_gradient);
+            gradients[l] = rows;
+            if l > 0 {
+                delta = Matrix::new(
+                    &delta
+                        .multiply(&layer.weights.transpose())
+                        .matrix()
+                        .iter()
+                        .zip(outputs[l - 1].matrix())
+                        .map(|(back, a)| {
+                            RowVector::new(
+                                &back
+                                    .vector()
+                                    .iter()
+                                    .zip(a.vector())
+                                    .map(|(d, a)| d * self.activation.derivative(*a))
+                                    .collect(),
+                            )
+                        })
+                        .collect(),
+                );
+            }
+        }
+        return (value, gradients);
+    }
+
+    /// `targets` has one column per output unit.
+    pub(super) fn fit(&mut self, data: &Matrix, targets: &Matrix, loss: &Loss) {
+        assert!(
+            data.len() == targets.len(),
+            "data and labels must have the same length"
+        );
+        let rows = data.len();
+        if rows == 0 {
+            panic!("the network needs at least one row");
+        }
+        let mut rng = Rng::new(self.seed);
+        let mut training = rng.permutation(rows);
+        let validation_len = match self.validation_fraction {
+            Some(fraction) if rows > 1 => {
+                ((rows as f32 * fraction).round() as usize).clamp(1, rows - 1)
+            }
+            _ => 0,
+        };
+        let validation = training.split_off(rows - validation_len);
+        let validation_data = select_rows(data, &validation);
+        let validation_targets = select_rows(targets, &validation);
+        let widths: Vec<usize> = [data.shape().1]
+            .into_iter()
+            .chain(self.hidden_layers.iter().copied())
+            .chain([targets.shape().1])
+            .collect();
+        self.layers = widths
+            .windows(2)
+            .map(|pair| Dense::new(pair[0], pair[1], &mut rng))
+            .collect();
+        self.train_loss = Vec::new();
+        self.validation_loss = Vec::new();
+        self.converged = false;
+        let mut state = OptimizerState::new(&self.layers);
+        let batch_size = self.batch_size.clamp(1, training.len());
+        let (mut best_loss, mut best_layers, mut epochs_without_improvement) =
+            (f32::INFINITY, self.layers.clone(), 0);
+        for _ in 0..self.max_epochs {
+            rng.shuffle(&mut training);
+            let mut total = 0.0;
+            for batch in training.chunks(batch_size) {
+                let (value, gradients) = self.loss_and_gradients(
+                    &select_rows(data, batch),
+                    &select_rows(targets, batch),
+                    loss,
+                );
+                total += value * batch.len() as f32;
+                self.optimizer
+                    .step(&mut state, &mut self.layers, &gradients);
+            }
+            self.train_loss.push(total / training.len() as f32);
+            let monitored = if validation.is_empty() {
+                total / training.len() as f32
+            } else {
+                let validation_loss =
+                    loss.value(&self.predict(&validation_data, loss), &validation_targets);
+                self.validation_loss.push(validation_loss);
+                validation_loss
+            };
+            if monitored.is_nan() {
+                break;
+            }
+            if monitored < best_loss - self.tolerance {
+                (best_loss, epochs_without_improvement) = (monitored, 0);
+                if !validation.is_empty() {
+                    best_layers = self.layers.clone();
+                }
+            } else {
+                epochs_without_improvement += 1;
+                if epochs_without_improvement >= self.patience {
+                    self.converged = true;
+                    break;
+                }
+            }
+        }
+        if !validation.is_empty() {
+            self.layers = best_layers;
+        }
+    }
+
+    pub(super) fn weights(&self) -> Vec<&Matrix> {
+        return self.layers.iter().map(|layer| &layer.weights).collect();
+    }
+
+    pub(super) fn biases(&self) -> Vec<&Vec<f32>> {
+        return self.layers.iter().map(|layer| &layer.biases).collect();
+    }
+
+    pub(super) fn train_loss_history(&self) -> &Vec<f32> {
+        return &self.train_loss;
+    }
+
+    pub(super) fn validation_loss_history(&self) -> &Vec<f32> {
+        return &self.validation_loss;
+    }
+
+    pub(super) fn converged(&self) -> bool {
+        return self.converged;
+    }
+}
+
+// `learning_rate` is the optimizer's, whichever optimizer is set
+impl Estimator for Network {
+    fn params(&self) -> Vec<(&'static str, ParamValue)> {
+        let (Optimizer::Sgd { learning_rate, .. } | Optimizer::Adam { learning_rate, .. }) =
+            self.optimizer;
+        return vec![
+            ("learning_rate", ParamValue::Float(learning_rate)),
+            (
+                "l2_regularization",
+                ParamValue::Float(self.l2_regularization),
+            ),
+            ("batch_size", ParamValue::Int(self.batch_size)),
+            ("max_epochs", ParamValue::Int(self.max_epochs)),
+            ("tolerance", ParamValue::Float(self.tolerance)),
+            ("seed", ParamValue::Int(self.seed as usize)),
+        ];
+    }
+
+    fn set_param(&mut self, name: &str, value: ParamValue) -> Result<(), ParamError> {
+        match name {
+            "learning_rate" => {
+                let (Optimizer::Sgd { learning_rate, .. } | Optimizer::Adam { learning_rate, .. }) =
+                    &mut self.optimizer;
+                *learning_rate = value.as_float(name)?;
+            }
+            "l2_regularization" => self.l2_regularization = value.as_float(name)?,
+            "batch_size" => self.batch_size = value.as_int(name)?.max(1),
+            "max_epochs" => self.max_epochs = value.as_int(name)?,
+            "tolerance" => self.tolerance = value.as_float(name)?,
+            "seed" => self.seed = value.as_int(name)? as u64,
+            _ => return Err(ParamError::Unknown(name.to_string())),
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backpropagation_matches_finite_differences() {
+        let mut rng = Rng::new(3);
+        let batch = Matrix::to_matrix(
+            &(0..6)
+                .map(|_| vec![rng.normal(), rng.normal(), rng.normal()])
+                .collect(),
+        );
+        let one_hot = Matrix::to_matrix(
+            &(0..6)
+                .map(|row| (0..3).map(|k| (row % 3 == k) as usize as f32).collect())
+                .collect(),
+        );
+        let values = Matrix::to_matrix(&(0..6).map(|row| vec![row as f32 / 3.0]).collect());
+        for (activation, loss, targets) in [
+            (Activation::Tanh, Loss::CrossEntropy, &one_hot),
+            (Activation::Sigmoid, Loss::SquaredError, &values),
+        ] {
+            let mut network = Network::new(&vec![4, 3]);
+            network.activation = activation;
+            network.l2_regularization = 0.1;
+            let widths = [3, 4, 3, targets.shape().1];
+            network.layers = widths
+                .windows(2)
+                .map(|pair| Dense::new(pair[0], pair[1], &mut rng))
+                .collect();
+            let (_, gradients) = network.loss_and_gradients(&batch, targets, &loss);
+            let epsilon = 1e-2;
+            for l in 0..3 {
+                // one weight and one bias per layer
+                for (i, j) in [(1, 0), (widths[l], widths[l + 1] - 1)] {
+                    let perturbed = |shift: f32| {
+                        let mut network = Network {
+                            layers: network.layers.clone(),
+                            ..Network::new(&vec![4, 3])
+                        };
+                        network.activation = activation;
+                        network.l2_regularization = 0.1;
+                        let mut parameters: Vec<Vec<f32>> = network.layers[l]
+                            .weights
+                            .matrix()
+                            .iter()
+                            .map(|row| row.vector().clone())
+                            .chain([network.layers[l].biases.clone()])
+                            .collect();
+                        parameters[i][j] += shift;
+                        network.layers[l].biases = parameters.pop().unwrap();
+                        network.layers[l].weights = Matrix::to_matrix(&parameters);
+                        network.loss_and_gradients(&batch, targets, &loss).0
+                    };
+                    let numerical = (perturbed(epsilon) - perturbed(-epsilon)) / (2.0 * epsilon);
+                    assert!((numerical - gradients[l][i][j]).abs() < 1e-3);
+                }
+            }
+        }
+    }
+}
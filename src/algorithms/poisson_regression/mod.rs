@@ -0,0 +1 @@
+pub mod poisson_regression;
@@ -0,0 +1,361 @@
+use crate::algorithms::estimators::{Estimator, ParamError, ParamValue, Regressor};
+use crate::linear_algebra::{LinalgError, Matrix, RowVector, kernels};
+
+/// Poisson generalized linear model with a log link, for count targets: the
+/// expected count is `e^(w·x + b)` and the weights minimize the mean
+/// negative log likelihood `mean(μ - y·ln μ)` plus `alpha / 2 * ||w||^2`.
+/// Fitted with Newton steps (iteratively reweighted least squares).
+pub struct PoissonRegression {
+    alpha: f32,
+    max_iterations: usize,
+    tolerance: f32,
+    weights: Vec<f32>,
+    bias: f32,
+    iterations: usize,
+    converged: bool,
+}
+
+// penalized mean negative log likelihood over the training rows, with
+// parameters laid out as the weights then the bias
+struct PoissonLoss<'a> {
+    data: &'a Vec<Vec<f64>>,
+    targets: &'a Vec<f64>,
+    alpha: f64,
+}
+
+impl<'a> PoissonLoss<'a> {
+    fn linear_predictor(&self, parameters: &[f64], row: &[f64]) -> f64 {
+        let bias = parameters[row.len()];
+        return row.iter().zip(parameters).map(|(x, w)| x * w).sum::<f64>() + bias;
+    }
+
+    fn value_and_gradient(&self, parameters: &[f64], gradient: &mut [f64]) -> f64 {
+        let rows = self.data.len() as f64;
+        let width = parameters.len();
+        gradient.iter_mut().for_each(|value| *value = 0.0);
+        let mut value = 0.0;
+        for (row, target) in self.data.iter().zip(self.targets.iter()) {
+            let eta = self.linear_predictor(parameters, row);
+            let mean = eta.exp();
+            value += (mean - target * eta) / rows;
+            let error = (mean - target) / rows;
+            gradient
+                .iter_mut()
+                .zip(row)
+                .for_each(|(g, x)| *g += error * x);
+            gradient[width - 1] += error;
+        }
+        for j in 0..width - 1 {
+            value += 0.5 * self.alpha * parameters[j] * parameters[j];
+            gradient[j] += self.alpha * parameters[j];
+        }
+        return value;
+    }
+
+    fn hessian(&self, parameters: &[f64]) -> Vec<Vec<f64>> {
+        let rows = self.data.len() as f64;
+        let width = parameters.len();
+        let mut hessian = vec![vec![0.0; width]; width];
+        for row in self.data.iter() {
+            let curvature = self.linear_predictor(parameters, row).exp() / rows;
+            let extended: Vec<f64> = row.iter().copied().chain([1.0]).collect();
+            for (j, x_j) in extended.iter().enumerate() {
+                hessian[j]
+                    .iter_mut()
+                    .zip(&extended)
+                    .for_each(|(h, x_k)| *h += curvature * x_j * x_k);
+            }
+        }
+        for (j, hessian_row) in hessian.iter_mut().enumerate().take(width - 1) {
+            hessian_row[j] += self.alpha;
+        }
+        return hessian;
+    }
+}
+
+impl PoissonRegression {
+    pub fn new(alpha: f32) -> Self {
+        return Self {
+            alpha,
+            max_iterations: 100,
+            tolerance: 1e-6,
+            weights: Vec::new(),
+            bias: 0.0,
+            iterations: 0,
+            converged: false,
+        };
+    }
+
+    pub fn set_max_iterations(&mut self, max_iterations: usize) {
+        self.max_iterations = max_iterations;
+    }
+
+    /// Stops once the largest gradient entry of the objective is below `tolerance`.
+    pub fn set_tolerance(&mut self, tolerance: f32) {
+        self.tolerance = tolerance;
+    }
+
+    /// `labels` is a single column of non-negative counts.
+    pub fn fit(&mut self, data: &Matrix, labels: &Matrix) -> Result<(), LinalgError> {
+        let (rows, columns) = data.shape();
+        if labels.len() != rows {
+            return Err(LinalgError::DimensionMismatch {
+                expected: (rows, 1),
+                found: labels.shape(),
+            });
+        }
+        let targets: Vec<f64> = labels
+            .matrix()
+            .iter()
+            .map(|label| label.get(0) as f64)
+            .collect();
+        if targets
+            .iter()
+            .any(|target| target.is_nan() || *target < 0.0)
+        {
+            panic!("poisson regression needs non-negative targets");
+        }
+        let mean = targets.iter().sum::<f64>() / rows.max(1) as f64;
+        if mean <= 0.0 {
+            panic!("poisson regression needs at least one positive target");
+        }
+        let data_f64 = data.to_f64();
+        let loss = PoissonLoss {
+            data: &data_f64,
+            targets: &targets,
+            alpha: self.alpha as f64,
+        };
+        // the intercept only model is the optimum when the weights are zero
+        let mut parameters = vec![0.0; columns + 1];
+        parameters[columns] = mean.ln();
+        let (parameters, iterations, converged) = self.newton(&loss, parameters)?;
+        self.weights = parameters[..columns].iter().map(|w| *w as f32).collect();
+        self.bias = parameters[columns] as f32;
+        self.iterations = iterations;
+        self.converged = converged;
+        Ok(())
+    }
+
+    // newton steps with backtracking, the objective is convex so the full
+    // step is accepted close to the minimum
+    fn newton(
+        &self,
+        loss: &PoissonLoss,
+        mut parameters: Vec<f64>,
+    ) -> Result<(Vec<f64>, usize, bool), LinalgError> {
+        let mut gradient = vec![0.0; parameters.len()];
+        let mut value = loss.value_and_gradient(&parameters, &mut gradient);
+        for iteration in 0..self.max_iterations {
+            if gradient.iter().fold(0.0_f64, |acc, g| acc.max(g.abs())) <= self.tolerance as f64 {
+                return Ok((parameters, iteration, true));
+            }
+            let hessian = Matrix::from_f64(&loss.hessian(&parameters));
+            let negative_gradient =
+                Matrix::from_f64(&gradient.iter().map(|g| vec![-g]).collect::<Vec<Vec<f64>>>());
+            let direction: Vec<f64> = hessian
+                .lstsq(&negative_gradient)?
+                .matrix()
+                .iter()
+                .map(|row| row.get(0) as f64)
+                .collect();
+            let slope: f64 = direction.iter().zip(&gradient).map(|(d, g)| d * g).sum();
+            let mut step = 1.0;
+            let mut next_gradient = vec![0.0; parameters.len()];
+            loop {
+                let candidate: Vec<f64> = parameters
+                    .iter()
+                    .zip(&direction)
+                    .map(|(p, d)| p + step * d)
+                    .collect();
+                let candidate_value = loss.value_and_gradient(&candidate, &mut next_gradient);
+                if candidate_value <= value + 1e-4 * step * slope || step < 1e-10 {
+                    parameters = candidate;
+                    value = candidate_value;
+                    break;
+                }
+                step *= 0.5;
+            }
+            gradient = next_gradient;
+        }
+        let converged =
+            gradient.iter().fold(0.0_f64, |acc, g| acc.max(g.abs())) <= self.tolerance as f64;
+        Ok((parameters, self.max_iterations, converged))
+    }
+
+    /// Linear predictor `w·x + b`, the log of the expected count.
+    pub fn decision_function(&self, data: &Matrix) -> RowVector {
+        return RowVector::new(
+            &data
+                .matrix()
+                .iter()
+                .map(|row| kernels::dot(row.vector(), &self.weights) + self.bias)
+                .collect(),
+        );
+    }
+
+    /// Expected counts.
+    pub fn predict(&self, data: &Matrix) -> RowVector {
+        let scores = self.decision_function(data);
+        return RowVector::new(&scores.vector().iter().map(|score| score.exp()).collect());
+    }
+
+    /// Mean Poisson deviance `2·mean(y·ln(y/μ) - (y - μ))`.
+    pub fn deviance(&self, data: &Matrix, labels: &Matrix) -> f32 {
+        let predictions = self.predict(data);
+        return mean_deviance(
+            labels.matrix().iter().map(|label| label.get(0)),
+            predictions.vector().iter().copied(),
+        );
+    }
+
+    /// Fraction of the deviance of the mean count model explained, the
+    /// Poisson analogue of r².
+    pub fn score(&self, data: &Matrix, labels: &Matrix) -> f32 {
+        let mean = labels
+            .matrix()
+            .iter()
+            .map(|label| label.get(0))
+            .sum::<f32>()
+            / labels.len().max(1) as f32;
+        let null_deviance = mean_deviance(
+            labels.matrix().iter().map(|label| label.get(0)),
+            std::iter::repeat(mean),
+        );
+        return 1.0 - self.deviance(data, labels) / null_deviance;
+    }
+
+    pub fn weights(&self) -> &Vec<f32> {
+        return &self.weights;
+    }
+
+    pub fn bias(&self) -> f32 {
+        return self.bias;
+    }
+
+    pub fn iterations(&self) -> usize {
+        return self.iterations;
+    }
+
+    /// False when the last `fit` stopped at `max_iterations`.
+    pub fn converged(&self) -> bool {
+        return self.converged;
+    }
+}
+
+fn mean_deviance(
+    targets: impl Iterator<Item = f32>,
+    predictions: impl Iterator<Item = f32>,
+) -> f32 {
+    let mut total = 0.0;
+    let mut count = 0;
+    for (target, prediction) in targets.zip(predictions) {
+        let (y, mu) = (target as f64, prediction as f64);
+        let log_ratio = if y > 0.0 { y * (y / mu).ln() } else { 0.0 };
+        total += 2.0 * (log_ratio - (y - mu));
+        count += 1;
+    }
+    return (total / count.max(1) as f64) as f32;
+}
+
+impl Estimator for PoissonRegression {
+    fn params(&self) -> Vec<(&'static str, ParamValue)> {
+        return vec![
+            ("alpha", ParamValue::Float(self.alpha)),
+            ("max_iterations", ParamValue::Int(self.max_iterations)),
+            ("tolerance", ParamValue::Float(self.tolerance)),
+        ];
+    }
+
+    fn set_param(&mut self, name: &str, value: ParamValue) -> Result<(), ParamError> {
+        match name {
+            "alpha" => self.alpha = value.as_float(name)?,
+            "max_iterations" => self.set_max_iterations(value.as_int(name)?),
+            "tolerance" => self.set_tolerance(value.as_float(name)?),
+            _ => return Err(ParamError::Unknown(name.to_string())),
+        }
+        Ok(())
+    }
+}
+
+// the trait's score is r² like every other regressor, `score` above is D²
+impl Regressor for PoissonRegression {
+    fn fit(&mut self, data: &Matrix, labels: &Matrix) -> Result<(), LinalgError> {
+        return PoissonRegression::fit(self, data, labels);
+    }
+
+    fn predict(&self, data: &Matrix) -> RowVector {
+        return PoissonRegression::predict(self, data);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::exponential_regression::exponential_regression::MultivariateExponentialRegression;
+    use crate::sampling::random::Rng;
+
+    // knuth's multiplication method, fine for the small means used here
+    fn poisson_sample(rng: &mut Rng, mean: f32) -> f32 {
+        let threshold = (-mean as f64).exp();
+        let mut count = 0;
+        let mut product = rng.next_f64();
+        while product > threshold {
+            count += 1;
+            product *= rng.next_f64();
+        }
+        count as f32
+    }
+
+    fn count_data(rows: usize) -> (Matrix, Matrix) {
+        let mut rng = Rng::new(17);
+        let mut data = Vec::new();
+        let mut labels = Vec::new();
+        for _ in 0..rows {
+            let x = vec![rng.normal(), rng.next_f32() * 2.0, rng.normal()];
+            let mean = (1.0 + 0.6 * x[0] - 0.5 * x[1]).exp();
+            labels.push(vec![poisson_sample(&mut rng, mean)]);
+            data.push(x);
+        }
+        (Matrix::to_matrix(&data), Matrix::to_matrix(&labels))
+    }
+
+    #[test]
+    fn test_poisson_recovers_coefficients() {
+        let (data, labels) = count_data(2000);
+        let mut regression = PoissonRegression::new(0.0);
+        regression.fit(&data, &labels).unwrap();
+        assert!(regression.converged() && regression.iterations() < 20);
+        let weights = regression.weights();
+        assert!((weights[0] - 0.6).abs() < 0.05);
+        assert!((weights[1] + 0.5).abs() < 0.05);
+        assert!(weights[2].abs() < 0.05);
+        assert!((regression.bias() - 1.0).abs() < 0.1);
+
+        let predictions = regression.predict(&data);
+        assert!(predictions.vector().iter().all(|p| *p > 0.0));
+        let total: f32 = predictions.vector().iter().sum();
+        let observed: f32 = labels.matrix().iter().map(|label| label.get(0)).sum();
+        // the log link with an intercept matches the observed total
+        assert!((total - observed).abs() < 1e-3 * observed);
+        let score = regression.score(&data, &labels);
+        assert!(score > 0.3 && score < 1.0);
+        assert!(regression.deviance(&data, &labels) > 0.0);
+
+        // least squares on the same mean function is noisier but close
+        let mut least_squares = MultivariateExponentialRegression::new();
+        least_squares.fit(&data, &labels).unwrap();
+        assert!((least_squares.weights()[0] - weights[0]).abs() < 0.1);
+    }
+
+    #[test]
+    fn test_poisson_penalty_shrinks_weights() {
+        let (data, labels) = count_data(500);
+        let mut plain = PoissonRegression::new(0.0);
+        plain.fit(&data, &labels).unwrap();
+        let mut penalized = PoissonRegression::new(1.0);
+        penalized.fit(&data, &labels).unwrap();
+        let norm = |weights: &Vec<f32>| weights.iter().map(|w| w * w).sum::<f32>();
+        assert!(norm(penalized.weights()) < 0.5 * norm(plain.weights()));
+        assert!(penalized.score(&data, &labels) < plain.score(&data, &labels));
+    }
+}
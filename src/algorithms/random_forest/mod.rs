@@ -0,0 +1 @@
+pub mod random_forest;
@@ -0,0 +1,602 @@
+use crate::algorithms::decision_tree::tree::{
+    ClassificationCriterion, FeatureKind, FeatureTable, MaxFeatures, RegressionCriterion, Target,
+    Tree, TreeParameters, normalize_importances,
+};
+use crate::algorithms::estimators::{
+    Classifier, Estimator, ParamError, ParamValue, Regressor, encode_classes,
+    labels_from_dataframe, labels_from_matrix, most_likely_classes,
+};
+use crate::dataframe::DataFrame;
+use crate::inference::inference::{accuracy, r2_score};
+use crate::linear_algebra::{LinalgError, Matrix, RowVector};
+use crate::parallel::parallel::parallel_map;
+use crate::sampling::random::Rng;
+
+/// Bagged regression trees. Each tree sees a bootstrap sample of the rows and
+/// considers `max_features` random features per split; predictions are the
+/// mean over trees. Trees are built in parallel with the `parallel` feature
+/// and every tree draws from its own seeded generator, so results only depend
+/// on the seed.
+pub struct RandomForestRegressor {
+    criterion: RegressionCriterion,
+    forest: Forest,
+    oob_score: Option<f32>,
+}
+
+/// Bagged classification trees averaging leaf class proportions, see
+/// `RandomForestRegressor`.
+pub struct RandomForestClassifier {
+    criterion: ClassificationCriterion,
+    forest: Forest,
+    classes: Vec<f32>,
+    oob_score: Option<f32>,
+}
+
+// settings and fitted trees shared by both forests
+struct Forest {
+    num_trees: usize,
+    parameters: TreeParameters,
+    bootstrap: bool,
+    seed: u64,
+    features: Vec<(String, FeatureKind)>,
+    trees: Vec<Tree>,
+    // rows each tree did not see, for the out of bag estimate
+    out_of_bag: Vec<Vec<usize>>,
+}
+
+impl Forest {
+    fn new(num_trees: usize, max_features: MaxFeatures) -> Self {
+        if num_trees == 0 {
+            panic!("a forest needs at least one tree");
+        }
+        let mut parameters = TreeParameters::new();
+        parameters.max_features = max_features;
+        return Self {
+            num_trees,
+            parameters,
+            bootstrap: true,
+            seed: 0,
+            features: Vec::new(),
+            trees: Vec::new(),
+            out_of_bag: Vec::new(),
+        };
+    }
+
+    fn grow(&mut self, table: &FeatureTable, target: &Target) {
+        let rows = table.len();
+        let mut rng = Rng::new(self.seed);
+        let seeds: Vec<u64> = (0..self.num_trees).map(|_| rng.next_u64()).collect();
+        let grown = parallel_map(self.num_trees, 1, |t| {
+            let mut rng = Rng::new(seeds[t]);
+            let samples: Vec<usize> = if self.bootstrap {
+                (0..rows).map(|_| rng.gen_range(rows)).collect()
+            } else {
+                (0..rows).collect()
+            };
+            let mut in_bag = vec![false; rows];
+            samples.iter().for_each(|sample| in_bag[*sample] = true);
+            let out_of_bag = (0..rows).filter(|row| !in_bag[*row]).collect();
+            (
+                Tree::grow(table, target, samples, &self.parameters, &mut rng),
+                out_of_bag,
+            )
+        });
+        (self.trees, self.out_of_bag) = grown.into_iter().unzip();
+        self.features = table.features.clone();
+    }
+
+    // mean leaf value over trees for every row
+    fn average(&self, table: &FeatureTable) -> Vec<Vec<f32>> {
+        assert!(!self.trees.is_empty(), "random forest must be fitted first");
+        let per_tree = parallel_map(self.trees.len(), 1, |t| self.trees[t].predict_table(table));
+        return (0..table.len())
+            .map(|i| {
+                let mut total = vec![0.0; per_tree[0][i].len()];
+                for leaves in per_tree.iter() {
+                    total
+                        .iter_mut()
+                        .zip(leaves[i])
+                        .for_each(|(sum, value)| *sum += value);
+                }
+                total
+                    .iter()
+                    .map(|sum| sum / self.trees.len() as f32)
+                    .collect()
+            })
+            .collect();
+    }
+
+    // mean leaf value over the trees that did not train on each row, `None`
+    // for rows that were in every bootstrap sample
+    fn out_of_bag_average(&self, table: &FeatureTable) -> Vec<Option<Vec<f32>>> {
+        let mut totals: Vec<Option<(Vec<f32>, usize)>> = vec![None; table.len()];
+        for (tree, rows) in self.trees.iter().zip(self.out_of_bag.iter()) {
+            for row in rows {
+                let leaf = tree.leaf(|feature| table.columns[feature][*row]);
+                let (total, count) = totals[*row].get_or_insert_with(|| (vec![0.0; leaf.len()], 0));
+                total
+                    .iter_mut()
+                    .zip(leaf)
+                    .for_each(|(sum, value)| *sum += value);
+                *count += 1;
+            }
+        }
+        return totals
+            .into_iter()
+            .map(|total| {
+                total.map(|(total, count)| total.iter().map(|sum| sum / count as f32).collect())
+            })
+            .collect();
+    }
+
+    // mean of the per tree normalized importances
+    fn feature_importances(&self) -> Vec<f32> {
+        let mut total = vec![0.0; self.features.len()];
+        for tree in self.trees.iter() {
+            let importances = normalize_importances(tree.importances());
+            total
+                .iter_mut()
+                .zip(importances)
+                .for_each(|(sum, value)| *sum += value as f64);
+        }
+        return normalize_importances(&total);
+    }
+}
+
+impl RandomForestRegressor {
+    /// Defaults to all features per split, as bagging alone already
+    /// decorrelates regression trees reasonably well.
+    pub fn new(num_trees: usize, criterion: RegressionCriterion) -> Self {
+        return Self {
+            criterion,
+            forest: Forest::new(num_trees, MaxFeatures::All),
+            oob_score: None,
+        };
+    }
+
+    pub fn set_max_depth(&mut self, max_depth: Option<usize>) {
+        self.forest.parameters.max_depth = max_depth;
+    }
+
+    pub fn set_min_samples_split(&mut self, min_samples_split: usize) {
+        self.forest.parameters.min_samples_split = min_samples_split;
+    }
+
+    pub fn set_min_samples_leaf(&mut self, min_samples_leaf: usize) {
+        if min_samples_leaf == 0 {
+            panic!("leaves need at least one sample");
+        }
+        self.forest.parameters.min_samples_leaf = min_samples_leaf;
+    }
+
+    pub fn set_max_features(&mut self, max_features: MaxFeatures) {
+        self.forest.parameters.max_features = max_features;
+    }
+
+    /// Without bootstrapping every tree trains on all rows and there is no
+    /// out of bag score.
+    pub fn set_bootstrap(&mut self, bootstrap: bool) {
+        self.forest.bootstrap = bootstrap;
+    }
+
+    pub fn set_seed(&mut self, seed: u64) {
+        self.forest.seed = seed;
+    }
+
+    pub fn fit(&mut self, data: &Matrix, labels: &Matrix) {
+        self.fit_table(
+            &FeatureTable::from_matrix(data),
+            &labels_from_matrix(labels),
+        );
+    }
+
+    pub fn fit_df(&mut self, df: &DataFrame, feature_columns: &Vec<String>, target_column: &str) {
+        self.fit_table(
+            &FeatureTable::learn(df, feature_columns),
+            &labels_from_dataframe(df, target_column),
+        );
+    }
+
+    fn fit_table(&mut self, table: &FeatureTable, labels: &Vec<f32>) {
+        assert!(
+            table.len() == labels.len(),
+            "data and labels must have the same length"
+        );
+        let target = Target::Regression {
+            values: labels,
+            criterion: self.criterion.clone(),
+        };
+        self.forest.grow(table, &target);
+        self.oob_score = None;
+        if self.forest.bootstrap {
+            let (predictions, oob_labels): (Vec<f32>, Vec<f32>) = self
+                .forest
+                .out_of_bag_average(table)
+                .iter()
+                .zip(labels.iter())
+                .filter_map(|(prediction, label)| {
+                    prediction
+                        .as_ref()
+                        .map(|prediction| (prediction[0], *label))
+                })
+                .unzip();
+            if !predictions.is_empty() {
+                self.oob_score = Some(r2_score(&predictions, &oob_labels));
+            }
+        }
+    }
+
+    pub fn predict(&self, data: &Matrix) -> RowVector {
+        return self.predict_table(&FeatureTable::from_matrix(data));
+    }
+
+    pub fn predict_df(&self, df: &DataFrame) -> RowVector {
+        return self.predict_table(&FeatureTable::encode(df, &self.forest.features));
+    }
+
+    fn predict_table(&self, table: &FeatureTable) -> RowVector {
+        return RowVector::new(
+            &self
+                .forest
+                .average(table)
+                .iter()
+                .map(|value| value[0])
+                .collect(),
+        );
+    }
+
+    /// R^2 of each row predicted by the trees that did not see it.
+    pub fn oob_score(&self) -> Option<f32> {
+        return self.oob_score;
+    }
+
+    pub fn feature_importances(&self) -> Vec<f32> {
+        return self.forest.feature_importances();
+    }
+
+    pub fn feature_names(&self) -> Vec<&String> {
+        return self.forest.features.iter().map(|(name, _)| name).collect();
+    }
+
+    pub fn num_trees(&self) -> usize {
+        return self.forest.num_trees;
+    }
+}
+
+impl RandomForestClassifier {
+    /// Defaults to the square root of the feature count per split.
+    pub fn new(num_trees: usize, criterion: ClassificationCriterion) -> Self {
+        return Self {
+            criterion,
+            forest: Forest::new(num_trees, MaxFeatures::Sqrt),
+            classes: Vec::new(),
+            oob_score: None,
+        };
+    }
+
+    pub fn set_max_depth(&mut self, max_depth: Option<usize>) {
+        self.forest.parameters.max_depth = max_depth;
+    }
+
+    pub fn set_min_samples_split(&mut self, min_samples_split: usize) {
+        self.forest.parameters.min_samples_split = min_samples_split;
+    }
+
+    pub fn set_min_samples_leaf(&mut self, min_samples_leaf: usize) {
+        if min_samples_leaf == 0 {
+            panic!("leaves need at least one sample");
+        }
+        self.forest.parameters.min_samples_leaf = min_samples_leaf;
+    }
+
+    pub fn set_max_features(&mut self, max_features: MaxFeatures) {
+        self.forest.parameters.max_features = max_features;
+    }
+
+    pub fn set_bootstrap(&mut self, bootstrap: bool) {
+        self.forest.bootstrap = bootstrap;
+    }
+
+    pub fn set_seed(&mut self, seed: u64) {
+        self.forest.seed = seed;
+    }
+
+    pub fn fit(&mut self, data: &Matrix, labels: &Matrix) {
+        self.fit_table(
+            &FeatureTable::from_matrix(data),
+            &labels_from_matrix(labels),
+        );
+    }
+
+    pub fn fit_df(&mut self, df: &DataFrame, feature_columns: &Vec<String>, target_column: &str) {
+        self.fit_table(
+            &FeatureTable::learn(df, feature_columns),
+            &labels_from_dataframe(df, target_column),
+        );
+    }
+
+    fn fit_table(&mut self, table: &FeatureTable, labels: &Vec<f32>) {
+        assert!(
+            table.len() == labels.len(),
+            "data and labels must have the same length"
+        );
+        let (classes, targets) = encode_classes(labels);
+        let target = Target::Classification {
+            classes: &targets,
+            num_classes: classes.len(),
+            criterion: self.criterion.clone(),
+        };
+        self.forest.grow(table, &target);
+        self.classes = classes;
+        self.oob_score = None;
+        if self.forest.bootstrap {
+            let (probabilities, oob_labels): (Vec<RowVector>, Vec<f32>) = self
+                .forest
+                .out_of_bag_average(table)
+                .iter()
+                .zip(labels.iter())
+                .filter_map(|(proportions, label)| {
+                    proportions
+                        .as_ref()
+                        .map(|proportions| (RowVector::new(proportions), *label))
+                })
+                .unzip();
+            if !probabilities.is_empty() {
+                let predictions = most_likely_classes(&self.classes, &Matrix::new(&probabilities));
+                self.oob_score = Some(accuracy(predictions.vector(), &oob_labels));
+            }
+        }
+    }
+
+    /// Mean class proportions over trees, columns in the order of `classes()`.
+    pub fn predict_proba(&self, data: &Matrix) -> Matrix {
+        return self.predict_proba_table(&FeatureTable::from_matrix(data));
+    }
+
+    pub fn predict_proba_df(&self, df: &DataFrame) -> Matrix {
+        return self.predict_proba_table(&FeatureTable::encode(df, &self.forest.features));
+    }
+
+    fn predict_proba_table(&self, table: &FeatureTable) -> Matrix {
+        return Matrix::to_matrix(&self.forest.average(table));
+    }
+
+    pub fn predict(&self, data: &Matrix) -> RowVector {
+        return most_likely_classes(&self.classes, &self.predict_proba(data));
+    }
+
+    pub fn predict_df(&self, df: &DataFrame) -> RowVector {
+        return most_likely_classes(&self.classes, &self.predict_proba_df(df));
+    }
+
+    pub fn classes(&self) -> &Vec<f32> {
+        return &self.classes;
+    }
+
+    /// Accuracy of each row predicted by the trees that did not see it.
+    pub fn oob_score(&self) -> Option<f32> {
+        return self.oob_score;
+    }
+
+    pub fn feature_importances(&self) -> Vec<f32> {
+        return self.forest.feature_importances();
+    }
+
+    pub fn feature_names(&self) -> Vec<&String> {
+        return self.forest.features.iter().map(|(name, _)| name).collect();
+    }
+
+    pub fn num_trees(&self) -> usize {
+        return self.forest.num_trees;
+    }
+}
+
+impl Estimator for RandomForestRegressor {
+    fn params(&self) -> Vec<(&'static str, ParamValue)> {
+        let parameters = &self.forest.parameters;
+        return vec![
+            ("num_trees", ParamValue::Int(self.forest.num_trees)),
+            ("max_depth", ParamValue::OptionalInt(parameters.max_depth)),
+            (
+                "min_samples_split",
+                ParamValue::Int(parameters.min_samples_split),
+            ),
+            (
+                "min_samples_leaf",
+                ParamValue::Int(parameters.min_samples_leaf),
+            ),
+            ("bootstrap", ParamValue::Bool(self.forest.bootstrap)),
+            ("seed", ParamValue::Int(self.forest.seed as usize)),
+        ];
+    }
+
+    fn set_param(&mut self, name: &str, value: ParamValue) -> Result<(), ParamError> {
+        match name {
+            "num_trees" => {
+                let num_trees = value.as_int(name)?;
+                if num_trees == 0 {
+                    panic!("a forest needs at least one tree");
+                }
+                self.forest.num_trees = num_trees;
+            }
+            "max_depth" => self.set_max_depth(value.as_optional_int(name)?),
+            "min_samples_split" => self.set_min_samples_split(value.as_int(name)?),
+            "min_samples_leaf" => self.set_min_samples_leaf(value.as_int(name)?),
+            "bootstrap" => self.set_bootstrap(value.as_bool(name)?),
+            "seed" => self.set_seed(value.as_int(name)? as u64),
+            _ => return Err(ParamError::Unknown(name.to_string())),
+        }
+        Ok(())
+    }
+}
+
+impl Regressor for RandomForestRegressor {
+    fn fit(&mut self, data: &Matrix, labels: &Matrix) -> Result<(), LinalgError> {
+        RandomForestRegressor::fit(self, data, labels);
+        Ok(())
+    }
+
+    fn predict(&self, data: &Matrix) -> RowVector {
+        return RandomForestRegressor::predict(self, data);
+    }
+}
+
+impl Estimator for RandomForestClassifier {
+    fn params(&self) -> Vec<(&'static str, ParamValue)> {
+        let parameters = &self.forest.parameters;
+        return vec![
+            ("num_trees", ParamValue::Int(self.forest.num_trees)),
+            ("max_depth", ParamValue::OptionalInt(parameters.max_depth)),
+            (
+                "min_samples_split",
+                ParamValue::Int(parameters.min_samples_split),
+            ),
+            (
+                "min_samples_leaf",
+                ParamValue::Int(parameters.min_samples_leaf),
+            ),
+            ("bootstrap", ParamValue::Bool(self.forest.bootstrap)),
+            ("seed", ParamValue::Int(self.forest.seed as usize)),
+        ];
+    }
+
+    fn set_param(&mut self, name: &str, value: ParamValue) -> Result<(), ParamError> {
+        match name {
+            "num_trees" => {
+                let num_trees = value.as_int(name)?;
+                if num_trees == 0 {
+                    panic!("a forest needs at least one tree");
+                }
+                self.forest.num_trees = num_trees;
+            }
+            "max_depth" => self.set_max_depth(value.as_optional_int(name)?),
+            "min_samples_split" => self.set_min_samples_split(value.as_int(name)?),
+            "min_samples_leaf" => self.set_min_samples_leaf(value.as_int(name)?),
+            "bootstrap" => self.set_bootstrap(value.as_bool(name)?),
+            "seed" => self.set_seed(value.as_int(name)? as u64),
+            _ => return Err(ParamError::Unknown(name.to_string())),
+        }
+        Ok(())
+    }
+}
+
+impl Classifier for RandomForestClassifier {
+    fn fit(&mut self, data: &Matrix, labels: &Matrix) -> Result<(), LinalgError> {
+        RandomForestClassifier::fit(self, data, labels);
+        Ok(())
+    }
+
+    fn predict(&self, data: &Matrix) -> RowVector {
+        return RandomForestClassifier::predict(self, data);
+    }
+
+    fn classes(&self) -> &Vec<f32> {
+        return RandomForestClassifier::classes(self);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::decision_tree::decision_tree::DecisionTreeRegressor;
+    use crate::dataframe::csv::df_from_csv;
+    use crate::inference::inference::rmse;
+    use crate::parallel::parallel::with_num_threads;
+
+    fn housing_features() -> Vec<String> {
+        [
+            "longitude",
+            "latitude",
+            "housing_median_age",
+            "total_rooms",
+            "total_bedrooms",
+            "population",
+            "households",
+            "median_income",
+            "ocean_proximity",
+        ]
+        .iter()
+        .map(|column| column.to_string())
+        .collect()
+    }
+
+    #[test]
+    fn test_forest_regressor_beats_single_tree() {
+        let df = df_from_csv("housing.csv", Some(2000));
+        let train = df.get_rows_as_df(&(0..1500).collect());
+        let test = df.get_rows_as_df(&(1500..2000).collect());
+        let features = housing_features();
+        let label = "median_house_value";
+        let mut tree = DecisionTreeRegressor::new(RegressionCriterion::SquaredError);
+        tree.fit_df(&train, &features, label);
+        let mut forest = RandomForestRegressor::new(20, RegressionCriterion::SquaredError);
+        forest.set_max_features(MaxFeatures::Fraction(0.5));
+        forest.set_seed(7);
+        forest.fit_df(&train, &features, label);
+        let test_labels = labels_from_dataframe(&test, label);
+        let tree_rmse = rmse(tree.predict_df(&test).vector(), &test_labels);
+        let forest_rmse = rmse(forest.predict_df(&test).vector(), &test_labels);
+        assert!(forest_rmse < tree_rmse);
+        let oob_score = forest.oob_score().unwrap();
+        assert!(oob_score > 0.5 && oob_score < 1.0);
+        let importances = forest.feature_importances();
+        assert!(importances.len() == features.len());
+        assert!((importances.iter().sum::<f32>() - 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_forest_is_reproducible_across_thread_counts() {
+        let df = df_from_csv("housing.csv", Some(500));
+        let features = housing_features();
+        let fit = |seed: u64| {
+            let mut forest = RandomForestRegressor::new(8, RegressionCriterion::SquaredError);
+            forest.set_max_features(MaxFeatures::Sqrt);
+            forest.set_max_depth(Some(6));
+            forest.set_seed(seed);
+            forest.fit_df(&df, &features, "median_house_value");
+            (forest.predict_df(&df), forest.oob_score())
+        };
+        let (single_threaded, single_oob) = with_num_threads(1, || fit(3));
+        let (multi_threaded, multi_oob) = with_num_threads(4, || fit(3));
+        assert!(single_threaded.vector() == multi_threaded.vector());
+        assert!(single_oob == multi_oob);
+        assert!(fit(4).0.vector() != single_threaded.vector());
+    }
+
+    #[test]
+    fn test_forest_classifier() {
+        // label is whether x0 + x1 > 10, x2 is noise
+        let data: Vec<Vec<f32>> = (0..300)
+            .map(|i| {
+                vec![
+                    (i % 10) as f32,
+                    ((i / 10) % 10) as f32,
+                    ((i * 7) % 13) as f32,
+                ]
+            })
+            .collect();
+        let labels: Vec<Vec<f32>> = data
+            .iter()
+            .map(|row| vec![(row[0] + row[1] > 10.0) as usize as f32])
+            .collect();
+        let (data, labels) = (Matrix::to_matrix(&data), Matrix::to_matrix(&labels));
+        let mut forest = RandomForestClassifier::new(25, ClassificationCriterion::Gini);
+        forest.set_seed(1);
+        forest.fit(&data, &labels);
+        assert!(forest.classes() == &vec![0.0, 1.0]);
+        assert!(forest.oob_score().unwrap() > 0.9);
+        let probabilities = forest.predict_proba(&data);
+        assert!(
+            probabilities
+                .matrix()
+                .iter()
+                .all(|row| (row.get(0) + row.get(1) - 1.0).abs() < 1e-5)
+        );
+        assert!(accuracy(forest.predict(&data).vector(), &labels_from_matrix(&labels)) > 0.95);
+        let importances = forest.feature_importances();
+        assert!(importances[2] < importances[0] && importances[2] < importances[1]);
+        forest.set_bootstrap(false);
+        forest.fit(&data, &labels);
+        assert!(forest.oob_score().is_none());
+    }
+}
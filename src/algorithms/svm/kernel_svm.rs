@@ -0,0 +1,707 @@
+use crate::algorithms::estimators::{
+    Classifier, Estimator, ParamError, ParamValue, encode_classes, labels_from_matrix,
+};
+use crate::algorithms::logistic_regression::logistic_regression::{ClassWeight, class_weights};
+use crate::algorithms::svm::linear_svm::positive_c;
+use crate::linear_algebra::{LinalgError, Matrix, RowVector, kernels};
+use crate::parallel::parallel::parallel_map;
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+/// Similarity between two rows: `Linear` is `a·b`, `Rbf` is
+/// `e^(-gamma * ||a - b||^2)` and `Polynomial` is `(gamma * a·b + coef0)^degree`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Kernel {
+    Linear,
+    Rbf,
+    Polynomial { degree: u32, coef0: f32 },
+}
+
+/// `Scale` uses `1 / (features * variance)` over every value of the training
+/// data.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Gamma {
+    Scale,
+    Value(f32),
+}
+
+/// Kernel support vector classifier trained with sequential minimal
+/// optimization (second order working set selection). More than two classes
+/// are fitted one pair of classes at a time and predicted by majority vote.
+pub struct Svc {
+    c: f32,
+    kernel: Kernel,
+    gamma: Gamma,
+    class_weight: ClassWeight,
+    tolerance: f32,
+    max_iterations: usize,
+    cache_size: usize,
+    classes: Vec<f32>,
+    gamma_value: f32,
+    support: Vec<usize>,
+    support_vectors: Vec<Vec<f32>>,
+    machines: Vec<BinaryMachine>,
+    iterations: usize,
+    converged: bool,
+}
+
+// one pair of classes, positive towards `positive`; coefficients are
+// `alpha * y` per position in the support vectors
+struct BinaryMachine {
+    negative: usize,
+    positive: usize,
+    coefficients: Vec<(usize, f32)>,
+    bias: f32,
+}
+
+#[derive(Clone)]
+struct KernelFunction {
+    kernel: Kernel,
+    gamma: f32,
+}
+
+impl KernelFunction {
+    fn evaluate(&self, a: &[f32], b: &[f32]) -> f32 {
+        return match &self.kernel {
+            Kernel::Linear => kernels::dot(a, b),
+            Kernel::Rbf => {
+                let distance: f32 = a.iter().zip(b).map(|(x, y)| (x - y) * (x - y)).sum();
+                (-self.gamma * distance).exp()
+            }
+            Kernel::Polynomial { degree, coef0 } => {
+                (self.gamma * kernels::dot(a, b) + coef0).powi(*degree as i32)
+            }
+        };
+    }
+}
+
+// kernel rows computed on demand, dropping the oldest beyond `capacity` rows
+struct KernelCache<'a> {
+    rows: Vec<&'a [f32]>,
+    kernel: &'a KernelFunction,
+    cached: Vec<Option<Arc<Vec<f32>>>>,
+    order: VecDeque<usize>,
+    capacity: usize,
+}
+
+impl<'a> KernelCache<'a> {
+    fn row(&mut self, i: usize) -> Arc<Vec<f32>> {
+        if let Some(row) = &self.cached[i] {
+            return row.clone();
+        }
+        let rows = &self.rows;
+        let values = parallel_map(rows.len(), 256, |j| self.kernel.evaluate(rows[i], rows[j]));
+        let row = Arc::new(values);
+        if self.order.len() >= self.capacity
+            && let Some(oldest) = self.order.pop_front()
+        {
+            self.cached[oldest] = None;
+        }
+        self.cached[i] = Some(row.clone());
+        self.order.push_back(i);
+        return row;
+    }
+}
+
+// dual variables and offset of one binary problem
+struct Solution {
+    alphas: Vec<f64>,
+    rho: f64,
+    iterations: usize,
+    converged: bool,
+}
+
+impl Svc {
+    pub fn new(c: f32, kernel: Kernel) -> Self {
+        return Self {
+            c: positive_c(c),
+            kernel,
+            gamma: Gamma::Scale,
+            class_weight: ClassWeight::Uniform,
+            tolerance: 1e-3,
+            max_iterations: 1_000_000,
+            cache_size: 200,
+            classes: Vec::new(),
+            gamma_value: 0.0,
+            support: Vec::new(),
+            support_vectors: Vec::new(),
+            machines: Vec::new(),
+            iterations: 0,
+            converged: false,
+        };
+    }
+
+    /// Unused by the linear kernel.
+    pub fn set_gamma(&mut self, gamma: Gamma) {
+        if let Gamma::Value(value) = gamma
+            && (value.is_nan() || value <= 0.0)
+        {
+            panic!("gamma must be positive");
+        }
+        self.gamma = gamma;
+    }
+
+    /// Scales `C` per class.
+    pub fn set_class_weight(&mut self, class_weight: ClassWeight) {
+        self.class_weight = class_weight;
+    }
+
+    /// Stops once the largest violation of the optimality conditions is below
+    /// `tolerance`.
+    pub fn set_tolerance(&mut self, tolerance: f32) {
+        self.tolerance = tolerance;
+    }
+
+    /// Most pair updates per binary problem.
+    pub fn set_max_iterations(&mut self, max_iterations: usize) {
+        self.max_iterations = max_iterations.max(1);
+    }
+
+    /// Megabytes of kernel rows kept between iterations.
+    pub fn set_cache_size(&mut self, cache_size: usize) {
+        self.cache_size = cache_size;
+    }
+
+    /// `labels` is a single column of class values.
+    pub fn fit(&mut self, data: &Matrix, labels: &Matrix) -> Result<(), LinalgError> {
+        let (rows, columns) = data.shape();
+        if labels.len() != rows {
+            return Err(LinalgError::DimensionMismatch {
+                expected: (rows, 1),
+                found: labels.shape(),
+            });
+        }
+        let (classes, targets) = encode_classes(&labels_from_matrix(labels));
+        let class_weights = class_weights(&self.class_weight, &classes, &targets);
+        let gamma = match self.gamma {
+            Gamma::Value(value) => value,
+            Gamma::Scale => {
+                let values = (rows * columns).max(1) as f64;
+                let mean = data
+                    .matrix()
+                    .iter()
+                    .map(|row| kernels::sum(row.vector()) as f64)
+                    .sum::<f64>()
+                    / values;
+                let variance = data
+                    .matrix()
+                    .iter()
+                    .flat_map(|row| row.vector().iter())
+                    .map(|value| (*value as f64 - mean).powi(2))
+                    .sum::<f64>()
+                    / values;
+                if variance > 0.0 {
+                    (1.0 / (columns as f64 * variance)) as f32
+                } else {
+                    1.0
+                }
+            }
+        };
+        let kernel = KernelFunction {
+            kernel: self.kernel.clone(),
+            gamma,
+        };
+        let mut machines = Vec::new();
+        let mut is_support = vec![false; rows];
+        let (mut iterations, mut converged) = (0, true);
+        for negative in 0..classes.len() {
+            for positive in negative + 1..classes.len() {
+                let members: Vec<usize> = (0..rows)
+                    .filter(|row| targets[*row] == negative || targets[*row] == positive)
+                    .collect();
+                let signs: Vec<f64> = members
+                    .iter()
+                    .map(|row| if targets[*row] == positive { 1.0 } else { -1.0 })
+                    .collect();
+                let bounds: Vec<f64> = members
+                    .iter()
+                    .map(|row| self.c as f64 * class_weights[targets[*row]])
+                    .collect();
+                let pair_rows: Vec<&[f32]> = members
+                    .iter()
+                    .map(|row| data.get(*row).vector().as_slice())
+                    .collect();
+                let solution = self.smo(pair_rows, &signs, &bounds, &kernel);
+                iterations += solution.iterations;
+                converged &= solution.converged;
+                let coefficients: Vec<(usize, f32)> = solution
+                    .alphas
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, alpha)| **alpha > 0.0)
+                    .map(|(k, alpha)| {
+                        is_support[members[k]] = true;
+                        (members[k], (alpha * signs[k]) as f32)
+                    })
+                    .collect();
+                machines.push(BinaryMachine {
+                    negative,
+                    positive,
+                    coefficients,
+                    bias: -solution.rho as f32,
+                });
+            }
+        }
+        // coefficients index the support vectors rather than the training rows
+        self.support = (0..rows).filter(|row| is_support[*row]).collect();
+        let mut position = vec![0; rows];
+        for (k, row) in self.support.iter().enumerate() {
+            position[*row] = k;
+        }
+        for machine in machines.iter_mut() {
+            machine
+                .coefficients
+                .iter_mut()
+                .for_each(|(row, _)| *row = position[*row]);
+        }
+        self.support_vectors = self
+            .support
+            .iter()
+            .map(|row| data.get(*row).vector().clone())
+            .collect();
+        self.machines = machines;
+        self.classes = classes;
+        self.gamma_value = gamma;
+        self.iterations = iterations;
+        self.converged = converged;
+        Ok(())
+    }
+
+    fn smo(
+        &self,
+        rows: Vec<&[f32]>,
+        signs: &Vec<f64>,
+        bounds: &Vec<f64>,
+        kernel: &KernelFunction,
+    ) -> Solution {
+        let n = rows.len();
+        let diagonal: Vec<f64> = rows
+            .iter()
+            .map(|row| kernel.evaluate(row, row) as f64)
+            .collect();
+        let capacity = (self.cache_size * (1 << 20) / (4 * n.max(1))).max(2);
+        let mut cache = KernelCache {
+            rows,
+            kernel,
+            cached: vec![None; n],
+            order: VecDeque::new(),
+            capacity,
+        };
+        let mut alphas = vec![0.0; n];
+        // gradient of the dual objective `alpha' Q alpha / 2 - sum(alpha)`
+        let mut gradient = vec![-1.0; n];
+        let upper = |alphas: &Vec<f64>, t: usize| {
+            (signs[t] > 0.0 && alphas[t] < bounds[t]) || (signs[t] < 0.0 && alphas[t] > 0.0)
+        };
+        let lower = |alphas: &Vec<f64>, t: usize| {
+            (signs[t] > 0.0 && alphas[t] > 0.0) || (signs[t] < 0.0 && alphas[t] < bounds[t])
+        };
+        let mut iterations = 0;
+        let mut converged = false;
+        while iterations < self.max_iterations {
+            let mut largest = f64::NEG_INFINITY;
+            let mut first = None;
+            for t in 0..n {
+                if upper(&alphas, t) && -signs[t] * gradient[t] >= largest {
+                    largest = -signs[t] * gradient[t];
+                    first = Some(t);
+                }
+            }
+            let Some(i) = first else {
+                converged = true;
+                break;
+            };
+            let kernel_i = cache.row(i);
+            let mut smallest = f64::INFINITY;
+            let mut best = f64::INFINITY;
+            let mut second = None;
+            for t in 0..n {
+                if !lower(&alphas, t) {
+                    continue;
+                }
+                let value = -signs[t] * gradient[t];
+                smallest = smallest.min(value);
+                let gain = largest - value;
+                if gain > 0.0 {
+                    let curvature = diagonal[i] + diagonal[t] - 2.0 * kernel_i[t] as f64;
+                    let objective = -gain * gain / curvature.max(1e-12);
+                    if objective <= best {
+                        best = objective;
+                        second = Some(t);
+                    }
+                }
+            }
+            let Some(j) = second.filter(|_| largest - smallest >= self.tolerance as f64) else {
+                converged = true;
+                break;
+            };
+            iterations += 1;
+            let kernel_j = cache.row(j);
+            let curvature = (diagonal[i] + diagonal[j] - 2.0 * kernel_i[j] as f64).max(1e-12);
+            let (old_i, old_j) = (alphas[i], alphas[j]);
+            let (bound_i, bound_j) = (bounds[i], bounds[j]);
+            // analytic update of the pair, clipped to the box
+            if signs[i] != signs[j] {
+                let delta = (-gradient[i] - gradient[j]) / curvature;
+                let difference = alphas[i] - alphas[j];
+                alphas[i] += delta;
+                alphas[j] += delta;
+                if difference > 0.0 {
+                    if alphas[j] < 0.0 {
+                        alphas[j] = 0.0;
+                        alphas[i] = difference;
+                    }
+                } else if alphas[i] < 0.0 {
+                    alphas[i] = 0.0;
+                    alphas[j] = -difference;
+                }
+                if difference > bound_i - bound_j {
+                    if alphas[i] > bound_i {
+                        alphas[i] = bound_i;
+                        alphas[j] = bound_i - difference;
+                    }
+                } else if alphas[j] > bound_j {
+                    alphas[j] = bound_j;
+                    alphas[i] = bound_j + difference;
+                }
+            } else {
+                let delta = (gradient[i] - gradient[j]) / curvature;
+                let sum = alphas[i] + alphas[j];
+                alphas[i] -= delta;
+                alphas[j] += delta;
+                if sum > bound_i {
+                    if alphas[i] > bound_i {
+                        alphas[i] = bound_i;
+                        alphas[j] = sum - bound_i;
+                    }
+                } else if alphas[j] < 0.0 {
+                    alphas[j] = 0.0;
+                    alphas[i] = sum;
+                }
+                if sum > bound_j {
+                    if alphas[j] > bound_j {
+                        alphas[j] = bound_j;
+                        alphas[i] = sum - bound_j;
+                    }
+                } else if alphas[i] < 0.0 {
+                    alphas[i] = 0.0;
+                    alphas[j] = sum;
+                }
+            }
+            let (change_i, change_j) = (
+                (alphas[i] - old_i) * signs[i],
+                (alphas[j] - old_j) * signs[j],
+            );
+            for t in 0..n {
+                gradient[t] +=
+                    signs[t] * (kernel_i[t] as f64 * change_i + kernel_j[t] as f64 * change_j);
+            }
+        }
+        // offset from the free variables, or the middle of the feasible range
+        let (mut total, mut free) = (0.0, 0);
+        let (mut upper_bound, mut lower_bound) = (f64::INFINITY, f64::NEG_INFINITY);
+        for t in 0..n {
+            let value = signs[t] * gradient[t];
+            let at_upper = alphas[t] >= bounds[t];
+            let at_lower = alphas[t] <= 0.0;
+            if at_upper == at_lower {
+                total += value;
+                free += 1;
+            } else if at_upper == (signs[t] < 0.0) {
+                upper_bound = upper_bound.min(value);
+            } else {
+                lower_bound = lower_bound.max(value);
+            }
+        }
+        let rho = if free > 0 {
+            total / free as f64
+        } else {
+            (upper_bound + lower_bound) / 2.0
+        };
+        return Solution {
+            alphas,
+            rho,
+            iterations,
+            converged,
+        };
+    }
+
+    fn kernel_function(&self) -> KernelFunction {
+        return KernelFunction {
+            kernel: self.kernel.clone(),
+            gamma: self.gamma_value,
+        };
+    }
+
+    /// One column per pair of classes `(a, b)` with `a < b` in the order of
+    /// `classes()`, positive towards `b`. A single column for two classes.
+    pub fn decision_function(&self, data: &Matrix) -> Matrix {
+        let kernel = self.kernel_function();
+        let rows = parallel_map(data.len(), 64, |i| {
+            let row = data.get(i).vector();
+            let similarities: Vec<f32> = self
+                .support_vectors
+                .iter()
+                .map(|vector| kernel.evaluate(row, vector))
+                .collect();
+            RowVector::new(
+                &self
+                    .machines
+                    .iter()
+                    .map(|machine| {
+                        machine
+                            .coefficients
+                            .iter()
+                            .fold(machine.bias, |acc, (k, coefficient)| {
+                                acc + coefficient * similarities[*k]
+                            })
+                    })
+                    .collect(),
+            )
+        });
+        return Matrix::new(&rows);
+    }
+
+    /// Majority vote over the pairs of classes, ties go to the first class.
+    pub fn predict(&self, data: &Matrix) -> RowVector {
+        let scores = self.decision_function(data);
+        return RowVector::new(
+            &scores
+                .matrix()
+                .iter()
+                .map(|row| {
+                    let mut votes = vec![0; self.classes.len()];
+                    for (machine, score) in self.machines.iter().zip(row.vector()) {
+                        if *score > 0.0 {
+                            votes[machine.positive] += 1;
+                        } else {
+                            votes[machine.negative] += 1;
+                        }
+                    }
+                    let best = (0..votes.len()).fold(0, |best, class| {
+                        if votes[class] > votes[best] {
+                            class
+                        } else {
+                            best
+                        }
+                    });
+                    self.classes[best]
+                })
+                .collect(),
+        );
+    }
+
+    pub fn classes(&self) -> &Vec<f32> {
+        return &self.classes;
+    }
+
+    /// Training rows with a nonzero dual coefficient in any pair.
+    pub fn support(&self) -> &Vec<usize> {
+        return &self.support;
+    }
+
+    pub fn support_vectors(&self) -> Matrix {
+        return Matrix::to_matrix(&self.support_vectors);
+    }
+
+    pub fn num_support_vectors(&self) -> usize {
+        return self.support.len();
+    }
+
+    /// Gamma used by the fitted kernel.
+    pub fn gamma(&self) -> f32 {
+        return self.gamma_value;
+    }
+
+    /// Pair updates over all binary problems.
+    pub fn iterations(&self) -> usize {
+        return self.iterations;
+    }
+
+    pub fn converged(&self) -> bool {
+        return self.converged;
+    }
+}
+
+impl Estimator for Svc {
+    // `gamma` is `None` for `Gamma::Scale`
+    fn params(&self) -> Vec<(&'static str, ParamValue)> {
+        let gamma = match self.gamma {
+            Gamma::Scale => None,
+            Gamma::Value(value) => Some(value),
+        };
+        return vec![
+            ("c", ParamValue::Float(self.c)),
+            ("gamma", ParamValue::OptionalFloat(gamma)),
+            ("tolerance", ParamValue::Float(self.tolerance)),
+            ("max_iterations", ParamValue::Int(self.max_iterations)),
+            ("cache_size", ParamValue::Int(self.cache_size)),
+        ];
+    }
+
+    fn set_param(&mut self, name: &str, value: ParamValue) -> Result<(), ParamError> {
+        match name {
+            "c" => self.c = positive_c(value.as_float(name)?),
+            "gamma" => self.set_gamma(match value.as_optional_float(name)? {
+                Some(value) => Gamma::Value(value),
+                None => Gamma::Scale,
+            }),
+            "tolerance" => self.set_tolerance(value.as_float(name)?),
+            "max_iterations" => self.set_max_iterations(value.as_int(name)?),
+            "cache_size" => self.set_cache_size(value.as_int(name)?),
+            _ => return Err(ParamError::Unknown(name.to_string())),
+        }
+        Ok(())
+    }
+}
+
+impl Classifier for Svc {
+    fn fit(&mut self, data: &Matrix, labels: &Matrix) -> Result<(), LinalgError> {
+        return Svc::fit(self, data, labels);
+    }
+
+    fn predict(&self, data: &Matrix) -> RowVector {
+        return Svc::predict(self, data);
+    }
+
+    fn classes(&self) -> &Vec<f32> {
+        return Svc::classes(self);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::svm::linear_svm::LinearSvc;
+    use crate::inference::inference::accuracy;
+    use crate::sampling::random::Rng;
+
+    // label 1 on the outer of two noisy concentric rings
+    fn rings(per_ring: usize, seed: u64) -> (Matrix, Matrix) {
+        let mut rng = Rng::new(seed);
+        let mut data = Vec::new();
+        let mut labels = Vec::new();
+        for (label, radius) in [(0.0, 1.0), (1.0, 3.0)] {
+            for _ in 0..per_ring {
+                let angle = rng.next_f32() * std::f32::consts::TAU;
+                let r = radius + 0.3 * rng.normal();
+                data.push(vec![r * angle.cos(), r * angle.sin()]);
+                labels.push(vec![label]);
+            }
+        }
+        (Matrix::to_matrix(&data), Matrix::to_matrix(&labels))
+    }
+
+    #[test]
+    fn test_kernel_svc_separates_rings() {
+        let (data, labels) = rings(200, 1);
+        let (test_data, test_labels) = rings(200, 2);
+        let test_labels = labels_from_matrix(&test_labels);
+        let mut linear = LinearSvc::new(1.0);
+        linear.fit(&data, &labels).unwrap();
+        assert!(accuracy(linear.predict(&test_data).vector(), &test_labels) < 0.75);
+
+        for kernel in [
+            Kernel::Rbf,
+            Kernel::Polynomial {
+                degree: 2,
+                coef0: 1.0,
+            },
+        ] {
+            let mut model = Svc::new(1.0, kernel);
+            model.fit(&data, &labels).unwrap();
+            assert!(model.converged());
+            assert!(accuracy(model.predict(&test_data).vector(), &test_labels) > 0.97);
+            assert!(model.num_support_vectors() < 200);
+            assert!(model.support_vectors().shape() == (model.num_support_vectors(), 2));
+            let scores = model.decision_function(&test_data);
+            let predictions = model.predict(&test_data);
+            assert!(scores.shape() == (400, 1));
+            assert!((0..400).all(|i| (scores.get(i).get(0) > 0.0) == (predictions.get(i) == 1.0)));
+        }
+
+        // a tiny cache gives the same model as the default one
+        let mut cached = Svc::new(1.0, Kernel::Rbf);
+        cached.fit(&data, &labels).unwrap();
+        let mut uncached = Svc::new(1.0, Kernel::Rbf);
+        uncached.set_cache_size(0);
+        uncached.fit(&data, &labels).unwrap();
+        assert!(cached.support() == uncached.support());
+        assert!(cached.gamma() == uncached.gamma());
+    }
+
+    #[test]
+    fn test_kernel_svc_margins_and_weights() {
+        // the linear kernel on separable data recovers the hard margin
+        let data = Matrix::to_matrix(&vec![
+            vec![0.0, 0.0],
+            vec![1.0, 0.0],
+            vec![0.0, 1.0],
+            vec![3.0, 3.0],
+            vec![4.0, 3.0],
+            vec![3.0, 4.0],
+        ]);
+        let labels = Matrix::to_matrix(&vec![
+            vec![-1.0],
+            vec![-1.0],
+            vec![-1.0],
+            vec![1.0],
+            vec![1.0],
+            vec![1.0],
+        ]);
+        let mut model = Svc::new(100.0, Kernel::Linear);
+        model.fit(&data, &labels).unwrap();
+        assert!(model.support() == &vec![1, 2, 3]);
+        let scores = model.decision_function(&data);
+        for i in [1, 2] {
+            assert!((scores.get(i).get(0) + 1.0).abs() < 1e-2);
+        }
+        assert!((scores.get(3).get(0) - 1.0).abs() < 1e-2);
+
+        // weighting a rare overlapping class raises its recall
+        let mut rng = Rng::new(4);
+        let mut data = Vec::new();
+        let mut labels = Vec::new();
+        for i in 0..400 {
+            let rare = i % 8 == 0;
+            let center = if rare { 1.0 } else { -1.0 };
+            data.push(vec![center + rng.normal(), rng.normal()]);
+            labels.push(vec![rare as usize as f32]);
+        }
+        let (data, labels) = (Matrix::to_matrix(&data), Matrix::to_matrix(&labels));
+        let recall = |model: &Svc| {
+            let predictions = model.predict(&data);
+            (0..400)
+                .filter(|i| i % 8 == 0 && predictions.get(*i) == 1.0)
+                .count()
+        };
+        let mut plain = Svc::new(1.0, Kernel::Rbf);
+        plain.fit(&data, &labels).unwrap();
+        let mut weighted = Svc::new(1.0, Kernel::Rbf);
+        weighted.set_class_weight(ClassWeight::Custom(vec![(1.0, 8.0)]));
+        weighted.fit(&data, &labels).unwrap();
+        assert!(recall(&weighted) > recall(&plain) + 10);
+    }
+
+    #[test]
+    fn test_kernel_svc_one_versus_one() {
+        let mut rng = Rng::new(5);
+        let centers = [(0.0, 4.0), (4.0, -3.0), (-4.0, -3.0), (0.0, 0.0)];
+        let mut data = Vec::new();
+        let mut labels = Vec::new();
+        for i in 0..400 {
+            let (x, y) = centers[i % 4];
+            data.push(vec![x + 0.7 * rng.normal(), y + 0.7 * rng.normal()]);
+            labels.push(vec![(i % 4) as f32 * 2.0]);
+        }
+        let (data, labels) = (Matrix::to_matrix(&data), Matrix::to_matrix(&labels));
+        let mut model = Svc::new(1.0, Kernel::Rbf);
+        model.set_gamma(Gamma::Value(0.5));
+        model.fit(&data, &labels).unwrap();
+        assert!(model.classes() == &vec![0.0, 2.0, 4.0, 6.0]);
+        assert!(model.gamma() == 0.5);
+        // the middle class can't be cut off by any single line
+        assert!(model.decision_function(&data).shape() == (400, 6));
+        assert!(accuracy(model.predict(&data).vector(), &labels_from_matrix(&labels)) > 0.97);
+    }
+}
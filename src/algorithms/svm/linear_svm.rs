@@ -0,0 +1,772 @@
+use crate::algorithms::estimators::{
+    Classifier, Estimator, ParamError, ParamValue, Regressor, encode_classes, labels_from_matrix,
+};
+use crate::algorithms::logistic_regression::logistic_regression::{ClassWeight, class_weights};
+use crate::linear_algebra::{LinalgError, Matrix, RowVector, kernels};
+use crate::parallel::parallel::parallel_map;
+use crate::sampling::random::Rng;
+
+/// `DualCoordinateDescent` optimizes one dual variable per row at a time and
+/// stops once the largest violation of the optimality conditions within a
+/// pass has shrunk by `tolerance` relative to the first pass, the bias is
+/// learned as the weight of a constant feature and so is regularized too.
+/// `Sgd` takes averaged stochastic subgradient steps on the primal with an
+/// unregularized bias and stops when an epoch barely changes the objective.
+#[derive(Clone, Debug, PartialEq)]
+pub enum LinearSvmSolver {
+    DualCoordinateDescent,
+    Sgd,
+}
+
+/// Linear support vector classifier minimizing
+/// `||w||^2 / 2 + C * sum(class_weight * max(0, 1 - y * (w·x + b)))`.
+/// More than two classes are fitted one class against the rest.
+pub struct LinearSvc {
+    c: f32,
+    solver: LinearSvmSolver,
+    class_weight: ClassWeight,
+    max_iterations: usize,
+    tolerance: f32,
+    seed: u64,
+    classes: Vec<f32>,
+    // one row of weights and one bias per score, a single score for two classes
+    weights: Vec<Vec<f32>>,
+    biases: Vec<f32>,
+    iterations: usize,
+    converged: bool,
+}
+
+/// Linear support vector regressor minimizing
+/// `||w||^2 / 2 + C * sum(max(0, |y - (w·x + b)| - epsilon))`, errors inside
+/// the epsilon tube cost nothing.
+pub struct LinearSvr {
+    c: f32,
+    epsilon: f32,
+    solver: LinearSvmSolver,
+    max_iterations: usize,
+    tolerance: f32,
+    seed: u64,
+    weights: Vec<f32>,
+    bias: f32,
+    iterations: usize,
+    converged: bool,
+}
+
+// weights then bias of one fitted margin, with the epochs it took
+struct Margin {
+    parameters: Vec<f64>,
+    epochs: usize,
+    converged: bool,
+}
+
+#[derive(Clone, Copy)]
+enum Loss {
+    Hinge,
+    EpsilonInsensitive(f64),
+}
+
+struct SolverOptions {
+    max_epochs: usize,
+    tolerance: f64,
+    seed: u64,
+}
+
+impl LinearSvc {
+    pub fn new(c: f32) -> Self {
+        return Self {
+            c: positive_c(c),
+            solver: LinearSvmSolver::DualCoordinateDescent,
+            class_weight: ClassWeight::Uniform,
+            max_iterations: 1000,
+            tolerance: 1e-3,
+            seed: 0,
+            classes: Vec::new(),
+            weights: Vec::new(),
+            biases: Vec::new(),
+            iterations: 0,
+            converged: false,
+        };
+    }
+
+    pub fn set_solver(&mut self, solver: LinearSvmSolver) {
+        self.solver = solver;
+    }
+
+    /// Scales `C` per class.
+    pub fn set_class_weight(&mut self, class_weight: ClassWeight) {
+        self.class_weight = class_weight;
+    }
+
+    /// Most passes over the training rows.
+    pub fn set_max_iterations(&mut self, max_iterations: usize) {
+        self.max_iterations = max_iterations.max(1);
+    }
+
+    /// Relative stopping threshold, see `LinearSvmSolver`.
+    pub fn set_tolerance(&mut self, tolerance: f32) {
+        self.tolerance = tolerance;
+    }
+
+    /// Seed of the row order within every pass.
+    pub fn set_seed(&mut self, seed: u64) {
+        self.seed = seed;
+    }
+
+    /// `labels` is a single column of class values.
+    pub fn fit(&mut self, data: &Matrix, labels: &Matrix) -> Result<(), LinalgError> {
+        let rows = data.len();
+        if labels.len() != rows {
+            return Err(LinalgError::DimensionMismatch {
+                expected: (rows, 1),
+                found: labels.shape(),
+            });
+        }
+        let (classes, targets) = encode_classes(&labels_from_matrix(labels));
+        let class_weights = class_weights(&self.class_weight, &classes, &targets);
+        let costs: Vec<f64> = targets
+            .iter()
+            .map(|target| self.c as f64 * class_weights[*target])
+            .collect();
+        let data_f64 = data.to_f64();
+        // the second class is positive with two classes, otherwise each class
+        // against the rest
+        let num_scores = if classes.len() == 2 { 1 } else { classes.len() };
+        let mut rng = Rng::new(self.seed);
+        let seeds: Vec<u64> = (0..num_scores).map(|_| rng.next_u64()).collect();
+        let margins: Vec<Margin> = parallel_map(num_scores, 1, |score| {
+            let positive = if num_scores == 1 { 1 } else { score };
+            let signs: Vec<f64> = targets
+                .iter()
+                .map(|target| if *target == positive { 1.0 } else { -1.0 })
+                .collect();
+            let options = SolverOptions {
+                max_epochs: self.max_iterations,
+                tolerance: self.tolerance as f64,
+                seed: seeds[score],
+            };
+            match self.solver {
+                LinearSvmSolver::DualCoordinateDescent => {
+                    dual_coordinate_descent(&data_f64, &signs, &costs, Loss::Hinge, &options)
+                }
+                LinearSvmSolver::Sgd => sgd(&data_f64, &signs, &costs, Loss::Hinge, &options),
+            }
+        });
+        let columns = data.shape().1;
+        self.weights = margins
+            .iter()
+            .map(|margin| {
+                margin.parameters[..columns]
+                    .iter()
+                    .map(|w| *w as f32)
+                    .collect()
+            })
+            .collect();
+        self.biases = margins
+            .iter()
+            .map(|margin| margin.parameters[columns] as f32)
+            .collect();
+        self.iterations = margins.iter().map(|margin| margin.epochs).max().unwrap();
+        self.converged = margins.iter().all(|margin| margin.converged);
+        self.classes = classes;
+        Ok(())
+    }
+
+    /// Signed distances to the margins, one column for two classes (positive
+    /// towards the second class) and one per class otherwise.
+    pub fn decision_function(&self, data: &Matrix) -> Matrix {
+        return Matrix::new(
+            &data
+                .matrix()
+                .iter()
+                .map(|row| {
+                    RowVector::new(
+                        &self
+                            .weights
+                            .iter()
+                            .zip(self.biases.iter())
+                            .map(|(weights, bias)| kernels::dot(row.vector(), weights) + bias)
+                            .collect(),
+                    )
+                })
+                .collect(),
+        );
+    }
+
+    pub fn predict(&self, data: &Matrix) -> RowVector {
+        let scores = self.decision_function(data);
+        return RowVector::new(
+            &scores
+                .matrix()
+                .iter()
+                .map(|row| {
+                    if row.len() == 1 {
+                        return self.classes[(row.get(0) > 0.0) as usize];
+                    }
+                    let best = row
+                        .vector()
+                        .iter()
+                        .enumerate()
+                        .fold(0, |best, (i, s)| if *s > row.get(best) { i } else { best });
+                    self.classes[best]
+                })
+                .collect(),
+        );
+    }
+
+    pub fn classes(&self) -> &Vec<f32> {
+        return &self.classes;
+    }
+
+    pub fn weights(&self) -> &Vec<Vec<f32>> {
+        return &self.weights;
+    }
+
+    pub fn biases(&self) -> &Vec<f32> {
+        return &self.biases;
+    }
+
+    /// Passes over the rows, the most over all one-vs-rest margins.
+    pub fn iterations(&self) -> usize {
+        return self.iterations;
+    }
+
+    pub fn converged(&self) -> bool {
+        return self.converged;
+    }
+}
+
+impl LinearSvr {
+    pub fn new(c: f32, epsilon: f32) -> Self {
+        if epsilon.is_nan() || epsilon < 0.0 {
+            panic!("epsilon must not be negative");
+        }
+        return Self {
+            c: positive_c(c),
+            epsilon,
+            solver: LinearSvmSolver::DualCoordinateDescent,
+            max_iterations: 1000,
+            tolerance: 1e-3,
+            seed: 0,
+            weights: Vec::new(),
+            bias: 0.0,
+            iterations: 0,
+            converged: false,
+        };
+    }
+
+    pub fn set_solver(&mut self, solver: LinearSvmSolver) {
+        self.solver = solver;
+    }
+
+    /// Most passes over the training rows.
+    pub fn set_max_iterations(&mut self, max_iterations: usize) {
+        self.max_iterations = max_iterations.max(1);
+    }
+
+    /// Relative stopping threshold, see `LinearSvmSolver`.
+    pub fn set_tolerance(&mut self, tolerance: f32) {
+        self.tolerance = tolerance;
+    }
+
+    /// Seed of the row order within every pass.
+    pub fn set_seed(&mut self, seed: u64) {
+        self.seed = seed;
+    }
+
+    /// `labels` is a single column of targets.
+    pub fn fit(&mut self, data: &Matrix, labels: &Matrix) -> Result<(), LinalgError> {
+        let (rows, columns) = data.shape();
+        if labels.len() != rows {
+            return Err(LinalgError::DimensionMismatch {
+                expected: (rows, 1),
+                found: labels.shape(),
+            });
+        }
+        let targets: Vec<f64> = labels
+            .matrix()
+            .iter()
+            .map(|label| label.get(0) as f64)
+            .collect();
+        let costs = vec![self.c as f64; rows];
+        let loss = Loss::EpsilonInsensitive(self.epsilon as f64);
+        let options = SolverOptions {
+            max_epochs: self.max_iterations,
+            tolerance: self.tolerance as f64,
+            seed: self.seed,
+        };
+        let data_f64 = data.to_f64();
+        let margin = match self.solver {
+            LinearSvmSolver::DualCoordinateDescent => {
+                dual_coordinate_descent(&data_f64, &targets, &costs, loss, &options)
+            }
+            LinearSvmSolver::Sgd => sgd(&data_f64, &targets, &costs, loss, &options),
+        };
+        self.weights = margin.parameters[..columns]
+            .iter()
+            .map(|w| *w as f32)
+            .collect();
+        self.bias = margin.parameters[columns] as f32;
+        self.iterations = margin.epochs;
+        self.converged = margin.converged;
+        Ok(())
+    }
+
+    pub fn predict(&self, data: &Matrix) -> RowVector {
+        return RowVector::new(
+            &data
+                .matrix()
+                .iter()
+                .map(|row| kernels::dot(row.vector(), &self.weights) + self.bias)
+                .collect(),
+        );
+    }
+
+    pub fn weights(&self) -> &Vec<f32> {
+        return &self.weights;
+    }
+
+    pub fn bias(&self) -> f32 {
+        return self.bias;
+    }
+
+    pub fn iterations(&self) -> usize {
+        return self.iterations;
+    }
+
+    pub fn converged(&self) -> bool {
+        return self.converged;
+    }
+}
+
+fn score(parameters: &[f64], row: &[f64]) -> f64 {
+    let bias = parameters[row.len()];
+    return row.iter().zip(parameters).map(|(x, w)| x * w).sum::<f64>() + bias;
+}
+
+// dual coordinate descent (hsieh et al. 2008, ho and lin 2012) over rows
+// extended with a constant feature; `targets` are ±1 signs for the hinge loss
+// and the regression targets otherwise, `costs` bound every dual variable
+fn dual_coordinate_descent(
+    data: &Vec<Vec<f64>>,
+    targets: &Vec<f64>,
+    costs: &Vec<f64>,
+    loss: Loss,
+    options: &SolverOptions,
+) -> Margin {
+    let width = data.first().map_or(0, |row| row.len()) + 1;
+    let mut parameters = vec![0.0; width];
+    let mut duals = vec![0.0; data.len()];
+    let diagonal: Vec<f64> = data
+        .iter()
+        .map(|row| row.iter().map(|x| x * x).sum::<f64>() + 1.0)
+        .collect();
+    let mut order: Vec<usize> = (0..data.len()).collect();
+    let mut rng = Rng::new(options.seed);
+    let mut first_violation = None;
+    for epoch in 0..options.max_epochs {
+        rng.shuffle(&mut order);
+        let mut violation: f64 = 0.0;
+        for i in order.iter().copied() {
+            let (dual, bound) = (duals[i], costs[i]);
+            if bound <= 0.0 {
+                continue;
+            }
+            let prediction = score(&parameters, &data[i]);
+            let updated = match loss {
+                Loss::Hinge => {
+                    let gradient = targets[i] * prediction - 1.0;
+                    let projected = if dual <= 0.0 {
+                        gradient.min(0.0)
+                    } else if dual >= bound {
+                        gradient.max(0.0)
+                    } else {
+                        gradient
+                    };
+                    violation = violation.max(projected.abs());
+                    (dual - gradient / diagonal[i]).clamp(0.0, bound)
+                }
+                Loss::EpsilonInsensitive(epsilon) => {
+                    // the dual variable is signed, its absolute value costs epsilon
+                    let gradient = prediction - targets[i];
+                    let (upper, lower) = (gradient + epsilon, gradient - epsilon);
+                    let projected = if dual == 0.0 {
+                        if upper < 0.0 {
+                            -upper
+                        } else if lower > 0.0 {
+                            lower
+                        } else {
+                            0.0
+                        }
+                    } else if dual >= bound {
+                        upper.max(0.0)
+                    } else if dual <= -bound {
+                        (-lower).max(0.0)
+                    } else if dual > 0.0 {
+                        upper.abs()
+                    } else {
+                        lower.abs()
+                    };
+                    violation = violation.max(projected);
+                    let step = if upper < diagonal[i] * dual {
+                        -upper / diagonal[i]
+                    } else if lower > diagonal[i] * dual {
+                        -lower / diagonal[i]
+                    } else {
+                        -dual
+                    };
+                    (dual + step).clamp(-bound, bound)
+                }
+            };
+            let change = match loss {
+                Loss::Hinge => (updated - dual) * targets[i],
+                Loss::EpsilonInsensitive(_) => updated - dual,
+            };
+            if change != 0.0 {
+                duals[i] = updated;
+                parameters
+                    .iter_mut()
+                    .zip(&data[i])
+                    .for_each(|(w, x)| *w += change * x);
+                parameters[width - 1] += change;
+            }
+        }
+        let initial = *first_violation.get_or_insert(violation);
+        if violation <= options.tolerance * initial {
+            return Margin {
+                parameters,
+                epochs: epoch + 1,
+                converged: true,
+            };
+        }
+    }
+    return Margin {
+        parameters,
+        epochs: options.max_epochs,
+        converged: false,
+    };
+}
+
+fn primal_objective(
+    data: &Vec<Vec<f64>>,
+    targets: &Vec<f64>,
+    costs: &Vec<f64>,
+    loss: Loss,
+    parameters: &[f64],
+) -> f64 {
+    let width = parameters.len();
+    let regularization: f64 = parameters[..width - 1].iter().map(|w| w * w).sum::<f64>() / 2.0;
+    let total: f64 = data
+        .iter()
+        .zip(targets.iter().zip(costs))
+        .map(|(row, (target, cost))| {
+            let prediction = score(parameters, row);
+            cost * match loss {
+                Loss::Hinge => (1.0 - target * prediction).max(0.0),
+                Loss::EpsilonInsensitive(epsilon) => {
+                    ((target - prediction).abs() - epsilon).max(0.0)
+                }
+            }
+        })
+        .sum();
+    return regularization + total;
+}
+
+// averaged stochastic subgradient descent on the primal divided by
+// `C * rows`, with steps `eta0 / (1 + eta0 * lambda * t)`
+fn sgd(
+    data: &Vec<Vec<f64>>,
+    targets: &Vec<f64>,
+    costs: &Vec<f64>,
+    loss: Loss,
+    options: &SolverOptions,
+) -> Margin {
+    let width = data.first().map_or(0, |row| row.len()) + 1;
+    let rows = data.len().max(1) as f64;
+    let mean_cost = costs.iter().sum::<f64>() / rows;
+    let lambda = 1.0 / (mean_cost * rows);
+    let initial_rate = 0.1;
+    let mut parameters = vec![0.0; width];
+    let mut averaged = vec![0.0; width];
+    let mut averaged_steps = 0.0;
+    let mut order: Vec<usize> = (0..data.len()).collect();
+    let mut rng = Rng::new(options.seed);
+    let mut step: f64 = 0.0;
+    let mut objective = f64::INFINITY;
+    for epoch in 0..options.max_epochs {
+        rng.shuffle(&mut order);
+        for i in order.iter().copied() {
+            let rate = initial_rate / (1.0 + initial_rate * lambda * step);
+            step += 1.0;
+            let prediction = score(&parameters, &data[i]);
+            // derivative of the loss with respect to the prediction
+            let slope = match loss {
+                Loss::Hinge if targets[i] * prediction < 1.0 => -targets[i],
+                Loss::EpsilonInsensitive(epsilon) if prediction - targets[i] > epsilon => 1.0,
+                Loss::EpsilonInsensitive(epsilon) if targets[i] - prediction > epsilon => -1.0,
+                _ => 0.0,
+            } * costs[i]
+                / mean_cost;
+            let shrink = 1.0 - rate * lambda;
+            parameters[..width - 1]
+                .iter_mut()
+                .zip(&data[i])
+                .for_each(|(w, x)| *w = shrink * *w - rate * slope * x);
+            parameters[width - 1] -= rate * slope;
+            // average from the second epoch on, the first one is burn in
+            if epoch > 0 {
+                averaged_steps += 1.0;
+                averaged
+                    .iter_mut()
+                    .zip(&parameters)
+                    .for_each(|(a, w)| *a += (w - *a) / averaged_steps);
+            }
+        }
+        let current = if epoch > 0 { &averaged } else { &parameters };
+        let next = primal_objective(data, targets, costs, loss, current);
+        if (objective - next).abs() <= options.tolerance * next.abs().max(1.0) {
+            return Margin {
+                parameters: current.clone(),
+                epochs: epoch + 1,
+                converged: true,
+            };
+        }
+        objective = next;
+    }
+    return Margin {
+        parameters: if options.max_epochs > 1 {
+            averaged
+        } else {
+            parameters
+        },
+        epochs: options.max_epochs,
+        converged: false,
+    };
+}
+
+impl Estimator for LinearSvc {
+    fn params(&self) -> Vec<(&'static str, ParamValue)> {
+        return vec![
+            ("c", ParamValue::Float(self.c)),
+            ("max_iterations", ParamValue::Int(self.max_iterations)),
+            ("tolerance", ParamValue::Float(self.tolerance)),
+            ("seed", ParamValue::Int(self.seed as usize)),
+        ];
+    }
+
+    fn set_param(&mut self, name: &str, value: ParamValue) -> Result<(), ParamError> {
+        match name {
+            "c" => self.c = positive_c(value.as_float(name)?),
+            "max_iterations" => self.set_max_iterations(value.as_int(name)?),
+            "tolerance" => self.set_tolerance(value.as_float(name)?),
+            "seed" => self.set_seed(value.as_int(name)? as u64),
+            _ => return Err(ParamError::Unknown(name.to_string())),
+        }
+        Ok(())
+    }
+}
+
+impl Classifier for LinearSvc {
+    fn fit(&mut self, data: &Matrix, labels: &Matrix) -> Result<(), LinalgError> {
+        return LinearSvc::fit(self, data, labels);
+    }
+
+    fn predict(&self, data: &Matrix) -> RowVector {
+        return LinearSvc::predict(self, data);
+    }
+
+    fn classes(&self) -> &Vec<f32> {
+        return LinearSvc::classes(self);
+    }
+}
+
+impl Estimator for LinearSvr {
+    fn params(&self) -> Vec<(&'static str, ParamValue)> {
+        return vec![
+            ("c", ParamValue::Float(self.c)),
+            ("epsilon", ParamValue::Float(self.epsilon)),
+            ("max_iterations", ParamValue::Int(self.max_iterations)),
+            ("tolerance", ParamValue::Float(self.tolerance)),
+            ("seed", ParamValue::Int(self.seed as usize)),
+        ];
+    }
+
+    fn set_param(&mut self, name: &str, value: ParamValue) -> Result<(), ParamError> {
+        match name {
+            "c" => self.c = positive_c(value.as_float(name)?),
+            "epsilon" => {
+                let epsilon = value.as_float(name)?;
+                if epsilon.is_nan() || epsilon < 0.0 {
+                    panic!("epsilon must not be negative");
+                }
+                self.epsilon = epsilon;
+            }
+            "max_iterations" => self.set_max_iterations(value.as_int(name)?),
+            "tolerance" => self.set_tolerance(value.as_float(name)?),
+            "seed" => self.set_seed(value.as_int(name)? as u64),
+            _ => return Err(ParamError::Unknown(name.to_string())),
+        }
+        Ok(())
+    }
+}
+
+impl Regressor for LinearSvr {
+    fn fit(&mut self, data: &Matrix, labels: &Matrix) -> Result<(), LinalgError> {
+        return LinearSvr::fit(self, data, labels);
+    }
+
+    fn predict(&self, data: &Matrix) -> RowVector {
+        return LinearSvr::predict(self, data);
+    }
+}
+
+pub(crate) fn positive_c(c: f32) -> f32 {
+    if c.is_nan() || c <= 0.0 {
+        panic!("C must be positive");
+    }
+    return c;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::estimators::{binary_data, labels_from_dataframe};
+    use crate::dataframe::csv::df_from_csv;
+    use crate::inference::inference::{accuracy, rmse};
+    use crate::pipeline::{
+        encoders::one_hot_encoder::OneHotEncoder,
+        imputers::imputer::{Imputer, ImputerStrategy},
+        pipeline::{CategoricalPipeline, ColumnTransformer, NumericalPipeline},
+        scalars::standard_scalar::StandardScalar,
+        transformers::Transformer,
+    };
+
+    #[test]
+    fn test_linear_svc_solvers_agree() {
+        let (data, labels) = binary_data(600, 1, 0.3);
+        let mut dual = LinearSvc::new(1.0);
+        dual.fit(&data, &labels).unwrap();
+        let mut sgd = LinearSvc::new(1.0);
+        sgd.set_solver(LinearSvmSolver::Sgd);
+        sgd.fit(&data, &labels).unwrap();
+        assert!(dual.converged() && sgd.converged());
+        for model in [&dual, &sgd] {
+            let predictions = model.predict(&data);
+            assert!(accuracy(predictions.vector(), &labels_from_matrix(&labels)) > 0.9);
+            let weights = &model.weights()[0];
+            assert!(weights[0] > 0.0 && weights[1] < 0.0);
+            assert!(weights[2].abs() < 0.3 * weights[0]);
+        }
+        // the same direction up to scale
+        let (a, b) = (&dual.weights()[0], &sgd.weights()[0]);
+        let cosine = kernels::dot(a, b) / (kernels::dot(a, a) * kernels::dot(b, b)).sqrt();
+        assert!(cosine > 0.98);
+        let scores = dual.decision_function(&data);
+        assert!(scores.shape() == (600, 1));
+        let predictions = dual.predict(&data);
+        assert!((0..600).all(|i| (scores.get(i).get(0) > 0.0) == (predictions.get(i) == 1.0)));
+    }
+
+    #[test]
+    fn test_linear_svc_class_weights_and_classes() {
+        // a rare class overlapping the common one
+        let mut rng = Rng::new(2);
+        let mut data = Vec::new();
+        let mut labels = Vec::new();
+        for i in 0..1000 {
+            let rare = i % 10 == 0;
+            let center = if rare { 1.0 } else { -1.0 };
+            data.push(vec![center + rng.normal(), rng.normal()]);
+            labels.push(vec![if rare { 7.0 } else { 3.0 }]);
+        }
+        let (data, labels) = (Matrix::to_matrix(&data), Matrix::to_matrix(&labels));
+        let recall = |model: &LinearSvc| {
+            let predictions = model.predict(&data);
+            (0..1000)
+                .filter(|i| i % 10 == 0 && predictions.get(*i) == 7.0)
+                .count()
+        };
+        let mut plain = LinearSvc::new(1.0);
+        plain.fit(&data, &labels).unwrap();
+        let mut balanced = LinearSvc::new(1.0);
+        balanced.set_class_weight(ClassWeight::Balanced);
+        balanced.fit(&data, &labels).unwrap();
+        assert!(plain.classes() == &vec![3.0, 7.0]);
+        assert!(recall(&balanced) > 2 * recall(&plain));
+        assert!(recall(&balanced) > 60);
+
+        // one against the rest over three classes
+        let centers = [(0.0, 4.0), (4.0, -3.0), (-4.0, -3.0)];
+        let mut data = Vec::new();
+        let mut labels = Vec::new();
+        for i in 0..300 {
+            let (x, y) = centers[i % 3];
+            data.push(vec![x + rng.normal(), y + rng.normal()]);
+            labels.push(vec![(i % 3) as f32]);
+        }
+        let (data, labels) = (Matrix::to_matrix(&data), Matrix::to_matrix(&labels));
+        for solver in [LinearSvmSolver::DualCoordinateDescent, LinearSvmSolver::Sgd] {
+            let mut model = LinearSvc::new(1.0);
+            model.set_solver(solver);
+            model.fit(&data, &labels).unwrap();
+            assert!(model.weights().len() == 3);
+            assert!(model.decision_function(&data).shape() == (300, 3));
+            assert!(accuracy(model.predict(&data).vector(), &labels_from_matrix(&labels)) > 0.95);
+        }
+    }
+
+    #[test]
+    fn test_linear_svr() {
+        let mut rng = Rng::new(3);
+        let data: Vec<Vec<f32>> = (0..500).map(|_| vec![rng.normal(), rng.normal()]).collect();
+        let labels: Vec<Vec<f32>> = data
+            .iter()
+            .map(|x| vec![3.0 * x[0] - 2.0 * x[1] + 1.0 + 0.1 * rng.normal()])
+            .collect();
+        let (data, labels) = (Matrix::to_matrix(&data), Matrix::to_matrix(&labels));
+        for solver in [LinearSvmSolver::DualCoordinateDescent, LinearSvmSolver::Sgd] {
+            let mut model = LinearSvr::new(1.0, 0.1);
+            model.set_tolerance(1e-2);
+            model.set_solver(solver);
+            model.fit(&data, &labels).unwrap();
+            assert!(model.converged());
+            assert!((model.weights()[0] - 3.0).abs() < 0.05);
+            assert!((model.weights()[1] + 2.0).abs() < 0.05);
+            assert!((model.bias() - 1.0).abs() < 0.05);
+            assert!(rmse(model.predict(&data).vector(), &labels_from_matrix(&labels)) < 0.15);
+        }
+        // a wide tube leaves most rows inside it
+        let mut wide = LinearSvr::new(10.0, 1.0);
+        wide.fit(&data, &labels).unwrap();
+        let predictions = wide.predict(&data);
+        let inside = (0..500)
+            .filter(|i| (predictions.get(*i) - labels.get(*i).get(0)).abs() <= 1.0 + 1e-3)
+            .count();
+        assert!(inside > 450);
+    }
+
+    #[test]
+    fn test_linear_svc_on_transformed_housing() {
+        let mut df = df_from_csv("housing.csv", Some(3000));
+        let values = labels_from_dataframe(&df, "median_house_value");
+        let mut sorted = values.clone();
+        sorted.sort_by(|a, b| a.total_cmp(b));
+        let median = sorted[sorted.len() / 2];
+        let labels: Vec<Vec<f32>> = values
+            .iter()
+            .map(|value| vec![(*value > median) as usize as f32])
+            .collect();
+        df.remove_column("median_house_value");
+        let encoder: Box<dyn Transformer> = Box::new(OneHotEncoder::new(false));
+        let imputer: Box<dyn Transformer> = Box::new(Imputer::new(&ImputerStrategy::Median));
+        let scalar: Box<dyn Transformer> = Box::new(StandardScalar::new());
+        let transformer = ColumnTransformer::new(
+            NumericalPipeline::new(vec![imputer, scalar]),
+            CategoricalPipeline::new(vec![encoder]),
+        );
+        let data = transformer.transform(&df);
+        let labels = Matrix::to_matrix(&labels);
+        let mut model = LinearSvc::new(1.0);
+        model.fit(&data, &labels).unwrap();
+        assert!(accuracy(model.predict(&data).vector(), &labels_from_matrix(&labels)) > 0.8);
+    }
+}
@@ -0,0 +1,4 @@
+pub mod kernel_svm;
+pub mod linear_svm;
+pub use kernel_svm::{Gamma, Kernel, Svc};
+pub use linear_svm::{LinearSvc, LinearSvmSolver, LinearSvr};
@@ -80,6 +80,29 @@ impl DataFrame {
         values[row_index] = new_value;
     }
 
+    pub fn replace_column_values(&mut self, column_name: &str, new_values: Vec<DataTypeValue>) {
+        let len = self.len();
+        let (dtype, values) = self.get_column_mut(column_name);
+        assert!(
+            new_values.len() == len,
+            "column {} needs {} values, got {}",
+            column_name,
+            len,
+            new_values.len()
+        );
+        let right_dtype = new_values.iter().all(|value| match dtype {
+            DataType::Float => matches!(value, DataTypeValue::Float(_) | DataTypeValue::Null),
+            DataType::String => matches!(value, DataTypeValue::String(_) | DataTypeValue::Null),
+            DataType::Id => false,
+        });
+        assert!(
+            right_dtype,
+            "column {} has a datatype {:?} that cant hold the new values",
+            column_name, dtype
+        );
+        *values = new_values;
+    }
+
     pub fn get_cell_value(&self, column_name: &str, row_index: usize) -> &DataTypeValue {
         let (_, values) = self.get_column(column_name);
         return &values[row_index];
@@ -639,6 +662,25 @@ mod tests {
         }));
     }
 
+    #[test]
+    fn test_replace_column_values() {
+        let row_limit = 10;
+        let mut df = dataframe(row_limit);
+        let new_values = vec![DataTypeValue::Float(1.0); row_limit];
+        df.replace_column_values("median_income", new_values.clone());
+        let (_, values) = df.get_column("median_income");
+        assert!(*values == new_values);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_replace_column_values_wrong_dtype() {
+        let row_limit = 10;
+        let mut df = dataframe(row_limit);
+        let new_values = vec![DataTypeValue::String("a".to_string()); row_limit];
+        df.replace_column_values("median_income", new_values);
+    }
+
     #[test]
     fn test_get_columns_by_index() {
         let row_limit = 10;
@@ -102,7 +102,7 @@ impl PartialEq for DataTypeValue {
 
 impl Eq for DataTypeValue {}
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum DataType {
     Float,
     String,
@@ -1,3 +1,5 @@
+use crate::linear_algebra::{Matrix, kernels};
+use crate::parallel::parallel::parallel_map;
 use std::iter::zip;
 
 pub fn mse(predictions: &Vec<f32>, labels: &Vec<f32>) -> f32 {
@@ -12,3 +14,74 @@ pub fn mse(predictions: &Vec<f32>, labels: &Vec<f32>) -> f32 {
 pub fn rmse(predictions: &Vec<f32>, labels: &Vec<f32>) -> f32 {
     f32::sqrt(mse(predictions, labels))
 }
+
+/// Share of predictions equal to their label.
+pub fn accuracy(predictions: &Vec<f32>, labels: &Vec<f32>) -> f32 {
+    let correct = zip(predictions, labels)
+        .filter(|(prediction, label)| prediction == label)
+        .count();
+    return correct as f32 / labels.len() as f32;
+}
+
+/// Coefficient of determination, 1 for a perfect fit and 0 for always
+/// predicting the mean label.
+pub fn r2_score(predictions: &Vec<f32>, labels: &Vec<f32>) -> f32 {
+    let mean = labels.iter().sum::<f32>() / labels.len() as f32;
+    let total: f32 = labels.iter().map(|label| (label - mean).powf(2.0)).sum();
+    let residual: f32 = zip(predictions, labels)
+        .map(|(prediction, label)| (label - prediction).powf(2.0))
+        .sum();
+    if total == 0.0 {
+        return if residual == 0.0 { 1.0 } else { 0.0 };
+    }
+    return 1.0 - residual / total;
+}
+
+/// Mean silhouette of the rows of `data` under the euclidean distance, from
+/// -1 (rows closer to another cluster) to 1 (tight, well separated clusters).
+/// Rows with a negative label are noise and left out, a row alone in its
+/// cluster scores 0, and fewer than two clusters score 0 overall.
+pub fn silhouette_score(data: &Matrix, labels: &Vec<f32>) -> f32 {
+    let rows: Vec<usize> = (0..labels.len())
+        .filter(|row| labels[*row] >= 0.0)
+        .collect();
+    let mut clusters: Vec<f32> = rows.iter().map(|row| labels[*row]).collect();
+    clusters.sort_by(|a, b| a.total_cmp(b));
+    clusters.dedup();
+    if clusters.len() < 2 {
+        return 0.0;
+    }
+    let cluster_of: Vec<usize> = rows
+        .iter()
+        .map(|row| clusters.iter().position(|c| *c == labels[*row]).unwrap())
+        .collect();
+    let sizes = cluster_of
+        .iter()
+        .fold(vec![0; clusters.len()], |mut sizes, c| {
+            sizes[*c] += 1;
+            sizes
+        });
+    let scores = parallel_map(rows.len(), 64, |i| {
+        if sizes[cluster_of[i]] == 1 {
+            return 0.0;
+        }
+        let mut totals = vec![0.0_f64; clusters.len()];
+        let point = data.get(rows[i]).vector();
+        for (j, other) in rows.iter().enumerate() {
+            let difference = kernels::subtract(point, data.get(*other).vector());
+            totals[cluster_of[j]] += (kernels::squared_norm(&difference) as f64).sqrt();
+        }
+        let own = totals[cluster_of[i]] / (sizes[cluster_of[i]] - 1) as f64;
+        let nearest = (0..clusters.len())
+            .filter(|c| *c != cluster_of[i])
+            .map(|c| totals[c] / sizes[c] as f64)
+            .fold(f64::INFINITY, f64::min);
+        let spread = own.max(nearest);
+        if spread == 0.0 {
+            0.0
+        } else {
+            (nearest - own) / spread
+        }
+    });
+    return (scores.iter().sum::<f64>() / scores.len() as f64) as f32;
+}
@@ -2,5 +2,6 @@ pub mod algorithms;
 pub mod dataframe;
 pub mod inference;
 pub mod linear_algebra;
+pub mod parallel;
 pub mod pipeline;
 pub mod sampling;
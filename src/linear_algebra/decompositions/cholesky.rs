@@ -0,0 +1,124 @@
+use crate::linear_algebra::{LinalgError, Matrix};
+
+/// `A = L * L^T` for a symmetric positive definite `A`.
+pub struct CholeskyDecomposition {
+    l: Vec<Vec<f64>>,
+}
+
+impl CholeskyDecomposition {
+    pub fn new(m: &Matrix) -> Result<Self, LinalgError> {
+        let (rows, columns) = m.shape();
+        if rows != columns {
+            return Err(LinalgError::NotSquare { rows, columns });
+        }
+        let n = rows;
+        let a = m.to_f64();
+        let scale = (0..n).fold(0.0_f64, |acc, i| acc.max(a[i][i].abs()));
+        let tolerance = n as f64 * f32::EPSILON as f64 * scale;
+        let mut l = vec![vec![0.0; n]; n];
+        for j in 0..n {
+            for i in 0..j {
+                if (a[i][j] - a[j][i]).abs() > tolerance.max(f32::EPSILON as f64) {
+                    return Err(LinalgError::NotPositiveDefinite);
+                }
+            }
+            let mut diagonal = a[j][j];
+            for k in 0..j {
+                diagonal -= l[j][k] * l[j][k];
+            }
+            if diagonal <= tolerance {
+                return Err(LinalgError::NotPositiveDefinite);
+            }
+            let diagonal = diagonal.sqrt();
+            l[j][j] = diagonal;
+            for i in (j + 1)..n {
+                let mut sum = a[i][j];
+                for k in 0..j {
+                    sum -= l[i][k] * l[j][k];
+                }
+                l[i][j] = sum / diagonal;
+            }
+        }
+        Ok(Self { l })
+    }
+
+    pub fn l(&self) -> Matrix {
+        Matrix::from_f64(&self.l)
+    }
+
+    pub fn det(&self) -> f32 {
+        let product = (0..self.l.len()).fold(1.0_f64, |acc, i| acc * self.l[i][i]);
+        (product * product) as f32
+    }
+
+    pub fn solve(&self, b: &Matrix) -> Result<Matrix, LinalgError> {
+        let n = self.l.len();
+        let (b_rows, b_columns) = b.shape();
+        if b_rows != n {
+            return Err(LinalgError::DimensionMismatch {
+                expected: (n, b_columns),
+                found: (b_rows, b_columns),
+            });
+        }
+        let mut x = b.to_f64();
+        for column in 0..b_columns {
+            // L * y = b
+            for i in 0..n {
+                let mut sum = x[i][column];
+                for k in 0..i {
+                    sum -= self.l[i][k] * x[k][column];
+                }
+                x[i][column] = sum / self.l[i][i];
+            }
+            // L^T * x = y
+            for i in (0..n).rev() {
+                let mut sum = x[i][column];
+                for k in (i + 1)..n {
+                    sum -= self.l[k][i] * x[k][column];
+                }
+                x[i][column] = sum / self.l[i][i];
+            }
+        }
+        Ok(Matrix::from_f64(&x))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cholesky() {
+        let m = Matrix::to_matrix(&vec![
+            vec![4.0, 12.0, -16.0],
+            vec![12.0, 37.0, -43.0],
+            vec![-16.0, -43.0, 98.0],
+        ]);
+        let cholesky = CholeskyDecomposition::new(&m).unwrap();
+        let expected_l = Matrix::to_matrix(&vec![
+            vec![2.0, 0.0, 0.0],
+            vec![6.0, 1.0, 0.0],
+            vec![-8.0, 5.0, 3.0],
+        ]);
+        assert!(cholesky.l() == expected_l);
+        assert!((cholesky.det() - 36.0).abs() < 1e-3);
+        let b = Matrix::to_matrix(&vec![vec![1.0], vec![2.0], vec![3.0]]);
+        let x = cholesky.solve(&b).unwrap();
+        let reconstructed = m.multiply(&x);
+        assert!((0..3).all(|i| (reconstructed.get(i).get(0) - b.get(i).get(0)).abs() < 1e-3));
+    }
+
+    #[test]
+    fn test_cholesky_not_positive_definite() {
+        let indefinite = Matrix::to_matrix(&vec![vec![1.0, 2.0], vec![2.0, 1.0]]);
+        assert!(matches!(
+            CholeskyDecomposition::new(&indefinite),
+            Err(LinalgError::NotPositiveDefinite)
+        ));
+        let asymmetric = Matrix::to_matrix(&vec![vec![2.0, 1.0], vec![0.0, 2.0]]);
+        assert!(matches!(
+            CholeskyDecomposition::new(&asymmetric),
+            Err(LinalgError::NotPositiveDefinite)
+        ));
+    }
+}
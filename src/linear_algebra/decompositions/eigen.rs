@@ -0,0 +1,307 @@
+use crate::linear_algebra::{LinalgError, Matrix};
+
+const MAX_SWEEPS: usize = 100;
+
+/// Eigendecomposition `A = V * diag(w) * V^T` of a symmetric matrix. The
+/// eigenvectors are the columns of `V` and the pairs are sorted by eigenvalue
+/// in descending order.
+pub struct SymmetricEigenDecomposition {
+    eigenvalues: Vec<f64>,
+    eigenvectors: Vec<Vec<f64>>,
+}
+
+impl SymmetricEigenDecomposition {
+    /// Full decomposition using cyclic Jacobi rotations.
+    pub fn new(m: &Matrix) -> Result<Self, LinalgError> {
+        let mut a = Self::symmetric_f64(m)?;
+        let n = a.len();
+        let mut v: Vec<Vec<f64>> = (0..n)
+            .map(|i| (0..n).map(|j| if i == j { 1.0 } else { 0.0 }).collect())
+            .collect();
+        let total = a
+            .iter()
+            .flat_map(|row| row.iter())
+            .fold(0.0, |acc, value| acc + value * value);
+        let mut converged = false;
+        for _ in 0..MAX_SWEEPS {
+            let mut off_diagonal = 0.0;
+            for p in 0..n {
+                for q in (p + 1)..n {
+                    off_diagonal += a[p][q] * a[p][q];
+                }
+            }
+            if off_diagonal <= f64::EPSILON * f64::EPSILON * total {
+                converged = true;
+                break;
+            }
+            for p in 0..n {
+                for q in (p + 1)..n {
+                    if a[p][q] == 0.0 {
+                        continue;
+                    }
+                    let theta = (a[q][q] - a[p][p]) / (2.0 * a[p][q]);
+                    let t = if theta == 0.0 {
+                        1.0
+                    } else {
+                        theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt())
+                    };
+                    let c = 1.0 / (t * t + 1.0).sqrt();
+                    let s = t * c;
+                    for row in a.iter_mut().chain(v.iter_mut()) {
+                        let (kp, kq) = (row[p], row[q]);
+                        row[p] = c * kp - s * kq;
+                        row[q] = s * kp + c * kq;
+                    }
+                    for k in 0..n {
+                        let (pk, qk) = (a[p][k], a[q][k]);
+                        a[p][k] = c * pk - s * qk;
+                        a[q][k] = s * pk + c * qk;
+                    }
+                }
+            }
+        }
+        if !converged {
+            return Err(LinalgError::NoConvergence {
+                iterations: MAX_SWEEPS,
+            });
+        }
+        let eigenvalues: Vec<f64> = (0..n).map(|i| a[i][i]).collect();
+        Ok(Self::sorted(eigenvalues, v))
+    }
+
+    /// The `k` dominant (largest magnitude) eigenpairs via power iteration,
+    /// orthogonalizing against the eigenvectors already found. Only the
+    /// matrix-vector product is needed, so this stays cheap for large matrices
+    /// when `k` is small.
+    pub fn power_iteration(
+        m: &Matrix,
+        k: usize,
+        max_iterations: usize,
+        tolerance: f32,
+    ) -> Result<Self, LinalgError> {
+        let a = Self::symmetric_f64(m)?;
+        let n = a.len();
+        let k = k.min(n);
+        let tolerance = tolerance as f64;
+        let mut eigenvalues: Vec<f64> = Vec::new();
+        let mut found: Vec<Vec<f64>> = Vec::new();
+        for component in 0..k {
+            // deterministic start vector that is unlikely to be orthogonal to any eigenvector
+            let mut vector: Vec<f64> = (0..n)
+                .map(|i| 1.0 + ((i * 7919 + component * 104729) % 997) as f64 / 997.0)
+                .collect();
+            Self::orthonormalize(&mut vector, &found);
+            let mut eigenvalue = 0.0;
+            let mut converged = false;
+            for _ in 0..max_iterations {
+                let mut next: Vec<f64> = a
+                    .iter()
+                    .map(|row| {
+                        row.iter()
+                            .zip(vector.iter())
+                            .fold(0.0, |acc, (a, v)| acc + a * v)
+                    })
+                    .collect();
+                let next_eigenvalue = next
+                    .iter()
+                    .zip(vector.iter())
+                    .fold(0.0, |acc, (w, v)| acc + w * v);
+                if Self::orthonormalize(&mut next, &found) == 0.0 {
+                    // the remaining subspace is annihilated by A, the eigenvalue is zero
+                    eigenvalue = 0.0;
+                    converged = true;
+                    break;
+                }
+                // a negative eigenvalue flips the sign of the iterate every step
+                let alignment = next
+                    .iter()
+                    .zip(vector.iter())
+                    .fold(0.0, |acc, (w, v)| acc + w * v);
+                if alignment < 0.0 {
+                    next.iter_mut().for_each(|value| *value = -*value);
+                }
+                let change = next
+                    .iter()
+                    .zip(vector.iter())
+                    .fold(0.0_f64, |acc, (w, v)| acc.max((w - v).abs()));
+                vector = next;
+                let settled = (next_eigenvalue - eigenvalue).abs()
+                    <= tolerance * next_eigenvalue.abs().max(f64::MIN_POSITIVE);
+                eigenvalue = next_eigenvalue;
+                if settled && change <= tolerance.sqrt() {
+                    converged = true;
+                    break;
+                }
+            }
+            if !converged {
+                return Err(LinalgError::NoConvergence {
+                    iterations: max_iterations,
+                });
+            }
+            eigenvalues.push(eigenvalue);
+            found.push(vector);
+        }
+        let eigenvectors: Vec<Vec<f64>> = (0..n)
+            .map(|i| found.iter().map(|vector| vector[i]).collect())
+            .collect();
+        Ok(Self::sorted(eigenvalues, eigenvectors))
+    }
+
+    fn symmetric_f64(m: &Matrix) -> Result<Vec<Vec<f64>>, LinalgError> {
+        let (rows, columns) = m.shape();
+        if rows != columns {
+            return Err(LinalgError::NotSquare { rows, columns });
+        }
+        let a = m.to_f64();
+        let scale = a
+            .iter()
+            .flat_map(|row| row.iter())
+            .fold(0.0_f64, |acc, value| acc.max(value.abs()));
+        let tolerance = rows as f64 * f32::EPSILON as f64 * scale;
+        for i in 0..rows {
+            for j in (i + 1)..rows {
+                if (a[i][j] - a[j][i]).abs() > tolerance {
+                    return Err(LinalgError::NotSymmetric);
+                }
+            }
+        }
+        Ok(a)
+    }
+
+    /// Removes the components along `basis` and normalizes, returning the norm
+    /// before normalization.
+    fn orthonormalize(vector: &mut [f64], basis: &[Vec<f64>]) -> f64 {
+        for basis_vector in basis {
+            let projection = vector
+                .iter()
+                .zip(basis_vector.iter())
+                .fold(0.0, |acc, (v, b)| acc + v * b);
+            for (value, b) in vector.iter_mut().zip(basis_vector.iter()) {
+                *value -= projection * b;
+            }
+        }
+        let norm = vector.iter().fold(0.0_f64, |acc, value| acc.hypot(*value));
+        if norm > 0.0 {
+            vector.iter_mut().for_each(|value| *value /= norm);
+        }
+        norm
+    }
+
+    fn sorted(eigenvalues: Vec<f64>, eigenvectors: Vec<Vec<f64>>) -> Self {
+        let mut order: Vec<usize> = (0..eigenvalues.len()).collect();
+        order.sort_by(|a, b| eigenvalues[*b].total_cmp(&eigenvalues[*a]));
+        Self {
+            eigenvalues: order.iter().map(|i| eigenvalues[*i]).collect(),
+            eigenvectors: eigenvectors
+                .iter()
+                .map(|row| order.iter().map(|i| row[*i]).collect())
+                .collect(),
+        }
+    }
+
+    pub fn eigenvalues(&self) -> Vec<f32> {
+        self.eigenvalues.iter().map(|value| *value as f32).collect()
+    }
+
+    /// Eigenvectors as the columns of an `n x k` matrix, in the same order as
+    /// `eigenvalues`.
+    pub fn eigenvectors(&self) -> Matrix {
+        Matrix::from_f64(&self.eigenvectors)
+    }
+
+    /// Ratio of the largest to the smallest eigenvalue magnitude.
+    pub fn condition_number(&self) -> f32 {
+        let magnitudes: Vec<f64> = self.eigenvalues.iter().map(|value| value.abs()).collect();
+        let max = magnitudes
+            .iter()
+            .fold(0.0_f64, |acc, value| acc.max(*value));
+        let min = magnitudes
+            .iter()
+            .fold(f64::INFINITY, |acc, value| acc.min(*value));
+        if min == 0.0 {
+            return f32::INFINITY;
+        }
+        (max / min) as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_eigenpairs(m: &Matrix, eigen: &SymmetricEigenDecomposition, tolerance: f32) {
+        let eigenvalues = eigen.eigenvalues();
+        let eigenvectors = eigen.eigenvectors();
+        let product = m.multiply(&eigenvectors);
+        for (j, eigenvalue) in eigenvalues.iter().enumerate() {
+            for i in 0..m.len() {
+                let expected = eigenvalue * eigenvectors.get(i).get(j);
+                assert!(
+                    (product.get(i).get(j) - expected).abs() < tolerance,
+                    "{} != {}",
+                    product.get(i).get(j),
+                    expected
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_symmetric_eigen() {
+        let m = Matrix::to_matrix(&vec![
+            vec![4.0, 1.0, 2.0],
+            vec![1.0, 2.0, 0.0],
+            vec![2.0, 0.0, 3.0],
+        ]);
+        let eigen = SymmetricEigenDecomposition::new(&m).unwrap();
+        let eigenvalues = eigen.eigenvalues();
+        assert!(eigenvalues.windows(2).all(|pair| pair[0] >= pair[1]));
+        let trace: f32 = eigenvalues.iter().sum();
+        assert!((trace - 9.0).abs() < 1e-4);
+        assert_eigenpairs(&m, &eigen, 1e-4);
+        let v = eigen.eigenvectors();
+        let v_t_v = v.transpose().multiply(&v);
+        assert!((0..3).all(|i| {
+            (0..3).all(|j| {
+                let expected = if i == j { 1.0 } else { 0.0 };
+                (v_t_v.get(i).get(j) - expected).abs() < 1e-5
+            })
+        }));
+    }
+
+    #[test]
+    fn test_symmetric_eigen_known_values() {
+        let m = Matrix::to_matrix(&vec![vec![2.0, 1.0], vec![1.0, 2.0]]);
+        let eigen = SymmetricEigenDecomposition::new(&m).unwrap();
+        let eigenvalues = eigen.eigenvalues();
+        assert!((eigenvalues[0] - 3.0).abs() < 1e-5);
+        assert!((eigenvalues[1] - 1.0).abs() < 1e-5);
+        assert!((eigen.condition_number() - 3.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_symmetric_eigen_rejects_asymmetric() {
+        let m = Matrix::to_matrix(&vec![vec![1.0, 2.0], vec![0.0, 1.0]]);
+        assert!(matches!(
+            SymmetricEigenDecomposition::new(&m),
+            Err(LinalgError::NotSymmetric)
+        ));
+    }
+
+    #[test]
+    fn test_power_iteration_top_k() {
+        let m = Matrix::to_matrix(&vec![
+            vec![6.0, 2.0, 1.0, 0.0],
+            vec![2.0, 5.0, 2.0, 1.0],
+            vec![1.0, 2.0, 4.0, 1.0],
+            vec![0.0, 1.0, 1.0, 1.0],
+        ]);
+        let full = SymmetricEigenDecomposition::new(&m).unwrap();
+        let top = SymmetricEigenDecomposition::power_iteration(&m, 2, 10000, 1e-10).unwrap();
+        let (full_values, top_values) = (full.eigenvalues(), top.eigenvalues());
+        assert!(top_values.len() == 2);
+        assert!((0..2).all(|i| (full_values[i] - top_values[i]).abs() < 1e-3));
+        assert!(top.eigenvectors().shape() == (4, 2));
+        assert_eigenpairs(&m, &top, 1e-2);
+    }
+}
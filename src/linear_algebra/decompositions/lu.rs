@@ -0,0 +1,207 @@
+use crate::linear_algebra::{LinalgError, Matrix};
+
+/// `P * A = L * U` with partial (row) pivoting. `L` has an implicit unit
+/// diagonal and is stored below the diagonal of `lu`, `U` on and above it.
+pub struct LuDecomposition {
+    lu: Vec<Vec<f64>>,
+    permutation: Vec<usize>,
+    sign: f64,
+    singular: bool,
+}
+
+impl LuDecomposition {
+    pub fn new(m: &Matrix) -> Result<Self, LinalgError> {
+        let (rows, columns) = m.shape();
+        if rows != columns {
+            return Err(LinalgError::NotSquare { rows, columns });
+        }
+        let n = rows;
+        let mut lu = m.to_f64();
+        let mut permutation: Vec<usize> = (0..n).collect();
+        let mut sign = 1.0;
+        let mut singular = false;
+        // pivots below this are treated as zero, the input only carries f32 precision
+        let scale = lu
+            .iter()
+            .flat_map(|row| row.iter())
+            .fold(0.0_f64, |acc, value| acc.max(value.abs()));
+        let tolerance = n as f64 * f32::EPSILON as f64 * scale;
+        for k in 0..n {
+            let pivot_row = (k..n)
+                .max_by(|a, b| lu[*a][k].abs().total_cmp(&lu[*b][k].abs()))
+                .unwrap();
+            if lu[pivot_row][k].abs() <= tolerance {
+                singular = true;
+                continue;
+            }
+            if pivot_row != k {
+                lu.swap(pivot_row, k);
+                permutation.swap(pivot_row, k);
+                sign = -sign;
+            }
+            for i in (k + 1)..n {
+                let factor = lu[i][k] / lu[k][k];
+                lu[i][k] = factor;
+                for j in (k + 1)..n {
+                    lu[i][j] -= factor * lu[k][j];
+                }
+            }
+        }
+        Ok(Self {
+            lu,
+            permutation,
+            sign,
+            singular,
+        })
+    }
+
+    pub fn is_singular(&self) -> bool {
+        self.singular
+    }
+
+    pub fn det(&self) -> f32 {
+        if self.singular {
+            return 0.0;
+        }
+        let product = (0..self.lu.len()).fold(self.sign, |acc, i| acc * self.lu[i][i]);
+        product as f32
+    }
+
+    /// Cheap lower bound on the condition number from the ratio of the
+    /// largest to the smallest pivot.
+    pub fn pivot_condition_estimate(&self) -> f32 {
+        let pivots: Vec<f64> = (0..self.lu.len()).map(|i| self.lu[i][i].abs()).collect();
+        let max = pivots.iter().fold(0.0_f64, |acc, pivot| acc.max(*pivot));
+        let min = pivots
+            .iter()
+            .fold(f64::INFINITY, |acc, pivot| acc.min(*pivot));
+        if min == 0.0 {
+            return f32::INFINITY;
+        }
+        (max / min) as f32
+    }
+
+    pub fn l(&self) -> Matrix {
+        let n = self.lu.len();
+        let l: Vec<Vec<f64>> = (0..n)
+            .map(|i| {
+                (0..n)
+                    .map(|j| match i.cmp(&j) {
+                        std::cmp::Ordering::Greater => self.lu[i][j],
+                        std::cmp::Ordering::Equal => 1.0,
+                        std::cmp::Ordering::Less => 0.0,
+                    })
+                    .collect()
+            })
+            .collect();
+        Matrix::from_f64(&l)
+    }
+
+    pub fn u(&self) -> Matrix {
+        let n = self.lu.len();
+        let u: Vec<Vec<f64>> = (0..n)
+            .map(|i| {
+                (0..n)
+                    .map(|j| if i <= j { self.lu[i][j] } else { 0.0 })
+                    .collect()
+            })
+            .collect();
+        Matrix::from_f64(&u)
+    }
+
+    /// Row permutation applied to `A`: row `i` of `L * U` is row `permutation[i]` of `A`.
+    pub fn permutation(&self) -> &Vec<usize> {
+        &self.permutation
+    }
+
+    pub fn solve(&self, b: &Matrix) -> Result<Matrix, LinalgError> {
+        let n = self.lu.len();
+        let (b_rows, b_columns) = b.shape();
+        if b_rows != n {
+            return Err(LinalgError::DimensionMismatch {
+                expected: (n, b_columns),
+                found: (b_rows, b_columns),
+            });
+        }
+        if self.singular {
+            return Err(LinalgError::Singular);
+        }
+        let b = b.to_f64();
+        let mut x: Vec<Vec<f64>> = self.permutation.iter().map(|i| b[*i].clone()).collect();
+        for column in 0..b_columns {
+            // forward substitution with the unit lower triangle
+            for i in 0..n {
+                let mut sum = x[i][column];
+                for k in 0..i {
+                    sum -= self.lu[i][k] * x[k][column];
+                }
+                x[i][column] = sum;
+            }
+            for i in (0..n).rev() {
+                let mut sum = x[i][column];
+                for k in (i + 1)..n {
+                    sum -= self.lu[i][k] * x[k][column];
+                }
+                x[i][column] = sum / self.lu[i][i];
+            }
+        }
+        Ok(Matrix::from_f64(&x))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lu_reconstructs_permuted_matrix() {
+        let m = Matrix::to_matrix(&vec![
+            vec![0.0, 2.0, 1.0],
+            vec![4.0, 1.0, -2.0],
+            vec![2.0, 3.0, 5.0],
+        ]);
+        let lu = LuDecomposition::new(&m).unwrap();
+        let product = lu.l().multiply(&lu.u());
+        for (i, row_index) in lu.permutation().iter().enumerate() {
+            for j in 0..3 {
+                assert!((product.get(i).get(j) - m.get(*row_index).get(j)).abs() < 1e-5);
+            }
+        }
+        assert!((lu.det() - -38.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_lu_solve() {
+        let m = Matrix::to_matrix(&vec![
+            vec![2.0, 1.0, -1.0],
+            vec![-3.0, -1.0, 2.0],
+            vec![-2.0, 1.0, 2.0],
+        ]);
+        let b = Matrix::to_matrix(&vec![vec![8.0], vec![-11.0], vec![-3.0]]);
+        let x = LuDecomposition::new(&m).unwrap().solve(&b).unwrap();
+        let expected = [2.0, 3.0, -1.0];
+        assert!((0..3).all(|i| (x.get(i).get(0) - expected[i]).abs() < 1e-5));
+    }
+
+    #[test]
+    fn test_lu_singular() {
+        let m = Matrix::to_matrix(&vec![vec![1.0, 2.0], vec![2.0, 4.0]]);
+        let lu = LuDecomposition::new(&m).unwrap();
+        assert!(lu.is_singular());
+        assert!(lu.det() == 0.0);
+        let b = Matrix::to_matrix(&vec![vec![1.0], vec![1.0]]);
+        assert!(lu.solve(&b) == Err(LinalgError::Singular));
+    }
+
+    #[test]
+    fn test_lu_not_square() {
+        let m = Matrix::to_matrix(&vec![vec![1.0, 2.0, 3.0], vec![4.0, 5.0, 6.0]]);
+        assert!(matches!(
+            LuDecomposition::new(&m),
+            Err(LinalgError::NotSquare {
+                rows: 2,
+                columns: 3
+            })
+        ));
+    }
+}
@@ -0,0 +1,10 @@
+pub mod cholesky;
+pub mod eigen;
+pub mod lu;
+pub mod qr;
+pub mod svd;
+pub use cholesky::CholeskyDecomposition;
+pub use eigen::SymmetricEigenDecomposition;
+pub use lu::LuDecomposition;
+pub use qr::QrDecomposition;
+pub use svd::SvdDecomposition;
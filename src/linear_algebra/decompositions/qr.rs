@@ -0,0 +1,185 @@
+use crate::linear_algebra::{LinalgError, Matrix};
+
+/// Householder QR of an `m x n` matrix with `m >= n`. The Householder vectors
+/// are stored on and below the diagonal of `qr`, the strict upper triangle of
+/// `R` above it and the diagonal of `R` in `r_diagonal`.
+pub struct QrDecomposition {
+    qr: Vec<Vec<f64>>,
+    r_diagonal: Vec<f64>,
+    rows: usize,
+    columns: usize,
+}
+
+impl QrDecomposition {
+    pub fn new(m: &Matrix) -> Result<Self, LinalgError> {
+        let (rows, columns) = m.shape();
+        if rows < columns {
+            return Err(LinalgError::DimensionMismatch {
+                expected: (columns, columns),
+                found: (rows, columns),
+            });
+        }
+        let mut qr = m.to_f64();
+        let mut r_diagonal = vec![0.0; columns];
+        for k in 0..columns {
+            let mut norm = (k..rows).fold(0.0_f64, |acc, i| acc.hypot(qr[i][k]));
+            if norm != 0.0 {
+                if qr[k][k] < 0.0 {
+                    norm = -norm;
+                }
+                for row in qr.iter_mut().skip(k) {
+                    row[k] /= norm;
+                }
+                qr[k][k] += 1.0;
+                for j in (k + 1)..columns {
+                    let s = (k..rows).fold(0.0, |acc, i| acc + qr[i][k] * qr[i][j]);
+                    let s = -s / qr[k][k];
+                    for row in qr.iter_mut().skip(k) {
+                        row[j] += s * row[k];
+                    }
+                }
+            }
+            r_diagonal[k] = -norm;
+        }
+        Ok(Self {
+            qr,
+            r_diagonal,
+            rows,
+            columns,
+        })
+    }
+
+    /// True when every diagonal entry of `R` is numerically nonzero.
+    pub fn is_full_rank(&self) -> bool {
+        let max = self
+            .r_diagonal
+            .iter()
+            .fold(0.0_f64, |acc, value| acc.max(value.abs()));
+        let tolerance = self.rows.max(self.columns) as f64 * f32::EPSILON as f64 * max;
+        max > 0.0 && self.r_diagonal.iter().all(|value| value.abs() > tolerance)
+    }
+
+    pub fn r(&self) -> Matrix {
+        let r: Vec<Vec<f64>> = (0..self.columns)
+            .map(|i| {
+                (0..self.columns)
+                    .map(|j| match i.cmp(&j) {
+                        std::cmp::Ordering::Less => self.qr[i][j],
+                        std::cmp::Ordering::Equal => self.r_diagonal[i],
+                        std::cmp::Ordering::Greater => 0.0,
+                    })
+                    .collect()
+            })
+            .collect();
+        Matrix::from_f64(&r)
+    }
+
+    /// Thin `Q` with orthonormal columns (`m x n`).
+    pub fn q(&self) -> Matrix {
+        let mut q = vec![vec![0.0; self.columns]; self.rows];
+        for k in (0..self.columns).rev() {
+            q[k][k] = 1.0;
+            for j in k..self.columns {
+                if self.qr[k][k] != 0.0 {
+                    let s = (k..self.rows).fold(0.0, |acc, i| acc + self.qr[i][k] * q[i][j]);
+                    let s = -s / self.qr[k][k];
+                    for i in k..self.rows {
+                        q[i][j] += s * self.qr[i][k];
+                    }
+                }
+            }
+        }
+        Matrix::from_f64(&q)
+    }
+
+    /// Least squares solution minimizing `||A * x - b||`.
+    pub fn solve(&self, b: &Matrix) -> Result<Matrix, LinalgError> {
+        let (b_rows, b_columns) = b.shape();
+        if b_rows != self.rows {
+            return Err(LinalgError::DimensionMismatch {
+                expected: (self.rows, b_columns),
+                found: (b_rows, b_columns),
+            });
+        }
+        if !self.is_full_rank() {
+            return Err(LinalgError::Singular);
+        }
+        let mut x = b.to_f64();
+        // x = Q^T * b
+        for k in 0..self.columns {
+            for j in 0..b_columns {
+                let s = (k..self.rows).fold(0.0, |acc, i| acc + self.qr[i][k] * x[i][j]);
+                let s = -s / self.qr[k][k];
+                for i in k..self.rows {
+                    x[i][j] += s * self.qr[i][k];
+                }
+            }
+        }
+        // R * x = Q^T * b
+        for k in (0..self.columns).rev() {
+            for j in 0..b_columns {
+                x[k][j] /= self.r_diagonal[k];
+            }
+            for i in 0..k {
+                for j in 0..b_columns {
+                    x[i][j] -= x[k][j] * self.qr[i][k];
+                }
+            }
+        }
+        x.truncate(self.columns);
+        Ok(Matrix::from_f64(&x))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_qr_reconstructs_matrix() {
+        let m = Matrix::to_matrix(&vec![
+            vec![12.0, -51.0, 4.0],
+            vec![6.0, 167.0, -68.0],
+            vec![-4.0, 24.0, -41.0],
+            vec![1.0, 1.0, 1.0],
+        ]);
+        let qr = QrDecomposition::new(&m).unwrap();
+        let q = qr.q();
+        let product = q.multiply(&qr.r());
+        assert!(
+            (0..4).all(|i| (0..3).all(|j| (product.get(i).get(j) - m.get(i).get(j)).abs() < 1e-3))
+        );
+        let q_t_q = q.transpose().multiply(&q);
+        assert!((0..3).all(|i| {
+            (0..3).all(|j| {
+                let expected = if i == j { 1.0 } else { 0.0 };
+                (q_t_q.get(i).get(j) - expected).abs() < 1e-5
+            })
+        }));
+    }
+
+    #[test]
+    fn test_qr_least_squares() {
+        // y = 2x + 1 with an exact fit
+        let m = Matrix::to_matrix(&vec![
+            vec![0.0, 1.0],
+            vec![1.0, 1.0],
+            vec![2.0, 1.0],
+            vec![3.0, 1.0],
+        ]);
+        let b = Matrix::to_matrix(&vec![vec![1.0], vec![3.0], vec![5.0], vec![7.0]]);
+        let x = QrDecomposition::new(&m).unwrap().solve(&b).unwrap();
+        assert!(x.len() == 2);
+        assert!((x.get(0).get(0) - 2.0).abs() < 1e-5);
+        assert!((x.get(1).get(0) - 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_qr_rank_deficient() {
+        let m = Matrix::to_matrix(&vec![vec![1.0, 2.0], vec![2.0, 4.0], vec![3.0, 6.0]]);
+        let qr = QrDecomposition::new(&m).unwrap();
+        assert!(!qr.is_full_rank());
+        let b = Matrix::to_matrix(&vec![vec![1.0], vec![2.0], vec![3.0]]);
+        assert!(qr.solve(&b) == Err(LinalgError::Singular));
+    }
+}
@@ -0,0 +1,290 @@
+use crate::linear_algebra::{LinalgError, Matrix};
+
+const MAX_SWEEPS: usize = 100;
+
+/// Thin SVD `A = U * diag(s) * V^T` computed with one-sided Jacobi rotations.
+/// For an `m x n` matrix with `k = min(m, n)`, `U` is `m x k`, `V` is `n x k`
+/// and the singular values are sorted in descending order.
+pub struct SvdDecomposition {
+    u: Vec<Vec<f64>>,
+    singular_values: Vec<f64>,
+    v: Vec<Vec<f64>>,
+}
+
+impl SvdDecomposition {
+    pub fn new(m: &Matrix) -> Result<Self, LinalgError> {
+        let (rows, columns) = m.shape();
+        if rows < columns {
+            // A^T = U * S * V^T  =>  A = V * S * U^T
+            let (u, singular_values, v) = Self::one_sided_jacobi(m.transpose().to_f64())?;
+            return Ok(Self {
+                u: v,
+                singular_values,
+                v: u,
+            });
+        }
+        let (u, singular_values, v) = Self::one_sided_jacobi(m.to_f64())?;
+        Ok(Self {
+            u,
+            singular_values,
+            v,
+        })
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn one_sided_jacobi(
+        mut u: Vec<Vec<f64>>,
+    ) -> Result<(Vec<Vec<f64>>, Vec<f64>, Vec<Vec<f64>>), LinalgError> {
+        let rows = u.len();
+        let columns = if rows == 0 { 0 } else { u[0].len() };
+        let mut v: Vec<Vec<f64>> = (0..columns)
+            .map(|i| {
+                (0..columns)
+                    .map(|j| if i == j { 1.0 } else { 0.0 })
+                    .collect()
+            })
+            .collect();
+        // columns whose squared norm falls below this are numerically zero and
+        // only carry rounding noise, rotating against them never settles
+        let negligible = f64::EPSILON
+            * u.iter()
+                .flat_map(|row| row.iter())
+                .fold(0.0, |acc, value| acc + value * value);
+        let mut converged = false;
+        for _ in 0..MAX_SWEEPS {
+            let mut rotated = false;
+            for p in 0..columns {
+                for q in (p + 1)..columns {
+                    let (mut alpha, mut beta, mut gamma) = (0.0, 0.0, 0.0);
+                    for row in u.iter() {
+                        alpha += row[p] * row[p];
+                        beta += row[q] * row[q];
+                        gamma += row[p] * row[q];
+                    }
+                    if gamma.abs() <= f64::EPSILON * (alpha * beta).sqrt()
+                        || alpha.min(beta) <= negligible
+                    {
+                        continue;
+                    }
+                    rotated = true;
+                    let zeta = (beta - alpha) / (2.0 * gamma);
+                    let t = zeta.signum() / (zeta.abs() + (1.0 + zeta * zeta).sqrt());
+                    let c = 1.0 / (1.0 + t * t).sqrt();
+                    let s = c * t;
+                    for row in u.iter_mut().chain(v.iter_mut()) {
+                        let (up, uq) = (row[p], row[q]);
+                        row[p] = c * up - s * uq;
+                        row[q] = s * up + c * uq;
+                    }
+                }
+            }
+            if !rotated {
+                converged = true;
+                break;
+            }
+        }
+        if !converged {
+            return Err(LinalgError::NoConvergence {
+                iterations: MAX_SWEEPS,
+            });
+        }
+        let norms: Vec<f64> = (0..columns)
+            .map(|j| u.iter().fold(0.0_f64, |acc, row| acc.hypot(row[j])))
+            .collect();
+        let mut order: Vec<usize> = (0..columns).collect();
+        order.sort_by(|a, b| norms[*b].total_cmp(&norms[*a]));
+        let singular_values: Vec<f64> = order.iter().map(|j| norms[*j]).collect();
+        let u = u
+            .iter()
+            .map(|row| {
+                order
+                    .iter()
+                    .map(|j| {
+                        if norms[*j] > 0.0 {
+                            row[*j] / norms[*j]
+                        } else {
+                            0.0
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+        let v = v
+            .iter()
+            .map(|row| order.iter().map(|j| row[*j]).collect())
+            .collect();
+        Ok((u, singular_values, v))
+    }
+
+    pub fn u(&self) -> Matrix {
+        Matrix::from_f64(&self.u)
+    }
+
+    pub fn v(&self) -> Matrix {
+        Matrix::from_f64(&self.v)
+    }
+
+    pub fn singular_values(&self) -> Vec<f32> {
+        self.singular_values.iter().map(|s| *s as f32).collect()
+    }
+
+    /// Singular values at or below this are treated as zero.
+    pub fn default_tolerance(&self) -> f32 {
+        let largest = self.singular_values.first().copied().unwrap_or(0.0);
+        (self.u.len().max(self.v.len()) as f64 * f32::EPSILON as f64 * largest) as f32
+    }
+
+    pub fn rank(&self, tolerance: Option<f32>) -> usize {
+        let tolerance = tolerance.unwrap_or_else(|| self.default_tolerance()) as f64;
+        self.singular_values
+            .iter()
+            .filter(|s| **s > tolerance)
+            .count()
+    }
+
+    pub fn condition_number(&self) -> f32 {
+        match (self.singular_values.first(), self.singular_values.last()) {
+            (Some(largest), Some(smallest)) if *smallest > 0.0 => (largest / smallest) as f32,
+            (Some(_), Some(_)) => f32::INFINITY,
+            _ => 0.0,
+        }
+    }
+
+    /// Moore-Penrose pseudo-inverse `V * diag(1/s) * U^T`, dropping singular
+    /// values at or below `tolerance`.
+    pub fn pinv(&self, tolerance: Option<f32>) -> Matrix {
+        let inverse_singular_values = self.inverse_singular_values(tolerance);
+        let columns = self.v.len();
+        let rows = self.u.len();
+        let mut pinv = vec![vec![0.0; rows]; columns];
+        for (i, pinv_row) in pinv.iter_mut().enumerate() {
+            for (j, value) in pinv_row.iter_mut().enumerate() {
+                *value = inverse_singular_values
+                    .iter()
+                    .enumerate()
+                    .fold(0.0, |acc, (k, s)| acc + self.v[i][k] * s * self.u[j][k]);
+            }
+        }
+        Matrix::from_f64(&pinv)
+    }
+
+    /// Minimum norm least squares solution of `A * x = b`.
+    pub fn solve(&self, b: &Matrix) -> Result<Matrix, LinalgError> {
+        let (b_rows, b_columns) = b.shape();
+        if b_rows != self.u.len() {
+            return Err(LinalgError::DimensionMismatch {
+                expected: (self.u.len(), b_columns),
+                found: (b_rows, b_columns),
+            });
+        }
+        let inverse_singular_values = self.inverse_singular_values(None);
+        let b = b.to_f64();
+        // U^T * b scaled by the inverted singular values
+        let mut projected = vec![vec![0.0; b_columns]; inverse_singular_values.len()];
+        for (k, projected_row) in projected.iter_mut().enumerate() {
+            for (i, b_row) in b.iter().enumerate() {
+                for (j, value) in projected_row.iter_mut().enumerate() {
+                    *value += self.u[i][k] * b_row[j];
+                }
+            }
+            for value in projected_row.iter_mut() {
+                *value *= inverse_singular_values[k];
+            }
+        }
+        let x: Vec<Vec<f64>> = self
+            .v
+            .iter()
+            .map(|v_row| {
+                (0..b_columns)
+                    .map(|j| {
+                        v_row
+                            .iter()
+                            .zip(projected.iter())
+                            .fold(0.0, |acc, (v, p)| acc + v * p[j])
+                    })
+                    .collect()
+            })
+            .collect();
+        Ok(Matrix::from_f64(&x))
+    }
+
+    fn inverse_singular_values(&self, tolerance: Option<f32>) -> Vec<f64> {
+        let tolerance = tolerance.unwrap_or_else(|| self.default_tolerance()) as f64;
+        self.singular_values
+            .iter()
+            .map(|s| if *s > tolerance { 1.0 / s } else { 0.0 })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: &Matrix, b: &Matrix, tolerance: f32) {
+        assert!(a.shape() == b.shape());
+        for (row_a, row_b) in a.matrix().iter().zip(b.matrix().iter()) {
+            for (value_a, value_b) in row_a.vector().iter().zip(row_b.vector().iter()) {
+                assert!(
+                    (value_a - value_b).abs() < tolerance,
+                    "{} != {}",
+                    value_a,
+                    value_b
+                );
+            }
+        }
+    }
+
+    fn reconstruct(svd: &SvdDecomposition) -> Matrix {
+        let s = svd.singular_values();
+        let u = svd.u();
+        let scaled_u = Matrix::to_matrix(
+            &u.matrix()
+                .iter()
+                .map(|row| {
+                    row.vector()
+                        .iter()
+                        .zip(s.iter())
+                        .map(|(u, s)| u * s)
+                        .collect()
+                })
+                .collect(),
+        );
+        scaled_u.multiply(&svd.v().transpose())
+    }
+
+    #[test]
+    fn test_svd_tall() {
+        let m = Matrix::to_matrix(&vec![
+            vec![3.0, 2.0, 2.0],
+            vec![2.0, 3.0, -2.0],
+            vec![1.0, 0.0, 4.0],
+            vec![0.0, 1.0, 1.0],
+        ]);
+        let svd = SvdDecomposition::new(&m).unwrap();
+        let s = svd.singular_values();
+        assert!(s.windows(2).all(|pair| pair[0] >= pair[1]));
+        assert_close(&reconstruct(&svd), &m, 1e-4);
+    }
+
+    #[test]
+    fn test_svd_wide() {
+        let m = Matrix::to_matrix(&vec![vec![3.0, 2.0, 2.0], vec![2.0, 3.0, -2.0]]);
+        let svd = SvdDecomposition::new(&m).unwrap();
+        let s = svd.singular_values();
+        assert!((s[0] - 5.0).abs() < 1e-4);
+        assert!((s[1] - 3.0).abs() < 1e-4);
+        assert_close(&reconstruct(&svd), &m, 1e-4);
+    }
+
+    #[test]
+    fn test_svd_rank_and_pinv() {
+        let m = Matrix::to_matrix(&vec![vec![1.0, 2.0], vec![2.0, 4.0], vec![3.0, 6.0]]);
+        let svd = SvdDecomposition::new(&m).unwrap();
+        assert!(svd.rank(None) == 1);
+        assert!(svd.condition_number().is_infinite() || svd.condition_number() > 1e6);
+        let pinv = svd.pinv(None);
+        // A * A^+ * A = A
+        assert_close(&m.multiply(&pinv).multiply(&m), &m, 1e-4);
+    }
+}
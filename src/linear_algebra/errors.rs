@@ -0,0 +1,53 @@
+use std::error::Error;
+use std::fmt;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum LinalgError {
+    NotSquare {
+        rows: usize,
+        columns: usize,
+    },
+    DimensionMismatch {
+        expected: (usize, usize),
+        found: (usize, usize),
+    },
+    Singular,
+    NotPositiveDefinite,
+    NotSymmetric,
+    IllConditioned {
+        condition_number: f32,
+    },
+    NoConvergence {
+        iterations: usize,
+    },
+}
+
+impl fmt::Display for LinalgError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LinalgError::NotSquare { rows, columns } => {
+                write!(f, "matrix must be square, got {}x{}", rows, columns)
+            }
+            LinalgError::DimensionMismatch { expected, found } => write!(
+                f,
+                "dimension mismatch: expected {}x{}, found {}x{}",
+                expected.0, expected.1, found.0, found.1
+            ),
+            LinalgError::Singular => write!(f, "matrix is singular"),
+            LinalgError::NotPositiveDefinite => {
+                write!(f, "matrix is not symmetric positive definite")
+            }
+            LinalgError::NotSymmetric => write!(f, "matrix is not symmetric"),
+            LinalgError::IllConditioned { condition_number } => write!(
+                f,
+                "matrix is ill-conditioned (condition number estimate {:e})",
+                condition_number
+            ),
+            LinalgError::NoConvergence { iterations } => {
+                write!(f, "failed to converge after {} iterations", iterations)
+            }
+        }
+    }
+}
+
+impl Error for LinalgError {}
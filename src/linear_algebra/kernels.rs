@@ -0,0 +1,155 @@
+// Vector kernels written so LLVM can vectorize them on stable Rust: the hot
+// loops run over `chunks_exact(LANES)` with one independent accumulator per
+// lane, which breaks the serial dependency of a plain `fold` and lets each
+// chunk map onto a SIMD register. The remainder is handled with a scalar tail.
+
+const LANES: usize = 8;
+
+fn assert_same_len(a: &[f32], b: &[f32]) {
+    assert!(
+        a.len() == b.len(),
+        "vector lengths differ: {} and {}",
+        a.len(),
+        b.len()
+    );
+}
+
+fn reduce_lanes(lanes: [f32; LANES]) -> f32 {
+    // pairwise so the result does not depend on a long serial chain
+    let quads = [
+        lanes[0] + lanes[4],
+        lanes[1] + lanes[5],
+        lanes[2] + lanes[6],
+        lanes[3] + lanes[7],
+    ];
+    (quads[0] + quads[2]) + (quads[1] + quads[3])
+}
+
+pub fn dot(a: &[f32], b: &[f32]) -> f32 {
+    assert_same_len(a, b);
+    let mut lanes = [0.0; LANES];
+    let (a_chunks, b_chunks) = (a.chunks_exact(LANES), b.chunks_exact(LANES));
+    let tail = zip_tail(a_chunks.remainder(), b_chunks.remainder(), |x, y| x * y);
+    for (a_chunk, b_chunk) in a_chunks.zip(b_chunks) {
+        for lane in 0..LANES {
+            lanes[lane] += a_chunk[lane] * b_chunk[lane];
+        }
+    }
+    reduce_lanes(lanes) + tail
+}
+
+pub fn sum(a: &[f32]) -> f32 {
+    let mut lanes = [0.0; LANES];
+    let chunks = a.chunks_exact(LANES);
+    let tail: f32 = chunks.remainder().iter().sum();
+    for chunk in chunks {
+        for lane in 0..LANES {
+            lanes[lane] += chunk[lane];
+        }
+    }
+    reduce_lanes(lanes) + tail
+}
+
+pub fn squared_norm(a: &[f32]) -> f32 {
+    dot(a, a)
+}
+
+pub fn max(a: &[f32]) -> f32 {
+    let mut lanes = [f32::NEG_INFINITY; LANES];
+    let chunks = a.chunks_exact(LANES);
+    let tail = chunks
+        .remainder()
+        .iter()
+        .fold(f32::NEG_INFINITY, |acc, value| acc.max(*value));
+    for chunk in chunks {
+        for lane in 0..LANES {
+            lanes[lane] = lanes[lane].max(chunk[lane]);
+        }
+    }
+    lanes.iter().fold(tail, |acc, value| acc.max(*value))
+}
+
+/// `y += alpha * x`
+pub fn axpy(alpha: f32, x: &[f32], y: &mut [f32]) {
+    assert_same_len(x, y);
+    for (y_value, x_value) in y.iter_mut().zip(x.iter()) {
+        *y_value += alpha * x_value;
+    }
+}
+
+/// `x *= alpha`
+pub fn scale(alpha: f32, x: &mut [f32]) {
+    for value in x.iter_mut() {
+        *value *= alpha;
+    }
+}
+
+pub fn scaled(alpha: f32, x: &[f32]) -> Vec<f32> {
+    x.iter().map(|value| alpha * value).collect()
+}
+
+pub fn add(a: &[f32], b: &[f32]) -> Vec<f32> {
+    elementwise(a, b, |x, y| x + y)
+}
+
+pub fn subtract(a: &[f32], b: &[f32]) -> Vec<f32> {
+    elementwise(a, b, |x, y| x - y)
+}
+
+pub fn multiply(a: &[f32], b: &[f32]) -> Vec<f32> {
+    elementwise(a, b, |x, y| x * y)
+}
+
+fn elementwise(a: &[f32], b: &[f32], op: impl Fn(f32, f32) -> f32) -> Vec<f32> {
+    assert_same_len(a, b);
+    a.iter().zip(b.iter()).map(|(x, y)| op(*x, *y)).collect()
+}
+
+fn zip_tail(a: &[f32], b: &[f32], op: impl Fn(f32, f32) -> f32) -> f32 {
+    a.iter().zip(b.iter()).map(|(x, y)| op(*x, *y)).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(len: usize, offset: usize) -> Vec<f32> {
+        (0..len)
+            .map(|i| ((i * 7 + offset) % 11) as f32 - 5.0)
+            .collect()
+    }
+
+    #[test]
+    fn test_reductions_match_scalar() {
+        for len in [0, 1, 7, 8, 9, 33, 100] {
+            let (a, b) = (sample(len, 0), sample(len, 3));
+            let expected_dot: f32 = a.iter().zip(b.iter()).map(|(x, y)| x * y).sum();
+            assert!(dot(&a, &b) == expected_dot);
+            assert!(sum(&a) == a.iter().sum::<f32>());
+            assert!(squared_norm(&a) == a.iter().map(|x| x * x).sum::<f32>());
+            let expected_max = a.iter().fold(f32::NEG_INFINITY, |acc, x| acc.max(*x));
+            assert!(max(&a) == expected_max);
+        }
+    }
+
+    #[test]
+    fn test_elementwise_kernels() {
+        let (a, b) = (sample(19, 0), sample(19, 5));
+        let mut y = b.clone();
+        axpy(2.0, &a, &mut y);
+        assert!((0..19).all(|i| y[i] == b[i] + 2.0 * a[i]));
+        let mut scaled = a.clone();
+        scale(-0.5, &mut scaled);
+        assert!((0..19).all(|i| scaled[i] == a[i] * -0.5));
+        assert!(super::scaled(-0.5, &a) == scaled);
+        assert!((0..19).all(|i| add(&a, &b)[i] == a[i] + b[i]));
+        assert!((0..19).all(|i| subtract(&a, &b)[i] == a[i] - b[i]));
+        assert!((0..19).all(|i| multiply(&a, &b)[i] == a[i] * b[i]));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_dot_length_mismatch() {
+        dot(&[1.0, 2.0], &[1.0]);
+    }
+}
@@ -1,4 +1,15 @@
+use crate::linear_algebra::decompositions::{LuDecomposition, QrDecomposition, SvdDecomposition};
+use crate::linear_algebra::errors::LinalgError;
+use crate::linear_algebra::kernels;
 use crate::linear_algebra::vectors::RowVector;
+use crate::parallel::parallel::parallel_map;
+
+// below this many multiply-adds per thread the spawn cost outweighs the work
+const PARALLEL_MIN_WORK: usize = 1 << 14;
+
+fn parallel_min_rows(work_per_row: usize) -> usize {
+    (PARALLEL_MIN_WORK / work_per_row.max(1)).max(1)
+}
 
 #[derive(Clone, PartialEq, Debug)]
 pub struct Matrix {
@@ -23,30 +34,26 @@ impl Matrix {
 
     pub fn transpose(&self) -> Matrix {
         let m = &self.matrix;
-        let mut output_matrix = Vec::new();
-        for i in 0..m[0].len() {
+        let output_matrix = parallel_map(m[0].len(), parallel_min_rows(m.len()), |i| {
             let mut output_vector = Vec::new();
             for j in 0..m.len() {
                 output_vector.push(m[j].get(i));
             }
-            output_matrix.push(RowVector::new(&output_vector));
-        }
+            RowVector::new(&output_vector)
+        });
         return Matrix::new(&output_matrix);
     }
 
     pub fn multiply(&self, m2: &Matrix) -> Matrix {
-        let mut output_matrix = Vec::new();
-        for i in 0..self.len() {
-            let mut new_row = Vec::new();
-            for j in 0..m2.get(0).len() {
-                let mut sum = 0.0;
-                for k in 0..m2.len() {
-                    sum += self.get(i).get(k) * m2.get(k).get(j);
-                }
-                new_row.push(sum);
+        let work_per_row = m2.len() * m2.get(0).len();
+        let output_matrix = parallel_map(self.len(), parallel_min_rows(work_per_row), |i| {
+            // accumulating whole rows of m2 keeps the inner loop contiguous
+            let mut new_row = vec![0.0; m2.get(0).len()];
+            for k in 0..m2.len() {
+                kernels::axpy(self.get(i).get(k), m2.get(k).vector(), &mut new_row);
             }
-            output_matrix.push(RowVector::new(&new_row));
-        }
+            RowVector::new(&new_row)
+        });
         return Matrix::new(&output_matrix);
     }
 
@@ -71,42 +78,92 @@ impl Matrix {
         return Matrix::new(&i_matrix);
     }
 
-    pub fn inverse(&self) -> Matrix {
-        let mut m_copy = self.matrix.clone();
-        let mut identity_m = self.identity().matrix.clone();
-        for i in 0..m_copy.len() {
-            let mut pivet_row_i = i;
-            while pivet_row_i < m_copy.len() && m_copy[pivet_row_i].get(i) == 0.0 {
-                pivet_row_i += 1;
-            }
-            if pivet_row_i == m_copy.len() {
-                panic!("cannot derive the identity matrix with a column of all zeros");
-            }
-            if pivet_row_i != i {
-                self.swap_rows(&mut m_copy, pivet_row_i, i);
-                self.swap_rows(&mut identity_m, pivet_row_i, i);
-                // i now indexes the previous row indexed by pivet_row_i and vice versa
-            }
-            let pivet_value = m_copy[i].get(i);
-            // sets the matrix[row_index][row_index] to 1
-            m_copy[i] = m_copy[i].multiply_by_scalar(1.0 / pivet_value);
-            identity_m[i] = identity_m[i].multiply_by_scalar(1.0 / pivet_value);
-            for j in 0..m_copy.len() {
-                if j != i {
-                    let factor = m_copy[j].get(i);
-                    m_copy[j] = m_copy[j].add_vector(&m_copy[i].multiply_by_scalar(-factor));
-                    identity_m[j] =
-                        identity_m[j].add_vector(&identity_m[i].multiply_by_scalar(-factor));
-                }
+    pub fn shape(&self) -> (usize, usize) {
+        if self.matrix.is_empty() {
+            (0, 0)
+        } else {
+            (self.matrix.len(), self.matrix[0].len())
+        }
+    }
+
+    pub fn zeros(rows: usize, columns: usize) -> Matrix {
+        Matrix::to_matrix(&vec![vec![0.0; columns]; rows])
+    }
+
+    pub(crate) fn to_f64(&self) -> Vec<Vec<f64>> {
+        self.matrix
+            .iter()
+            .map(|row| row.vector().iter().map(|value| *value as f64).collect())
+            .collect()
+    }
+
+    pub(crate) fn from_f64(multidim_vec: &[Vec<f64>]) -> Matrix {
+        Matrix::to_matrix(
+            &multidim_vec
+                .iter()
+                .map(|row| row.iter().map(|value| *value as f32).collect())
+                .collect(),
+        )
+    }
+
+    /// Solves `self * x = b` for a square `self` using LU with partial pivoting.
+    pub fn solve(&self, b: &Matrix) -> Result<Matrix, LinalgError> {
+        let lu = self.checked_lu()?;
+        lu.solve(b)
+    }
+
+    /// Least squares solution of `self * x = b`. Full column rank systems are
+    /// solved with Householder QR, anything else falls back to the minimum norm
+    /// solution from the SVD.
+    pub fn lstsq(&self, b: &Matrix) -> Result<Matrix, LinalgError> {
+        let (rows, columns) = self.shape();
+        if b.len() != rows {
+            return Err(LinalgError::DimensionMismatch {
+                expected: (rows, b.shape().1),
+                found: b.shape(),
+            });
+        }
+        if rows >= columns {
+            let qr = QrDecomposition::new(self)?;
+            if qr.is_full_rank() {
+                return qr.solve(b);
             }
         }
-        return Matrix::new(&identity_m);
+        SvdDecomposition::new(self)?.solve(b)
+    }
+
+    pub fn det(&self) -> Result<f32, LinalgError> {
+        Ok(LuDecomposition::new(self)?.det())
+    }
+
+    pub fn rank(&self) -> Result<usize, LinalgError> {
+        Ok(SvdDecomposition::new(self)?.rank(None))
     }
 
-    fn swap_rows(&self, m: &mut Vec<RowVector>, row1: usize, row2: usize) {
-        let temp = m[row1].clone();
-        m[row1] = m[row2].clone();
-        m[row2] = temp;
+    pub fn pinv(&self) -> Result<Matrix, LinalgError> {
+        Ok(SvdDecomposition::new(self)?.pinv(None))
+    }
+
+    /// Ratio of the largest to the smallest singular value (2-norm condition number).
+    pub fn condition_number(&self) -> Result<f32, LinalgError> {
+        Ok(SvdDecomposition::new(self)?.condition_number())
+    }
+
+    pub fn inverse(&self) -> Result<Matrix, LinalgError> {
+        let lu = self.checked_lu()?;
+        lu.solve(&self.identity())
+    }
+
+    fn checked_lu(&self) -> Result<LuDecomposition, LinalgError> {
+        let lu = LuDecomposition::new(self)?;
+        if lu.is_singular() {
+            return Err(LinalgError::Singular);
+        }
+        let condition_number = lu.pivot_condition_estimate();
+        if condition_number > 1.0 / f32::EPSILON {
+            return Err(LinalgError::IllConditioned { condition_number });
+        }
+        Ok(lu)
     }
 
     pub fn matrix(&self) -> &Vec<RowVector> {
@@ -121,6 +178,7 @@ impl Matrix {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::parallel::parallel::with_num_threads;
     use std::iter::zip;
 
     #[test]
@@ -203,7 +261,7 @@ mod tests {
             RowVector::new(&vec![5.0 / 3.0, -3.0 / 2.0, 2.0 / 3.0]),
             RowVector::new(&vec![-1.0 / 3.0, 1.0 / 2.0, -1.0 / 3.0]),
         ]);
-        let inverse_m = m.inverse();
+        let inverse_m = m.inverse().unwrap();
         assert!(
             zip(expected_m.matrix().iter(), inverse_m.matrix().iter()).all(|(v1, v2)| zip(
                 v1.vector().iter(),
@@ -244,4 +302,90 @@ mod tests {
         let output_m = m.transpose();
         assert!(output_m == expected_m);
     }
+
+    #[test]
+    fn test_solve_matrix() {
+        let m = Matrix::to_matrix(&vec![
+            vec![0.0, 2.0, 1.0],
+            vec![1.0, 1.0, 1.0],
+            vec![2.0, 1.0, 0.0],
+        ]);
+        let b = Matrix::to_matrix(&vec![vec![7.0], vec![6.0], vec![4.0]]);
+        let x = m.solve(&b).unwrap();
+        let expected = [1.0, 2.0, 3.0];
+        assert!((0..3).all(|i| (x.get(i).get(0) - expected[i]).abs() < 1e-5));
+    }
+
+    #[test]
+    fn test_singular_matrix_errors() {
+        let m = Matrix::to_matrix(&vec![
+            vec![1.0, 2.0, 3.0],
+            vec![2.0, 4.0, 6.0],
+            vec![1.0, 0.0, 1.0],
+        ]);
+        assert!(m.inverse() == Err(LinalgError::Singular));
+        assert!(m.solve(&Matrix::zeros(3, 1)) == Err(LinalgError::Singular));
+        assert!(m.det().unwrap() == 0.0);
+        assert!(m.rank().unwrap() == 2);
+        let ill_conditioned = Matrix::to_matrix(&vec![vec![1.0, 0.0], vec![0.0, 1e-7]]);
+        assert!(ill_conditioned.condition_number().unwrap() > 1e6);
+        assert!(ill_conditioned.inverse().is_err());
+    }
+
+    #[test]
+    fn test_lstsq_matrix() {
+        let m = Matrix::to_matrix(&vec![
+            vec![1.0, 1.0],
+            vec![1.0, 2.0],
+            vec![1.0, 3.0],
+            vec![1.0, 4.0],
+        ]);
+        let b = Matrix::to_matrix(&vec![vec![6.0], vec![5.0], vec![7.0], vec![10.0]]);
+        let x = m.lstsq(&b).unwrap();
+        assert!((x.get(0).get(0) - 3.5).abs() < 1e-4);
+        assert!((x.get(1).get(0) - 1.4).abs() < 1e-4);
+        // duplicated column falls back to the minimum norm solution
+        let rank_deficient =
+            Matrix::to_matrix(&vec![vec![1.0, 1.0], vec![2.0, 2.0], vec![3.0, 3.0]]);
+        let b = Matrix::to_matrix(&vec![vec![2.0], vec![4.0], vec![6.0]]);
+        let x = rank_deficient.lstsq(&b).unwrap();
+        assert!((x.get(0).get(0) - 1.0).abs() < 1e-4);
+        assert!((x.get(1).get(0) - 1.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_det_pinv_condition_number() {
+        let m = Matrix::to_matrix(&vec![vec![4.0, 7.0], vec![2.0, 6.0]]);
+        assert!((m.det().unwrap() - 10.0).abs() < 1e-4);
+        let pinv = m.pinv().unwrap();
+        let inverse = m.inverse().unwrap();
+        assert!(zip(pinv.matrix(), inverse.matrix()).all(|(v1, v2)| {
+            zip(v1.vector(), v2.vector()).all(|(c1, c2)| (c1 - c2).abs() < 1e-4)
+        }));
+        let diagonal = Matrix::to_matrix(&vec![vec![10.0, 0.0], vec![0.0, 0.5]]);
+        assert!((diagonal.condition_number().unwrap() - 20.0).abs() < 1e-3);
+        let not_square = Matrix::zeros(2, 3);
+        assert!(matches!(
+            not_square.det(),
+            Err(LinalgError::NotSquare { .. })
+        ));
+    }
+
+    #[test]
+    fn test_multiply_matrix_thread_count_independent() {
+        let m1 = Matrix::to_matrix(
+            &(0..300)
+                .map(|i| {
+                    (0..40)
+                        .map(|j| ((i * 31 + j * 17) % 13) as f32 - 6.0)
+                        .collect()
+                })
+                .collect(),
+        );
+        let m2 = m1.transpose();
+        let sequential = with_num_threads(1, || m1.multiply(&m2));
+        let parallel = with_num_threads(4, || m1.multiply(&m2));
+        assert!(sequential == parallel);
+        assert!(m2.transpose() == m1);
+    }
 }
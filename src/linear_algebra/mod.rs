@@ -1,4 +1,13 @@
-pub mod matrices;
-pub mod vectors;
-pub use matrices::Matrix;
-pub use vectors::RowVector;
+#[allow(clippy::needless_range_loop)]
+pub mod decompositions;
+pub mod errors;
+#[allow(clippy::needless_range_loop)]
+pub mod kernels;
+pub mod matrices;
+pub(crate) mod optimize;
+pub mod sparse;
+pub mod vectors;
+pub use errors::LinalgError;
+pub use matrices::Matrix;
+pub use sparse::SparseMatrix;
+pub use vectors::RowVector;
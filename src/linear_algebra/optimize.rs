@@ -0,0 +1,220 @@
+use std::collections::VecDeque;
+
+/// Settings for `lbfgs`. Stops when the largest (pseudo) gradient entry drops
+/// to `tolerance` or after `max_iterations`.
+pub(crate) struct LbfgsOptions {
+    pub max_iterations: usize,
+    pub tolerance: f64,
+    pub memory: usize,
+}
+
+pub(crate) struct Minimum {
+    pub x: Vec<f64>,
+    pub iterations: usize,
+    pub converged: bool,
+}
+
+/// Minimizes `f(x) + sum(l1[i] * |x[i]|)` where `objective` returns `f(x)` and
+/// writes its gradient into the second argument. With every `l1` entry zero
+/// this is plain L-BFGS, otherwise the orthant-wise variant (OWL-QN), which
+/// keeps the search inside one orthant per step so coefficients can land on
+/// exactly zero.
+pub(crate) fn lbfgs<F>(objective: F, x0: Vec<f64>, l1: &[f64], options: &LbfgsOptions) -> Minimum
+where
+    F: Fn(&[f64], &mut [f64]) -> f64,
+{
+    assert!(l1.len() == x0.len(), "l1 weights must match the parameters");
+    let n = x0.len();
+    let l1_value = |x: &[f64]| -> f64 { x.iter().zip(l1).map(|(value, c)| c * value.abs()).sum() };
+    let mut x = x0;
+    let mut gradient = vec![0.0; n];
+    let mut value = objective(&x, &mut gradient) + l1_value(&x);
+    // (s, y, 1 / y.s) of the most recent steps
+    let mut history: VecDeque<(Vec<f64>, Vec<f64>, f64)> = VecDeque::new();
+    let mut iterations = 0;
+    let mut converged = false;
+    while iterations < options.max_iterations {
+        let pseudo_gradient = pseudo_gradient(&x, &gradient, l1);
+        if max_abs(&pseudo_gradient) <= options.tolerance {
+            converged = true;
+            break;
+        }
+        iterations += 1;
+
+        let mut direction = two_loop(&pseudo_gradient, &history);
+        for i in 0..n {
+            if l1[i] > 0.0 && direction[i] * pseudo_gradient[i] >= 0.0 {
+                direction[i] = 0.0;
+            }
+        }
+        if dot(&direction, &pseudo_gradient) >= 0.0 {
+            history.clear();
+            direction = pseudo_gradient.iter().map(|g| -g).collect();
+        }
+        let orthant: Vec<f64> = (0..n)
+            .map(|i| {
+                if x[i] != 0.0 {
+                    x[i].signum()
+                } else {
+                    -pseudo_gradient[i].signum()
+                }
+            })
+            .collect();
+
+        // backtracking line search with the Armijo condition
+        let mut step = if history.is_empty() {
+            (1.0 / dot(&direction, &direction).sqrt()).min(1.0)
+        } else {
+            1.0
+        };
+        let mut accepted = None;
+        for _ in 0..60 {
+            let candidate: Vec<f64> = (0..n)
+                .map(|i| {
+                    let value = x[i] + step * direction[i];
+                    if l1[i] > 0.0 && value * orthant[i] <= 0.0 {
+                        0.0
+                    } else {
+                        value
+                    }
+                })
+                .collect();
+            let mut candidate_gradient = vec![0.0; n];
+            let candidate_value =
+                objective(&candidate, &mut candidate_gradient) + l1_value(&candidate);
+            let decrease: f64 = (0..n)
+                .map(|i| pseudo_gradient[i] * (candidate[i] - x[i]))
+                .sum();
+            if candidate_value <= value + 1e-4 * decrease {
+                accepted = Some((candidate, candidate_gradient, candidate_value));
+                break;
+            }
+            step *= 0.5;
+        }
+        let Some((candidate, candidate_gradient, candidate_value)) = accepted else {
+            // no decrease left at working precision
+            break;
+        };
+
+        let s: Vec<f64> = (0..n).map(|i| candidate[i] - x[i]).collect();
+        let y: Vec<f64> = (0..n)
+            .map(|i| candidate_gradient[i] - gradient[i])
+            .collect();
+        let sy = dot(&s, &y);
+        if sy > 1e-12 {
+            if history.len() == options.memory {
+                history.pop_front();
+            }
+            history.push_back((s, y, 1.0 / sy));
+        }
+        let previous_value = value;
+        x = candidate;
+        gradient = candidate_gradient;
+        value = candidate_value;
+        if previous_value - value
+            <= 64.0 * f64::EPSILON * previous_value.abs().max(value.abs()).max(1.0)
+        {
+            converged =
+                max_abs(&self::pseudo_gradient(&x, &gradient, l1)) <= options.tolerance.sqrt();
+            break;
+        }
+    }
+    Minimum {
+        x,
+        iterations,
+        converged,
+    }
+}
+
+// steepest descent direction of f + l1 norm, choosing the one sided derivative
+// that decreases the objective at coordinates sitting on zero
+fn pseudo_gradient(x: &[f64], gradient: &[f64], l1: &[f64]) -> Vec<f64> {
+    (0..x.len())
+        .map(|i| {
+            let c = l1[i];
+            if c == 0.0 {
+                gradient[i]
+            } else if x[i] > 0.0 {
+                gradient[i] + c
+            } else if x[i] < 0.0 {
+                gradient[i] - c
+            } else if gradient[i] + c < 0.0 {
+                gradient[i] + c
+            } else if gradient[i] - c > 0.0 {
+                gradient[i] - c
+            } else {
+                0.0
+            }
+        })
+        .collect()
+}
+
+// -H * gradient with H the L-BFGS inverse Hessian approximation
+fn two_loop(gradient: &[f64], history: &VecDeque<(Vec<f64>, Vec<f64>, f64)>) -> Vec<f64> {
+    let mut q = gradient.to_vec();
+    let mut alphas = Vec::with_capacity(history.len());
+    for (s, y, rho) in history.iter().rev() {
+        let alpha = rho * dot(s, &q);
+        q.iter_mut().zip(y).for_each(|(q, y)| *q -= alpha * y);
+        alphas.push(alpha);
+    }
+    if let Some((s, y, _)) = history.back() {
+        let gamma = dot(s, y) / dot(y, y);
+        q.iter_mut().for_each(|q| *q *= gamma);
+    }
+    for ((s, y, rho), alpha) in history.iter().zip(alphas.iter().rev()) {
+        let beta = rho * dot(y, &q);
+        q.iter_mut()
+            .zip(s)
+            .for_each(|(q, s)| *q += (alpha - beta) * s);
+    }
+    q.iter().map(|value| -value).collect()
+}
+
+fn dot(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(a, b)| a * b).sum()
+}
+
+fn max_abs(a: &[f64]) -> f64 {
+    a.iter().fold(0.0, |acc: f64, value| acc.max(value.abs()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options() -> LbfgsOptions {
+        LbfgsOptions {
+            max_iterations: 500,
+            tolerance: 1e-8,
+            memory: 10,
+        }
+    }
+
+    #[test]
+    fn test_lbfgs_rosenbrock() {
+        let rosenbrock = |x: &[f64], gradient: &mut [f64]| {
+            gradient[0] = -2.0 * (1.0 - x[0]) - 400.0 * x[0] * (x[1] - x[0] * x[0]);
+            gradient[1] = 200.0 * (x[1] - x[0] * x[0]);
+            (1.0 - x[0]).powi(2) + 100.0 * (x[1] - x[0] * x[0]).powi(2)
+        };
+        let minimum = lbfgs(rosenbrock, vec![-1.2, 1.0], &[0.0, 0.0], &options());
+        assert!(minimum.converged);
+        assert!((minimum.x[0] - 1.0).abs() < 1e-5);
+        assert!((minimum.x[1] - 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_owlqn_zeroes_small_coefficients() {
+        // (x0 - 3)^2 + (x1 - 0.2)^2 + |x0| + |x1| has its minimum at (2.5, 0)
+        let quadratic = |x: &[f64], gradient: &mut [f64]| {
+            gradient[0] = 2.0 * (x[0] - 3.0);
+            gradient[1] = 2.0 * (x[1] - 0.2);
+            (x[0] - 3.0).powi(2) + (x[1] - 0.2).powi(2)
+        };
+        let minimum = lbfgs(quadratic, vec![0.0, 0.0], &[1.0, 1.0], &options());
+        assert!(minimum.converged);
+        assert!((minimum.x[0] - 2.5).abs() < 1e-6);
+        assert!(minimum.x[1] == 0.0);
+    }
+}
@@ -0,0 +1,292 @@
+use crate::linear_algebra::{LinalgError, Matrix, RowVector, kernels};
+
+/// Compressed sparse row matrix. The nonzeros of row `i` are
+/// `values[indptr[i]..indptr[i + 1]]` at columns `indices[indptr[i]..indptr[i + 1]]`,
+/// with the column indices of each row sorted. A CSC view of a matrix is the
+/// CSR form of its transpose.
+#[derive(Clone, PartialEq, Debug)]
+pub struct SparseMatrix {
+    rows: usize,
+    columns: usize,
+    indptr: Vec<usize>,
+    indices: Vec<usize>,
+    values: Vec<f32>,
+}
+
+impl SparseMatrix {
+    /// Builds a matrix from `(row, column, value)` entries. Duplicate entries
+    /// are summed and explicit zeros are dropped.
+    pub fn from_triplets(rows: usize, columns: usize, triplets: &[(usize, usize, f32)]) -> Self {
+        let mut row_entries: Vec<Vec<(usize, f32)>> = vec![Vec::new(); rows];
+        for (row, column, value) in triplets.iter() {
+            assert!(
+                *row < rows && *column < columns,
+                "entry ({}, {}) is out of bounds for a {}x{} matrix",
+                row,
+                column,
+                rows,
+                columns
+            );
+            row_entries[*row].push((*column, *value));
+        }
+        let mut indptr = vec![0];
+        let mut indices = Vec::new();
+        let mut values = Vec::new();
+        for entries in row_entries.iter_mut() {
+            entries.sort_by_key(|(column, _)| *column);
+            let mut merged: Vec<(usize, f32)> = Vec::new();
+            for (column, value) in entries.iter() {
+                match merged.last_mut() {
+                    Some((last_column, last_value)) if last_column == column => {
+                        *last_value += value;
+                    }
+                    _ => merged.push((*column, *value)),
+                }
+            }
+            for (column, value) in merged {
+                if value != 0.0 {
+                    indices.push(column);
+                    values.push(value);
+                }
+            }
+            indptr.push(indices.len());
+        }
+        Self {
+            rows,
+            columns,
+            indptr,
+            indices,
+            values,
+        }
+    }
+
+    pub fn from_dense(m: &Matrix) -> Self {
+        let (rows, columns) = m.shape();
+        let mut triplets = Vec::new();
+        for (i, row) in m.matrix().iter().enumerate() {
+            for (j, value) in row.vector().iter().enumerate() {
+                if *value != 0.0 {
+                    triplets.push((i, j, *value));
+                }
+            }
+        }
+        Self::from_triplets(rows, columns, &triplets)
+    }
+
+    pub fn to_dense(&self) -> Matrix {
+        let mut dense = vec![vec![0.0; self.columns]; self.rows];
+        for (i, row) in dense.iter_mut().enumerate() {
+            for (column, value) in self.row(i) {
+                row[column] = value;
+            }
+        }
+        Matrix::to_matrix(&dense)
+    }
+
+    pub fn shape(&self) -> (usize, usize) {
+        (self.rows, self.columns)
+    }
+
+    pub fn len(&self) -> usize {
+        self.rows
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows == 0
+    }
+
+    /// Number of stored nonzero values.
+    pub fn nnz(&self) -> usize {
+        self.values.len()
+    }
+
+    /// `(column, value)` pairs of the nonzeros in row `i`.
+    pub fn row(&self, i: usize) -> impl Iterator<Item = (usize, f32)> + '_ {
+        let (start, end) = (self.indptr[i], self.indptr[i + 1]);
+        self.indices[start..end]
+            .iter()
+            .copied()
+            .zip(self.values[start..end].iter().copied())
+    }
+
+    pub fn get(&self, i: usize, j: usize) -> f32 {
+        let (start, end) = (self.indptr[i], self.indptr[i + 1]);
+        match self.indices[start..end].binary_search(&j) {
+            Ok(position) => self.values[start + position],
+            Err(_) => 0.0,
+        }
+    }
+
+    pub fn transpose(&self) -> SparseMatrix {
+        let mut counts = vec![0; self.columns + 1];
+        for column in self.indices.iter() {
+            counts[column + 1] += 1;
+        }
+        for i in 0..self.columns {
+            counts[i + 1] += counts[i];
+        }
+        let indptr = counts.clone();
+        let mut next = counts;
+        let mut indices = vec![0; self.nnz()];
+        let mut values = vec![0.0; self.nnz()];
+        // rows are visited in order so the transposed column indices stay sorted
+        for i in 0..self.rows {
+            for (column, value) in self.row(i) {
+                let position = next[column];
+                indices[position] = i;
+                values[position] = value;
+                next[column] += 1;
+            }
+        }
+        SparseMatrix {
+            rows: self.columns,
+            columns: self.rows,
+            indptr,
+            indices,
+            values,
+        }
+    }
+
+    /// Sparse-dense product `self * m`.
+    pub fn multiply_dense(&self, m: &Matrix) -> Result<Matrix, LinalgError> {
+        let (m_rows, m_columns) = m.shape();
+        if m_rows != self.columns {
+            return Err(LinalgError::DimensionMismatch {
+                expected: (self.columns, m_columns),
+                found: (m_rows, m_columns),
+            });
+        }
+        let output: Vec<RowVector> = (0..self.rows)
+            .map(|i| {
+                let mut output_row = vec![0.0; m_columns];
+                for (column, value) in self.row(i) {
+                    kernels::axpy(value, m.get(column).vector(), &mut output_row);
+                }
+                RowVector::new(&output_row)
+            })
+            .collect();
+        Ok(Matrix::new(&output))
+    }
+
+    /// Dense Gram matrix `self^T * self`, accumulated one row at a time so the
+    /// cost only depends on the nonzeros per row.
+    pub fn gram(&self) -> Matrix {
+        let mut gram = vec![vec![0.0_f64; self.columns]; self.columns];
+        for i in 0..self.rows {
+            for (column_a, value_a) in self.row(i) {
+                for (column_b, value_b) in self.row(i) {
+                    gram[column_a][column_b] += value_a as f64 * value_b as f64;
+                }
+            }
+        }
+        Matrix::from_f64(&gram)
+    }
+
+    /// Places the columns of `other` to the right of the columns of `self`.
+    pub fn hstack(&self, other: &SparseMatrix) -> SparseMatrix {
+        assert!(
+            self.rows == other.rows,
+            "cannot stack a matrix with {} rows next to one with {} rows",
+            self.rows,
+            other.rows
+        );
+        let mut indptr = vec![0];
+        let mut indices = Vec::with_capacity(self.nnz() + other.nnz());
+        let mut values = Vec::with_capacity(self.nnz() + other.nnz());
+        for i in 0..self.rows {
+            for (column, value) in self.row(i) {
+                indices.push(column);
+                values.push(value);
+            }
+            for (column, value) in other.row(i) {
+                indices.push(self.columns + column);
+                values.push(value);
+            }
+            indptr.push(indices.len());
+        }
+        SparseMatrix {
+            rows: self.rows,
+            columns: self.columns + other.columns,
+            indptr,
+            indices,
+            values,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example() -> Matrix {
+        Matrix::to_matrix(&vec![
+            vec![1.0, 0.0, 0.0, 2.0],
+            vec![0.0, 0.0, 3.0, 0.0],
+            vec![0.0, 0.0, 0.0, 0.0],
+            vec![4.0, 5.0, 0.0, 6.0],
+        ])
+    }
+
+    #[test]
+    fn test_sparse_round_trip() {
+        let dense = example();
+        let sparse = SparseMatrix::from_dense(&dense);
+        assert!(sparse.nnz() == 6);
+        assert!(sparse.shape() == (4, 4));
+        assert!(sparse.get(3, 1) == 5.0);
+        assert!(sparse.get(2, 2) == 0.0);
+        assert!(sparse.to_dense() == dense);
+    }
+
+    #[test]
+    fn test_sparse_from_triplets_sums_duplicates() {
+        let sparse = SparseMatrix::from_triplets(
+            2,
+            3,
+            &[(0, 2, 1.0), (0, 0, 2.0), (0, 2, 1.5), (1, 1, 0.0)],
+        );
+        assert!(sparse.nnz() == 2);
+        assert!(sparse.get(0, 2) == 2.5);
+        assert!(
+            sparse
+                .row(0)
+                .map(|(column, _)| column)
+                .collect::<Vec<usize>>()
+                == vec![0, 2]
+        );
+    }
+
+    #[test]
+    fn test_sparse_transpose() {
+        let dense = example();
+        let sparse = SparseMatrix::from_dense(&dense);
+        assert!(sparse.transpose().to_dense() == dense.transpose());
+    }
+
+    #[test]
+    fn test_sparse_multiply_dense() {
+        let dense = example();
+        let sparse = SparseMatrix::from_dense(&dense);
+        let other = Matrix::to_matrix(&vec![
+            vec![1.0, 2.0],
+            vec![3.0, 4.0],
+            vec![5.0, 6.0],
+            vec![7.0, 8.0],
+        ]);
+        assert!(sparse.multiply_dense(&other).unwrap() == dense.multiply(&other));
+        assert!(sparse.gram() == dense.transpose().multiply(&dense));
+        assert!(sparse.multiply_dense(&Matrix::zeros(3, 1)).is_err());
+    }
+
+    #[test]
+    fn test_sparse_hstack() {
+        let left = SparseMatrix::from_dense(&Matrix::to_matrix(&vec![vec![1.0], vec![0.0]]));
+        let right =
+            SparseMatrix::from_dense(&Matrix::to_matrix(&vec![vec![0.0, 2.0], vec![3.0, 0.0]]));
+        let stacked = left.hstack(&right);
+        assert!(
+            stacked.to_dense()
+                == Matrix::to_matrix(&vec![vec![1.0, 0.0, 2.0], vec![0.0, 3.0, 0.0]])
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use std::iter::zip;
+use crate::linear_algebra::kernels;
 
 #[derive(Clone, PartialEq, Debug)]
 pub struct RowVector {
@@ -16,8 +16,9 @@ impl RowVector {
         &self.vector
     }
 
+    /// Euclidean length of the vector.
     pub fn norm(&self) -> f32 {
-        self.vector.iter().fold(0.0, |acc, c| acc + c)
+        kernels::squared_norm(&self.vector).sqrt()
     }
 
     pub fn len(&self) -> usize {
@@ -25,17 +26,21 @@ impl RowVector {
     }
 
     pub fn multiply_by_scalar(&self, scalar: f32) -> RowVector {
-        let scaled_vector = self.vector().iter().map(|c| c * scalar).collect();
+        let scaled_vector = kernels::scaled(scalar, &self.vector);
         RowVector::new(&scaled_vector)
     }
 
+    /// Sums over the length of the shorter vector.
     pub fn add_vector(&self, v2: &RowVector) -> RowVector {
-        let summed_vectors: Vec<f32> = zip(self.vector(), v2.vector())
-            .map(|(c1, c2)| *c1 + *c2)
-            .collect();
+        let len = self.len().min(v2.len());
+        let summed_vectors = kernels::add(&self.vector[..len], &v2.vector[..len]);
         RowVector::new(&summed_vectors)
     }
 
+    pub fn dot(&self, v2: &RowVector) -> f32 {
+        kernels::dot(self.vector(), v2.vector())
+    }
+
     pub fn get(&self, i: usize) -> f32 {
         self.vector[i].clone()
     }
@@ -42,6 +42,22 @@ impl Transformer for CombinedAttributesAdder {
         df.insert_column("bedrooms_per_room", &bedrooms_per_room, &DataType::Float);
         return df;
     }
+
+    fn get_feature_names_out(
+        &self,
+        input_features: &Vec<String>,
+        _column_names: &Vec<String>,
+    ) -> Vec<String> {
+        let mut names = input_features.clone();
+        for name in [
+            "rooms_per_household",
+            "population_per_household",
+            "bedrooms_per_room",
+        ] {
+            names.push(name.to_string());
+        }
+        return names;
+    }
 }
 fn main() {
     let filename = "housing.csv";
@@ -53,60 +69,38 @@ fn main() {
         df.get_rows_as_df(&train_indices),
         df.get_rows_as_df(&test_indices),
     );
-    let (mut train_features, mut test_features) = (train_set.clone(), test_set.clone());
     let label = "median_house_value";
-    train_features.remove_column(label);
-    test_features.remove_column(label);
-    let polynomial_features = PolynomialFeatures::new(2);
-    let columns: Vec<String> = train_features
-        .numeric_columns()
-        .into_iter()
-        .map(|column| column.clone())
-        .collect();
     let imputer: Box<dyn Transformer> = Box::new(Imputer::new(&ImputerStrategy::Median));
-    let train_features = imputer.transform(&train_features, &columns);
-    let test_features = imputer.transform(&test_features, &columns);
-    let train_features = polynomial_features.transform(&train_features, &columns);
-    let test_features = polynomial_features.transform(&test_features, &columns);
-    let (train_labels, test_labels) = (
-        train_set
-            .get_columns_as_df(&vec![label.to_string()])
-            .as_matrix(false),
-        test_set
-            .get_columns_as_df(&vec![label.to_string()])
-            .as_matrix(false),
-    );
+    let polynomial_features: Box<dyn Transformer> = Box::new(PolynomialFeatures::new(2));
     let combined_attr_adder: Box<dyn Transformer> = Box::new(CombinedAttributesAdder::new());
     let one_hot_encoder: Box<dyn Transformer> = Box::new(OneHotEncoder::new(true));
     let std_scalar: Box<dyn Transformer> = Box::new(StandardScalar::new());
     let cat_transformers = vec![one_hot_encoder];
     let cat_pipeline = CategoricalPipeline::new(cat_transformers);
-    let num_transformers = vec![combined_attr_adder, std_scalar];
+    let num_transformers = vec![
+        imputer,
+        polynomial_features,
+        combined_attr_adder,
+        std_scalar,
+    ];
     let num_pipeline = NumericalPipeline::new(num_transformers);
     let column_transformer = ColumnTransformer::new(num_pipeline, cat_pipeline);
-    let (train_inputs, test_inputs) = (
-        column_transformer.transform(&train_features),
-        column_transformer.transform(&test_features),
-    );
-    let mut linear_regression = LinearRegression::new(0.0);
-    linear_regression.fit(&train_inputs, &train_labels);
-    let (train_predictions, test_predictions) = (
-        linear_regression.predict(&train_inputs),
-        linear_regression.predict(&test_inputs),
+    let mut pipeline = Pipeline::new(
+        Preprocessor::Columns(column_transformer),
+        FinalEstimator::Regressor(Box::new(LinearRegression::new(0.0))),
     );
-    let (train_labels, test_labels) = (
-        train_labels.matrix()
-            .into_iter()
+    pipeline.fit(&train_set, label).unwrap();
+    let labels = |df: &DataFrame| {
+        df.get_columns_as_df(&vec![label.to_string()])
+            .as_matrix(false)
+            .matrix()
+            .iter()
             .map(|label| label.get(0))
-            .collect::<Vec<f32>>(),
-        test_labels.matrix()
-            .into_iter()
-            .map(|label| label.get(0))
-            .collect::<Vec<f32>>(),
-    );
+            .collect::<Vec<f32>>()
+    };
     let (train_rmse, test_rmse) = (
-        rmse(&train_predictions.vector(), &train_labels),
-        rmse(&test_predictions.vector(), &test_labels),
+        rmse(pipeline.predict(&train_set).vector(), &labels(&train_set)),
+        rmse(pipeline.predict(&test_set).vector(), &labels(&test_set)),
     );
     println!("{}, {}", train_rmse, test_rmse);
 }
@@ -0,0 +1,134 @@
+use std::panic;
+#[cfg(test)]
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+static NUM_THREADS: AtomicUsize = AtomicUsize::new(0);
+
+/// Sets the number of worker threads used by the parallel kernels, `0` (the
+/// default) uses the available parallelism of the machine. Without the
+/// `parallel` feature everything runs on the calling thread regardless.
+pub fn set_num_threads(num_threads: usize) {
+    NUM_THREADS.store(num_threads, Ordering::Relaxed);
+}
+
+#[cfg(test)]
+static NUM_THREADS_LOCK: Mutex<()> = Mutex::new(());
+
+/// Runs `f` with the thread count set to `num_threads`, holding a lock so
+/// tests comparing thread counts cannot change it under each other.
+#[cfg(test)]
+pub(crate) fn with_num_threads<T>(num_threads: usize, f: impl FnOnce() -> T) -> T {
+    let _guard = NUM_THREADS_LOCK
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    set_num_threads(num_threads);
+    let output = f();
+    set_num_threads(0);
+    return output;
+}
+
+pub fn num_threads() -> usize {
+    if !cfg!(feature = "parallel") {
+        return 1;
+    }
+    match NUM_THREADS.load(Ordering::Relaxed) {
+        0 => thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(1),
+        n => n,
+    }
+}
+
+/// Maps `f` over `0..len`, giving each thread a contiguous chunk of at least
+/// `min_chunk` indices. Results come back in index order and each index is
+/// computed exactly as the sequential loop would, so the output does not
+/// depend on the thread count.
+pub fn parallel_map<T, F>(len: usize, min_chunk: usize, f: F) -> Vec<T>
+where
+    T: Send,
+    F: Fn(usize) -> T + Sync,
+{
+    let threads = num_threads().min(len / min_chunk.max(1)).max(1);
+    if threads == 1 {
+        return (0..len).map(f).collect();
+    }
+    let chunk = len.div_ceil(threads);
+    thread::scope(|scope| {
+        let handles: Vec<_> = (0..threads)
+            .map(|t| {
+                let f = &f;
+                scope.spawn(move || {
+                    (t * chunk..((t + 1) * chunk).min(len))
+                        .map(f)
+                        .collect::<Vec<T>>()
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|handle| {
+                handle
+                    .join()
+                    .unwrap_or_else(|payload| panic::resume_unwind(payload))
+            })
+            .collect()
+    })
+}
+
+/// Runs two independent closures, concurrently when more than one thread is
+/// available.
+pub fn join<A, B, RA, RB>(a: A, b: B) -> (RA, RB)
+where
+    A: FnOnce() -> RA + Send,
+    B: FnOnce() -> RB + Send,
+    RA: Send,
+    RB: Send,
+{
+    if num_threads() == 1 {
+        return (a(), b());
+    }
+    thread::scope(|scope| {
+        let handle = scope.spawn(b);
+        let ra = a();
+        let rb = handle
+            .join()
+            .unwrap_or_else(|payload| panic::resume_unwind(payload));
+        (ra, rb)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parallel_map_is_ordered() {
+        let expected: Vec<usize> = (0..1000).map(|i| i * i).collect();
+        for threads in [0, 1, 3, 8] {
+            with_num_threads(threads, || {
+                assert!(parallel_map(1000, 1, |i| i * i) == expected);
+                assert!(parallel_map(5, 16, |i| i * i) == expected[..5].to_vec());
+            });
+        }
+    }
+
+    #[test]
+    fn test_join() {
+        let (a, b) = join(|| (0..100).sum::<usize>(), || "done".to_string());
+        assert!(a == 4950);
+        assert!(b == "done");
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_parallel_map_propagates_panics() {
+        parallel_map(100, 1, |i| {
+            if i == 50 {
+                panic!("worker failed");
+            }
+            i
+        });
+    }
+}
@@ -1,78 +1,148 @@
 use crate::{
     dataframe::{DataFrame, DataType, DataTypeValue},
+    linear_algebra::SparseMatrix,
     pipeline::transformers::Transformer,
 };
 use std::collections::HashMap;
 
+/// One column per category, named after it. Unfitted, the categories come
+/// from the frame being encoded; once fitted, columns seen during `fit` keep
+/// their training categories so every frame gets the same output columns,
+/// with unseen categories and nulls encoded as all zeros.
 pub struct OneHotEncoder {
     drop: bool,
+    // every sorted category of the fitted columns, including a dropped one
+    categories: Option<HashMap<String, Vec<String>>>,
 }
 
 impl OneHotEncoder {
     pub fn new(drop: bool) -> Self {
-        Self { drop }
+        Self {
+            drop,
+            categories: None,
+        }
+    }
+
+    /// Categories of a fitted column that get an output column, sorted.
+    pub fn categories(&self, column_name: &str) -> Option<&[String]> {
+        return self
+            .fitted_categories(column_name)
+            .map(|categories| self.kept(categories));
+    }
+
+    fn fitted_categories(&self, column_name: &str) -> Option<&Vec<String>> {
+        return self
+            .categories
+            .as_ref()
+            .and_then(|categories| categories.get(column_name));
+    }
+
+    // the last category has no column when dropping
+    fn kept<'a>(&self, categories: &'a [String]) -> &'a [String] {
+        if self.drop && !categories.is_empty() {
+            return &categories[..categories.len() - 1];
+        }
+        return categories;
+    }
+
+    fn categories_of(&self, column_name: &str, values: &[DataTypeValue]) -> Vec<String> {
+        let mut categories: Vec<String> = Vec::new();
+        for value in values.iter() {
+            match value {
+                DataTypeValue::String(inner) => categories.push(inner.clone()),
+                DataTypeValue::Null => {}
+                _ => panic!(
+                    "dtype value {:?} in column {} is not categorical",
+                    value, column_name
+                ),
+            }
+        }
+        categories.sort();
+        categories.dedup();
+        return categories;
     }
-    fn extract_categorical_values(
+
+    /// Sorted categories that get an output column and, for each row, the
+    /// index of its category among them (`None` for nulls, dropped and unseen
+    /// categories).
+    fn category_indices(
         &self,
         column_name: &str,
-        values: &Vec<DataTypeValue>,
-    ) -> HashMap<String, Vec<DataTypeValue>> {
-        let mut categories: HashMap<String, Vec<DataTypeValue>> = HashMap::new();
-        let null_placeholder = "null".to_string();
-        for (i, value) in values.iter().enumerate() {
-            let inner = match value {
-                DataTypeValue::String(inner) => inner.clone(),
-                DataTypeValue::Null => null_placeholder.clone(),
-                _ => {
-                    panic!("dtype value is not categorical")
-                }
-            };
-            assert!(
-                categories
+        values: &[DataTypeValue],
+    ) -> (Vec<String>, Vec<Option<usize>>) {
+        let categories = match self.categories(column_name) {
+            Some(categories) => {
+                if let Some(value) = values
                     .iter()
-                    .all(|(category, _)| *category != null_placeholder),
-                "{}",
-                format!(
-                    "Column {} has designated category of {}",
-                    column_name, null_placeholder
-                )
-            );
-            for (category, cat_values) in categories.iter_mut() {
-                if *category == *inner {
-                    cat_values.push(DataTypeValue::Float(1.0));
-                } else {
-                    cat_values.push(DataTypeValue::Float(0.0));
+                    .find(|value| !matches!(value, DataTypeValue::String(_) | DataTypeValue::Null))
+                {
+                    panic!(
+                        "dtype value {:?} in column {} is not categorical",
+                        value, column_name
+                    );
                 }
+                categories.to_vec()
             }
-            if *inner != null_placeholder {
-                if !categories.contains_key(inner.as_str()) {
-                    let mut cat_values = vec![DataTypeValue::Float(0.0); i];
-                    cat_values.push(DataTypeValue::Float(1.0));
-                    categories.insert(inner.clone(), cat_values);
+            None => self.kept(&self.categories_of(column_name, values)).to_vec(),
+        };
+        let positions: HashMap<&String, usize> = categories
+            .iter()
+            .enumerate()
+            .map(|(i, category)| (category, i))
+            .collect();
+        let row_indices = values
+            .iter()
+            .map(|value| match value {
+                DataTypeValue::String(inner) => positions.get(inner).copied(),
+                _ => None,
+            })
+            .collect();
+        (categories, row_indices)
+    }
+
+    /// Sparse output mode: one-hot encodes `column_names` into a CSR matrix
+    /// with one column per category, in the same column order that
+    /// `transform` inserts them, without materializing the zeros.
+    pub fn transform_sparse(&self, df: &DataFrame, column_names: &Vec<String>) -> SparseMatrix {
+        let mut triplets = Vec::new();
+        let mut offset = 0;
+        for column_name in column_names {
+            let (_, values) = df.get_column(column_name);
+            let (categories, row_indices) = self.category_indices(column_name, values);
+            for (i, index) in row_indices.iter().enumerate() {
+                if let Some(index) = index {
+                    triplets.push((i, offset + index, 1.0));
                 }
             }
+            offset += categories.len();
         }
-        return categories;
+        SparseMatrix::from_triplets(df.len(), offset, &triplets)
     }
 }
+
 impl Transformer for OneHotEncoder {
+    fn fit(&mut self, df: &DataFrame, column_names: &Vec<String>) {
+        let categories = column_names
+            .iter()
+            .map(|column_name| {
+                let (_, values) = df.get_column(column_name);
+                (column_name.clone(), self.categories_of(column_name, values))
+            })
+            .collect();
+        self.categories = Some(categories);
+    }
+
     fn transform(&self, df: &DataFrame, column_names: &Vec<String>) -> DataFrame {
         let mut df_one_hot_encoded = df.clone();
         for column_name in column_names {
             let (_, values) = df.get_column(column_name);
-            let categorical_values = self.extract_categorical_values(column_name.as_str(), values);
-            let mut categories = categorical_values
-                .iter()
-                .map(|(category, _)| category.clone())
-                .collect::<Vec<String>>();
-            categories.sort();
-            if self.drop {
-                let cat_len = categories.len();
-                categories.remove(cat_len - 1);
-            }
-            for category in categories.iter() {
-                let cat_values = categorical_values.get(category).unwrap();
-                df_one_hot_encoded.insert_column(category, cat_values, &DataType::Float);
+            let (categories, row_indices) = self.category_indices(column_name, values);
+            for (k, category) in categories.iter().enumerate() {
+                let cat_values: Vec<DataTypeValue> = row_indices
+                    .iter()
+                    .map(|index| DataTypeValue::Float((*index == Some(k)) as u8 as f32))
+                    .collect();
+                df_one_hot_encoded.insert_column(category, &cat_values, &DataType::Float);
             }
         }
         for column_name in column_names {
@@ -80,6 +150,59 @@ impl Transformer for OneHotEncoder {
         }
         return df_one_hot_encoded;
     }
+
+    /// Restores every column from its category columns. A row without a set
+    /// category column gets the dropped category when there is one and null
+    /// otherwise.
+    fn inverse_transform(&self, df: &DataFrame, column_names: &Vec<String>) -> Option<DataFrame> {
+        let mut df_decoded = df.clone();
+        for column_name in column_names {
+            let categories = self.fitted_categories(column_name).unwrap_or_else(|| {
+                panic!("one hot encoder must be fitted on {} first", column_name)
+            });
+            let kept = self.kept(categories);
+            let category_values: Vec<&Vec<DataTypeValue>> = kept
+                .iter()
+                .map(|category| df.get_column(category).1)
+                .collect();
+            let values: Vec<DataTypeValue> = (0..df.len())
+                .map(|row| {
+                    let set = category_values.iter().position(|values| match values[row] {
+                        DataTypeValue::Float(value) => value >= 0.5,
+                        _ => panic!("category column values must be floats"),
+                    });
+                    match set {
+                        Some(k) => DataTypeValue::String(kept[k].clone()),
+                        None if kept.len() < categories.len() => {
+                            DataTypeValue::String(categories[categories.len() - 1].clone())
+                        }
+                        None => DataTypeValue::Null,
+                    }
+                })
+                .collect();
+            for category in kept.iter() {
+                df_decoded.remove_column(category);
+            }
+            df_decoded.insert_column(column_name, &values, &DataType::String);
+        }
+        return Some(df_decoded);
+    }
+
+    fn get_feature_names_out(
+        &self,
+        input_features: &Vec<String>,
+        column_names: &Vec<String>,
+    ) -> Vec<String> {
+        let mut names = input_features.clone();
+        for column_name in column_names {
+            let categories = self.categories(column_name).unwrap_or_else(|| {
+                panic!("one hot encoder must be fitted on {} first", column_name)
+            });
+            names.extend(categories.iter().cloned());
+        }
+        names.retain(|name| !column_names.contains(name));
+        return names;
+    }
 }
 
 #[cfg(test)]
@@ -149,6 +272,29 @@ mod tests {
         test_df_one_hot_encoded(true);
     }
 
+    #[test]
+    fn test_one_hot_encoded_sparse_matches_dense() {
+        let df = df_from_csv("housing.csv", Some(1000));
+        let categorical_columns = vec!["ocean_proximity".to_string()];
+        for drop in [false, true] {
+            let one_hot_encoder = OneHotEncoder::new(drop);
+            let sparse = one_hot_encoder.transform_sparse(&df, &categorical_columns);
+            let df_one_hot_encoded = one_hot_encoder.transform(&df, &categorical_columns);
+            let original_columns = df.columns();
+            let encoded_columns: Vec<String> = df_one_hot_encoded
+                .columns()
+                .into_iter()
+                .filter(|column| !original_columns.contains(column))
+                .cloned()
+                .collect();
+            let dense = df_one_hot_encoded
+                .get_columns_as_df(&encoded_columns)
+                .as_matrix(false);
+            assert!(sparse.shape() == dense.shape());
+            assert!(sparse.to_dense() == dense);
+        }
+    }
+
     #[test]
     #[should_panic]
     fn test_df_one_hot_encoded_with_numeric_column() {
@@ -159,4 +305,44 @@ mod tests {
         let one_hot_encoder = OneHotEncoder::new(false);
         one_hot_encoder.transform(&df, &vec![numeric_column.to_string()]);
     }
+
+    #[test]
+    fn test_fitted_one_hot_encoder_keeps_training_categories() {
+        let df = df_from_csv("housing.csv", Some(1000));
+        let categorical_columns = vec!["ocean_proximity".to_string()];
+        let mut one_hot_encoder = OneHotEncoder::new(false);
+        one_hot_encoder.fit(&df, &categorical_columns);
+        let categories = one_hot_encoder
+            .categories("ocean_proximity")
+            .unwrap()
+            .to_vec();
+        assert!(categories.len() > 1);
+        let row = df.get_rows_as_df(&vec![0]);
+        let encoded = one_hot_encoder.transform(&row, &categorical_columns);
+        assert!(categories.iter().all(|category| {
+            let (_, values) = encoded.get_column(category);
+            let expected = *row.get_cell_value("ocean_proximity", 0)
+                == DataTypeValue::String(category.clone());
+            values == &vec![DataTypeValue::Float(expected as u8 as f32)]
+        }));
+    }
+
+    #[test]
+    fn test_one_hot_inverse_transform() {
+        let df = df_from_csv("housing.csv", Some(1000));
+        let categorical_columns = vec!["ocean_proximity".to_string()];
+        let (_, values) = df.get_column("ocean_proximity");
+        for drop in [false, true] {
+            let mut one_hot_encoder = OneHotEncoder::new(drop);
+            one_hot_encoder.fit(&df, &categorical_columns);
+            let encoded = one_hot_encoder.transform(&df, &categorical_columns);
+            let decoded = one_hot_encoder
+                .inverse_transform(&encoded, &categorical_columns)
+                .unwrap();
+            assert!(decoded.columns() == df.columns());
+            let (dtype, decoded_values) = decoded.get_column("ocean_proximity");
+            assert!(*dtype == DataType::String);
+            assert!(decoded_values == values);
+        }
+    }
 }
@@ -1,29 +1,42 @@
 use crate::{
     dataframe::{DataFrame, DataType, DataTypeValue},
+    parallel::parallel::parallel_map,
     pipeline::transformers::Transformer,
 };
+use std::collections::HashMap;
+use std::iter::zip;
 
 #[derive(Clone)]
 pub enum ImputerStrategy {
     Median,
 }
 
+/// Fills nulls in float columns, skipping string columns. Unfitted, it fills
+/// from each frame's own medians; once fitted, columns seen during `fit`
+/// reuse the training medians.
 pub struct Imputer {
     strategy: ImputerStrategy,
+    medians: Option<HashMap<String, f32>>,
 }
 
 impl Imputer {
     pub fn new(strategy: &ImputerStrategy) -> Self {
         Self {
             strategy: strategy.clone(),
+            medians: None,
         }
     }
-}
 
-impl Transformer for Imputer {
-    fn transform(&self, df: &DataFrame, column_names: &Vec<String>) -> DataFrame {
-        let mut df = df.clone();
-        let df_column_names: Vec<String> = df
+    /// Training median of a fitted column.
+    pub fn median(&self, column_name: &str) -> Option<f32> {
+        return self
+            .medians
+            .as_ref()
+            .and_then(|medians| medians.get(column_name).copied());
+    }
+
+    fn imputed_columns(df: &DataFrame, column_names: &Vec<String>) -> Vec<String> {
+        return df
             .columns()
             .into_iter()
             .filter(|df_column_name| {
@@ -31,30 +44,73 @@ impl Transformer for Imputer {
             })
             .map(|column_name| column_name.clone())
             .collect();
-        for df_column_name in df_column_names.iter() {
-            let (dtype, _) = df.get_column(df_column_name);
-            match dtype {
-                DataType::Float => {
-                    let median = df.median(df_column_name);
-                    for i in 0..df.len() {
-                        let current_value = df.get_cell_value(df_column_name, i).clone();
-                        match current_value {
-                            DataTypeValue::Float(_) => {}
-                            DataTypeValue::Null => {
-                                df.modify_cell(df_column_name, i, DataTypeValue::Float(median));
-                            }
-                            _ => panic!("value type is inconsistent with column datatype header"),
-                        }
+    }
+}
+
+impl Transformer for Imputer {
+    fn fit(&mut self, df: &DataFrame, column_names: &Vec<String>) {
+        let medians = Self::imputed_columns(df, column_names)
+            .into_iter()
+            .filter(|column_name| matches!(df.get_column(column_name).0, DataType::Float))
+            .map(|column_name| {
+                let median = df.median(&column_name);
+                (column_name, median)
+            })
+            .collect();
+        self.medians = Some(medians);
+    }
+
+    fn transform(&self, df: &DataFrame, column_names: &Vec<String>) -> DataFrame {
+        let mut df = df.clone();
+        let df_column_names = Self::imputed_columns(&df, column_names);
+        let imputed_columns: Vec<Option<Vec<DataTypeValue>>> =
+            parallel_map(df_column_names.len(), 1, |column_index| {
+                let df_column_name = &df_column_names[column_index];
+                let (dtype, values) = df.get_column(df_column_name);
+                match dtype {
+                    DataType::Float => {
+                        let median = self
+                            .median(df_column_name)
+                            .unwrap_or_else(|| df.median(df_column_name));
+                        Some(
+                            values
+                                .iter()
+                                .map(|current_value| match current_value {
+                                    DataTypeValue::Float(_) => current_value.clone(),
+                                    DataTypeValue::Null => DataTypeValue::Float(median),
+                                    _ => panic!(
+                                        "value type is inconsistent with column datatype header"
+                                    ),
+                                })
+                                .collect(),
+                        )
+                    }
+                    DataType::String => None,
+                    _ => {
+                        panic!("id datatypes cannot be processed")
                     }
                 }
-                DataType::String => {}
-                _ => {
-                    panic!("id datatypes cannot be processed")
-                }
+            });
+        for (df_column_name, imputed_values) in zip(df_column_names, imputed_columns) {
+            if let Some(imputed_values) = imputed_values {
+                df.replace_column_values(&df_column_name, imputed_values);
             }
         }
         return df;
     }
+
+    /// The identity, imputed values cannot be told apart from observed ones.
+    fn inverse_transform(&self, df: &DataFrame, _column_names: &Vec<String>) -> Option<DataFrame> {
+        return Some(df.clone());
+    }
+
+    fn get_feature_names_out(
+        &self,
+        input_features: &Vec<String>,
+        _column_names: &Vec<String>,
+    ) -> Vec<String> {
+        return input_features.clone();
+    }
 }
 
 #[cfg(test)]
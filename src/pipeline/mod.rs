@@ -1,6 +1,8 @@
 pub mod encoders;
 pub mod imputers;
+pub mod pca;
 pub mod pipeline;
 pub mod polynomial_features;
 pub mod scalars;
+pub mod targets;
 pub mod transformers;
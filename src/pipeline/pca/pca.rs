@@ -0,0 +1,437 @@
+use crate::{
+    dataframe::{DataFrame, DataType, DataTypeValue},
+    linear_algebra::{Matrix, decompositions::SymmetricEigenDecomposition},
+    pipeline::transformers::Transformer,
+};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Components {
+    /// Keep this many components.
+    Count(usize),
+    /// Keep the fewest components explaining at least this share of the
+    /// variance.
+    VarianceRatio(f32),
+}
+
+#[derive(Clone, Debug)]
+struct FittedPca {
+    columns: Vec<String>,
+    means: Vec<f64>,
+    // one row per component, one column per input column
+    loadings: Vec<Vec<f64>>,
+    explained_variance: Vec<f64>,
+    total_variance: f64,
+}
+
+/// Principal component analysis. Centers the selected columns and projects
+/// them onto the leading eigenvectors of their covariance, replacing them with
+/// `pc1..pcN` columns. As a pipeline step an unfitted `PCA` fits on the frame
+/// it is given (like `StandardScalar`), a fitted one reuses its components.
+pub struct PCA {
+    components: Components,
+    columns: Option<Vec<String>>,
+    fitted: Option<FittedPca>,
+}
+
+impl PCA {
+    pub fn new(components: Components) -> Self {
+        match components {
+            Components::Count(0) => panic!("pca must keep at least one component"),
+            Components::VarianceRatio(ratio) if !(ratio > 0.0 && ratio <= 1.0) => {
+                panic!("variance ratio must be in (0, 1]")
+            }
+            _ => {}
+        }
+        return Self {
+            components,
+            columns: None,
+            fitted: None,
+        };
+    }
+
+    /// Columns to reduce in place of the ones a pipeline passes in, such as
+    /// the `column^degree` columns added by `PolynomialFeatures`.
+    pub fn set_columns(&mut self, columns: &Vec<String>) {
+        self.columns = Some(columns.clone());
+    }
+
+    pub fn fit(&mut self, df: &DataFrame, column_names: &Vec<String>) {
+        let columns = self.columns.clone().unwrap_or(column_names.clone());
+        self.fitted = Some(self.fit_columns(df, &columns));
+    }
+
+    pub fn fit_transform(&mut self, df: &DataFrame, column_names: &Vec<String>) -> DataFrame {
+        self.fit(df, column_names);
+        return self.transform(df, column_names);
+    }
+
+    fn fit_columns(&self, df: &DataFrame, columns: &Vec<String>) -> FittedPca {
+        if columns.is_empty() {
+            panic!("pca needs at least one column");
+        }
+        let data = read_columns(df, columns);
+        let rows = df.len();
+        if rows < 2 {
+            panic!("pca needs at least two rows");
+        }
+        let means: Vec<f64> = data
+            .iter()
+            .map(|column| column.iter().sum::<f64>() / rows as f64)
+            .collect();
+        let dimensions = columns.len();
+        let mut covariance = vec![vec![0.0; dimensions]; dimensions];
+        for i in 0..dimensions {
+            for j in i..dimensions {
+                let value = data[i]
+                    .iter()
+                    .zip(data[j].iter())
+                    .map(|(a, b)| (a - means[i]) * (b - means[j]))
+                    .sum::<f64>()
+                    / (rows - 1) as f64;
+                covariance[i][j] = value;
+                covariance[j][i] = value;
+            }
+        }
+        let decomposition = SymmetricEigenDecomposition::new(&Matrix::from_f64(&covariance))
+            .expect("covariance matrices are square and symmetric");
+        let eigenvalues: Vec<f64> = decomposition
+            .eigenvalues()
+            .iter()
+            .map(|value| (*value as f64).max(0.0))
+            .collect();
+        let total_variance: f64 = eigenvalues.iter().sum();
+        let keep = match self.components {
+            Components::Count(count) => count.min(dimensions),
+            Components::VarianceRatio(ratio) => {
+                let mut cumulative = 0.0;
+                eigenvalues
+                    .iter()
+                    .position(|value| {
+                        cumulative += value;
+                        cumulative >= ratio as f64 * total_variance * (1.0 - 1e-9)
+                    })
+                    .map_or(dimensions, |index| index + 1)
+            }
+        };
+        let eigenvectors = decomposition.eigenvectors().to_f64();
+        let loadings = (0..keep)
+            .map(|k| {
+                let mut component: Vec<f64> = (0..dimensions).map(|j| eigenvectors[j][k]).collect();
+                // eigenvector signs are arbitrary, make the largest loading positive
+                let largest =
+                    component.iter().fold(
+                        0.0,
+                        |acc: f64, value| if value.abs() > acc.abs() { *value } else { acc },
+                    );
+                if largest < 0.0 {
+                    component.iter_mut().for_each(|value| *value = -*value);
+                }
+                component
+            })
+            .collect();
+        return FittedPca {
+            columns: columns.clone(),
+            means,
+            loadings,
+            explained_variance: eigenvalues[..keep].to_vec(),
+            total_variance,
+        };
+    }
+
+    fn fitted(&self) -> &FittedPca {
+        return self.fitted.as_ref().expect("pca must be fitted first");
+    }
+
+    /// Maps `pc1..pcN` columns back to the original columns, exact when every
+    /// component was kept.
+    pub fn inverse_transform(&self, df: &DataFrame) -> DataFrame {
+        let fitted = self.fitted();
+        let names = self.component_names();
+        let scores = read_columns(df, &names);
+        let mut df = df.clone();
+        for name in names.iter() {
+            df.remove_column(name);
+        }
+        for (j, column) in fitted.columns.iter().enumerate() {
+            let values = (0..df.len())
+                .map(|row| {
+                    let value = fitted.means[j]
+                        + fitted
+                            .loadings
+                            .iter()
+                            .zip(scores.iter())
+                            .map(|(component, score)| component[j] * score[row])
+                            .sum::<f64>();
+                    DataTypeValue::Float(value as f32)
+                })
+                .collect();
+            df.insert_column(column, &values, &DataType::Float);
+        }
+        return df;
+    }
+
+    /// `pc1..pcN`, the names of the emitted columns.
+    pub fn component_names(&self) -> Vec<String> {
+        return (1..=self.num_components())
+            .map(|k| format!("pc{}", k))
+            .collect();
+    }
+
+    pub fn num_components(&self) -> usize {
+        return self.fitted().loadings.len();
+    }
+
+    /// The columns the components are built from.
+    pub fn feature_names(&self) -> &Vec<String> {
+        return &self.fitted().columns;
+    }
+
+    /// Variance of the data along each kept component.
+    pub fn explained_variance(&self) -> Vec<f32> {
+        return self
+            .fitted()
+            .explained_variance
+            .iter()
+            .map(|value| *value as f32)
+            .collect();
+    }
+
+    /// Share of the total variance along each kept component.
+    pub fn explained_variance_ratio(&self) -> Vec<f32> {
+        let fitted = self.fitted();
+        return fitted
+            .explained_variance
+            .iter()
+            .map(|value| (value / fitted.total_variance.max(f64::MIN_POSITIVE)) as f32)
+            .collect();
+    }
+
+    /// Components as rows of unit length, one column per input column.
+    pub fn loadings(&self) -> Matrix {
+        return Matrix::from_f64(&self.fitted().loadings);
+    }
+}
+
+impl Transformer for PCA {
+    fn fit(&mut self, df: &DataFrame, column_names: &Vec<String>) {
+        PCA::fit(self, df, column_names);
+    }
+
+    fn transform(&self, df: &DataFrame, column_names: &Vec<String>) -> DataFrame {
+        let fitted = match &self.fitted {
+            Some(fitted) => fitted,
+            None => &self.fit_columns(df, self.columns.as_ref().unwrap_or(column_names)),
+        };
+        let data = read_columns(df, &fitted.columns);
+        let mut df = df.clone();
+        for column in fitted.columns.iter() {
+            df.remove_column(column);
+        }
+        for (k, component) in fitted.loadings.iter().enumerate() {
+            let values = (0..df.len())
+                .map(|row| {
+                    let score: f64 = component
+                        .iter()
+                        .zip(data.iter().zip(fitted.means.iter()))
+                        .map(|(loading, (column, mean))| loading * (column[row] - mean))
+                        .sum();
+                    DataTypeValue::Float(score as f32)
+                })
+                .collect();
+            df.insert_column(&format!("pc{}", k + 1), &values, &DataType::Float);
+        }
+        return df;
+    }
+
+    fn inverse_transform(&self, df: &DataFrame, _column_names: &Vec<String>) -> Option<DataFrame> {
+        return Some(PCA::inverse_transform(self, df));
+    }
+
+    fn get_feature_names_out(
+        &self,
+        input_features: &Vec<String>,
+        _column_names: &Vec<String>,
+    ) -> Vec<String> {
+        let fitted = self.fitted();
+        let mut names: Vec<String> = input_features
+            .iter()
+            .filter(|name| !fitted.columns.contains(name))
+            .cloned()
+            .collect();
+        names.extend(self.component_names());
+        return names;
+    }
+}
+
+// column major values, panicking on anything but non null floats
+fn read_columns(df: &DataFrame, columns: &Vec<String>) -> Vec<Vec<f64>> {
+    return columns
+        .iter()
+        .map(|column| {
+            let (_, values) = df.get_column(column);
+            values
+                .iter()
+                .map(|value| match value {
+                    DataTypeValue::Float(inner) => *inner as f64,
+                    _ => panic!(
+                        "pca column {} must only contain floats, found {:?}",
+                        column, value
+                    ),
+                })
+                .collect()
+        })
+        .collect();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dataframe::csv::df_from_csv;
+    use crate::pipeline::{
+        encoders::one_hot_encoder::OneHotEncoder,
+        imputers::imputer::{Imputer, ImputerStrategy},
+        pipeline::{CategoricalPipeline, ColumnTransformer, NumericalPipeline},
+        polynomial_features::polynomial_features::PolynomialFeatures,
+        scalars::standard_scalar::StandardScalar,
+    };
+    use crate::sampling::random::Rng;
+    use std::collections::HashMap;
+
+    // x and y strongly correlated, z independent noise
+    fn correlated_df(rows: usize) -> (DataFrame, Vec<String>) {
+        let columns = vec!["x".to_string(), "y".to_string(), "z".to_string()];
+        let mut rng = Rng::new(1);
+        let mut df = DataFrame::new();
+        for name in columns.iter() {
+            df.insert_column(name, &Vec::new(), &DataType::Float);
+        }
+        for _ in 0..rows {
+            let t = 3.0 * rng.normal();
+            let values = [
+                t + 0.1 * rng.normal(),
+                2.0 * t + 5.0 + 0.1 * rng.normal(),
+                0.5 * rng.normal(),
+            ];
+            let row: HashMap<String, DataTypeValue> = columns
+                .iter()
+                .zip(values.iter())
+                .map(|(name, value)| (name.clone(), DataTypeValue::Float(*value)))
+                .collect();
+            df.insert_row(&row);
+        }
+        (df, columns)
+    }
+
+    fn column(df: &DataFrame, name: &str) -> Vec<f32> {
+        df.get_column(name)
+            .1
+            .iter()
+            .map(|value| df.extract_value_as_float(value))
+            .collect()
+    }
+
+    #[test]
+    fn test_pca_components_and_inverse() {
+        let (df, columns) = correlated_df(500);
+        let mut pca = PCA::new(Components::Count(3));
+        let reduced = pca.fit_transform(&df, &columns);
+        let ratios = pca.explained_variance_ratio();
+        assert!(ratios[0] > 0.98);
+        assert!((ratios.iter().sum::<f32>() - 1.0).abs() < 1e-5);
+        assert!(ratios.windows(2).all(|pair| pair[0] >= pair[1]));
+        // loadings are orthonormal and the first points along (1, 2, 0)
+        let loadings = pca.loadings();
+        for i in 0..3 {
+            for j in 0..3 {
+                let dot = loadings.get(i).dot(loadings.get(j));
+                assert!((dot - if i == j { 1.0 } else { 0.0 }).abs() < 1e-4);
+            }
+        }
+        let first = loadings.get(0);
+        assert!((first.get(0) - 1.0 / 5f32.sqrt()).abs() < 0.01);
+        assert!((first.get(1) - 2.0 / 5f32.sqrt()).abs() < 0.01);
+        assert!(
+            reduced
+                .columns()
+                .iter()
+                .all(|name| !columns.contains(*name))
+        );
+        assert!(reduced.len() == 500);
+        // scores are centered with the explained variance
+        let pc1 = column(&reduced, "pc1");
+        let mean = pc1.iter().sum::<f32>() / 500.0;
+        let variance = pc1.iter().map(|value| (value - mean).powi(2)).sum::<f32>() / 499.0;
+        assert!(mean.abs() < 1e-3);
+        assert!((variance - pca.explained_variance()[0]).abs() < 1e-3 * variance);
+
+        let restored = pca.inverse_transform(&reduced);
+        for name in columns.iter() {
+            let original = column(&df, name);
+            let round_trip = column(&restored, name);
+            assert!(
+                original
+                    .iter()
+                    .zip(round_trip.iter())
+                    .all(|(a, b)| (a - b).abs() < 1e-3)
+            );
+        }
+        assert!(!restored.columns().iter().any(|name| name.starts_with("pc")));
+    }
+
+    #[test]
+    fn test_pca_variance_threshold() {
+        let (df, columns) = correlated_df(500);
+        let mut pca = PCA::new(Components::VarianceRatio(0.95));
+        pca.fit(&df, &columns);
+        assert!(pca.num_components() == 1);
+        assert!(pca.component_names() == vec!["pc1".to_string()]);
+        let mut pca = PCA::new(Components::VarianceRatio(1.0));
+        pca.fit(&df, &columns);
+        assert!(pca.num_components() == 3);
+        // the rank one reconstruction keeps most of x
+        let mut pca = PCA::new(Components::Count(1));
+        let reduced = pca.fit_transform(&df, &columns);
+        let restored = pca.inverse_transform(&reduced);
+        let (x, restored_x) = (column(&df, "x"), column(&restored, "x"));
+        let error = x
+            .iter()
+            .zip(restored_x.iter())
+            .map(|(a, b)| (a - b).abs())
+            .fold(0.0, f32::max);
+        assert!(error < 0.5);
+    }
+
+    #[test]
+    fn test_pca_in_numerical_pipeline() {
+        let df = df_from_csv("housing.csv", Some(2000));
+        let numeric_columns: Vec<String> = df.numeric_columns().into_iter().cloned().collect();
+        let mut polynomial_columns = numeric_columns.clone();
+        polynomial_columns.extend(numeric_columns.iter().map(|column| format!("{}^2", column)));
+        let mut pca = PCA::new(Components::VarianceRatio(0.9));
+        pca.set_columns(&polynomial_columns);
+        let imputer: Box<dyn Transformer> = Box::new(Imputer::new(&ImputerStrategy::Median));
+        let polynomial: Box<dyn Transformer> = Box::new(PolynomialFeatures::new(2));
+        let scalar: Box<dyn Transformer> = Box::new(StandardScalar::new());
+        let pca: Box<dyn Transformer> = Box::new(pca);
+        let numeric_pipeline = NumericalPipeline::new(vec![imputer, polynomial, pca]);
+        let reduced = numeric_pipeline.transform(&df, &numeric_columns);
+        let components: Vec<&String> = reduced
+            .columns()
+            .into_iter()
+            .filter(|name| name.starts_with("pc"))
+            .collect();
+        assert!(!components.is_empty() && components.len() < polynomial_columns.len());
+        assert!(reduced.numeric_columns().len() == components.len());
+
+        let encoder: Box<dyn Transformer> = Box::new(OneHotEncoder::new(false));
+        let imputer: Box<dyn Transformer> = Box::new(Imputer::new(&ImputerStrategy::Median));
+        let pca: Box<dyn Transformer> = Box::new(PCA::new(Components::Count(4)));
+        let transformer = ColumnTransformer::new(
+            NumericalPipeline::new(vec![imputer, scalar, pca]),
+            CategoricalPipeline::new(vec![encoder]),
+        );
+        let output = transformer.transform(&df);
+        assert!(output.shape().0 == 2000);
+        assert!(output.shape().1 == 4 + df.get_value_frequencies("ocean_proximity").len());
+    }
+}
@@ -1,7 +1,13 @@
 use super::transformers::Transformer;
-use crate::dataframe::{DataFrame, DataType, DataTypeValue};
-use crate::linear_algebra::Matrix;
+use crate::algorithms::estimators::{Classifier, Regressor, labels_from_dataframe};
+use crate::dataframe::{DataFrame, DataType};
+use crate::inference::inference::{accuracy, r2_score};
+use crate::linear_algebra::{LinalgError, Matrix, RowVector};
+use crate::parallel::parallel::parallel_map;
+use std::iter::zip;
 
+/// Steps run in order, each on the columns the step before it kept or
+/// added.
 pub struct NumericalPipeline {
     transformers: Vec<Box<dyn Transformer>>,
 }
@@ -13,15 +19,31 @@ impl NumericalPipeline {
 }
 
 impl Transformer for NumericalPipeline {
+    fn fit(&mut self, df: &DataFrame, column_names: &Vec<String>) {
+        fit_steps(&mut self.transformers, df, column_names);
+    }
+
     fn transform(&self, df: &DataFrame, column_names: &Vec<String>) -> DataFrame {
-        let mut df = df.clone();
-        for transformer in self.transformers.iter() {
-            df = transformer.transform(&df, column_names);
-        }
-        return df;
+        return transform_steps(&self.transformers, df, column_names);
+    }
+
+    /// Inverts the steps in reverse order, `None` when one of them has no
+    /// inverse.
+    fn inverse_transform(&self, df: &DataFrame, column_names: &Vec<String>) -> Option<DataFrame> {
+        return inverse_transform_steps(&self.transformers, df, column_names);
+    }
+
+    fn get_feature_names_out(
+        &self,
+        input_features: &Vec<String>,
+        column_names: &Vec<String>,
+    ) -> Vec<String> {
+        return steps_feature_names_out(&self.transformers, input_features, column_names);
     }
 }
 
+/// Steps run in order, each on the columns the step before it kept or
+/// added.
 pub struct CategoricalPipeline {
     transformers: Vec<Box<dyn Transformer>>,
 }
@@ -33,57 +55,646 @@ impl CategoricalPipeline {
 }
 
 impl Transformer for CategoricalPipeline {
+    fn fit(&mut self, df: &DataFrame, column_names: &Vec<String>) {
+        fit_steps(&mut self.transformers, df, column_names);
+    }
+
     fn transform(&self, df: &DataFrame, column_names: &Vec<String>) -> DataFrame {
-        let mut df = df.clone();
-        for transformer in self.transformers.iter() {
-            df = transformer.transform(&df, column_names);
+        return transform_steps(&self.transformers, df, column_names);
+    }
+
+    /// Inverts the steps in reverse order, `None` when one of them has no
+    /// inverse.
+    fn inverse_transform(&self, df: &DataFrame, column_names: &Vec<String>) -> Option<DataFrame> {
+        return inverse_transform_steps(&self.transformers, df, column_names);
+    }
+
+    fn get_feature_names_out(
+        &self,
+        input_features: &Vec<String>,
+        column_names: &Vec<String>,
+    ) -> Vec<String> {
+        return steps_feature_names_out(&self.transformers, input_features, column_names);
+    }
+}
+
+// fits every step on the output of the steps before it
+fn fit_steps(
+    transformers: &mut Vec<Box<dyn Transformer>>,
+    df: &DataFrame,
+    column_names: &Vec<String>,
+) {
+    let mut df = df.clone();
+    let mut columns = column_names.clone();
+    let last = transformers.len().saturating_sub(1);
+    for (i, transformer) in transformers.iter_mut().enumerate() {
+        transformer.fit(&df, &columns);
+        if i < last {
+            let transformed = transformer.transform(&df, &columns);
+            columns = step_columns(&df, &transformed, &columns);
+            df = transformed;
+        }
+    }
+}
+
+fn transform_steps(
+    transformers: &Vec<Box<dyn Transformer>>,
+    df: &DataFrame,
+    column_names: &Vec<String>,
+) -> DataFrame {
+    let mut df = df.clone();
+    let mut columns = column_names.clone();
+    for transformer in transformers.iter() {
+        let transformed = transformer.transform(&df, &columns);
+        columns = step_columns(&df, &transformed, &columns);
+        df = transformed;
+    }
+    return df;
+}
+
+fn inverse_transform_steps(
+    transformers: &Vec<Box<dyn Transformer>>,
+    df: &DataFrame,
+    column_names: &Vec<String>,
+) -> Option<DataFrame> {
+    let mut df = df.clone();
+    let inputs = steps_columns(transformers, column_names);
+    for (transformer, columns) in zip(transformers, inputs).rev() {
+        df = transformer.inverse_transform(&df, &columns)?;
+    }
+    return Some(df);
+}
+
+// the columns a step kept followed by the columns it added, which the next
+// step works on
+fn step_columns(
+    df: &DataFrame,
+    transformed: &DataFrame,
+    column_names: &Vec<String>,
+) -> Vec<String> {
+    let (before, after) = (df.columns(), transformed.columns());
+    let mut columns: Vec<String> = column_names
+        .iter()
+        .filter(|column| after.contains(column))
+        .cloned()
+        .collect();
+    columns.extend(
+        after
+            .into_iter()
+            .filter(|column| !before.contains(column))
+            .cloned(),
+    );
+    return columns;
+}
+
+// columns every fitted step works on, the same as the transform passes along
+fn steps_columns(
+    transformers: &Vec<Box<dyn Transformer>>,
+    column_names: &Vec<String>,
+) -> Vec<Vec<String>> {
+    let mut columns = vec![column_names.clone()];
+    for transformer in transformers.iter() {
+        let last = columns.last().unwrap();
+        columns.push(transformer.get_feature_names_out(last, last));
+    }
+    columns.pop();
+    return columns;
+}
+
+fn steps_feature_names_out(
+    transformers: &Vec<Box<dyn Transformer>>,
+    input_features: &Vec<String>,
+    column_names: &Vec<String>,
+) -> Vec<String> {
+    return zip(transformers, steps_columns(transformers, column_names))
+        .fold(input_features.clone(), |names, (transformer, columns)| {
+            transformer.get_feature_names_out(&names, &columns)
+        });
+}
+
+/// Picks the input columns of a `ColumnTransformer` branch. The id column is
+/// never selected.
+pub enum ColumnSelector {
+    /// These columns, in this order.
+    Names(Vec<String>),
+    /// Every column of this type.
+    DataType(DataType),
+    /// Columns whose name matches a pattern in which `*` stands for any run of
+    /// characters, such as `total_*` or `*_per_household`.
+    Pattern(String),
+    /// Columns for which the predicate holds, given their name and type.
+    Predicate(ColumnPredicate),
+}
+
+pub type ColumnPredicate = Box<dyn Fn(&str, &DataType) -> bool + Send + Sync>;
+
+impl ColumnSelector {
+    pub fn names(names: &[&str]) -> Self {
+        return ColumnSelector::Names(names.iter().map(|name| name.to_string()).collect());
+    }
+
+    pub fn predicate<F>(predicate: F) -> Self
+    where
+        F: Fn(&str, &DataType) -> bool + Send + Sync + 'static,
+    {
+        return ColumnSelector::Predicate(Box::new(predicate));
+    }
+
+    /// Selected columns of `df`, in frame order apart from `Names`.
+    pub fn select(&self, df: &DataFrame) -> Vec<String> {
+        if let ColumnSelector::Names(names) = self {
+            for name in names.iter() {
+                if !df.columns().contains(&name) || name == DataFrame::id_column() {
+                    panic!("column {} not found", name);
+                }
+            }
+            return names.clone();
+        }
+        return df
+            .columns()
+            .into_iter()
+            .filter(|column_name| *column_name != DataFrame::id_column())
+            .filter(|column_name| {
+                let (dtype, _) = df.get_column(column_name);
+                match self {
+                    ColumnSelector::DataType(selected) => dtype == selected,
+                    ColumnSelector::Pattern(pattern) => matches_pattern(pattern, column_name),
+                    ColumnSelector::Predicate(predicate) => predicate(column_name, dtype),
+                    ColumnSelector::Names(_) => unreachable!(),
+                }
+            })
+            .map(|column_name| column_name.clone())
+            .collect();
+    }
+}
+
+fn matches_pattern(pattern: &str, name: &str) -> bool {
+    let parts: Vec<&str> = pattern.split('*').collect();
+    if parts.len() == 1 {
+        return pattern == name;
+    }
+    let (first, last) = (parts[0], parts[parts.len() - 1]);
+    if name.len() < first.len() + last.len() || !name.starts_with(first) || !name.ends_with(last) {
+        return false;
+    }
+    // the wildcards in between take the earliest match of every other part
+    let mut rest = &name[first.len()..name.len() - last.len()];
+    for part in parts[1..parts.len() - 1].iter() {
+        match rest.find(part) {
+            Some(position) => rest = &rest[position + part.len()..],
+            None => return false,
         }
-        return df;
     }
+    return true;
+}
+
+/// What happens to the columns no branch selects.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Remainder {
+    Drop,
+    /// Appended unchanged after the branch outputs.
+    Passthrough,
 }
 
+// columns chosen on the training frame, reused for every later frame
+struct FittedColumns {
+    inputs: Vec<Vec<String>>,
+    // columns as the branches name them and as the output names them
+    branch_outputs: Vec<Vec<String>>,
+    outputs: Vec<Vec<String>>,
+    remainder: Vec<String>,
+}
+
+/// Named branches, each running a transformer over the columns its selector
+/// picks. The outputs are concatenated in branch order, followed by the
+/// remainder when it is passed through. Branches run concurrently, a column
+/// may feed several branches and a branch selecting nothing is skipped. An
+/// output column that several branches, or a branch and the remainder, share
+/// is prefixed with the branch name as `branch__column`. Once fitted, every
+/// frame goes through the columns selected on the training frame.
 pub struct ColumnTransformer {
-    num_pipeline: NumericalPipeline,
-    categorical_pipeline: CategoricalPipeline,
+    transformers: Vec<(String, Box<dyn Transformer>, ColumnSelector)>,
+    remainder: Remainder,
+    fitted: Option<FittedColumns>,
 }
 
 impl ColumnTransformer {
+    /// Numeric columns through `num_pipeline` and string columns through
+    /// `categorical_pipeline`, in branches named `numerical` and
+    /// `categorical`.
     pub fn new(num_pipeline: NumericalPipeline, categorical_pipeline: CategoricalPipeline) -> Self {
-        Self {
-            num_pipeline,
-            categorical_pipeline,
+        return Self::with_transformers(vec![
+            (
+                "numerical",
+                Box::new(num_pipeline),
+                ColumnSelector::DataType(DataType::Float),
+            ),
+            (
+                "categorical",
+                Box::new(categorical_pipeline),
+                ColumnSelector::DataType(DataType::String),
+            ),
+        ]);
+    }
+
+    /// `(name, transformer, selector)` branches, dropping the remainder.
+    pub fn with_transformers(
+        transformers: Vec<(&str, Box<dyn Transformer>, ColumnSelector)>,
+    ) -> Self {
+        let mut names: Vec<&str> = transformers.iter().map(|(name, _, _)| *name).collect();
+        names.sort();
+        if let Some(name) = names.windows(2).find(|pair| pair[0] == pair[1]) {
+            panic!("duplicate transformer name {}", name[0]);
         }
+        return Self {
+            transformers: transformers
+                .into_iter()
+                .map(|(name, transformer, selector)| (name.to_string(), transformer, selector))
+                .collect(),
+            remainder: Remainder::Drop,
+            fitted: None,
+        };
     }
-    pub fn transform(&self, df: &DataFrame) -> Matrix {
-        let categorical_columns = df
-            .categorical_columns()
-            .into_iter()
-            .map(|column_name| column_name.clone())
+
+    pub fn set_remainder(&mut self, remainder: Remainder) {
+        self.remainder = remainder;
+    }
+
+    // input columns of every branch and the remainder columns
+    fn select(&self, df: &DataFrame) -> (Vec<Vec<String>>, Vec<String>) {
+        let inputs: Vec<Vec<String>> = self
+            .transformers
+            .iter()
+            .map(|(_, _, selector)| selector.select(df))
             .collect();
-        let df_transformed = self
-            .categorical_pipeline
-            .transform(df, &categorical_columns);
-        let numeric_columns = df
-            .numeric_columns()
-            .into_iter()
-            .map(|column_name| column_name.clone())
+        let remainder = match self.remainder {
+            Remainder::Drop => Vec::new(),
+            Remainder::Passthrough => df
+                .columns()
+                .into_iter()
+                .filter(|column_name| {
+                    *column_name != DataFrame::id_column()
+                        && inputs.iter().all(|columns| !columns.contains(column_name))
+                })
+                .map(|column_name| column_name.clone())
+                .collect(),
+        };
+        return (inputs, remainder);
+    }
+
+    // the branches see their own copies of the columns, so they can run
+    // concurrently
+    fn transform_branches(&self, df: &DataFrame, inputs: &Vec<Vec<String>>) -> Vec<DataFrame> {
+        return parallel_map(self.transformers.len(), 1, |i| {
+            let (_, transformer, _) = &self.transformers[i];
+            let columns = &inputs[i];
+            if columns.is_empty() {
+                return df.get_columns_as_df(columns);
+            }
+            transformer.transform(&df.get_columns_as_df(columns), columns)
+        });
+    }
+
+    // output names of the branch columns, prefixed where they collide
+    fn output_names(
+        &self,
+        branch_outputs: &Vec<Vec<String>>,
+        remainder: &Vec<String>,
+    ) -> Vec<Vec<String>> {
+        let shared = |column_name: &String| {
+            remainder.contains(column_name)
+                || branch_outputs
+                    .iter()
+                    .filter(|columns| columns.contains(column_name))
+                    .count()
+                    > 1
+        };
+        return zip(self.transformers.iter(), branch_outputs.iter())
+            .map(|((branch, _, _), columns)| {
+                columns
+                    .iter()
+                    .map(|column_name| {
+                        if shared(column_name) {
+                            return format!("{}__{}", branch, column_name);
+                        }
+                        column_name.clone()
+                    })
+                    .collect()
+            })
             .collect();
-        let df_transformed = self
-            .num_pipeline
-            .transform(&df_transformed, &numeric_columns);
-        let output_matrix = df_transformed.as_matrix(false);
-        return output_matrix;
     }
+
+    fn combine(
+        df: &DataFrame,
+        outputs: &Vec<DataFrame>,
+        names: &Vec<Vec<String>>,
+        remainder: &Vec<String>,
+    ) -> DataFrame {
+        let mut df_transformed = df.get_columns_as_df(&Vec::new());
+        for (output, names) in zip(outputs, names) {
+            for (column_name, name) in zip(feature_columns(output), names) {
+                insert_output(&mut df_transformed, output, &column_name, name);
+            }
+        }
+        for column_name in remainder.iter() {
+            insert_output(&mut df_transformed, df, column_name, column_name);
+        }
+        return df_transformed;
+    }
+
+    /// Fits the branches on the columns they select from a training frame and
+    /// keeps that selection, along with the output columns of every branch.
+    pub fn fit(&mut self, df: &DataFrame) {
+        self.fit_transform(df);
+    }
+
+    pub fn fit_transform(&mut self, df: &DataFrame) -> Matrix {
+        return self.fit_transform_df(df).as_matrix(false);
+    }
+
+    /// Like `fit_transform`, keeping the named columns of the output.
+    pub fn fit_transform_df(&mut self, df: &DataFrame) -> DataFrame {
+        let (inputs, remainder) = self.select(df);
+        for ((_, transformer, _), columns) in zip(self.transformers.iter_mut(), inputs.iter()) {
+            if !columns.is_empty() {
+                transformer.fit(&df.get_columns_as_df(columns), columns);
+            }
+        }
+        let outputs = self.transform_branches(df, &inputs);
+        let branch_outputs: Vec<Vec<String>> = outputs.iter().map(feature_columns).collect();
+        self.fitted = Some(FittedColumns {
+            outputs: self.output_names(&branch_outputs, &remainder),
+            inputs,
+            branch_outputs,
+            remainder,
+        });
+        let fitted = self.fitted();
+        return Self::combine(df, &outputs, &fitted.outputs, &fitted.remainder);
+    }
+
+    /// Model inputs, one column per output column in the order of
+    /// `get_feature_names_out` once fitted.
+    pub fn transform(&self, df: &DataFrame) -> Matrix {
+        return self.transform_df(df).as_matrix(false);
+    }
+
+    /// Like `transform`, keeping the named columns of the output.
+    pub fn transform_df(&self, df: &DataFrame) -> DataFrame {
+        let (inputs, remainder) = match &self.fitted {
+            Some(fitted) => (fitted.inputs.clone(), fitted.remainder.clone()),
+            None => self.select(df),
+        };
+        let outputs = self.transform_branches(df, &inputs);
+        let names = self.output_names(&outputs.iter().map(feature_columns).collect(), &remainder);
+        return Self::combine(df, &outputs, &names, &remainder);
+    }
+
+    /// Maps a frame of output columns, as returned by `transform_df`, back to
+    /// the input columns of the training frame: the inputs of every branch in
+    /// order, then the remainder. A column feeding several branches is
+    /// restored by the first. `None` when a branch has no inverse.
+    pub fn inverse_transform(&self, df: &DataFrame) -> Option<DataFrame> {
+        let fitted = self.fitted();
+        let restored = parallel_map(self.transformers.len(), 1, |i| {
+            let (_, transformer, _) = &self.transformers[i];
+            let mut outputs = df.get_columns_as_df(&Vec::new());
+            for (name, column_name) in zip(&fitted.outputs[i], &fitted.branch_outputs[i]) {
+                let (dtype, values) = df.get_column(name);
+                outputs.insert_column(column_name, values, dtype);
+            }
+            if fitted.inputs[i].is_empty() {
+                return Some(outputs);
+            }
+            transformer.inverse_transform(&outputs, &fitted.inputs[i])
+        });
+        let restored: Vec<DataFrame> = restored.into_iter().collect::<Option<_>>()?;
+        let mut df_restored = df.get_columns_as_df(&Vec::new());
+        let frames = zip(restored.iter(), fitted.inputs.iter()).chain([(df, &fitted.remainder)]);
+        for (frame, columns) in frames {
+            for column_name in columns {
+                if df_restored.columns().contains(&column_name) {
+                    continue;
+                }
+                let (dtype, values) = frame.get_column(column_name);
+                df_restored.insert_column(column_name, values, dtype);
+            }
+        }
+        return Some(df_restored);
+    }
+
+    /// Names of the output columns for the training frame, the outputs of
+    /// every branch in order followed by the remainder.
+    pub fn get_feature_names_out(&self) -> Vec<String> {
+        let fitted = self.fitted();
+        return fitted
+            .outputs
+            .iter()
+            .chain([&fitted.remainder])
+            .flatten()
+            .cloned()
+            .collect();
+    }
+
+    fn fitted(&self) -> &FittedColumns {
+        return self
+            .fitted
+            .as_ref()
+            .expect("column transformer must be fitted first");
+    }
+
+    fn branch(&self, name: &str) -> usize {
+        return self
+            .transformers
+            .iter()
+            .position(|(branch, _, _)| branch == name)
+            .unwrap_or_else(|| panic!("unknown transformer {}", name));
+    }
+
+    /// Columns the branch `name` selected from the training frame.
+    pub fn input_columns(&self, name: &str) -> &Vec<String> {
+        return &self.fitted().inputs[self.branch(name)];
+    }
+
+    /// Columns the branch `name` output for the training frame, prefixed
+    /// where they collide.
+    pub fn output_columns(&self, name: &str) -> &Vec<String> {
+        return &self.fitted().outputs[self.branch(name)];
+    }
+
+    /// Columns passed through unchanged, empty when the remainder is dropped.
+    pub fn remainder_columns(&self) -> &Vec<String> {
+        return &self.fitted().remainder;
+    }
+}
+
+/// Preprocessing in front of the final model of a `Pipeline`: a
+/// `ColumnTransformer`, or a single transformer applied to every feature
+/// column whose output must be all numeric.
+pub enum Preprocessor {
+    Columns(ColumnTransformer),
+    Transformer(Box<dyn Transformer>),
+}
+
+impl Preprocessor {
+    fn fit_transform_df(&mut self, df: &DataFrame) -> DataFrame {
+        match self {
+            Preprocessor::Columns(column_transformer) => column_transformer.fit_transform_df(df),
+            Preprocessor::Transformer(transformer) => {
+                let columns = feature_columns(df);
+                transformer.fit(df, &columns);
+                transformer.transform(df, &columns)
+            }
+        }
+    }
+
+    fn transform(&self, df: &DataFrame) -> Matrix {
+        match self {
+            Preprocessor::Columns(column_transformer) => column_transformer.transform(df),
+            Preprocessor::Transformer(transformer) => transformer
+                .transform(df, &feature_columns(df))
+                .as_matrix(false),
+        }
+    }
+}
+
+fn insert_output(df_transformed: &mut DataFrame, frame: &DataFrame, column_name: &str, name: &str) {
+    if df_transformed
+        .columns()
+        .iter()
+        .any(|column| *column == name)
+    {
+        panic!("column {} is output more than once", name);
+    }
+    let (dtype, values) = frame.get_column(column_name);
+    df_transformed.insert_column(name, values, dtype);
+}
+
+fn feature_columns(df: &DataFrame) -> Vec<String> {
+    return df
+        .columns()
+        .into_iter()
+        .filter(|column_name| *column_name != DataFrame::id_column())
+        .map(|column_name| column_name.clone())
+        .collect();
+}
+
+pub enum FinalEstimator {
+    Regressor(Box<dyn Regressor>),
+    Classifier(Box<dyn Classifier>),
+}
+
+/// Preprocessing and a final model as one object taking raw `DataFrame`s.
+/// `fit` fits the preprocessing on the training frame and the model on its
+/// output; `predict` and `score` run new frames through the same steps,
+/// ignoring the target column when it is present.
+pub struct Pipeline {
+    preprocessor: Preprocessor,
+    estimator: FinalEstimator,
+    target: Option<String>,
+    feature_names: Vec<String>,
+}
+
+impl Pipeline {
+    pub fn new(preprocessor: Preprocessor, estimator: FinalEstimator) -> Self {
+        return Self {
+            preprocessor,
+            estimator,
+            target: None,
+            feature_names: Vec::new(),
+        };
+    }
+
+    /// `target` is a numeric column of `df`, every other column is a feature.
+    pub fn fit(&mut self, df: &DataFrame, target: &str) -> Result<(), LinalgError> {
+        let labels = Matrix::to_matrix(
+            &labels_from_dataframe(df, target)
+                .into_iter()
+                .map(|label| vec![label])
+                .collect(),
+        );
+        let features = without_column(df, target);
+        let df_inputs = self.preprocessor.fit_transform_df(&features);
+        let inputs = df_inputs.as_matrix(false);
+        self.target = Some(target.to_string());
+        self.feature_names = feature_columns(&df_inputs);
+        return match &mut self.estimator {
+            FinalEstimator::Regressor(regressor) => regressor.fit(&inputs, &labels),
+            FinalEstimator::Classifier(classifier) => classifier.fit(&inputs, &labels),
+        };
+    }
+
+    /// Model inputs for the feature columns of `df`.
+    pub fn transform(&self, df: &DataFrame) -> Matrix {
+        return self
+            .preprocessor
+            .transform(&without_column(df, self.target()));
+    }
+
+    pub fn predict(&self, df: &DataFrame) -> RowVector {
+        let inputs = self.transform(df);
+        return match &self.estimator {
+            FinalEstimator::Regressor(regressor) => regressor.predict(&inputs),
+            FinalEstimator::Classifier(classifier) => classifier.predict(&inputs),
+        };
+    }
+
+    /// r² for a regressor and accuracy for a classifier against the target
+    /// column of `df`.
+    pub fn score(&self, df: &DataFrame) -> f32 {
+        let predictions = self.predict(df);
+        let labels = labels_from_dataframe(df, self.target());
+        return match &self.estimator {
+            FinalEstimator::Regressor(_) => r2_score(predictions.vector(), &labels),
+            FinalEstimator::Classifier(_) => accuracy(predictions.vector(), &labels),
+        };
+    }
+
+    /// Names of the model inputs, such as the columns matching the weights of
+    /// a linear model.
+    pub fn get_feature_names_out(&self) -> &Vec<String> {
+        assert!(self.target.is_some(), "pipeline must be fitted first");
+        return &self.feature_names;
+    }
+
+    pub fn preprocessor(&self) -> &Preprocessor {
+        return &self.preprocessor;
+    }
+
+    pub fn estimator(&self) -> &FinalEstimator {
+        return &self.estimator;
+    }
+
+    fn target(&self) -> &str {
+        return self
+            .target
+            .as_deref()
+            .expect("pipeline must be fitted first");
+    }
+}
+
+fn without_column(df: &DataFrame, column_name: &str) -> DataFrame {
+    let mut df = df.clone();
+    if df.columns().iter().any(|column| *column == column_name) {
+        df.remove_column(column_name);
+    }
+    return df;
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::algorithms::linear_regression::linear_regression::LinearRegression;
+    use crate::algorithms::logistic_regression::logistic_regression::{
+        LogisticRegression, Penalty,
+    };
+    use crate::sampling::random::Rng;
     use crate::{
-        dataframe::csv::df_from_csv,
+        dataframe::{DataTypeValue, csv::df_from_csv},
         pipeline::{
             encoders::one_hot_encoder::{self, OneHotEncoder},
             imputers::imputer::{Imputer, ImputerStrategy},
+            pca::pca::{Components, PCA},
+            polynomial_features::polynomial_features::PolynomialFeatures,
             scalars::standard_scalar::StandardScalar,
             transformers::Transformer,
         },
@@ -103,4 +714,356 @@ mod tests {
         let output_matrix = pipeline.transform(&df);
         assert!(output_matrix.matrix().iter().all(|v| { v.len() == 14 }));
     }
+
+    fn housing_column_transformer() -> ColumnTransformer {
+        let imputer: Box<dyn Transformer> = Box::new(Imputer::new(&ImputerStrategy::Median));
+        let scalar: Box<dyn Transformer> = Box::new(StandardScalar::new());
+        let encoder: Box<dyn Transformer> = Box::new(OneHotEncoder::new(false));
+        return ColumnTransformer::new(
+            NumericalPipeline::new(vec![imputer, scalar]),
+            CategoricalPipeline::new(vec![encoder]),
+        );
+    }
+
+    #[test]
+    fn test_regression_pipeline() {
+        let df = df_from_csv("housing.csv", Some(3000));
+        let mut rows = Rng::new(1).permutation(df.len());
+        let test = df.get_rows_as_df(&rows.split_off(2500));
+        let train = df.get_rows_as_df(&rows);
+        let mut pipeline = Pipeline::new(
+            Preprocessor::Columns(housing_column_transformer()),
+            FinalEstimator::Regressor(Box::new(LinearRegression::new(0.0))),
+        );
+        pipeline.fit(&train, "median_house_value").unwrap();
+        assert!(pipeline.score(&train) > 0.65);
+        assert!(pipeline.score(&test) > 0.65);
+        // preprocessing reuses the training statistics, so a single row is
+        // predicted exactly as it is within the whole frame
+        let predictions = pipeline.predict(&test);
+        let single = pipeline.predict(&test.get_rows_as_df(&vec![100]));
+        assert!(single.len() == 1);
+        assert!((single.get(0) - predictions.get(100)).abs() < 1e-2);
+    }
+
+    #[test]
+    fn test_classification_pipeline() {
+        let mut df = df_from_csv("housing.csv", Some(2000));
+        let (_, values) = df.get_column("median_house_value");
+        let expensive: Vec<DataTypeValue> = values
+            .iter()
+            .map(|value| match value {
+                DataTypeValue::Float(value) => {
+                    DataTypeValue::Float((*value > 200000.0) as u8 as f32)
+                }
+                _ => panic!("median_house_value must be numeric"),
+            })
+            .collect();
+        df.insert_column("expensive", &expensive, &DataType::Float);
+        df.remove_column("median_house_value");
+        let mut pipeline = Pipeline::new(
+            Preprocessor::Columns(housing_column_transformer()),
+            FinalEstimator::Classifier(Box::new(LogisticRegression::new(Penalty::L2(0.01)))),
+        );
+        pipeline.fit(&df, "expensive").unwrap();
+        assert!(pipeline.score(&df) > 0.75);
+        assert!(
+            pipeline
+                .predict(&df)
+                .vector()
+                .iter()
+                .all(|prediction| *prediction == 0.0 || *prediction == 1.0)
+        );
+    }
+
+    #[test]
+    fn test_steps_work_on_added_columns() {
+        let df = df_from_csv("housing.csv", Some(500));
+        let columns = vec!["total_rooms".to_string(), "households".to_string()];
+        let polynomial: Box<dyn Transformer> = Box::new(PolynomialFeatures::new(2));
+        let scalar: Box<dyn Transformer> = Box::new(StandardScalar::new());
+        let mut numeric_pipeline = NumericalPipeline::new(vec![polynomial, scalar]);
+        numeric_pipeline.fit(&df, &columns);
+        let transformed = numeric_pipeline.transform(&df, &columns);
+        let (_, values) = transformed.get_column("total_rooms^2");
+        let mean = values
+            .iter()
+            .map(|value| match value {
+                DataTypeValue::Float(value) => *value,
+                _ => panic!("total_rooms^2 must be numeric"),
+            })
+            .sum::<f32>()
+            / values.len() as f32;
+        assert!(mean.abs() < 1e-3);
+        assert!(
+            numeric_pipeline.get_feature_names_out(&columns, &columns)
+                == vec!["total_rooms", "households", "total_rooms^2", "households^2"]
+        );
+    }
+
+    #[test]
+    fn test_column_transformer_selectors_and_remainder() {
+        let mut df = df_from_csv("housing.csv", Some(500));
+        df.remove_column("ocean_proximity");
+        let branches = || -> Vec<(&str, Box<dyn Transformer>, ColumnSelector)> {
+            vec![
+                (
+                    "scale",
+                    Box::new(StandardScalar::new()),
+                    ColumnSelector::names(&["median_income", "housing_median_age"]),
+                ),
+                (
+                    "geo",
+                    Box::new(PolynomialFeatures::new(2)),
+                    ColumnSelector::Pattern("l*itude".to_string()),
+                ),
+                (
+                    "rooms",
+                    Box::new(Imputer::new(&ImputerStrategy::Median)),
+                    ColumnSelector::predicate(|name, dtype| {
+                        name.starts_with("total_") && *dtype == DataType::Float
+                    }),
+                ),
+            ]
+        };
+        let mut transformer = ColumnTransformer::with_transformers(branches());
+        transformer.set_remainder(Remainder::Passthrough);
+        let output = transformer.fit_transform(&df);
+        assert!(
+            transformer.output_columns("scale") == &vec!["median_income", "housing_median_age"]
+        );
+        assert!(
+            transformer.output_columns("geo")
+                == &vec!["longitude", "latitude", "longitude^2", "latitude^2"]
+        );
+        assert!(transformer.input_columns("rooms") == &vec!["total_rooms", "total_bedrooms"]);
+        assert!(
+            transformer.remainder_columns()
+                == &vec!["population", "households", "median_house_value"]
+        );
+        assert!(output.shape() == (500, 11));
+        let mean = output.matrix().iter().map(|row| row.get(0)).sum::<f32>() / 500.0;
+        assert!(mean.abs() < 1e-4);
+        assert!(transformer.transform(&df) == output);
+
+        let mut dropped = ColumnTransformer::with_transformers(branches());
+        assert!(dropped.fit_transform(&df).shape() == (500, 8));
+        assert!(dropped.remainder_columns().is_empty());
+    }
+
+    #[test]
+    fn test_column_patterns() {
+        assert!(matches_pattern("total_*", "total_rooms"));
+        assert!(matches_pattern("*_per_*", "rooms_per_household"));
+        assert!(matches_pattern("*", ""));
+        assert!(!matches_pattern("a*a", "a"));
+        assert!(!matches_pattern("total_*", "rooms_total"));
+        assert!(!matches_pattern("median_income", "median_income^2"));
+    }
+
+    #[test]
+    fn test_overlapping_selectors_prefix_shared_outputs() {
+        let df = df_from_csv("housing.csv", Some(300));
+        let mut transformer = ColumnTransformer::with_transformers(vec![
+            (
+                "numbers",
+                Box::new(Imputer::new(&ImputerStrategy::Median)),
+                ColumnSelector::DataType(DataType::Float),
+            ),
+            (
+                "medians",
+                Box::new(StandardScalar::new()),
+                ColumnSelector::Pattern("median_*".to_string()),
+            ),
+        ]);
+        let transformed = transformer.fit_transform_df(&df);
+        assert!(
+            transformer.output_columns("medians")
+                == &vec!["medians__median_income", "medians__median_house_value"]
+        );
+        let numbers = transformer.output_columns("numbers");
+        assert!(numbers.contains(&"numbers__median_income".to_string()));
+        assert!(numbers.contains(&"households".to_string()));
+        assert!(feature_columns(&transformed) == transformer.get_feature_names_out());
+        let restored = transformer.inverse_transform(&transformed).unwrap();
+        assert!(restored.get_column("median_income") == df.get_column("median_income"));
+        assert!(restored.get_column("households") == df.get_column("households"));
+    }
+
+    #[test]
+    #[should_panic(expected = "duplicate transformer name scale")]
+    fn test_duplicate_transformer_names() {
+        ColumnTransformer::with_transformers(vec![
+            (
+                "scale",
+                Box::new(StandardScalar::new()),
+                ColumnSelector::names(&["population"]),
+            ),
+            (
+                "scale",
+                Box::new(StandardScalar::new()),
+                ColumnSelector::names(&["households"]),
+            ),
+        ]);
+    }
+
+    #[test]
+    fn test_transformers_name_their_outputs() {
+        let df = df_from_csv("housing.csv", Some(300));
+        let numeric_columns = feature_columns(
+            &df.get_columns_as_df(&df.numeric_columns().into_iter().cloned().collect()),
+        );
+        let categorical_columns = vec!["ocean_proximity".to_string()];
+        let mut pca = PCA::new(Components::Count(2));
+        pca.set_columns(&vec!["total_rooms".to_string(), "households".to_string()]);
+        let steps: Vec<(Box<dyn Transformer>, &Vec<String>)> = vec![
+            (Box::new(StandardScalar::new()), &numeric_columns),
+            (
+                Box::new(Imputer::new(&ImputerStrategy::Median)),
+                &numeric_columns,
+            ),
+            (Box::new(PolynomialFeatures::new(3)), &numeric_columns),
+            (Box::new(OneHotEncoder::new(true)), &categorical_columns),
+            (Box::new(pca), &numeric_columns),
+        ];
+        let df = Imputer::new(&ImputerStrategy::Median).transform(&df, &numeric_columns);
+        for (mut step, columns) in steps {
+            step.fit(&df, columns);
+            let transformed = step.transform(&df, columns);
+            assert!(
+                step.get_feature_names_out(&feature_columns(&df), columns)
+                    == feature_columns(&transformed)
+            );
+        }
+    }
+
+    #[test]
+    fn test_feature_names_follow_weights() {
+        let df = df_from_csv("housing.csv", Some(2000));
+        let polynomial: Box<dyn Transformer> = Box::new(PolynomialFeatures::new(2));
+        let imputer: Box<dyn Transformer> = Box::new(Imputer::new(&ImputerStrategy::Median));
+        let encoder: Box<dyn Transformer> = Box::new(OneHotEncoder::new(false));
+        let mut transformer = ColumnTransformer::with_transformers(vec![
+            (
+                "rooms",
+                Box::new(NumericalPipeline::new(vec![imputer, polynomial])),
+                ColumnSelector::Pattern("total_*".to_string()),
+            ),
+            (
+                "ocean",
+                Box::new(CategoricalPipeline::new(vec![encoder])),
+                ColumnSelector::DataType(DataType::String),
+            ),
+        ]);
+        transformer.set_remainder(Remainder::Passthrough);
+        let features = without_column(&df, "median_house_value");
+        let df_inputs = transformer.fit_transform_df(&features);
+        let names = transformer.get_feature_names_out();
+        assert!(feature_columns(&df_inputs) == names);
+        assert!(
+            names[..4]
+                == [
+                    "total_rooms",
+                    "total_bedrooms",
+                    "total_rooms^2",
+                    "total_bedrooms^2"
+                ]
+        );
+        assert!(names.contains(&"INLAND".to_string()));
+        assert!(names.ends_with(&["median_income".to_string()]));
+        assert!(transformer.transform_df(&features).columns() == df_inputs.columns());
+
+        let mut pipeline = Pipeline::new(
+            Preprocessor::Columns(transformer),
+            FinalEstimator::Regressor(Box::new(LinearRegression::new(0.0))),
+        );
+        pipeline.fit(&df, "median_house_value").unwrap();
+        assert!(pipeline.get_feature_names_out() == &names);
+        let mut regression = LinearRegression::new(0.0);
+        regression
+            .fit(
+                &df_inputs.as_matrix(false),
+                &df.get_columns_as_df(&vec!["median_house_value".to_string()])
+                    .as_matrix(false),
+            )
+            .unwrap();
+        assert!(regression.weights().len() == names.len());
+    }
+
+    // a step without an inverse
+    struct Copied;
+
+    impl Transformer for Copied {
+        fn transform(&self, df: &DataFrame, _column_names: &Vec<String>) -> DataFrame {
+            return df.clone();
+        }
+
+        fn get_feature_names_out(
+            &self,
+            input_features: &Vec<String>,
+            _column_names: &Vec<String>,
+        ) -> Vec<String> {
+            return input_features.clone();
+        }
+    }
+
+    #[test]
+    fn test_inverse_transform_without_inverse_step() {
+        let df = df_from_csv("housing.csv", Some(300));
+        let scalar: Box<dyn Transformer> = Box::new(StandardScalar::new());
+        let mut transformer = ColumnTransformer::with_transformers(vec![
+            (
+                "scaled",
+                Box::new(NumericalPipeline::new(vec![scalar, Box::new(Copied)])),
+                ColumnSelector::names(&["median_income"]),
+            ),
+            (
+                "ocean",
+                Box::new(OneHotEncoder::new(false)),
+                ColumnSelector::DataType(DataType::String),
+            ),
+        ]);
+        let transformed = transformer.fit_transform_df(&df);
+        assert!(transformer.inverse_transform(&transformed).is_none());
+    }
+
+    #[test]
+    fn test_column_transformer_inverse_transform() {
+        let df = df_from_csv("housing.csv", Some(500));
+        let scalar: Box<dyn Transformer> = Box::new(StandardScalar::new());
+        let polynomial: Box<dyn Transformer> = Box::new(PolynomialFeatures::new(2));
+        let mut transformer = ColumnTransformer::with_transformers(vec![
+            (
+                "incomes",
+                Box::new(NumericalPipeline::new(vec![polynomial, scalar])),
+                ColumnSelector::names(&["median_income", "median_house_value"]),
+            ),
+            (
+                "ocean",
+                Box::new(OneHotEncoder::new(true)),
+                ColumnSelector::DataType(DataType::String),
+            ),
+        ]);
+        transformer.set_remainder(Remainder::Passthrough);
+        let transformed = transformer.fit_transform_df(&df);
+        let restored = transformer.inverse_transform(&transformed).unwrap();
+        let mut expected = vec!["median_income", "median_house_value", "ocean_proximity"];
+        expected.extend(
+            transformer
+                .remainder_columns()
+                .iter()
+                .map(|column| column.as_str()),
+        );
+        assert!(feature_columns(&restored) == expected);
+        assert!(restored.get_column("ocean_proximity") == df.get_column("ocean_proximity"));
+        assert!(restored.get_column("population") == df.get_column("population"));
+        let (_, values) = df.get_column("median_house_value");
+        let (_, restored_values) = restored.get_column("median_house_value");
+        assert!(zip(values, restored_values).all(|(value, restored_value)| {
+            match (value, restored_value) {
+                (DataTypeValue::Float(a), DataTypeValue::Float(b)) => (a - b).abs() <= 1e-3 * a,
+                _ => false,
+            }
+        }));
+    }
 }
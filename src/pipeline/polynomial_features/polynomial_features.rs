@@ -47,6 +47,31 @@ impl Transformer for PolynomialFeatures {
         }
         return df_with_polynomial_features;
     }
+
+    /// Drops the power columns.
+    fn inverse_transform(&self, df: &DataFrame, column_names: &Vec<String>) -> Option<DataFrame> {
+        let mut df = df.clone();
+        for column_name in column_names {
+            for i in 2..self.degrees + 1 {
+                df.remove_column(&format!("{}^{}", column_name, i));
+            }
+        }
+        return Some(df);
+    }
+
+    fn get_feature_names_out(
+        &self,
+        input_features: &Vec<String>,
+        column_names: &Vec<String>,
+    ) -> Vec<String> {
+        let mut names = input_features.clone();
+        for column_name in column_names {
+            for i in 2..self.degrees + 1 {
+                names.push(format!("{}^{}", column_name, i));
+            }
+        }
+        return names;
+    }
 }
 
 #[cfg(test)]
@@ -1,19 +1,33 @@
 use crate::{
     dataframe::{DataFrame, DataTypeValue},
+    parallel::parallel::parallel_map,
     pipeline::transformers::Transformer,
 };
+use std::collections::HashMap;
+use std::iter::zip;
 
-pub struct StandardScalar;
+/// Scales columns to zero mean and unit standard deviation. Unfitted, it
+/// scales every frame by that frame's own statistics; once fitted, columns
+/// seen during `fit` reuse the training mean and standard deviation.
+pub struct StandardScalar {
+    statistics: Option<HashMap<String, (f32, f32)>>,
+}
 
 impl StandardScalar {
     pub fn new() -> Self {
-        Self
+        Self { statistics: None }
     }
-}
-impl Transformer for StandardScalar {
-    fn transform(&self, df: &DataFrame, column_names: &Vec<String>) -> DataFrame {
-        let mut df = df.clone();
-        let df_column_names: Vec<String> = df
+
+    /// Training mean and standard deviation of a fitted column.
+    pub fn statistics(&self, column_name: &str) -> Option<(f32, f32)> {
+        return self
+            .statistics
+            .as_ref()
+            .and_then(|statistics| statistics.get(column_name).copied());
+    }
+
+    fn scaled_columns(df: &DataFrame, column_names: &Vec<String>) -> Vec<String> {
+        return df
             .columns()
             .into_iter()
             .filter(|df_column_name| {
@@ -21,23 +35,82 @@ impl Transformer for StandardScalar {
             })
             .map(|column_name| column_name.clone())
             .collect();
-        for df_column_name in df_column_names {
-            let mean = df.mean(&df_column_name);
-            let std = df.std(&df_column_name, Some(mean));
-            for i in (0..df.len()) {
-                let current_value = df.get_cell_value(&df_column_name, i).clone();
-                let current_value = match current_value {
-                    DataTypeValue::Float(inner) => inner,
+    }
+}
+
+fn mean_and_std(df: &DataFrame, column_name: &str) -> (f32, f32) {
+    let mean = df.mean(column_name);
+    return (mean, df.std(column_name, Some(mean)));
+}
+
+impl Transformer for StandardScalar {
+    fn fit(&mut self, df: &DataFrame, column_names: &Vec<String>) {
+        let df_column_names = Self::scaled_columns(df, column_names);
+        let statistics = parallel_map(df_column_names.len(), 1, |column_index| {
+            mean_and_std(df, &df_column_names[column_index])
+        });
+        self.statistics = Some(zip(df_column_names, statistics).collect());
+    }
+
+    fn transform(&self, df: &DataFrame, column_names: &Vec<String>) -> DataFrame {
+        let mut df = df.clone();
+        let df_column_names = Self::scaled_columns(&df, column_names);
+        let scaled_columns: Vec<Vec<DataTypeValue>> =
+            parallel_map(df_column_names.len(), 1, |column_index| {
+                let df_column_name = &df_column_names[column_index];
+                let (mean, std) = self
+                    .statistics(df_column_name)
+                    .unwrap_or_else(|| mean_and_std(&df, df_column_name));
+                let (_, values) = df.get_column(df_column_name);
+                values
+                    .iter()
+                    .map(|current_value| {
+                        let current_value = match current_value {
+                            DataTypeValue::Float(inner) => inner,
+                            _ => panic!(
+                                "invalid datatype: {:?} for standard scalar in column {}",
+                                current_value, df_column_name
+                            ),
+                        };
+                        DataTypeValue::Float((current_value - mean) / std)
+                    })
+                    .collect()
+            });
+        for (df_column_name, scaled_values) in zip(df_column_names, scaled_columns) {
+            df.replace_column_values(&df_column_name, scaled_values);
+        }
+        return df;
+    }
+
+    /// Undoes the scaling of fitted columns.
+    fn inverse_transform(&self, df: &DataFrame, column_names: &Vec<String>) -> Option<DataFrame> {
+        let mut df = df.clone();
+        for df_column_name in Self::scaled_columns(&df, column_names) {
+            let (mean, std) = self.statistics(&df_column_name).unwrap_or_else(|| {
+                panic!("standard scalar must be fitted on {} first", df_column_name)
+            });
+            let (_, values) = df.get_column(&df_column_name);
+            let values = values
+                .iter()
+                .map(|value| match value {
+                    DataTypeValue::Float(inner) => DataTypeValue::Float(inner * std + mean),
                     _ => panic!(
                         "invalid datatype: {:?} for standard scalar in column {}",
-                        current_value, df_column_name
+                        value, df_column_name
                     ),
-                };
-                let new_value = (current_value - mean) / std;
-                df.modify_cell(&df_column_name, i, DataTypeValue::Float(new_value));
-            }
+                })
+                .collect();
+            df.replace_column_values(&df_column_name, values);
         }
-        return df;
+        return Some(df);
+    }
+
+    fn get_feature_names_out(
+        &self,
+        input_features: &Vec<String>,
+        _column_names: &Vec<String>,
+    ) -> Vec<String> {
+        return input_features.clone();
     }
 }
 
@@ -90,4 +163,27 @@ mod tests {
             return true;
         }));
     }
+
+    #[test]
+    fn test_std_scalar_inverse_transform() {
+        let df = df_from_csv("housing.csv", Some(100));
+        let columns = vec!["median_income".to_string(), "total_rooms".to_string()];
+        let mut std_scalar = StandardScalar::new();
+        std_scalar.fit(&df, &columns);
+        let restored = std_scalar
+            .inverse_transform(&std_scalar.transform(&df, &columns), &columns)
+            .unwrap();
+        assert!(columns.iter().all(|column| {
+            let (_, values) = df.get_column(column);
+            let (_, restored_values) = restored.get_column(column);
+            zip(values, restored_values).all(|(value, restored_value)| {
+                match (value, restored_value) {
+                    (DataTypeValue::Float(a), DataTypeValue::Float(b)) => {
+                        (a - b).abs() <= 1e-3 * a.abs().max(1.0)
+                    }
+                    _ => false,
+                }
+            })
+        }));
+    }
 }
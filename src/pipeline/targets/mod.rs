@@ -0,0 +1 @@
+pub mod transformed_target;
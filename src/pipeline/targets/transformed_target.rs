@@ -0,0 +1,229 @@
+use crate::algorithms::estimators::{
+    Estimator, ParamError, ParamValue, Regressor, labels_from_matrix,
+};
+use crate::dataframe::{DataFrame, DataType, DataTypeValue};
+use crate::linear_algebra::{LinalgError, Matrix, RowVector};
+use crate::pipeline::scalars::standard_scalar::StandardScalar;
+use crate::pipeline::transformers::Transformer;
+use std::collections::HashMap;
+
+/// How the target is transformed before fitting.
+pub enum TargetTransform {
+    /// Fitted on the training target as a single float column and inverted
+    /// on the predictions.
+    Transformer(Box<dyn Transformer>),
+    /// A function and its inverse, such as `f32::ln` and `f32::exp`.
+    Functions(fn(f32) -> f32, fn(f32) -> f32),
+}
+
+/// Fits a regressor on a transformed target and maps its predictions back,
+/// so `predict` and `score` are in the units of the original labels. The
+/// target is standardized with a `StandardScalar` unless `set_transform`
+/// says otherwise, and `fit` panics on a transformer without an inverse.
+pub struct TransformedTargetRegressor {
+    regressor: Box<dyn Regressor>,
+    transform: TargetTransform,
+}
+
+// name of the single column the target transformer sees
+const TARGET: &str = "target";
+
+impl TransformedTargetRegressor {
+    pub fn new(regressor: Box<dyn Regressor>) -> Self {
+        return Self {
+            regressor,
+            transform: TargetTransform::Transformer(Box::new(StandardScalar::new())),
+        };
+    }
+
+    pub fn set_transform(&mut self, transform: TargetTransform) {
+        self.transform = transform;
+    }
+
+    /// The wrapped regressor, predicting the transformed target.
+    pub fn regressor(&self) -> &dyn Regressor {
+        return self.regressor.as_ref();
+    }
+
+    fn transformed(&mut self, labels: &Vec<f32>) -> Vec<f32> {
+        let transformed = match &mut self.transform {
+            TargetTransform::Transformer(transformer) => {
+                let columns = vec![TARGET.to_string()];
+                let df = target_frame(labels);
+                transformer.fit(&df, &columns);
+                let df_transformed = transformer.transform(&df, &columns);
+                if transformer
+                    .inverse_transform(&df_transformed, &columns)
+                    .is_none()
+                {
+                    panic!("target transformer has no inverse");
+                }
+                target_values(&df_transformed)
+            }
+            TargetTransform::Functions(function, _) => {
+                labels.iter().map(|label| function(*label)).collect()
+            }
+        };
+        if let Some(row) = transformed.iter().position(|value| !value.is_finite()) {
+            panic!(
+                "transformed target of {} is not finite, found {}",
+                labels[row], transformed[row]
+            );
+        }
+        return transformed;
+    }
+
+    fn inverse_transformed(&self, values: &Vec<f32>) -> Vec<f32> {
+        match &self.transform {
+            TargetTransform::Transformer(transformer) => {
+                let columns = vec![TARGET.to_string()];
+                let df = transformer
+                    .inverse_transform(&target_frame(values), &columns)
+                    .expect("target transformer has no inverse");
+                return target_values(&df);
+            }
+            TargetTransform::Functions(_, inverse) => {
+                return values.iter().map(|value| inverse(*value)).collect();
+            }
+        }
+    }
+}
+
+fn target_frame(values: &Vec<f32>) -> DataFrame {
+    let mut df = DataFrame::new();
+    df.insert_column(TARGET, &Vec::new(), &DataType::Float);
+    for value in values.iter() {
+        let row = HashMap::from([(TARGET.to_string(), DataTypeValue::Float(*value))]);
+        df.insert_row(&row);
+    }
+    return df;
+}
+
+fn target_values(df: &DataFrame) -> Vec<f32> {
+    let (_, values) = df.get_column(TARGET);
+    return values
+        .iter()
+        .map(|value| match value {
+            DataTypeValue::Float(inner) => *inner,
+            _ => panic!("target transformer must output floats, found {:?}", value),
+        })
+        .collect();
+}
+
+// the hyperparameters of the wrapped regressor
+impl Estimator for TransformedTargetRegressor {
+    fn params(&self) -> Vec<(&'static str, ParamValue)> {
+        return self.regressor.params();
+    }
+
+    fn set_param(&mut self, name: &str, value: ParamValue) -> Result<(), ParamError> {
+        return self.regressor.set_param(name, value);
+    }
+}
+
+impl Regressor for TransformedTargetRegressor {
+    fn fit(&mut self, data: &Matrix, labels: &Matrix) -> Result<(), LinalgError> {
+        let transformed = self.transformed(&labels_from_matrix(labels));
+        let labels = Matrix::to_matrix(&transformed.into_iter().map(|label| vec![label]).collect());
+        return self.regressor.fit(data, &labels);
+    }
+
+    fn predict(&self, data: &Matrix) -> RowVector {
+        let predictions = self.regressor.predict(data);
+        return RowVector::new(&self.inverse_transformed(predictions.vector()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::linear_regression::linear_regression::LinearRegression;
+    use crate::dataframe::csv::df_from_csv;
+    use crate::pipeline::pipeline::{
+        CategoricalPipeline, ColumnTransformer, FinalEstimator, NumericalPipeline, Pipeline,
+        Preprocessor,
+    };
+    use crate::pipeline::{
+        encoders::one_hot_encoder::OneHotEncoder,
+        imputers::imputer::{Imputer, ImputerStrategy},
+    };
+    use crate::sampling::random::Rng;
+    use std::iter::zip;
+
+    #[test]
+    fn test_functions_transform_target() {
+        // y = e^(0.5 x0 - x1 + 1), linear after taking the log
+        let mut rng = Rng::new(4);
+        let data: Vec<Vec<f32>> = (0..200).map(|_| vec![rng.normal(), rng.normal()]).collect();
+        let labels: Vec<Vec<f32>> = data
+            .iter()
+            .map(|row| vec![(0.5 * row[0] - row[1] + 1.0).exp()])
+            .collect();
+        let (data, labels) = (Matrix::to_matrix(&data), Matrix::to_matrix(&labels));
+        let mut regressor = TransformedTargetRegressor::new(Box::new(LinearRegression::new(0.0)));
+        regressor.set_transform(TargetTransform::Functions(f32::ln, f32::exp));
+        regressor.fit(&data, &labels).unwrap();
+        assert!(regressor.score(&data, &labels) > 0.9999);
+        assert!(regressor.regressor().score(&data, &labels) < 0.9);
+        assert!(regressor.param("ridge_value") == Some(ParamValue::Float(0.0)));
+    }
+
+    // a transformer without an inverse
+    struct Shifted;
+
+    impl Transformer for Shifted {
+        fn transform(&self, df: &DataFrame, _column_names: &Vec<String>) -> DataFrame {
+            let values = target_values(df).iter().map(|value| value + 1.0).collect();
+            return target_frame(&values);
+        }
+
+        fn get_feature_names_out(
+            &self,
+            input_features: &Vec<String>,
+            _column_names: &Vec<String>,
+        ) -> Vec<String> {
+            return input_features.clone();
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "target transformer has no inverse")]
+    fn test_target_transformer_without_inverse() {
+        let data = Matrix::to_matrix(&vec![vec![0.0], vec![1.0], vec![2.0]]);
+        let labels = Matrix::to_matrix(&vec![vec![1.0], vec![3.0], vec![5.0]]);
+        let mut regressor = TransformedTargetRegressor::new(Box::new(LinearRegression::new(0.0)));
+        regressor.set_transform(TargetTransform::Transformer(Box::new(Shifted)));
+        regressor.fit(&data, &labels).unwrap();
+    }
+
+    #[test]
+    fn test_scaled_target_in_pipeline() {
+        let df = df_from_csv("housing.csv", Some(2000));
+        let pipeline = |regressor: Box<dyn Regressor>| {
+            let imputer: Box<dyn Transformer> = Box::new(Imputer::new(&ImputerStrategy::Median));
+            let scalar: Box<dyn Transformer> = Box::new(StandardScalar::new());
+            let encoder: Box<dyn Transformer> = Box::new(OneHotEncoder::new(false));
+            let mut pipeline = Pipeline::new(
+                Preprocessor::Columns(ColumnTransformer::new(
+                    NumericalPipeline::new(vec![imputer, scalar]),
+                    CategoricalPipeline::new(vec![encoder]),
+                )),
+                FinalEstimator::Regressor(regressor),
+            );
+            pipeline.fit(&df, "median_house_value").unwrap();
+            pipeline
+        };
+        let plain = pipeline(Box::new(LinearRegression::new(0.0)));
+        let scaled = pipeline(Box::new(TransformedTargetRegressor::new(Box::new(
+            LinearRegression::new(0.0),
+        ))));
+        // a linear model fits a standardized target just as well, and the
+        // predictions come back in dollars
+        assert!((plain.score(&df) - scaled.score(&df)).abs() < 1e-3);
+        let (plain_predictions, scaled_predictions) = (plain.predict(&df), scaled.predict(&df));
+        assert!(
+            zip(plain_predictions.vector(), scaled_predictions.vector())
+                .all(|(a, b)| (a - b).abs() < 1e-3 * a.abs().max(1e4))
+        );
+    }
+}
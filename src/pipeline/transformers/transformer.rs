@@ -1,5 +1,28 @@
 use crate::dataframe::DataFrame;
 
-pub trait Transformer {
+// Send + Sync so independent pipeline branches can run on separate threads
+pub trait Transformer: Send + Sync {
+    /// Learns whatever the step needs from the training frame. Steps that
+    /// work out their statistics from every frame they transform keep this
+    /// default.
+    fn fit(&mut self, _df: &DataFrame, _column_names: &Vec<String>) {}
+
     fn transform(&self, df: &DataFrame, column_names: &Vec<String>) -> DataFrame;
+
+    /// Maps a frame output by `transform`, given the same `column_names`,
+    /// back to the original columns and units. Steps without an inverse keep
+    /// this default and return `None`.
+    fn inverse_transform(&self, _df: &DataFrame, _column_names: &Vec<String>) -> Option<DataFrame> {
+        return None;
+    }
+
+    /// Columns, in order and without the id column, of the frame `transform`
+    /// returns for a frame with columns `input_features` and the same
+    /// `column_names`. Steps that decide their columns from the data must be
+    /// fitted first.
+    fn get_feature_names_out(
+        &self,
+        input_features: &Vec<String>,
+        column_names: &Vec<String>,
+    ) -> Vec<String>;
 }
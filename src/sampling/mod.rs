@@ -1 +1,2 @@
-pub mod sampling;
+pub mod random;
+pub mod sampling;
@@ -0,0 +1,105 @@
+/// Small seeded pseudo random number generator (xorshift64* seeded through
+/// splitmix64). Not cryptographically secure, but fast and reproducible, which
+/// is what shuffling, bootstrapping and initialization need.
+#[derive(Clone, Debug)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        // splitmix64 spreads nearby seeds apart and never yields the all zero state
+        let mut z = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+        Self {
+            state: if z == 0 { 0x2545_F491_4F6C_DD1D } else { z },
+        }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    /// Uniform in `[0, 1)`.
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    /// Uniform in `[0, 1)`.
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Uniform in `0..upper`.
+    pub fn gen_range(&mut self, upper: usize) -> usize {
+        assert!(upper > 0, "cannot sample from an empty range");
+        ((self.next_u64() as u128 * upper as u128) >> 64) as usize
+    }
+
+    /// Standard normal sample (Box-Muller).
+    pub fn normal(&mut self) -> f32 {
+        let u1 = 1.0 - self.next_f64();
+        let u2 = self.next_f64();
+        ((-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()) as f32
+    }
+
+    /// Fisher-Yates shuffle.
+    pub fn shuffle<T>(&mut self, values: &mut [T]) {
+        for i in (1..values.len()).rev() {
+            let j = self.gen_range(i + 1);
+            values.swap(i, j);
+        }
+    }
+
+    /// Shuffled `0..len`.
+    pub fn permutation(&mut self, len: usize) -> Vec<usize> {
+        let mut indices: Vec<usize> = (0..len).collect();
+        self.shuffle(&mut indices);
+        indices
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rng_is_reproducible() {
+        let (mut a, mut b) = (Rng::new(42), Rng::new(42));
+        assert!((0..100).all(|_| a.next_u64() == b.next_u64()));
+        let mut c = Rng::new(43);
+        assert!(Rng::new(42).next_u64() != c.next_u64());
+    }
+
+    #[test]
+    fn test_rng_ranges() {
+        let mut rng = Rng::new(7);
+        let mut counts = [0; 5];
+        for _ in 0..10000 {
+            let value = rng.next_f32();
+            assert!((0.0..1.0).contains(&value));
+            counts[rng.gen_range(5)] += 1;
+        }
+        assert!(counts.iter().all(|count| (1700..2300).contains(count)));
+        let normals: Vec<f32> = (0..10000).map(|_| rng.normal()).collect();
+        let mean = normals.iter().sum::<f32>() / normals.len() as f32;
+        let variance =
+            normals.iter().map(|x| (x - mean).powi(2)).sum::<f32>() / normals.len() as f32;
+        assert!(mean.abs() < 0.05);
+        assert!((variance - 1.0).abs() < 0.1);
+    }
+
+    #[test]
+    fn test_permutation() {
+        let mut rng = Rng::new(1);
+        let mut permutation = rng.permutation(100);
+        assert!(permutation != (0..100).collect::<Vec<usize>>());
+        permutation.sort();
+        assert!(permutation == (0..100).collect::<Vec<usize>>());
+    }
+}
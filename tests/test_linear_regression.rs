@@ -44,7 +44,7 @@ fn test_linear_regression() {
         column_transformer.transform(&test_features),
     );
     let mut linear_regression = LinearRegression::new(0.0);
-    linear_regression.fit(&train_inputs, &train_labels);
+    linear_regression.fit(&train_inputs, &train_labels).unwrap();
     assert!(linear_regression.weights().len() == train_inputs.get(0).len());
     assert!(linear_regression.bias() != 0.0);
     let (train_predictions, test_predictions) = (
@@ -52,11 +52,13 @@ fn test_linear_regression() {
         linear_regression.predict(&test_inputs),
     );
     let (train_labels, test_labels) = (
-        train_labels.matrix()
+        train_labels
+            .matrix()
             .into_iter()
             .map(|label| label.get(0))
             .collect::<Vec<f32>>(),
-        test_labels.matrix()
+        test_labels
+            .matrix()
             .into_iter()
             .map(|label| label.get(0))
             .collect::<Vec<f32>>(),